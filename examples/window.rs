@@ -384,6 +384,10 @@ impl ApplicationHandler<UserEvent> for Application {
                 window.modifiers = modifiers.state();
                 info!("Modifiers changed to {:?}", window.modifiers);
             },
+            WindowEvent::KeyboardLayoutChanged(layouts) => {
+                let active = layouts.names.get(layouts.active);
+                info!("Keyboard layout changed to {active:?} of {:?}", layouts.names);
+            },
             WindowEvent::MouseWheel { delta, .. } => match delta {
                 MouseScrollDelta::LineDelta(x, y) => {
                     info!("Mouse wheel Line Delta: ({x},{y})");
@@ -439,9 +443,15 @@ impl ApplicationHandler<UserEvent> for Application {
                 Ime::Preedit(text, caret_pos) => {
                     info!("Preedit: {}, with caret at {:?}", text, caret_pos);
                 },
+                Ime::StyledPreedit { segments, .. } => {
+                    info!("Preedit segments: {:?}", segments);
+                },
                 Ime::Commit(text) => {
                     info!("Committed: {}", text);
                 },
+                Ime::DeleteSurrounding { before_bytes, after_bytes } => {
+                    info!("Delete {before_bytes} bytes before and {after_bytes} after the cursor");
+                },
                 Ime::Disabled => info!("IME disabled for Window={window_id:?}"),
             },
             WindowEvent::PinchGesture { delta, .. } => {
@@ -478,7 +488,15 @@ impl ApplicationHandler<UserEvent> for Application {
             | WindowEvent::DroppedFile(_)
             | WindowEvent::HoveredFile(_)
             | WindowEvent::Destroyed
+            | WindowEvent::PopupDismissed
             | WindowEvent::Touch(_)
+            | WindowEvent::TabletTool { .. }
+            | WindowEvent::ClipboardDone { .. }
+            | WindowEvent::DragEntered { .. }
+            | WindowEvent::DragMoved { .. }
+            | WindowEvent::DragDropped { .. }
+            | WindowEvent::DragLeft
+            | WindowEvent::DragFinished { .. }
             | WindowEvent::Moved(_) => (),
         }
     }
@@ -1,7 +1,7 @@
 //! End user application handling.
 
 use crate::event::{DeviceEvent, DeviceId, StartCause, WindowEvent};
-use crate::event_loop::ActiveEventLoop;
+use crate::event_loop::{ActiveEventLoop, Interest, SourceToken, TimerId};
 use crate::window::WindowId;
 
 /// The handler of the application events.
@@ -107,6 +107,26 @@ pub trait ApplicationHandler<T: 'static = ()> {
         let _ = (event_loop, device_id, event);
     }
 
+    /// Emitted when the timer scheduled with [`ActiveEventLoop::schedule_timer`] fires.
+    fn timer_fired(&mut self, event_loop: &ActiveEventLoop, timer_id: TimerId) {
+        let _ = (event_loop, timer_id);
+    }
+
+    /// Emitted when the file descriptor registered with [`register_fd`] is ready.
+    ///
+    /// The readiness is reported on every iteration of the event loop as long as it persists, so
+    /// the file descriptor should be read or written until it would block.
+    ///
+    /// ## Platform-specific
+    ///
+    /// Only emitted on X11 and Wayland.
+    ///
+    /// [`register_fd`]: crate::platform::unix::ActiveEventLoopExtUnix::register_fd
+    #[cfg_attr(not(any(x11_platform, wayland_platform)), allow(rustdoc::broken_intra_doc_links))]
+    fn fd_ready(&mut self, event_loop: &ActiveEventLoop, token: SourceToken, readiness: Interest) {
+        let _ = (event_loop, token, readiness);
+    }
+
     /// Emitted when the event loop is about to block and wait for new events.
     ///
     /// Most applications shouldn't need to hook into this event since there is no real relationship
@@ -265,6 +285,16 @@ impl<A: ?Sized + ApplicationHandler<T>, T: 'static> ApplicationHandler<T> for &m
         (**self).device_event(event_loop, device_id, event);
     }
 
+    #[inline]
+    fn timer_fired(&mut self, event_loop: &ActiveEventLoop, timer_id: TimerId) {
+        (**self).timer_fired(event_loop, timer_id);
+    }
+
+    #[inline]
+    fn fd_ready(&mut self, event_loop: &ActiveEventLoop, token: SourceToken, readiness: Interest) {
+        (**self).fd_ready(event_loop, token, readiness);
+    }
+
     #[inline]
     fn about_to_wait(&mut self, event_loop: &ActiveEventLoop) {
         (**self).about_to_wait(event_loop);
@@ -322,6 +352,16 @@ impl<A: ?Sized + ApplicationHandler<T>, T: 'static> ApplicationHandler<T> for Bo
         (**self).device_event(event_loop, device_id, event);
     }
 
+    #[inline]
+    fn timer_fired(&mut self, event_loop: &ActiveEventLoop, timer_id: TimerId) {
+        (**self).timer_fired(event_loop, timer_id);
+    }
+
+    #[inline]
+    fn fd_ready(&mut self, event_loop: &ActiveEventLoop, token: SourceToken, readiness: Interest) {
+        (**self).fd_ready(event_loop, token, readiness);
+    }
+
     #[inline]
     fn about_to_wait(&mut self, event_loop: &ActiveEventLoop) {
         (**self).about_to_wait(event_loop);
@@ -0,0 +1,112 @@
+//! Types for exchanging data with other clients through the clipboard.
+//!
+//! The clipboard itself is accessed through the platform specific extension traits, see
+//! `platform::clipboard` on the platforms that support it.
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// The MIME type used for UTF-8 encoded plain text.
+pub const TEXT_MIME_TYPE: &str = "text/plain;charset=utf-8";
+
+/// The selection to operate on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Selection {
+    /// The regular clipboard, used by explicit copy and paste actions.
+    Clipboard,
+
+    /// The primary selection, which usually holds the last selected text and is pasted
+    /// with the middle mouse button.
+    Primary,
+}
+
+/// A payload tagged with its MIME type.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct MimeData {
+    /// The MIME type of the `data`, like `text/plain;charset=utf-8` or `image/png`.
+    pub mime_type: String,
+
+    /// The raw payload.
+    pub data: Vec<u8>,
+}
+
+impl MimeData {
+    /// Create a new payload with the given MIME type.
+    pub fn new(mime_type: impl Into<String>, data: impl Into<Vec<u8>>) -> Self {
+        Self { mime_type: mime_type.into(), data: data.into() }
+    }
+
+    /// Create a plain text payload, using the [`TEXT_MIME_TYPE`].
+    pub fn text(text: impl Into<String>) -> Self {
+        Self::new(TEXT_MIME_TYPE, text.into())
+    }
+
+    /// Whether the payload holds plain text.
+    pub fn is_text(&self) -> bool {
+        is_text_mime_type(&self.mime_type)
+    }
+
+    /// The payload as text, if it's plain text encoded as valid UTF-8.
+    pub fn as_text(&self) -> Option<&str> {
+        if self.is_text() {
+            std::str::from_utf8(&self.data).ok()
+        } else {
+            None
+        }
+    }
+}
+
+/// The MIME types and X11 targets used by the clients to exchange UTF-8 text.
+pub(crate) const TEXT_MIME_TYPES: [&str; 4] =
+    [TEXT_MIME_TYPE, "text/plain", "UTF8_STRING", "STRING"];
+
+/// Whether the `mime_type` denotes UTF-8 compatible plain text.
+pub(crate) fn is_text_mime_type(mime_type: &str) -> bool {
+    TEXT_MIME_TYPES.contains(&mime_type)
+}
+
+/// Pick the first of the `requested` MIME types which is `offered`.
+///
+/// The text is requested by its legacy names as well when the exact MIME type is not offered.
+/// Returns the requested MIME type along with the index of the matching offered one.
+#[cfg_attr(not(any(x11_platform, wayland_platform)), allow(dead_code))]
+pub(crate) fn negotiate_mime_type<'a, S: AsRef<str>>(
+    requested: &'a [String],
+    offered: &[S],
+) -> Option<(&'a str, usize)> {
+    let find = |mime_type: &str| offered.iter().position(|offer| offer.as_ref() == mime_type);
+    requested.iter().find_map(|mime_type| {
+        let index = find(mime_type).or_else(|| {
+            if is_text_mime_type(mime_type) {
+                find(TEXT_MIME_TYPE).or_else(|| find("UTF8_STRING"))
+            } else {
+                None
+            }
+        })?;
+        Some((mime_type.as_str(), index))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn negotiate_mime_type_prefers_requested_order() {
+        let requested = vec!["image/png".to_owned(), TEXT_MIME_TYPE.to_owned()];
+        let offered = ["text/html", TEXT_MIME_TYPE, "image/png"];
+        assert_eq!(negotiate_mime_type(&requested, &offered), Some(("image/png", 2)));
+    }
+
+    #[test]
+    fn negotiate_mime_type_falls_back_to_legacy_text() {
+        let requested = vec!["text/plain".to_owned()];
+        assert_eq!(
+            negotiate_mime_type(&requested, &["TARGETS", "UTF8_STRING"]),
+            Some(("text/plain", 1))
+        );
+        assert_eq!(negotiate_mime_type(&requested, &["image/png"]), None);
+    }
+}
@@ -0,0 +1,147 @@
+//! Types for the drag and drop between windows and other clients.
+//!
+//! The drop target is controlled through the platform specific extension traits, see
+//! `platform::dnd` on the platforms that support it.
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::clipboard::MimeData;
+
+/// The MIME type of the list of dropped URLs.
+pub const URI_LIST_MIME_TYPE: &str = "text/uri-list";
+
+/// The action performed with the dragged data once it's dropped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum DndAction {
+    /// The data is copied to the target.
+    Copy,
+
+    /// The data is moved to the target, the source removes it afterwards.
+    Move,
+
+    /// The target creates a link to the data.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Wayland:** Unsupported, the data is copied instead.
+    Link,
+}
+
+bitflags::bitflags! {
+    /// The set of the actions offered by the drag source.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(transparent))]
+    pub struct DndActions: u32 {
+        const COPY = 1 << 0;
+        const MOVE = 1 << 1;
+        const LINK = 1 << 2;
+    }
+}
+
+impl From<DndAction> for DndActions {
+    fn from(action: DndAction) -> Self {
+        match action {
+            DndAction::Copy => Self::COPY,
+            DndAction::Move => Self::MOVE,
+            DndAction::Link => Self::LINK,
+        }
+    }
+}
+
+/// The data offered by the drag started from the window.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct DragData {
+    /// The data in all the MIME types it's offered in.
+    pub items: Vec<MimeData>,
+}
+
+impl DragData {
+    /// Create the drag data offered in several MIME types.
+    pub fn new(items: Vec<MimeData>) -> Self {
+        Self { items }
+    }
+
+    /// Create the drag data holding plain text.
+    pub fn text(text: impl Into<String>) -> Self {
+        Self::new(vec![MimeData::text(text)])
+    }
+
+    /// Create the drag data holding the list of URLs, like the `file://` URLs of the files.
+    pub fn urls<I>(urls: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: AsRef<str>,
+    {
+        let mut list = String::new();
+        for url in urls {
+            list.push_str(url.as_ref());
+            list.push_str("\r\n");
+        }
+        Self::new(vec![MimeData::new(URI_LIST_MIME_TYPE, list)])
+    }
+}
+
+/// The data dropped into the window.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum DropData {
+    /// Plain text, like the selection dragged from the browser.
+    Text(String),
+
+    /// The list of URLs, the dropped files are delivered as the `file://` URLs.
+    Urls(Vec<String>),
+
+    /// The data in any other MIME type.
+    Mime(MimeData),
+}
+
+impl DropData {
+    /// Interpret the dropped data according to its MIME type.
+    pub fn from_mime_data(data: MimeData) -> Self {
+        if data.mime_type == URI_LIST_MIME_TYPE {
+            if let Ok(list) = std::str::from_utf8(&data.data) {
+                // Lines starting with `#` are comments, as specified by RFC 2483.
+                let urls = list
+                    .split("\r\n")
+                    .flat_map(|line| line.split('\n'))
+                    .map(str::trim)
+                    .filter(|url| !url.is_empty() && !url.starts_with('#'))
+                    .map(ToOwned::to_owned)
+                    .collect();
+                return Self::Urls(urls);
+            }
+        } else if let Some(text) = data.as_text() {
+            return Self::Text(text.to_owned());
+        }
+
+        Self::Mime(data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn drop_data_from_mime_data() {
+        let urls =
+            MimeData::new(URI_LIST_MIME_TYPE, "# comment\r\nfile:///tmp/a\r\nhttps://b/\r\n");
+        assert_eq!(
+            DropData::from_mime_data(urls),
+            DropData::Urls(vec!["file:///tmp/a".to_owned(), "https://b/".to_owned()])
+        );
+        assert_eq!(DropData::from_mime_data(MimeData::text("text")), DropData::Text("text".into()));
+
+        let urls = DragData::urls(["file:///tmp/a", "https://b/"]).items.remove(0);
+        assert_eq!(
+            DropData::from_mime_data(urls),
+            DropData::Urls(vec!["file:///tmp/a".to_owned(), "https://b/".to_owned()])
+        );
+
+        let image = MimeData::new("image/png", vec![0x89, 0x50]);
+        assert_eq!(DropData::from_mime_data(image.clone()), DropData::Mime(image));
+    }
+}
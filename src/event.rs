@@ -32,6 +32,23 @@
 //! This leaves out timing details like [`ControlFlow::WaitUntil`] but hopefully
 //! describes what happens in what order.
 //!
+//! ## Serialization
+//!
+//! With the `serde` feature, [`Event`] and all the types it carries implement `Serialize` and
+//! `Deserialize`, to send the events to another process or to write golden files in tests. The
+//! representation is stable and portable across the platforms: the enums are externally tagged
+//! with the names of their variants, the structs use the names of their fields, and
+//!
+//! - [`WindowId`] and [`DeviceId`] are integers. The devices without an identifier on the
+//!   platform, like on Wayland, are `0`. The device identifiers out of the range of the
+//!   platform fail to deserialize, and on Linux they belong to the backend of the event loop.
+//! - [`KeyEvent`] always has the `text_with_all_modifiers` and `key_without_modifiers` fields,
+//!   the platforms without them report the `text` and the `logical_key` instead.
+//! - The instants of [`StartCause`] are durations, relative to the first instant serialized or
+//!   deserialized by the process.
+//! - The `inner_size_writer` of [`WindowEvent::ScaleFactorChanged`] is left out, the
+//!   deserialized one doesn't resize any window.
+//!
 //! [`EventLoop::run_app(...)`]: crate::event_loop::EventLoop::run_app
 //! [`ControlFlow::WaitUntil`]: crate::event_loop::ControlFlow::WaitUntil
 use std::path::PathBuf;
@@ -45,10 +62,12 @@ use smol_str::SmolStr;
 #[cfg(web_platform)]
 use web_time::Instant;
 
+use crate::clipboard::MimeData;
+use crate::dnd::{DndAction, DndActions, DropData};
 use crate::dpi::{PhysicalPosition, PhysicalSize};
 use crate::error::ExternalError;
-use crate::event_loop::AsyncRequestSerial;
-use crate::keyboard::{self, ModifiersKeyState, ModifiersKeys, ModifiersState};
+use crate::event_loop::{AsyncRequestSerial, Interest, SourceToken, TimerId};
+use crate::keyboard::{self, KeyboardLayouts, ModifiersKeyState, ModifiersKeys, ModifiersState};
 use crate::platform_impl;
 #[cfg(doc)]
 use crate::window::Window;
@@ -58,6 +77,7 @@ use crate::window::{ActivationToken, Theme, WindowId};
 ///
 /// See the module-level docs for more information on the event loop manages each event.
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum Event<T: 'static> {
     /// See [`ApplicationHandler::new_events`] for details.
     ///
@@ -74,6 +94,16 @@ pub enum Event<T: 'static> {
     /// [`ApplicationHandler::device_event`]: crate::application::ApplicationHandler::device_event
     DeviceEvent { device_id: DeviceId, event: DeviceEvent },
 
+    /// See [`ApplicationHandler::timer_fired`] for details.
+    ///
+    /// [`ApplicationHandler::timer_fired`]: crate::application::ApplicationHandler::timer_fired
+    TimerFired(TimerId),
+
+    /// See [`ApplicationHandler::fd_ready`] for details.
+    ///
+    /// [`ApplicationHandler::fd_ready`]: crate::application::ApplicationHandler::fd_ready
+    FdReady { token: SourceToken, readiness: Interest },
+
     /// See [`ApplicationHandler::user_event`] for details.
     ///
     /// [`ApplicationHandler::user_event`]: crate::application::ApplicationHandler::user_event
@@ -120,6 +150,8 @@ impl<T> Event<T> {
             UserEvent(_) => Err(self),
             WindowEvent { window_id, event } => Ok(WindowEvent { window_id, event }),
             DeviceEvent { device_id, event } => Ok(DeviceEvent { device_id, event }),
+            TimerFired(timer_id) => Ok(TimerFired(timer_id)),
+            FdReady { token, readiness } => Ok(FdReady { token, readiness }),
             NewEvents(cause) => Ok(NewEvents(cause)),
             AboutToWait => Ok(AboutToWait),
             LoopExiting => Ok(LoopExiting),
@@ -157,6 +189,7 @@ pub enum StartCause {
 
 /// Describes an event from a [`Window`].
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum WindowEvent {
     /// The activation token was delivered back and now could be used.
     #[cfg_attr(not(any(x11_platform, wayland_platform)), allow(rustdoc::broken_intra_doc_links))]
@@ -165,6 +198,16 @@ pub enum WindowEvent {
     /// [`request_activation_token`]: crate::platform::startup_notify::WindowExtStartupNotify::request_activation_token
     ActivationTokenDone { serial: AsyncRequestSerial, token: ActivationToken },
 
+    /// The clipboard contents were delivered back.
+    #[cfg_attr(not(any(x11_platform, wayland_platform)), allow(rustdoc::broken_intra_doc_links))]
+    /// Delivered in response to [`request_clipboard`].
+    ///
+    /// The `data` is `None` when the selection is empty or none of the requested MIME types
+    /// is offered.
+    ///
+    /// [`request_clipboard`]: crate::platform::clipboard::WindowExtClipboard::request_clipboard
+    ClipboardDone { serial: AsyncRequestSerial, data: Option<MimeData> },
+
     /// The size of the window has changed. Contains the client area's new dimensions.
     Resized(PhysicalSize<u32>),
 
@@ -181,6 +224,13 @@ pub enum WindowEvent {
     /// The window has been destroyed.
     Destroyed,
 
+    /// The popup has been dismissed, e.g. by a click outside of the windows of the application.
+    ///
+    /// The popup is hidden, and should be dropped. See [`WindowAttributes::with_popup`].
+    ///
+    /// [`WindowAttributes::with_popup`]: crate::window::WindowAttributes::with_popup
+    PopupDismissed,
+
     /// A file has been dropped into the window.
     ///
     /// When the user drops multiple files at once, this event will be emitted for each file
@@ -199,6 +249,70 @@ pub enum WindowEvent {
     /// hovered.
     HoveredFileCancelled,
 
+    /// A drag and drop operation has entered the window.
+    ///
+    /// The drop is accepted or rejected with the platform specific extension traits, by default
+    /// only the files are accepted. See `platform::dnd` on the platforms that support it.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **X11:** The `actions` only contain the action requested by the source.
+    /// - **Only X11 / Wayland:** Supported.
+    DragEntered {
+        /// The MIME types the data is offered in.
+        mime_types: Vec<String>,
+
+        /// The actions offered by the source.
+        actions: DndActions,
+
+        /// (x,y) coords in pixels relative to the top-left corner of the window.
+        position: PhysicalPosition<f64>,
+    },
+
+    /// The drag and drop operation has moved over the window.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Only X11 / Wayland:** Supported.
+    DragMoved {
+        /// (x,y) coords in pixels relative to the top-left corner of the window.
+        position: PhysicalPosition<f64>,
+    },
+
+    /// The data was dropped into the window.
+    ///
+    /// Only emitted when the drop was accepted, in the accepted MIME type.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Only X11 / Wayland:** Supported.
+    DragDropped {
+        /// The dropped data.
+        data: DropData,
+
+        /// The action to perform with the data.
+        action: DndAction,
+
+        /// (x,y) coords in pixels relative to the top-left corner of the window.
+        position: PhysicalPosition<f64>,
+    },
+
+    /// The drag and drop operation has left the window without dropping.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Only X11 / Wayland:** Supported.
+    DragLeft,
+
+    /// The drag started from the window has finished.
+    #[cfg_attr(not(any(x11_platform, wayland_platform)), allow(rustdoc::broken_intra_doc_links))]
+    /// Delivered in response to [`start_drag`].
+    ///
+    /// The `action` is the one performed by the target, or `None` when the drag was cancelled.
+    ///
+    /// [`start_drag`]: crate::platform::dnd::WindowExtDragDrop::start_drag
+    DragFinished { action: Option<DndAction> },
+
     /// The window gained or lost focus.
     ///
     /// The parameter is true if the window has gained focus, and false if it has lost focus.
@@ -229,6 +343,20 @@ pub enum WindowEvent {
     /// The keyboard modifiers have changed.
     ModifiersChanged(Modifiers),
 
+    /// The active keyboard layout or the set of layouts has changed.
+    ///
+    /// It's sent to the window with the keyboard focus. When the change happens without any
+    /// window focused, query the layouts with [`ActiveEventLoop::keyboard_layouts`] instead.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Wayland:** Also sent once the keyboard enters the first window, with the initial
+    ///   layouts.
+    /// - **Android / iOS / macOS / Orbital / Web / Windows:** Unsupported.
+    ///
+    /// [`ActiveEventLoop::keyboard_layouts`]: crate::event_loop::ActiveEventLoop::keyboard_layouts
+    KeyboardLayoutChanged(KeyboardLayouts),
+
     /// An event from an input method.
     ///
     /// **Note:** You have to explicitly enable this event using [`Window::set_ime_allowed`].
@@ -291,7 +419,7 @@ pub enum WindowEvent {
     ///
     /// ## Platform-specific
     ///
-    /// - Only available on **macOS** and **iOS**.
+    /// - Only available on **macOS**, **iOS**, **Wayland** and **X11**.
     /// - On iOS, not recognized by default. It must be enabled when needed.
     PinchGesture {
         device_id: DeviceId,
@@ -307,8 +435,12 @@ pub enum WindowEvent {
     ///
     /// ## Platform-specific
     ///
-    /// - Only available on **iOS**.
+    /// - Only available on **iOS**, **Wayland** and **X11**.
     /// - On iOS, not recognized by default. It must be enabled when needed.
+    /// - **Wayland:** Reported for the swipes of three fingers or more, the two-finger ones
+    ///   scroll. Holding the fingers still on the touchpad starts and ends a pan without any
+    ///   delta.
+    /// - **X11:** Reported for the swipes of XInput 2.4, requiring three fingers or more.
     PanGesture {
         device_id: DeviceId,
         /// Change in pixels of pan gesture from last update.
@@ -343,7 +475,7 @@ pub enum WindowEvent {
     ///
     /// ## Platform-specific
     ///
-    /// - Only available on **macOS** and **iOS**.
+    /// - Only available on **macOS**, **iOS**, **Wayland** and **X11**.
     /// - On iOS, not recognized by default. It must be enabled when needed.
     RotationGesture {
         device_id: DeviceId,
@@ -374,6 +506,24 @@ pub enum WindowEvent {
     /// [`transform`]: https://developer.mozilla.org/en-US/docs/Web/CSS/transform
     Touch(Touch),
 
+    /// A tablet tool, like a pen or an eraser, has been used over the window.
+    ///
+    /// The tool doesn't necessarily move the cursor, its position comes along with the
+    /// [`TabletToolEvent::Motion`] events instead.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - Only available on **Wayland** and **X11**.
+    /// - **Wayland:** Only reported when enabled with
+    ///   [`EventLoopBuilderExtWayland::with_tablet_tools`], the tools then no longer move the
+    ///   cursor.
+    /// - **X11:** The tools also move the cursor. XInput2 doesn't report the proximity, the tool
+    ///   is considered out of proximity once it leaves the window or another device moves the
+    ///   cursor.
+    ///
+    /// [`EventLoopBuilderExtWayland::with_tablet_tools`]: crate::platform::wayland::EventLoopBuilderExtWayland::with_tablet_tools
+    TabletTool { device_id: DeviceId, tool: TabletTool, event: TabletToolEvent },
+
     /// The window's scale factor has changed.
     ///
     /// The following user actions can cause DPI changes:
@@ -391,6 +541,12 @@ pub enum WindowEvent {
         /// Handle to update inner size during scale changes.
         ///
         /// See [`InnerSizeWriter`] docs for more details.
+        ///
+        /// The writer isn't serialized, the deserialized one is detached from any window.
+        #[cfg_attr(
+            feature = "serde",
+            serde(skip, default = "event_serde::detached_inner_size_writer")
+        )]
         inner_size_writer: InnerSizeWriter,
     },
 
@@ -478,6 +634,7 @@ impl DeviceId {
 ///
 /// Note that these events are delivered regardless of input focus.
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum DeviceEvent {
     Added,
     Removed,
@@ -667,6 +824,7 @@ pub struct KeyEvent {
 
 /// Describes keyboard modifiers event.
 #[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Modifiers {
     pub(crate) state: ModifiersState,
 
@@ -796,13 +954,45 @@ pub enum Ime {
     /// this indicates that preedit was cleared.
     ///
     /// The cursor position is byte-wise indexed.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Wayland / X11:** Dead keys and compose sequences handled by winit itself are also
+    ///   reported as preedit while IME is allowed, with the dead keys shown by their
+    ///   non-combining variant and the compose key by `·`. The preedit is cleared before the
+    ///   [`KeyboardInput`][WindowEvent::KeyboardInput] carrying the composed text, or when the
+    ///   window loses the keyboard focus, which cancels the sequence.
     Preedit(String, Option<(usize, usize)>),
 
+    /// Notifies when a new composing text should be set at the cursor position, along with the
+    /// styles the input method wants it drawn with.
+    ///
+    /// It's sent as an extra event right after the equivalent [`Preedit`][Self::Preedit] one, on
+    /// the platforms reporting the styles, so it can be ignored when the styles aren't drawn. The
+    /// segments are byte-wise indexed, the text outside of them has no particular style.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - Only available on **X11**.
+    StyledPreedit { text: String, cursor: Option<(usize, usize)>, segments: Vec<PreeditSegment> },
+
     /// Notifies when text should be inserted into the editor widget.
     ///
     /// Right before this event winit will send empty [`Self::Preedit`] event.
     Commit(String),
 
+    /// Notifies when the text around the cursor should be deleted.
+    ///
+    /// The lengths are byte-wise and relative to the cursor, or to the selection when there's
+    /// one, as last reported with [`Window::set_ime_surrounding_text`]. The current preedit,
+    /// cleared by winit right before this event, isn't part of them. The [`Commit`][Self::Commit]
+    /// following this event, if any, is inserted in place of the deleted text.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - Only available on **Wayland**.
+    DeleteSurrounding { before_bytes: usize, after_bytes: usize },
+
     /// Notifies when the IME was disabled.
     ///
     /// After receiving this event you won't get any more [`Preedit`][Self::Preedit] or
@@ -812,6 +1002,32 @@ pub enum Ime {
     Disabled,
 }
 
+/// A segment of the preedit text drawn with the same style.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct PreeditSegment {
+    /// The byte-wise index of the start of the segment.
+    pub start: usize,
+    /// The byte-wise index of the end of the segment, exclusive.
+    pub end: usize,
+    pub style: PreeditStyle,
+}
+
+bitflags::bitflags! {
+    /// The style of a preedit segment.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(transparent))]
+    pub struct PreeditStyle: u32 {
+        /// Underlined, usually the whole preedit or the clauses not being converted.
+        const UNDERLINE = 1 << 0;
+        /// Drawn with the foreground and background colors swapped, usually the clause being
+        /// converted.
+        const REVERSE = 1 << 1;
+        /// Highlighted, usually a clause stressed by the input method.
+        const HIGHLIGHT = 1 << 2;
+    }
+}
+
 /// Describes touch-screen input state.
 #[derive(Debug, Hash, PartialEq, Eq, Clone, Copy)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
@@ -848,6 +1064,7 @@ pub enum TouchPhase {
 /// [`padding`]: https://developer.mozilla.org/en-US/docs/Web/CSS/padding
 /// [`transform`]: https://developer.mozilla.org/en-US/docs/Web/CSS/transform
 #[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Touch {
     pub device_id: DeviceId,
     pub phase: TouchPhase,
@@ -857,17 +1074,41 @@ pub struct Touch {
     ///
     /// ## Platform-specific
     ///
-    /// - Only available on **iOS** 9.0+, **Windows** 8+, **Web**, and **Android**.
+    /// - Only available on **iOS** 9.0+, **Windows** 8+, **Web**, **Android** and **X11**.
     /// - **Android**: This will never be [None]. If the device doesn't support pressure
     ///   sensitivity, force will either be 0.0 or 1.0. Also see the
     ///   [android documentation](https://developer.android.com/reference/android/view/MotionEvent#AXIS_PRESSURE).
+    /// - **Wayland**: The protocol doesn't report the pressure of the touches, use the
+    ///   [`contact`](Self::contact) as an approximation.
     pub force: Option<Force>,
+    /// Describes the area of the screen the finger is in contact with. May be `None` if the
+    /// platform doesn't report the shape of the touches.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - Only available on **Wayland** and **X11**.
+    pub contact: Option<TouchContact>,
     /// Unique identifier of a finger.
     pub id: u64,
 }
 
+/// Describes the contact area of a touch, approximated by an ellipse centered on its location.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct TouchContact {
+    /// The length of the major axis of the ellipse in physical pixels.
+    pub major: f64,
+    /// The length of the minor axis of the ellipse in physical pixels, orthogonal to the major
+    /// one.
+    pub minor: f64,
+    /// The clockwise angle in degrees of the major axis to the positive y-axis, between -180 and
+    /// 180. May be `None` if the platform doesn't report the orientation of the touches.
+    pub orientation: Option<f64>,
+}
+
 /// Describes the force of a touch event
 #[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum Force {
     /// On iOS, the force is calibrated so that the same number corresponds to
     /// roughly the same amount of pressure on the screen regardless of the
@@ -919,6 +1160,103 @@ impl Force {
     }
 }
 
+/// A tool used on a graphics tablet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct TabletTool {
+    /// The physical type of the tool.
+    pub kind: TabletToolKind,
+    /// The unique serial number of the tool, `0` when the tool doesn't report one.
+    ///
+    /// Along with the [`kind`](Self::kind), it tells the tools apart, e.g. to remember the
+    /// brush picked for each pen.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **X11:** Always `0`.
+    pub serial: u64,
+}
+
+/// The physical type of a [`TabletTool`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum TabletToolKind {
+    Pen,
+    Eraser,
+    Brush,
+    Pencil,
+    Airbrush,
+    Finger,
+    Mouse,
+    Lens,
+    /// The platform didn't tell the type of the tool.
+    Unknown,
+}
+
+/// Describes what a [`TabletTool`] did.
+///
+/// The tool comes into proximity with a [`TabletToolEvent::ProximityIn`], followed by the
+/// [`TabletToolEvent::Motion`] giving its position and axes. While in proximity, the tip may
+/// touch the tablet and the buttons may be pressed, until the tool leaves with a
+/// [`TabletToolEvent::ProximityOut`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum TabletToolEvent {
+    /// The tool came into proximity of the tablet, over the window.
+    ProximityIn,
+
+    /// The tool left the proximity of the tablet, or moved away from the window.
+    ProximityOut,
+
+    /// The tip of the tool touched the tablet, or was lifted from it.
+    Tip(ElementState),
+
+    /// A button of the tool was pressed or released.
+    Button { button: TabletToolButton, state: ElementState },
+
+    /// The tool moved, or the values of its axes changed.
+    Motion {
+        /// The position of the tool, relative to the top-left corner of the window.
+        position: PhysicalPosition<f64>,
+        axes: TabletToolAxes,
+    },
+}
+
+/// A button of a [`TabletTool`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum TabletToolButton {
+    /// The first button on the barrel of the tool, usually the closest to the tip.
+    Primary,
+    /// The second button on the barrel of the tool.
+    Secondary,
+    /// The third button on the barrel of the tool.
+    Tertiary,
+    /// Another button, identified by its platform-specific code.
+    Other(u32),
+}
+
+/// The state of the axes of a [`TabletTool`].
+///
+/// The axes the tool lacks are `None`.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct TabletToolAxes {
+    /// The pressure of the tip, between `0.0` and `1.0`.
+    pub pressure: Option<f64>,
+    /// The distance of the tool from the tablet, between `0.0` and `1.0`.
+    ///
+    /// The range isn't calibrated, `1.0` is only the farthest the tablet can sense.
+    pub distance: Option<f64>,
+    /// The tilt of the tool in degrees, from the normal of the tablet towards the positive X
+    /// and Y axes.
+    pub tilt: Option<(f64, f64)>,
+    /// The clockwise rotation of the tool around its own axis, in degrees.
+    pub rotation: Option<f64>,
+    /// The position of the slider on the tool, between `-1.0` and `1.0`.
+    pub slider: Option<f64>,
+}
+
 /// Identifier for a specific analog axis on some device.
 pub type AxisId = u32;
 
@@ -1017,6 +1355,228 @@ impl PartialEq for InnerSizeWriter {
     }
 }
 
+#[cfg(feature = "serde")]
+mod event_serde {
+    use std::sync::{OnceLock, Weak};
+    #[cfg(not(web_platform))]
+    use std::time::{Duration, Instant};
+
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use smol_str::SmolStr;
+    #[cfg(web_platform)]
+    use web_time::{Duration, Instant};
+
+    use super::{DeviceId, ElementState, InnerSizeWriter, KeyEvent, StartCause};
+    use crate::keyboard::{Key, KeyLocation, PhysicalKey};
+    use crate::platform_impl;
+
+    pub fn detached_inner_size_writer() -> InnerSizeWriter {
+        InnerSizeWriter { new_inner_size: Weak::new() }
+    }
+
+    // NOTE: The instants are serialized relative to the first one serialized or deserialized in
+    // the process, since they don't have any absolute representation.
+    fn anchor() -> Instant {
+        static ANCHOR: OnceLock<Instant> = OnceLock::new();
+        *ANCHOR.get_or_init(Instant::now)
+    }
+
+    fn from_instant(instant: Instant) -> Duration {
+        instant.saturating_duration_since(anchor())
+    }
+
+    fn to_instant(duration: Duration) -> Instant {
+        anchor() + duration
+    }
+
+    #[derive(Serialize, Deserialize)]
+    #[serde(rename = "StartCause")]
+    enum StartCauseSerialize {
+        ResumeTimeReached { start: Duration, requested_resume: Duration },
+        WaitCancelled { start: Duration, requested_resume: Option<Duration> },
+        Poll,
+        Init,
+    }
+
+    impl Serialize for StartCause {
+        fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+        {
+            let s = match *self {
+                StartCause::ResumeTimeReached { start, requested_resume } => {
+                    StartCauseSerialize::ResumeTimeReached {
+                        start: from_instant(start),
+                        requested_resume: from_instant(requested_resume),
+                    }
+                },
+                StartCause::WaitCancelled { start, requested_resume } => {
+                    StartCauseSerialize::WaitCancelled {
+                        start: from_instant(start),
+                        requested_resume: requested_resume.map(from_instant),
+                    }
+                },
+                StartCause::Poll => StartCauseSerialize::Poll,
+                StartCause::Init => StartCauseSerialize::Init,
+            };
+            s.serialize(serializer)
+        }
+    }
+
+    impl<'de> Deserialize<'de> for StartCause {
+        fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            Ok(match StartCauseSerialize::deserialize(deserializer)? {
+                StartCauseSerialize::ResumeTimeReached { start, requested_resume } => {
+                    StartCause::ResumeTimeReached {
+                        start: to_instant(start),
+                        requested_resume: to_instant(requested_resume),
+                    }
+                },
+                StartCauseSerialize::WaitCancelled { start, requested_resume } => {
+                    StartCause::WaitCancelled {
+                        start: to_instant(start),
+                        requested_resume: requested_resume.map(to_instant),
+                    }
+                },
+                StartCauseSerialize::Poll => StartCause::Poll,
+                StartCauseSerialize::Init => StartCause::Init,
+            })
+        }
+    }
+
+    impl Serialize for DeviceId {
+        fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+        {
+            serializer.serialize_newtype_struct("DeviceId", &self.0.into_raw())
+        }
+    }
+
+    impl<'de> Deserialize<'de> for DeviceId {
+        fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            #[derive(Deserialize)]
+            #[serde(rename = "DeviceId")]
+            struct DeviceIdSerialize(i64);
+
+            let DeviceIdSerialize(raw) = DeviceIdSerialize::deserialize(deserializer)?;
+            platform_impl::DeviceId::from_raw(raw).map(DeviceId).ok_or_else(|| {
+                serde::de::Error::custom(format_args!("device identifier out of range: {raw}"))
+            })
+        }
+    }
+
+    // NOTE: The platform specific fields are always present, the platforms without them report
+    // the text and the logical key instead.
+    #[derive(Serialize, Deserialize)]
+    #[serde(rename = "KeyEvent")]
+    struct KeyEventSerialize {
+        physical_key: PhysicalKey,
+        logical_key: Key,
+        text: Option<SmolStr>,
+        location: KeyLocation,
+        state: ElementState,
+        repeat: bool,
+        text_with_all_modifiers: Option<SmolStr>,
+        key_without_modifiers: Key,
+    }
+
+    impl Serialize for KeyEvent {
+        fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+        {
+            #[cfg(any(
+                windows_platform,
+                macos_platform,
+                x11_platform,
+                wayland_platform,
+                orbital_platform
+            ))]
+            let (text_with_all_modifiers, key_without_modifiers) = (
+                self.platform_specific.text_with_all_modifiers.clone(),
+                self.platform_specific.key_without_modifiers.clone(),
+            );
+            #[cfg(not(any(
+                windows_platform,
+                macos_platform,
+                x11_platform,
+                wayland_platform,
+                orbital_platform
+            )))]
+            let (text_with_all_modifiers, key_without_modifiers) =
+                (self.text.clone(), self.logical_key.clone());
+
+            let s = KeyEventSerialize {
+                physical_key: self.physical_key,
+                logical_key: self.logical_key.clone(),
+                text: self.text.clone(),
+                location: self.location,
+                state: self.state,
+                repeat: self.repeat,
+                text_with_all_modifiers,
+                key_without_modifiers,
+            };
+            s.serialize(serializer)
+        }
+    }
+
+    impl<'de> Deserialize<'de> for KeyEvent {
+        fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            let KeyEventSerialize {
+                physical_key,
+                logical_key,
+                text,
+                location,
+                state,
+                repeat,
+                text_with_all_modifiers,
+                key_without_modifiers,
+            } = KeyEventSerialize::deserialize(deserializer)?;
+
+            #[cfg(any(
+                windows_platform,
+                macos_platform,
+                x11_platform,
+                wayland_platform,
+                orbital_platform
+            ))]
+            let platform_specific =
+                platform_impl::KeyEventExtra { text_with_all_modifiers, key_without_modifiers };
+            #[cfg(not(any(
+                windows_platform,
+                macos_platform,
+                x11_platform,
+                wayland_platform,
+                orbital_platform
+            )))]
+            let platform_specific = {
+                let _ = (text_with_all_modifiers, key_without_modifiers);
+                platform_impl::KeyEventExtra {}
+            };
+
+            Ok(KeyEvent {
+                physical_key,
+                logical_key,
+                text,
+                location,
+                state,
+                repeat,
+                platform_specific,
+            })
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::dpi::PhysicalPosition;
@@ -1050,15 +1610,34 @@ mod tests {
 
                 with_window_event(CloseRequested);
                 with_window_event(Destroyed);
+                with_window_event(PopupDismissed);
                 with_window_event(Focused(true));
                 with_window_event(Moved((0, 0).into()));
                 with_window_event(Resized((0, 0).into()));
                 with_window_event(DroppedFile("x.txt".into()));
                 with_window_event(HoveredFile("x.txt".into()));
                 with_window_event(HoveredFileCancelled);
+                with_window_event(DragEntered {
+                    mime_types: vec![crate::dnd::URI_LIST_MIME_TYPE.to_owned()],
+                    actions: crate::dnd::DndActions::COPY,
+                    position: (0, 0).into(),
+                });
+                with_window_event(DragMoved { position: (0, 0).into() });
+                with_window_event(DragDropped {
+                    data: crate::dnd::DropData::Text("x".into()),
+                    action: crate::dnd::DndAction::Copy,
+                    position: (0, 0).into(),
+                });
+                with_window_event(DragLeft);
+                with_window_event(DragFinished { action: Some(crate::dnd::DndAction::Move) });
+                with_window_event(ClipboardDone {
+                    serial: crate::event_loop::AsyncRequestSerial::get(),
+                    data: Some(crate::clipboard::MimeData::text("x")),
+                });
                 with_window_event(Ime(Enabled));
                 with_window_event(CursorMoved { device_id: did, position: (0, 0).into(), root_position: (0, 0).into() });
                 with_window_event(ModifiersChanged(event::Modifiers::default()));
+                with_window_event(KeyboardLayoutChanged(Default::default()));
                 with_window_event(CursorEntered { device_id: did });
                 with_window_event(CursorLeft { device_id: did });
                 with_window_event(MouseWheel {
@@ -1095,7 +1674,20 @@ mod tests {
                     location: (0.0, 0.0).into(),
                     id: 0,
                     force: Some(event::Force::Normalized(0.0)),
+                    contact: Some(event::TouchContact {
+                        major: 2.0,
+                        minor: 1.0,
+                        orientation: None,
+                    }),
                 }));
+                with_window_event(TabletTool {
+                    device_id: did,
+                    tool: event::TabletTool { kind: event::TabletToolKind::Pen, serial: 0 },
+                    event: event::TabletToolEvent::Motion {
+                        position: (0.0, 0.0).into(),
+                        axes: event::TabletToolAxes::default(),
+                    },
+                });
                 with_window_event(ThemeChanged(crate::window::Theme::Light));
                 with_window_event(Occluded(true));
             }
@@ -1184,8 +1776,10 @@ mod tests {
             location: (0.0, 0.0).into(),
             id: 0,
             force: Some(event::Force::Normalized(0.0)),
+            contact: None,
         }
         .clone();
+        let _ = event::TouchContact { major: 0.0, minor: 0.0, orientation: None }.clone();
         let _ =
             event::Force::Calibrated { force: 0.0, max_possible_force: 0.0, altitude_angle: None }
                 .clone();
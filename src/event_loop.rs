@@ -18,9 +18,13 @@ use std::time::{Duration, Instant};
 #[cfg(web_platform)]
 use web_time::{Duration, Instant};
 
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
 use crate::application::ApplicationHandler;
 use crate::error::{EventLoopError, OsError};
 use crate::event::{DeviceId, Event};
+use crate::keyboard::KeyboardLayouts;
 use crate::monitor::MonitorHandle;
 use crate::platform_impl;
 use crate::window::{CustomCursor, CustomCursorSource, Theme, Window, WindowAttributes};
@@ -341,11 +345,12 @@ unsafe impl<T> rwh_05::HasRawDisplayHandle for EventLoop<T> {
 impl<T> AsFd for EventLoop<T> {
     /// Get the underlying [EventLoop]'s `fd` which you can register
     /// into other event loop, like [`calloop`] or [`mio`]. When doing so, the
-    /// loop must be polled with the [`pump_app_events`] API.
+    /// loop must be polled with the [`pump_app_events`] or the [`EventLoopExtUnix`] API.
     ///
     /// [`calloop`]: https://crates.io/crates/calloop
     /// [`mio`]: https://crates.io/crates/mio
     /// [`pump_app_events`]: crate::platform::pump_events::EventLoopExtPumpEvents::pump_app_events
+    /// [`EventLoopExtUnix`]: crate::platform::unix::EventLoopExtUnix
     fn as_fd(&self) -> BorrowedFd<'_> {
         self.event_loop.as_fd()
     }
@@ -355,11 +360,12 @@ impl<T> AsFd for EventLoop<T> {
 impl<T> AsRawFd for EventLoop<T> {
     /// Get the underlying [EventLoop]'s raw `fd` which you can register
     /// into other event loop, like [`calloop`] or [`mio`]. When doing so, the
-    /// loop must be polled with the [`pump_app_events`] API.
+    /// loop must be polled with the [`pump_app_events`] or the [`EventLoopExtUnix`] API.
     ///
     /// [`calloop`]: https://crates.io/crates/calloop
     /// [`mio`]: https://crates.io/crates/mio
     /// [`pump_app_events`]: crate::platform::pump_events::EventLoopExtPumpEvents::pump_app_events
+    /// [`EventLoopExtUnix`]: crate::platform::unix::EventLoopExtUnix
     fn as_raw_fd(&self) -> RawFd {
         self.event_loop.as_raw_fd()
     }
@@ -448,6 +454,56 @@ impl ActiveEventLoop {
         self.p.system_theme()
     }
 
+    /// Returns the keyboard layouts and the active one.
+    ///
+    /// Returns `None` if they cannot be determined, like before the keymap is known.
+    ///
+    /// See [`WindowEvent::KeyboardLayoutChanged`] to follow the changes.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Wayland:** The layouts of the first seat with a keyboard.
+    /// - **X11:** The names libxkbcommon doesn't provide are read from the XKB group names.
+    /// - **Android / iOS / macOS / Orbital / Web / Windows:** Unsupported.
+    ///
+    /// [`WindowEvent::KeyboardLayoutChanged`]: crate::event::WindowEvent::KeyboardLayoutChanged
+    pub fn keyboard_layouts(&self) -> Option<KeyboardLayouts> {
+        self.p.keyboard_layouts()
+    }
+
+    /// Schedule the timer, [`ApplicationHandler::timer_fired`] is called once the deadline is
+    /// reached, or every time the interval elapses until the timer is cancelled.
+    ///
+    /// Unlike [`ControlFlow::WaitUntil`], any number of timers could be scheduled at once.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Android / iOS / macOS / Orbital / Web / Windows:** Unsupported, the timer never fires.
+    pub fn schedule_timer(&self, schedule: TimerSchedule) -> TimerId {
+        let _span = tracing::debug_span!(
+            "winit::ActiveEventLoop::schedule_timer",
+            schedule = ?schedule
+        )
+        .entered();
+
+        let timer_id = TimerId::next();
+        self.p.schedule_timer(timer_id, schedule);
+        timer_id
+    }
+
+    /// Cancel the timer scheduled with [`ActiveEventLoop::schedule_timer`].
+    ///
+    /// Cancelling the timer which won't fire anymore has no effect.
+    pub fn cancel_timer(&self, timer_id: TimerId) {
+        let _span = tracing::debug_span!(
+            "winit::ActiveEventLoop::cancel_timer",
+            timer_id = ?timer_id
+        )
+        .entered();
+
+        self.p.cancel_timer(timer_id)
+    }
+
     /// Sets the [`ControlFlow`].
     pub fn set_control_flow(&self, control_flow: ControlFlow) {
         self.p.set_control_flow(control_flow)
@@ -609,6 +665,56 @@ pub enum DeviceEvents {
     Never,
 }
 
+/// When the timer fires, see [`ActiveEventLoop::schedule_timer`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TimerSchedule {
+    /// Fire once, when the deadline is reached.
+    Deadline(Instant),
+    /// Fire repeatedly, every time the interval elapses.
+    Interval(Duration),
+}
+
+/// A unique identifier of the timer scheduled with [`ActiveEventLoop::schedule_timer`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct TimerId(usize);
+
+impl TimerId {
+    pub(crate) fn next() -> Self {
+        static CURRENT_ID: AtomicUsize = AtomicUsize::new(0);
+        Self(CURRENT_ID.fetch_add(1, Ordering::Relaxed))
+    }
+}
+
+/// A unique identifier of the file descriptor registered with
+/// [`ActiveEventLoopExtUnix::register_fd`].
+///
+/// [`ActiveEventLoopExtUnix::register_fd`]: crate::platform::unix::ActiveEventLoopExtUnix::register_fd
+#[cfg_attr(not(any(x11_platform, wayland_platform)), allow(rustdoc::broken_intra_doc_links))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SourceToken(usize);
+
+impl SourceToken {
+    #[cfg(any(x11_platform, wayland_platform))]
+    pub(crate) fn next() -> Self {
+        static CURRENT_TOKEN: AtomicUsize = AtomicUsize::new(0);
+        Self(CURRENT_TOKEN.fetch_add(1, Ordering::Relaxed))
+    }
+}
+
+bitflags::bitflags! {
+    /// The readiness of the registered file descriptor, see [`ApplicationHandler::fd_ready`].
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(transparent))]
+    pub struct Interest: u8 {
+        /// The file descriptor is readable, or reached the end of file or an error.
+        const READABLE = 1 << 0;
+        /// The file descriptor is writable.
+        const WRITABLE = 1 << 1;
+    }
+}
+
 /// A unique identifier of the winit's async request.
 ///
 /// This could be used to identify the async request once it's done
@@ -619,6 +725,7 @@ pub enum DeviceEvents {
 /// Then once event is arriving the working list is being traversed and a job
 /// executed and removed from the list.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(transparent))]
 pub struct AsyncRequestSerial {
     serial: usize,
 }
@@ -646,6 +753,8 @@ pub(crate) fn dispatch_event_for_app<T: 'static, A: ApplicationHandler<T>>(
         Event::NewEvents(cause) => app.new_events(event_loop, cause),
         Event::WindowEvent { window_id, event } => app.window_event(event_loop, window_id, event),
         Event::DeviceEvent { device_id, event } => app.device_event(event_loop, device_id, event),
+        Event::TimerFired(timer_id) => app.timer_fired(event_loop, timer_id),
+        Event::FdReady { token, readiness } => app.fd_ready(event_loop, token, readiness),
         Event::UserEvent(event) => app.user_event(event_loop, event),
         Event::Suspended => app.suspended(event_loop),
         Event::Resumed => app.resumed(event_loop),
@@ -0,0 +1,460 @@
+//! Driving the application with futures.
+//!
+//! The [`AsyncEventLoop`] polls the futures spawned with [`LocalHandle::spawn_local`] on the
+//! main thread, from within the event loop callbacks. Thus the futures can create windows with
+//! [`LocalHandle::create_window`], and render synchronously once awaiting
+//! [`LocalHandle::redraw_requested`] resolves, like the [`ApplicationHandler`] would.
+//!
+//! The events are exposed as a [`Stream`] with [`LocalHandle::events`].
+//!
+//! ```no_run
+//! use deft_winit::event::{Event, WindowEvent};
+//! use deft_winit::event_loop::EventLoop;
+//! use deft_winit::executor::AsyncEventLoop;
+//! use deft_winit::window::Window;
+//!
+//! let event_loop = EventLoop::with_user_event().build().unwrap();
+//! let event_loop = AsyncEventLoop::<()>::new(event_loop);
+//! let handle = event_loop.handle();
+//!
+//! event_loop
+//!     .run(async move {
+//!         let window = handle.create_window(Window::default_attributes()).unwrap();
+//!
+//!         let draw_handle = handle.clone();
+//!         let window_id = window.id();
+//!         handle.spawn_local(async move {
+//!             loop {
+//!                 draw_handle.redraw_requested(window_id).await;
+//!                 // Draw.
+//!             }
+//!         });
+//!
+//!         let mut events = handle.events();
+//!         while let Some(event) = events.next_event().await {
+//!             if let Event::WindowEvent { event: WindowEvent::CloseRequested, .. } = event {
+//!                 break;
+//!             }
+//!         }
+//!     })
+//!     .unwrap();
+//! ```
+//!
+//! [`ApplicationHandler`]: crate::application::ApplicationHandler
+
+use std::cell::{Cell, RefCell};
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::rc::Rc;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Wake, Waker};
+
+use futures_core::Stream;
+
+use crate::error::{EventLoopError, OsError};
+use crate::event::{Event, WindowEvent};
+use crate::event_loop::{ActiveEventLoop, EventLoop, EventLoopClosed, EventLoopProxy};
+use crate::window::{Window, WindowAttributes, WindowId};
+
+/// The user event of the [`EventLoop`] driven by the [`AsyncEventLoop`].
+///
+/// The user events are sent with the [`AsyncEventLoopProxy`], and are delivered as
+/// [`Event::UserEvent`] by the [`EventStream`].
+pub struct AsyncEvent<T>(Message<T>);
+
+impl<T> fmt::Debug for AsyncEvent<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad("AsyncEvent { .. }")
+    }
+}
+
+enum Message<T> {
+    /// The tasks were woken up from another thread.
+    Wake,
+    User(T),
+}
+
+type Task = Pin<Box<dyn Future<Output = ()>>>;
+
+#[derive(Default)]
+struct Ready {
+    tasks: VecDeque<usize>,
+    /// Whether the tasks are being polled, so the new ones are picked up without a wake up.
+    polling: bool,
+}
+
+/// The tasks woken up, shared with the wakers which may live on other threads.
+struct ReadyQueue<T: 'static> {
+    ready: Mutex<Ready>,
+    proxy: Mutex<EventLoopProxy<AsyncEvent<T>>>,
+}
+
+impl<T: Send> ReadyQueue<T> {
+    fn push(&self, id: usize) {
+        let mut ready = self.ready.lock().unwrap();
+        if ready.tasks.contains(&id) {
+            return;
+        }
+
+        ready.tasks.push_back(id);
+        if !ready.polling && ready.tasks.len() == 1 {
+            let _ = self.proxy.lock().unwrap().send_event(AsyncEvent(Message::Wake));
+        }
+    }
+
+    fn pop(&self) -> Option<usize> {
+        let mut ready = self.ready.lock().unwrap();
+        let id = ready.tasks.pop_front();
+        ready.polling = id.is_some();
+        id
+    }
+}
+
+struct TaskWaker<T: 'static> {
+    id: usize,
+    queue: Arc<ReadyQueue<T>>,
+}
+
+impl<T: Send> Wake for TaskWaker<T> {
+    fn wake(self: Arc<Self>) {
+        self.wake_by_ref()
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        self.queue.push(self.id)
+    }
+}
+
+struct EventQueue<T: 'static> {
+    events: VecDeque<Event<T>>,
+    /// Whether the stream is alive, the events are only queued while it is.
+    stream_alive: bool,
+    /// The waker of the task polling the stream.
+    waker: Option<Waker>,
+    closed: bool,
+}
+
+#[derive(Default)]
+struct RedrawSlot {
+    fired: Cell<bool>,
+    waker: Cell<Option<Waker>>,
+}
+
+struct Shared<T: 'static> {
+    tasks: RefCell<HashMap<usize, Task>>,
+    next_task: Cell<usize>,
+    queue: Arc<ReadyQueue<T>>,
+    /// The event loop, set while the tasks are polled from within its callback.
+    event_loop: Cell<Option<*const ActiveEventLoop>>,
+    events: RefCell<EventQueue<T>>,
+    redraws: RefCell<Vec<(WindowId, Rc<RedrawSlot>)>>,
+}
+
+impl<T: Send + 'static> Shared<T> {
+    fn handle_event(&self, event: Event<T>) {
+        match event {
+            Event::WindowEvent { window_id, event: WindowEvent::RedrawRequested } => {
+                self.redraws.borrow_mut().retain(|(id, slot)| {
+                    if *id != window_id {
+                        // Forget the slots of the dropped futures.
+                        return Rc::strong_count(slot) > 1;
+                    }
+
+                    slot.fired.set(true);
+                    if let Some(waker) = slot.waker.take() {
+                        waker.wake();
+                    }
+                    false
+                });
+            },
+            Event::LoopExiting => {
+                let mut events = self.events.borrow_mut();
+                events.closed = true;
+                if let Some(waker) = events.waker.take() {
+                    waker.wake();
+                }
+            },
+            _ => (),
+        }
+
+        let mut events = self.events.borrow_mut();
+        if events.stream_alive {
+            events.events.push_back(event);
+            if let Some(waker) = events.waker.take() {
+                waker.wake();
+            }
+        }
+    }
+
+    fn poll_tasks(&self, event_loop: &ActiveEventLoop) {
+        // The callback may be reentered while polling, e.g. when creating a window.
+        let previous = self.event_loop.replace(Some(event_loop as *const _));
+
+        while let Some(id) = self.queue.pop() {
+            // The task is taken out while polled, so it can spawn the other ones.
+            let Some(mut task) = self.tasks.borrow_mut().remove(&id) else {
+                continue;
+            };
+
+            let waker = Waker::from(Arc::new(TaskWaker { id, queue: self.queue.clone() }));
+            if task.as_mut().poll(&mut Context::from_waker(&waker)).is_pending() {
+                self.tasks.borrow_mut().insert(id, task);
+            }
+        }
+
+        self.event_loop.set(previous);
+    }
+}
+
+/// The [`EventLoop`] running the futures.
+///
+/// See the [module-level docs](self) for more information.
+pub struct AsyncEventLoop<T: 'static> {
+    event_loop: EventLoop<AsyncEvent<T>>,
+    shared: Rc<Shared<T>>,
+}
+
+impl<T: Send + 'static> AsyncEventLoop<T> {
+    pub fn new(event_loop: EventLoop<AsyncEvent<T>>) -> Self {
+        let queue = Arc::new(ReadyQueue {
+            ready: Mutex::default(),
+            proxy: Mutex::new(event_loop.create_proxy()),
+        });
+        let shared = Rc::new(Shared {
+            tasks: RefCell::default(),
+            next_task: Cell::new(0),
+            queue,
+            event_loop: Cell::new(None),
+            events: RefCell::new(EventQueue {
+                events: VecDeque::new(),
+                stream_alive: false,
+                waker: None,
+                closed: false,
+            }),
+            redraws: RefCell::default(),
+        });
+
+        Self { event_loop, shared }
+    }
+
+    /// The handle to spawn the futures and to access the event loop from within them.
+    pub fn handle(&self) -> LocalHandle<T> {
+        LocalHandle { shared: self.shared.clone() }
+    }
+
+    /// Creates an [`AsyncEventLoopProxy`] that can be used to dispatch user events
+    /// to the main event loop, possibly from another thread.
+    pub fn create_proxy(&self) -> AsyncEventLoopProxy<T> {
+        AsyncEventLoopProxy { proxy: self.event_loop.create_proxy() }
+    }
+
+    /// Run the event loop until the `future` completes.
+    ///
+    /// The other futures spawned are dropped once the event loop exits.
+    ///
+    /// See [`EventLoop::run_app`] for the platform-specific behavior.
+    #[cfg(not(any(
+        all(web_platform, target_feature = "exception-handling"),
+        target_env = "ohos"
+    )))]
+    pub fn run<F>(self, future: F) -> Result<(), EventLoopError>
+    where
+        F: Future<Output = ()> + 'static,
+    {
+        let handle = self.handle();
+        self.handle().spawn_local(async move {
+            future.await;
+            handle.with_event_loop(ActiveEventLoop::exit);
+        });
+
+        let shared = self.shared.clone();
+        let result = self.event_loop.event_loop.run(move |event, event_loop| {
+            let event = match event {
+                Event::UserEvent(AsyncEvent(Message::Wake)) => None,
+                Event::UserEvent(AsyncEvent(Message::User(event))) => Some(Event::UserEvent(event)),
+                event => event.map_nonuser_event().ok(),
+            };
+
+            if let Some(event) = event {
+                shared.handle_event(event);
+            }
+
+            shared.poll_tasks(event_loop);
+        });
+
+        // The tasks may hold the handles, break the cycles.
+        let tasks = self.shared.tasks.take();
+        drop(tasks);
+
+        result
+    }
+}
+
+/// The handle to the [`AsyncEventLoop`], which is only available on the main thread.
+pub struct LocalHandle<T: 'static> {
+    shared: Rc<Shared<T>>,
+}
+
+impl<T> Clone for LocalHandle<T> {
+    fn clone(&self) -> Self {
+        Self { shared: self.shared.clone() }
+    }
+}
+
+impl<T: Send + 'static> LocalHandle<T> {
+    /// Spawn the future, polled on the main thread from within the event loop callbacks.
+    pub fn spawn_local<F>(&self, future: F)
+    where
+        F: Future<Output = ()> + 'static,
+    {
+        let id = self.shared.next_task.get();
+        self.shared.next_task.set(id + 1);
+        self.shared.tasks.borrow_mut().insert(id, Box::pin(future));
+        self.shared.queue.push(id);
+    }
+
+    /// The stream of the events.
+    ///
+    /// There's a single stream of the events at a time, and the events are only queued while
+    /// it's alive. The stream ends once the event loop exits.
+    ///
+    /// # Panics
+    ///
+    /// Panics when the stream created earlier is still alive.
+    pub fn events(&self) -> EventStream<T> {
+        let mut events = self.shared.events.borrow_mut();
+        assert!(!events.stream_alive, "only a single stream of the events may be alive");
+        events.stream_alive = true;
+        EventStream { shared: self.shared.clone() }
+    }
+
+    /// Resolves on the next [`WindowEvent::RedrawRequested`] for the window.
+    ///
+    /// The future is polled from within the callback delivering the event, so the window can
+    /// be drawn synchronously.
+    pub fn redraw_requested(&self, window_id: WindowId) -> RedrawRequested {
+        let slot = Rc::new(RedrawSlot::default());
+        self.shared.redraws.borrow_mut().push((window_id, slot.clone()));
+        RedrawRequested { slot }
+    }
+
+    /// Run the closure with the [`ActiveEventLoop`].
+    ///
+    /// # Panics
+    ///
+    /// Panics when not called from within the spawned futures.
+    pub fn with_event_loop<R>(&self, f: impl FnOnce(&ActiveEventLoop) -> R) -> R {
+        let event_loop =
+            self.shared.event_loop.get().expect("the event loop is only available to the futures");
+        // SAFETY: The event loop is only set while its callback polls the futures.
+        f(unsafe { &*event_loop })
+    }
+
+    /// See [`ActiveEventLoop::create_window`].
+    ///
+    /// # Panics
+    ///
+    /// Panics when not called from within the spawned futures.
+    pub fn create_window(&self, window_attributes: WindowAttributes) -> Result<Window, OsError> {
+        self.with_event_loop(|event_loop| event_loop.create_window(window_attributes))
+    }
+}
+
+/// The [`Stream`] of the events, created with [`LocalHandle::events`].
+pub struct EventStream<T: 'static> {
+    shared: Rc<Shared<T>>,
+}
+
+impl<T> EventStream<T> {
+    /// Resolves to the next event, or `None` once the event loop exits.
+    pub fn next_event(&mut self) -> NextEvent<'_, T> {
+        NextEvent { stream: self }
+    }
+}
+
+impl<T> Stream for EventStream<T> {
+    type Item = Event<T>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let mut events = self.shared.events.borrow_mut();
+        if let Some(event) = events.events.pop_front() {
+            Poll::Ready(Some(event))
+        } else if events.closed {
+            Poll::Ready(None)
+        } else {
+            events.waker = Some(cx.waker().clone());
+            Poll::Pending
+        }
+    }
+}
+
+impl<T> Drop for EventStream<T> {
+    fn drop(&mut self) {
+        let mut events = self.shared.events.borrow_mut();
+        events.stream_alive = false;
+        events.waker = None;
+        events.events.clear();
+    }
+}
+
+/// The future returned by [`EventStream::next_event`].
+pub struct NextEvent<'a, T: 'static> {
+    stream: &'a mut EventStream<T>,
+}
+
+impl<T> Future for NextEvent<'_, T> {
+    type Output = Option<Event<T>>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        Pin::new(&mut *self.stream).poll_next(cx)
+    }
+}
+
+/// The future returned by [`LocalHandle::redraw_requested`].
+pub struct RedrawRequested {
+    slot: Rc<RedrawSlot>,
+}
+
+impl Future for RedrawRequested {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        if self.slot.fired.get() {
+            Poll::Ready(())
+        } else {
+            self.slot.waker.set(Some(cx.waker().clone()));
+            Poll::Pending
+        }
+    }
+}
+
+/// Used to send custom events to the [`AsyncEventLoop`].
+pub struct AsyncEventLoopProxy<T: 'static> {
+    proxy: EventLoopProxy<AsyncEvent<T>>,
+}
+
+impl<T: 'static> Clone for AsyncEventLoopProxy<T> {
+    fn clone(&self) -> Self {
+        Self { proxy: self.proxy.clone() }
+    }
+}
+
+impl<T: 'static> AsyncEventLoopProxy<T> {
+    /// See [`EventLoopProxy::send_event`].
+    pub fn send_event(&self, event: T) -> Result<(), EventLoopClosed<T>> {
+        self.proxy.send_event(AsyncEvent(Message::User(event))).map_err(|EventLoopClosed(event)| {
+            match event.0 {
+                Message::User(event) => EventLoopClosed(event),
+                Message::Wake => unreachable!(),
+            }
+        })
+    }
+}
+
+impl<T: 'static> fmt::Debug for AsyncEventLoopProxy<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad("AsyncEventLoopProxy { .. }")
+    }
+}
@@ -1698,6 +1698,24 @@ pub enum KeyLocation {
     Numpad,
 }
 
+/// The layouts of the keyboard and the one currently in use.
+///
+/// See [`ActiveEventLoop::keyboard_layouts`] and [`WindowEvent::KeyboardLayoutChanged`].
+///
+/// [`ActiveEventLoop::keyboard_layouts`]: crate::event_loop::ActiveEventLoop::keyboard_layouts
+/// [`WindowEvent::KeyboardLayoutChanged`]: crate::event::WindowEvent::KeyboardLayoutChanged
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct KeyboardLayouts {
+    /// The human readable names of the layouts, like `English (US)`.
+    ///
+    /// The name is empty when the layout doesn't have one.
+    pub names: Vec<String>,
+
+    /// The index in [`names`][Self::names] of the active layout.
+    pub active: usize,
+}
+
 bitflags! {
     /// Represents the current state of the keyboard modifiers
     ///
@@ -1757,6 +1775,7 @@ pub enum ModifiersKeyState {
 // on macOS due to their AltGr/Option situation.
 bitflags! {
     #[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(transparent))]
     pub(crate) struct ModifiersKeys: u8 {
         const LSHIFT   = 0b0000_0001;
         const RSHIFT   = 0b0000_0010;
@@ -154,6 +154,7 @@
 //! * `rwh_06`: Implement `raw-window-handle v0.6` traits.
 //! * `serde`: Enables serialization/deserialization of certain types with [Serde](https://crates.io/crates/serde).
 //! * `mint`: Enables mint (math interoperability standard types) conversions.
+//! * `futures`: Enables the `executor` module, driving the application with futures.
 //!
 //! See the [`platform`] module for documentation on platform-specific cargo
 //! features.
@@ -200,15 +201,20 @@ pub use dpi;
 pub mod application;
 #[cfg(any(doc, doctest, test))]
 pub mod changelog;
+pub mod clipboard;
 #[macro_use]
 pub mod error;
 mod cursor;
+pub mod dnd;
 pub mod event;
 pub mod event_loop;
+#[cfg(feature = "futures")]
+pub mod executor;
 mod icon;
 pub mod keyboard;
 pub mod monitor;
 mod platform_impl;
+pub mod record;
 mod utils;
 pub mod window;
 
@@ -0,0 +1,65 @@
+//! Access to the clipboard and the primary selection.
+//!
+//! The data is exchanged as a list of [`MimeData`], so the same contents could be offered in
+//! several representations, letting the receiving client pick the one it understands best.
+//!
+//! Reading the clipboard is asynchronous, the result is delivered with
+//! [`WindowEvent::ClipboardDone`] carrying the serial returned by
+//! [`WindowExtClipboard::request_clipboard`].
+//!
+//! ## Platform-specific
+//!
+//! - **Wayland:** The compositor only allows setting the selection in response to user input,
+//!   thus [`WindowExtClipboard::set_clipboard`] should be called from the keyboard or mouse
+//!   input handlers.
+//!
+//! [`WindowEvent::ClipboardDone`]: crate::event::WindowEvent::ClipboardDone
+
+use crate::clipboard::{MimeData, Selection};
+use crate::error::NotSupportedError;
+use crate::event_loop::AsyncRequestSerial;
+use crate::window::Window;
+
+pub trait WindowExtClipboard {
+    /// Offer the `data` through the given `selection`.
+    ///
+    /// The window stays the owner of the selection until some other client takes it over.
+    /// Passing an empty `data` releases the selection owned by this window.
+    fn set_clipboard(
+        &self,
+        selection: Selection,
+        data: Vec<MimeData>,
+    ) -> Result<(), NotSupportedError>;
+
+    /// Request the contents of the given `selection`.
+    ///
+    /// The `mime_types` are ordered by preference, the first one offered by the owner of the
+    /// selection is transferred. The contents will be delivered inside
+    /// [`WindowEvent::ClipboardDone`] with the returned serial.
+    ///
+    /// [`WindowEvent::ClipboardDone`]: crate::event::WindowEvent::ClipboardDone
+    fn request_clipboard(
+        &self,
+        selection: Selection,
+        mime_types: &[&str],
+    ) -> Result<AsyncRequestSerial, NotSupportedError>;
+}
+
+impl WindowExtClipboard for Window {
+    fn set_clipboard(
+        &self,
+        selection: Selection,
+        data: Vec<MimeData>,
+    ) -> Result<(), NotSupportedError> {
+        self.window.set_clipboard(selection, data)
+    }
+
+    fn request_clipboard(
+        &self,
+        selection: Selection,
+        mime_types: &[&str],
+    ) -> Result<AsyncRequestSerial, NotSupportedError> {
+        let mime_types = mime_types.iter().map(|mime_type| mime_type.to_string()).collect();
+        self.window.request_clipboard(selection, mime_types)
+    }
+}
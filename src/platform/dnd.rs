@@ -0,0 +1,71 @@
+//! Drag and drop from and into the window.
+//!
+//! The window is notified about the hovering drag with [`WindowEvent::DragEntered`], which
+//! carries the MIME types the data is offered in. Until the window responds, only the files are
+//! accepted, to keep [`WindowEvent::DroppedFile`] working.
+//!
+//! The response could be updated at any moment while the drag is hovering the window, for
+//! example on [`WindowEvent::DragMoved`] to accept the drop only over some area. Once the data
+//! is dropped, it's delivered with [`WindowEvent::DragDropped`] in the accepted MIME type.
+//!
+//! The data is dragged out of the window with [`WindowExtDragDrop::start_drag`], the
+//! outcome is delivered with [`WindowEvent::DragFinished`].
+//!
+//! [`WindowEvent::DragEntered`]: crate::event::WindowEvent::DragEntered
+//! [`WindowEvent::DragMoved`]: crate::event::WindowEvent::DragMoved
+//! [`WindowEvent::DragDropped`]: crate::event::WindowEvent::DragDropped
+//! [`WindowEvent::DroppedFile`]: crate::event::WindowEvent::DroppedFile
+//! [`WindowEvent::DragFinished`]: crate::event::WindowEvent::DragFinished
+
+use crate::dnd::{DndAction, DndActions, DragData};
+use crate::error::NotSupportedError;
+use crate::platform_impl::DropResponse;
+use crate::window::Window;
+
+pub trait WindowExtDragDrop {
+    /// Accept the drop hovering the window in the given MIME type, performing the `action`.
+    ///
+    /// The drop is rejected when the `mime_type` is not offered by the source.
+    fn accept_drop(&self, mime_type: &str, action: DndAction) -> Result<(), NotSupportedError>;
+
+    /// Reject the drop hovering the window.
+    fn reject_drop(&self) -> Result<(), NotSupportedError>;
+
+    /// Start dragging the `data` out of the window, letting the target perform one of the
+    /// `allowed_actions`.
+    ///
+    /// The drag follows the pointer, so it should be started while the mouse button is held,
+    /// usually in response to [`WindowEvent::CursorMoved`]. The target could be any other client
+    /// or one of our own windows. The outcome is delivered with [`WindowEvent::DragFinished`].
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **X11:** The drag is only tracked while the button pressed inside the window is held, and
+    ///   dropped once it's released.
+    ///
+    /// [`WindowEvent::CursorMoved`]: crate::event::WindowEvent::CursorMoved
+    /// [`WindowEvent::DragFinished`]: crate::event::WindowEvent::DragFinished
+    fn start_drag(
+        &self,
+        data: DragData,
+        allowed_actions: DndActions,
+    ) -> Result<(), NotSupportedError>;
+}
+
+impl WindowExtDragDrop for Window {
+    fn accept_drop(&self, mime_type: &str, action: DndAction) -> Result<(), NotSupportedError> {
+        self.window.set_drop_response(DropResponse::Accept(mime_type.to_owned(), action))
+    }
+
+    fn reject_drop(&self) -> Result<(), NotSupportedError> {
+        self.window.set_drop_response(DropResponse::Reject)
+    }
+
+    fn start_drag(
+        &self,
+        data: DragData,
+        allowed_actions: DndActions,
+    ) -> Result<(), NotSupportedError> {
+        self.window.start_drag(data, allowed_actions)
+    }
+}
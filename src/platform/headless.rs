@@ -0,0 +1,201 @@
+//! # Headless
+//!
+//! The headless backend doesn't need a display server: it creates virtual windows on virtual
+//! monitors, and only delivers the events caused by the application itself, along with the
+//! ones injected with [`EventLoopExtHeadless`] or [`ActiveEventLoopExtHeadless`]. Thus the
+//! [`ApplicationHandler`] implementations can be tested deterministically, e.g. by driving the
+//! event loop with [`pump_app_events`].
+//!
+//! The window setters are honoured, so the getters report what was set, and the changes are
+//! reported with the matching [`WindowEvent`]s, e.g. [`Window::request_inner_size`] resizes the
+//! window right away, and delivers [`WindowEvent::Resized`].
+//!
+//! The headless event loop may be created on any thread, so it can be used from the tests.
+//!
+//! There are no raw window or display handles: the `rwh_06` getters return
+//! `HandleError::NotSupported`, while the infallible `rwh_04` and `rwh_05` ones panic.
+//!
+//! ```no_run
+//! use std::time::Duration;
+//!
+//! use deft_winit::application::ApplicationHandler;
+//! use deft_winit::event::WindowEvent;
+//! use deft_winit::event_loop::{ActiveEventLoop, EventLoop};
+//! use deft_winit::platform::headless::{EventLoopBuilderExtHeadless, EventLoopExtHeadless};
+//! use deft_winit::platform::pump_events::EventLoopExtPumpEvents;
+//! use deft_winit::window::{Window, WindowId};
+//!
+//! #[derive(Default)]
+//! struct App {
+//!     window: Option<Window>,
+//!     closed: bool,
+//! }
+//!
+//! impl ApplicationHandler for App {
+//!     fn resumed(&mut self, event_loop: &ActiveEventLoop) {
+//!         self.window = Some(event_loop.create_window(Window::default_attributes()).unwrap());
+//!     }
+//!
+//!     fn window_event(&mut self, _: &ActiveEventLoop, _: WindowId, event: WindowEvent) {
+//!         if event == WindowEvent::CloseRequested {
+//!             self.closed = true;
+//!         }
+//!     }
+//! }
+//!
+//! let mut event_loop = EventLoop::builder().with_headless().build().unwrap();
+//! let mut app = App::default();
+//! event_loop.pump_app_events(Some(Duration::ZERO), &mut app);
+//!
+//! let window_id = app.window.as_ref().unwrap().id();
+//! event_loop.inject_window_event(window_id, WindowEvent::CloseRequested).unwrap();
+//! event_loop.pump_app_events(Some(Duration::ZERO), &mut app);
+//! assert!(app.closed);
+//! ```
+//!
+//! [`ApplicationHandler`]: crate::application::ApplicationHandler
+//! [`pump_app_events`]: crate::platform::pump_events::EventLoopExtPumpEvents::pump_app_events
+//! [`Window::request_inner_size`]: crate::window::Window::request_inner_size
+
+use crate::dpi::{PhysicalPosition, PhysicalSize};
+use crate::error::NotSupportedError;
+use crate::event::{DeviceEvent, DeviceId, WindowEvent};
+use crate::event_loop::{ActiveEventLoop, EventLoop, EventLoopBuilder};
+use crate::window::WindowId;
+
+/// The description of a virtual monitor.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VirtualMonitor {
+    pub name: String,
+    pub position: PhysicalPosition<i32>,
+    pub size: PhysicalSize<u32>,
+    pub scale_factor: f64,
+    pub refresh_rate_millihertz: u32,
+}
+
+impl Default for VirtualMonitor {
+    fn default() -> Self {
+        Self {
+            name: "Virtual-1".to_owned(),
+            position: PhysicalPosition::new(0, 0),
+            size: PhysicalSize::new(1920, 1080),
+            scale_factor: 1.0,
+            refresh_rate_millihertz: 60_000,
+        }
+    }
+}
+
+/// The [`DeviceId`] of the virtual input device, to use in the injected events.
+pub fn device_id() -> DeviceId {
+    DeviceId(crate::platform_impl::DeviceId::Headless(crate::platform_impl::headless::DeviceId))
+}
+
+/// Additional methods on [`ActiveEventLoop`] that are specific to the headless backend.
+pub trait ActiveEventLoopExtHeadless {
+    /// True if the [`ActiveEventLoop`] uses the headless backend.
+    fn is_headless(&self) -> bool;
+
+    /// Inject the event for the window, delivered on the next iteration of the event loop.
+    ///
+    /// The window state is updated along, e.g. [`WindowEvent::Resized`] changes the size of
+    /// the window, and [`WindowEvent::Focused`] unfocuses the other windows.
+    fn inject_window_event(
+        &self,
+        window_id: WindowId,
+        event: WindowEvent,
+    ) -> Result<(), NotSupportedError>;
+
+    /// Inject the event of the virtual input device, delivered on the next iteration of the
+    /// event loop, depending on [`ActiveEventLoop::listen_device_events`].
+    fn inject_device_event(&self, event: DeviceEvent) -> Result<(), NotSupportedError>;
+
+    /// Replace the virtual monitors, there's a single [`VirtualMonitor::default`] one initially.
+    fn set_virtual_monitors(&self, monitors: Vec<VirtualMonitor>) -> Result<(), NotSupportedError>;
+}
+
+impl ActiveEventLoopExtHeadless for ActiveEventLoop {
+    #[inline]
+    fn is_headless(&self) -> bool {
+        self.p.is_headless()
+    }
+
+    #[inline]
+    fn inject_window_event(
+        &self,
+        window_id: WindowId,
+        event: WindowEvent,
+    ) -> Result<(), NotSupportedError> {
+        self.p.inject_window_event(window_id.0, event)
+    }
+
+    #[inline]
+    fn inject_device_event(&self, event: DeviceEvent) -> Result<(), NotSupportedError> {
+        self.p.inject_device_event(event)
+    }
+
+    #[inline]
+    fn set_virtual_monitors(&self, monitors: Vec<VirtualMonitor>) -> Result<(), NotSupportedError> {
+        self.p.set_virtual_monitors(monitors)
+    }
+}
+
+/// Additional methods on [`EventLoop`] that are specific to the headless backend.
+///
+/// See [`ActiveEventLoopExtHeadless`] for the details.
+pub trait EventLoopExtHeadless {
+    /// True if the [`EventLoop`] uses the headless backend.
+    fn is_headless(&self) -> bool;
+
+    /// See [`ActiveEventLoopExtHeadless::inject_window_event`].
+    fn inject_window_event(
+        &self,
+        window_id: WindowId,
+        event: WindowEvent,
+    ) -> Result<(), NotSupportedError>;
+
+    /// See [`ActiveEventLoopExtHeadless::inject_device_event`].
+    fn inject_device_event(&self, event: DeviceEvent) -> Result<(), NotSupportedError>;
+
+    /// See [`ActiveEventLoopExtHeadless::set_virtual_monitors`].
+    fn set_virtual_monitors(&self, monitors: Vec<VirtualMonitor>) -> Result<(), NotSupportedError>;
+}
+
+impl<T: 'static> EventLoopExtHeadless for EventLoop<T> {
+    #[inline]
+    fn is_headless(&self) -> bool {
+        self.event_loop.window_target().is_headless()
+    }
+
+    #[inline]
+    fn inject_window_event(
+        &self,
+        window_id: WindowId,
+        event: WindowEvent,
+    ) -> Result<(), NotSupportedError> {
+        self.event_loop.window_target().inject_window_event(window_id, event)
+    }
+
+    #[inline]
+    fn inject_device_event(&self, event: DeviceEvent) -> Result<(), NotSupportedError> {
+        self.event_loop.window_target().inject_device_event(event)
+    }
+
+    #[inline]
+    fn set_virtual_monitors(&self, monitors: Vec<VirtualMonitor>) -> Result<(), NotSupportedError> {
+        self.event_loop.window_target().set_virtual_monitors(monitors)
+    }
+}
+
+/// Additional methods on [`EventLoopBuilder`] that are specific to the headless backend.
+pub trait EventLoopBuilderExtHeadless {
+    /// Force using the headless backend.
+    fn with_headless(&mut self) -> &mut Self;
+}
+
+impl<T> EventLoopBuilderExtHeadless for EventLoopBuilder<T> {
+    #[inline]
+    fn with_headless(&mut self) -> &mut Self {
+        self.platform_specific.forced_backend = Some(crate::platform_impl::Backend::Headless);
+        self
+    }
+}
@@ -0,0 +1,124 @@
+//! Layer shell surfaces, such as panels, docks and overlays.
+//!
+//! ## Platform-specific
+//!
+//! - **Wayland:** Uses the `wlr-layer-shell` protocol, falling back to a regular window when
+//!   the compositor doesn't support it.
+//! - **X11:** Creates a window of the `_NET_WM_WINDOW_TYPE_DOCK` type, or
+//!   `_NET_WM_WINDOW_TYPE_DESKTOP` for [`Layer::Background`], placed on the primary monitor
+//!   and reserving the exclusive zone with `_NET_WM_STRUT_PARTIAL`.
+
+use crate::window::WindowAttributes;
+
+/// Additional methods on [`WindowAttributes`] to build layer shell surfaces.
+pub trait WindowAttributesExtLayerShell {
+    /// Build a layer shell surface, such as a panel, a dock or an overlay, instead of a regular
+    /// window.
+    ///
+    /// The surface is placed by the compositor in the given `layer`, on the edges of the output
+    /// it's `anchor`ed to. Anchoring to two opposite edges stretches the surface along them, the
+    /// inner size is then only used in the other direction. The `margins` are the distances to the
+    /// anchored edges.
+    ///
+    /// The `exclusive_zone` is the size of the area along the anchored edge the other windows
+    /// should avoid, such as the height of a panel. `0` lets the other surfaces overlap it, and
+    /// `-1` asks to not be moved to make room for the exclusive zones of the other surfaces.
+    ///
+    /// The sizes are in logical pixels. The compositor may close the surface at any time, which
+    /// is reported with [`WindowEvent::CloseRequested`].
+    ///
+    /// See the [module-level docs](self) for the platform-specific behaviour.
+    ///
+    /// [`WindowEvent::CloseRequested`]: crate::event::WindowEvent::CloseRequested
+    fn with_layer_shell(
+        self,
+        layer: Layer,
+        anchor: LayerAnchor,
+        exclusive_zone: i32,
+        keyboard_interactivity: KeyboardInteractivity,
+        margins: LayerMargins,
+    ) -> Self;
+}
+
+impl WindowAttributesExtLayerShell for WindowAttributes {
+    #[inline]
+    fn with_layer_shell(
+        mut self,
+        layer: Layer,
+        anchor: LayerAnchor,
+        exclusive_zone: i32,
+        keyboard_interactivity: KeyboardInteractivity,
+        margins: LayerMargins,
+    ) -> Self {
+        self.platform_specific.layer_shell = Some(crate::platform_impl::LayerShellAttributes {
+            layer,
+            anchor,
+            exclusive_zone,
+            keyboard_interactivity,
+            margins,
+        });
+        self
+    }
+}
+
+/// The layer of a layer shell surface, see [`WindowAttributesExtLayerShell::with_layer_shell`].
+///
+/// The layers are stacked in order, with the regular windows between [`Layer::Bottom`] and
+/// [`Layer::Top`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Layer {
+    /// Below everything, e.g. a wallpaper.
+    Background,
+    /// Below the regular windows.
+    Bottom,
+    /// Above the regular windows, e.g. a panel.
+    #[default]
+    Top,
+    /// Above everything, including the fullscreen windows, e.g. a lock screen or an HUD.
+    Overlay,
+}
+
+bitflags::bitflags! {
+    /// The edges of the output a layer shell surface is anchored to.
+    ///
+    /// See [`WindowAttributesExtLayerShell::with_layer_shell`] for details.
+    #[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+    pub struct LayerAnchor: u32 {
+        const TOP = 1 << 0;
+        const BOTTOM = 1 << 1;
+        const LEFT = 1 << 2;
+        const RIGHT = 1 << 3;
+    }
+}
+
+/// Whether a layer shell surface receives the keyboard input.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum KeyboardInteractivity {
+    /// The surface never gets the keyboard focus.
+    #[default]
+    None,
+    /// The surface takes the keyboard focus exclusively while it's above the regular windows.
+    Exclusive,
+    /// The surface gets the keyboard focus like the regular windows, e.g. when clicked.
+    ///
+    /// Requires the version 4 of the layer shell, the older compositors use
+    /// [`KeyboardInteractivity::Exclusive`] on the top and overlay layers instead, and
+    /// [`KeyboardInteractivity::None`] on the others.
+    OnDemand,
+}
+
+/// The distances of a layer shell surface to the edges it's anchored to, in logical pixels.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct LayerMargins {
+    pub top: i32,
+    pub right: i32,
+    pub bottom: i32,
+    pub left: i32,
+}
+
+impl LayerMargins {
+    /// The same margin on all the edges.
+    pub const fn uniform(margin: i32) -> Self {
+        Self { top: margin, right: margin, bottom: margin, left: margin }
+    }
+}
@@ -4,14 +4,24 @@
 
 #[cfg(any(android_platform, docsrs))]
 pub mod android;
+#[cfg(any(x11_platform, wayland_platform, docsrs))]
+pub mod clipboard;
+#[cfg(any(x11_platform, wayland_platform, docsrs))]
+pub mod dnd;
+#[cfg(any(x11_platform, wayland_platform, docsrs))]
+pub mod headless;
 #[cfg(any(ios_platform, docsrs))]
 pub mod ios;
+#[cfg(any(x11_platform, wayland_platform, docsrs))]
+pub mod layer_shell;
 #[cfg(any(macos_platform, docsrs))]
 pub mod macos;
 #[cfg(any(orbital_platform, docsrs))]
 pub mod orbital;
 #[cfg(any(x11_platform, wayland_platform, docsrs))]
 pub mod startup_notify;
+#[cfg(any(x11_platform, wayland_platform, docsrs))]
+pub mod unix;
 #[cfg(any(wayland_platform, docsrs))]
 pub mod wayland;
 #[cfg(any(web_platform, docsrs))]
@@ -62,6 +62,7 @@ impl EventLoopExtStartupNotify for ActiveEventLoop {
             crate::platform_impl::ActiveEventLoop::Wayland(_) => env::var(WAYLAND_VAR),
             #[cfg(x11_platform)]
             crate::platform_impl::ActiveEventLoop::X(_) => env::var(X11_VAR),
+            crate::platform_impl::ActiveEventLoop::Headless(_) => Err(env::VarError::NotPresent),
         }
         .ok()
         .map(ActivationToken::from_raw)
@@ -0,0 +1,142 @@
+//! Integration of the file descriptors with the event loop on Unix.
+//!
+//! The registered file descriptor wakes up the event loop directly once it's ready, which is
+//! delivered with [`ApplicationHandler::fd_ready`], so the sockets, pipes or `inotify` don't
+//! require a helper thread forwarding through the [`EventLoopProxy`].
+//!
+//! The other way around, [`EventLoopExtUnix`] lets the event loop live inside of a foreign main
+//! loop, such as the one of glib or tokio, without a dedicated thread.
+//!
+//! [`ApplicationHandler::fd_ready`]: crate::application::ApplicationHandler::fd_ready
+//! [`EventLoopProxy`]: crate::event_loop::EventLoopProxy
+
+use std::os::unix::io::{AsFd, BorrowedFd, OwnedFd};
+use std::time::Duration;
+
+use crate::application::ApplicationHandler;
+use crate::error::OsError;
+use crate::event_loop::{self, ActiveEventLoop, EventLoop, Interest, SourceToken};
+use crate::platform::pump_events::PumpStatus;
+
+pub trait ActiveEventLoopExtUnix {
+    /// Register the file descriptor to report its readiness for the given `interest`.
+    ///
+    /// The event loop owns the file descriptor until it's unregistered with
+    /// [`ActiveEventLoopExtUnix::unregister_fd`]. To keep reading or writing it, register a
+    /// duplicate made with [`AsFd::as_fd`] and [`BorrowedFd::try_clone_to_owned`].
+    fn register_fd(&self, fd: OwnedFd, interest: Interest) -> Result<SourceToken, OsError>;
+
+    /// Stop reporting the readiness of the registered file descriptor, closing it.
+    fn unregister_fd(&self, token: SourceToken);
+}
+
+impl ActiveEventLoopExtUnix for ActiveEventLoop {
+    #[inline]
+    fn register_fd(&self, fd: OwnedFd, interest: Interest) -> Result<SourceToken, OsError> {
+        self.p.register_fd(fd, interest)
+    }
+
+    #[inline]
+    fn unregister_fd(&self, token: SourceToken) {
+        self.p.unregister_fd(token)
+    }
+}
+
+/// What the foreign main loop has to wait for, returned by [`EventLoopExtUnix::prepare`].
+#[derive(Debug, Clone, Copy)]
+pub struct Prepared<'a> {
+    fd: BorrowedFd<'a>,
+    timeout: Option<Duration>,
+}
+
+impl<'a> Prepared<'a> {
+    /// The file descriptor to poll for readability.
+    ///
+    /// All the event sources of the event loop, including the file descriptors registered with
+    /// [`ActiveEventLoopExtUnix::register_fd`], are aggregated behind this single descriptor.
+    #[inline]
+    pub fn fd(&self) -> BorrowedFd<'a> {
+        self.fd
+    }
+
+    /// How long the foreign main loop may wait before calling
+    /// [`EventLoopExtUnix::dispatch_app_events`], `None` means it may wait indefinitely.
+    ///
+    /// This accounts for the [`ControlFlow`], the scheduled timers and the repeat of the held
+    /// keys, and is zero when there are events ready to be dispatched already.
+    ///
+    /// [`ControlFlow`]: crate::event_loop::ControlFlow
+    #[inline]
+    pub fn timeout(&self) -> Option<Duration> {
+        self.timeout
+    }
+}
+
+/// Additional methods on [`EventLoop`] to drive it from a foreign main loop.
+///
+/// Unlike [`pump_app_events`], the wait itself is left to the caller: [`prepare`] reports the
+/// file descriptor to poll along with the timeout, and once it's readable or the timeout has
+/// elapsed, [`dispatch_app_events`] processes whatever is ready without blocking.
+///
+/// ```no_run
+/// # use deft_winit::application::ApplicationHandler;
+/// # use deft_winit::event_loop::EventLoop;
+/// # use deft_winit::platform::pump_events::PumpStatus;
+/// # use deft_winit::platform::unix::EventLoopExtUnix;
+/// # fn poll(_fd: std::os::unix::io::BorrowedFd<'_>, _timeout: Option<std::time::Duration>) {}
+/// # fn run<A: ApplicationHandler>(mut event_loop: EventLoop<()>, app: &mut A) {
+/// loop {
+///     let prepared = event_loop.prepare();
+///     // Hand these over to the reactor of the foreign main loop.
+///     poll(prepared.fd(), prepared.timeout());
+///
+///     if let PumpStatus::Exit(_) = event_loop.dispatch_app_events(app) {
+///         break;
+///     }
+/// }
+/// # }
+/// ```
+///
+/// [`pump_app_events`]: crate::platform::pump_events::EventLoopExtPumpEvents::pump_app_events
+/// [`prepare`]: Self::prepare
+/// [`dispatch_app_events`]: Self::dispatch_app_events
+pub trait EventLoopExtUnix: AsFd {
+    /// A type provided by the user that can be passed through [`Event::UserEvent`].
+    ///
+    /// [`Event::UserEvent`]: crate::event::Event::UserEvent
+    type UserEvent: 'static;
+
+    /// Prepare to wait for the new events.
+    ///
+    /// This writes out the pending requests to the display server, so it must be called right
+    /// before each wait of the foreign main loop.
+    fn prepare(&mut self) -> Prepared<'_>;
+
+    /// Dispatch the events which are ready, without blocking.
+    ///
+    /// The first call starts the event loop, the same way as [`pump_app_events`] does.
+    ///
+    /// [`pump_app_events`]: crate::platform::pump_events::EventLoopExtPumpEvents::pump_app_events
+    fn dispatch_app_events<A: ApplicationHandler<Self::UserEvent>>(
+        &mut self,
+        app: &mut A,
+    ) -> PumpStatus;
+}
+
+impl<T> EventLoopExtUnix for EventLoop<T> {
+    type UserEvent = T;
+
+    fn prepare(&mut self) -> Prepared<'_> {
+        let timeout = self.event_loop.prepare();
+        Prepared { fd: self.event_loop.as_fd(), timeout }
+    }
+
+    fn dispatch_app_events<A: ApplicationHandler<Self::UserEvent>>(
+        &mut self,
+        app: &mut A,
+    ) -> PumpStatus {
+        self.event_loop.pump_events(Some(Duration::ZERO), |event, event_loop| {
+            event_loop::dispatch_event_for_app(app, event_loop, event)
+        })
+    }
+}
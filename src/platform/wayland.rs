@@ -55,6 +55,17 @@ pub trait EventLoopBuilderExtWayland {
     /// By default, the window is only allowed to be created on the main
     /// thread, to make platform compatibility easier.
     fn with_any_thread(&mut self, any_thread: bool) -> &mut Self;
+
+    /// Whether to report the graphics tablet tools with [`WindowEvent::TabletTool`].
+    ///
+    /// Once the tablets are bound, the compositor stops emulating the pointer for their tools, so
+    /// the pens no longer produce [`WindowEvent::CursorMoved`] and [`WindowEvent::MouseInput`].
+    /// The tablet tools are thus only reported when asked for, and are disabled by default.
+    ///
+    /// [`WindowEvent::TabletTool`]: crate::event::WindowEvent::TabletTool
+    /// [`WindowEvent::CursorMoved`]: crate::event::WindowEvent::CursorMoved
+    /// [`WindowEvent::MouseInput`]: crate::event::WindowEvent::MouseInput
+    fn with_tablet_tools(&mut self, tablet_tools: bool) -> &mut Self;
 }
 
 impl<T> EventLoopBuilderExtWayland for EventLoopBuilder<T> {
@@ -69,6 +80,12 @@ impl<T> EventLoopBuilderExtWayland for EventLoopBuilder<T> {
         self.platform_specific.any_thread = any_thread;
         self
     }
+
+    #[inline]
+    fn with_tablet_tools(&mut self, tablet_tools: bool) -> &mut Self {
+        self.platform_specific.tablet_tools = tablet_tools;
+        self
+    }
 }
 
 /// Additional methods on [`Window`] that are specific to Wayland.
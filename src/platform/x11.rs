@@ -2,11 +2,14 @@
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
+use crate::error::ExternalError;
+use crate::event::{ElementState, MouseButton};
 use crate::event_loop::{ActiveEventLoop, EventLoop, EventLoopBuilder};
+use crate::keyboard::PhysicalKey;
 use crate::monitor::MonitorHandle;
 use crate::window::{Window, WindowAttributes};
 
-use crate::dpi::Size;
+use crate::dpi::{Position, Size};
 
 /// X window type. Maps directly to
 /// [`_NET_WM_WINDOW_TYPE`](https://specifications.freedesktop.org/wm-spec/wm-spec-1.5.html).
@@ -93,7 +96,7 @@ pub trait ActiveEventLoopExtX11 {
 impl ActiveEventLoopExtX11 for ActiveEventLoop {
     #[inline]
     fn is_x11(&self) -> bool {
-        !self.p.is_wayland()
+        self.p.is_x11()
     }
 }
 
@@ -106,7 +109,7 @@ pub trait EventLoopExtX11 {
 impl<T: 'static> EventLoopExtX11 for EventLoop<T> {
     #[inline]
     fn is_x11(&self) -> bool {
-        !self.event_loop.is_wayland()
+        self.event_loop.is_x11()
     }
 }
 
@@ -137,9 +140,58 @@ impl<T> EventLoopBuilderExtX11 for EventLoopBuilder<T> {
 }
 
 /// Additional methods on [`Window`] that are specific to X11.
-pub trait WindowExtX11 {}
+///
+/// The `inject_*` methods synthesize the input with the XTEST extension, e.g. to drive the
+/// windows from the end-to-end tests under Xvfb. The X server generates the same events as for
+/// the genuine input, so they're processed like the user input, keymap and XInput2 included.
+/// Each method waits until the server has processed the input, so the resulting events are
+/// delivered on the next iteration of the event loop.
+///
+/// They return [`ExternalError::NotSupported`] if the X server doesn't have the XTEST extension,
+/// or if the window doesn't use X11.
+pub trait WindowExtX11 {
+    /// Press or release the key, delivered to the focused window.
+    ///
+    /// Returns [`ExternalError::Ignored`] if the key has no X11 keycode.
+    fn inject_key(&self, key: PhysicalKey, state: ElementState) -> Result<(), ExternalError>;
+
+    /// Move the pointer to the position, relative to the top-left corner of the window.
+    ///
+    /// Returns [`ExternalError::Ignored`] if the position is out of the X11 coordinates range.
+    fn inject_pointer_motion(&self, position: Position) -> Result<(), ExternalError>;
+
+    /// Press or release the pointer button at the pointer position.
+    fn inject_button(&self, button: MouseButton, state: ElementState) -> Result<(), ExternalError>;
 
-impl WindowExtX11 for Window {}
+    /// Scroll by the given number of columns and lines, delivered as
+    /// [`MouseScrollDelta::LineDelta`] steps of one, with the positive values scrolling right
+    /// and up.
+    ///
+    /// [`MouseScrollDelta::LineDelta`]: crate::event::MouseScrollDelta::LineDelta
+    fn inject_scroll(&self, columns: i32, lines: i32) -> Result<(), ExternalError>;
+}
+
+impl WindowExtX11 for Window {
+    #[inline]
+    fn inject_key(&self, key: PhysicalKey, state: ElementState) -> Result<(), ExternalError> {
+        self.window.inject_key(key, state)
+    }
+
+    #[inline]
+    fn inject_pointer_motion(&self, position: Position) -> Result<(), ExternalError> {
+        self.window.inject_pointer_motion(position)
+    }
+
+    #[inline]
+    fn inject_button(&self, button: MouseButton, state: ElementState) -> Result<(), ExternalError> {
+        self.window.inject_button(button, state)
+    }
+
+    #[inline]
+    fn inject_scroll(&self, columns: i32, lines: i32) -> Result<(), ExternalError> {
+        self.window.inject_scroll(columns, lines)
+    }
+}
 
 /// Additional methods on [`WindowAttributes`] that are specific to X11.
 pub trait WindowAttributesExtX11 {
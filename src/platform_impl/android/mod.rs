@@ -17,7 +17,10 @@ use crate::dpi::{PhysicalPosition, PhysicalSize, Position, Size};
 use crate::error;
 use crate::error::EventLoopError;
 use crate::event::{self, Force, InnerSizeWriter, StartCause};
-use crate::event_loop::{self, ActiveEventLoop as RootAEL, ControlFlow, DeviceEvents};
+use crate::event_loop::{
+    self, ActiveEventLoop as RootAEL, ControlFlow, DeviceEvents, TimerId, TimerSchedule,
+};
+use crate::keyboard::KeyboardLayouts;
 use crate::platform::pump_events::PumpStatus;
 use crate::platform_impl::Fullscreen;
 use crate::window::{
@@ -416,6 +419,7 @@ impl<T: 'static> EventLoop<T> {
                                 location,
                                 id: pointer.pointer_id() as u64,
                                 force: Some(Force::Normalized(pointer.pressure() as f64)),
+                                contact: None,
                             }),
                         };
                         callback(event, self.window_target());
@@ -671,6 +675,10 @@ impl ActiveEventLoop {
     #[inline]
     pub fn listen_device_events(&self, _allowed: DeviceEvents) {}
 
+    pub fn schedule_timer(&self, _timer_id: TimerId, _schedule: TimerSchedule) {}
+
+    pub fn cancel_timer(&self, _timer_id: TimerId) {}
+
     #[cfg(feature = "rwh_05")]
     #[inline]
     pub fn raw_display_handle_rwh_05(&self) -> rwh_05::RawDisplayHandle {
@@ -682,6 +690,11 @@ impl ActiveEventLoop {
         None
     }
 
+    #[inline]
+    pub fn keyboard_layouts(&self) -> Option<KeyboardLayouts> {
+        None
+    }
+
     #[cfg(feature = "rwh_06")]
     #[inline]
     pub fn raw_display_handle_rwh_06(
@@ -766,6 +779,16 @@ impl DeviceId {
     pub const fn dummy() -> Self {
         DeviceId(0)
     }
+
+    #[cfg(feature = "serde")]
+    pub(crate) fn into_raw(self) -> i64 {
+        self.0 as i64
+    }
+
+    #[cfg(feature = "serde")]
+    pub(crate) fn from_raw(raw: i64) -> Option<Self> {
+        i32::try_from(raw).ok().map(DeviceId)
+    }
 }
 
 #[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
@@ -926,6 +949,8 @@ impl Window {
 
     pub fn set_ime_purpose(&self, _purpose: ImePurpose) {}
 
+    pub fn set_ime_surrounding_text(&self, _text: String, _cursor: usize, _anchor: usize) {}
+
     pub fn commit_ime(&self) {}
     
     pub fn focus_window(&self) {}
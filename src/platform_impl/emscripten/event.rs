@@ -13,5 +13,15 @@ impl DeviceId {
     pub const fn dummy() -> Self {
         DeviceId
     }
+
+    #[cfg(feature = "serde")]
+    pub(crate) fn into_raw(self) -> i64 {
+        0
+    }
+
+    #[cfg(feature = "serde")]
+    pub(crate) fn from_raw(_raw: i64) -> Option<Self> {
+        Some(DeviceId)
+    }
 }
 
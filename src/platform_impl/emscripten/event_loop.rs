@@ -1,7 +1,8 @@
 use crate::cursor::CustomCursorSource;
 use crate::error::EventLoopError;
 use crate::event::Event;
-use crate::event_loop::{ControlFlow, DeviceEvents, EventLoopClosed};
+use crate::event_loop::{ControlFlow, DeviceEvents, EventLoopClosed, TimerId, TimerSchedule};
+use crate::keyboard::KeyboardLayouts;
 use crate::platform_impl::emscripten::event_hub::EventHub;
 use crate::platform_impl::{CustomCursorFuture, PlatformCustomCursor};
 use crate::window::Theme;
@@ -138,11 +139,19 @@ impl ActiveEventLoop {
         //TODO impl listen device events
     }
 
+    pub fn schedule_timer(&self, _timer_id: TimerId, _schedule: TimerSchedule) {}
+
+    pub fn cancel_timer(&self, _timer_id: TimerId) {}
+
     pub fn system_theme(&self) -> Option<Theme> {
         //TODO impl system theme
         None
     }
 
+    pub fn keyboard_layouts(&self) -> Option<KeyboardLayouts> {
+        None
+    }
+
     pub(crate) fn set_control_flow(&self, _control_flow: ControlFlow) {
         //TODO impl
     }
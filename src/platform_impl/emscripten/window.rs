@@ -467,6 +467,9 @@ impl Inner {
         // Currently not implemented
     }
 
+    #[inline]
+    pub fn set_ime_surrounding_text(&self, _text: String, _cursor: usize, _anchor: usize) {}
+
     #[inline]
     pub fn commit_ime(&self) {}
 
@@ -19,8 +19,10 @@ use objc2_ui_kit::{UIApplication, UIApplicationMain, UIDevice, UIScreen, UIUserI
 use crate::error::EventLoopError;
 use crate::event::Event;
 use crate::event_loop::{
-    ActiveEventLoop as RootActiveEventLoop, ControlFlow, DeviceEvents, EventLoopClosed,
+    ActiveEventLoop as RootActiveEventLoop, ControlFlow, DeviceEvents, EventLoopClosed, TimerId,
+    TimerSchedule,
 };
+use crate::keyboard::KeyboardLayouts;
 use crate::platform::ios::Idiom;
 use crate::platform_impl::ios::app_state::{EventLoopHandler, HandlePendingUserEvents};
 use crate::window::{CustomCursor, CustomCursorSource, Theme};
@@ -52,6 +54,10 @@ impl ActiveEventLoop {
     #[inline]
     pub fn listen_device_events(&self, _allowed: DeviceEvents) {}
 
+    pub fn schedule_timer(&self, _timer_id: TimerId, _schedule: TimerSchedule) {}
+
+    pub fn cancel_timer(&self, _timer_id: TimerId) {}
+
     #[cfg(feature = "rwh_05")]
     #[inline]
     pub fn raw_display_handle_rwh_05(&self) -> rwh_05::RawDisplayHandle {
@@ -63,6 +69,11 @@ impl ActiveEventLoop {
         None
     }
 
+    #[inline]
+    pub fn keyboard_layouts(&self) -> Option<KeyboardLayouts> {
+        None
+    }
+
     #[cfg(feature = "rwh_06")]
     #[inline]
     pub fn raw_display_handle_rwh_06(
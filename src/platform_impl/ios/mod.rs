@@ -35,6 +35,16 @@ impl DeviceId {
     pub const fn dummy() -> Self {
         DeviceId
     }
+
+    #[cfg(feature = "serde")]
+    pub(crate) fn into_raw(self) -> i64 {
+        0
+    }
+
+    #[cfg(feature = "serde")]
+    pub(crate) fn from_raw(_raw: i64) -> Option<Self> {
+        Some(DeviceId)
+    }
 }
 
 pub(crate) const DEVICE_ID: RootDeviceId = RootDeviceId(DeviceId);
@@ -961,6 +961,7 @@ impl WinitView {
                     id: touch_id,
                     location: physical_location,
                     force,
+                    contact: None,
                     phase,
                 }),
             }));
@@ -390,6 +390,8 @@ impl Inner {
     pub fn set_ime_purpose(&self, _purpose: ImePurpose) {
         warn!("`Window::set_ime_purpose` is ignored on iOS")
     }
+
+    pub fn set_ime_surrounding_text(&self, _text: String, _cursor: usize, _anchor: usize) {}
     
     pub fn commit_ime(&self) {}
 
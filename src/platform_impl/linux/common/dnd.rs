@@ -0,0 +1,86 @@
+//! The parsing of the drag and drop payloads shared by the backends.
+
+use std::io;
+use std::path::{Path, PathBuf};
+use std::str::Utf8Error;
+
+use percent_encoding::percent_decode;
+
+use crate::dnd::{DndAction, URI_LIST_MIME_TYPE};
+
+/// The response of the window to the drag hovering it.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum DropResponse {
+    /// Accept only the files, used until the window responds.
+    #[default]
+    Files,
+    /// Accept the data in the given MIME type.
+    Accept(String, DndAction),
+    /// Reject the drop.
+    Reject,
+}
+
+impl DropResponse {
+    /// The index of the accepted MIME type among the `offered` ones, along with the action.
+    pub fn accepted<S: AsRef<str>>(&self, offered: &[S]) -> Option<(usize, DndAction)> {
+        let (mime_type, action) = match self {
+            DropResponse::Files => (URI_LIST_MIME_TYPE, DndAction::Copy),
+            DropResponse::Accept(mime_type, action) => (mime_type.as_str(), *action),
+            DropResponse::Reject => return None,
+        };
+
+        let index = offered.iter().position(|offer| offer.as_ref() == mime_type)?;
+        Some((index, action))
+    }
+}
+
+#[derive(Debug)]
+pub enum DndDataParseError {
+    EmptyData,
+    InvalidUtf8(#[allow(dead_code)] Utf8Error),
+    HostnameSpecified(#[allow(dead_code)] String),
+    UnexpectedProtocol(#[allow(dead_code)] String),
+    UnresolvablePath(#[allow(dead_code)] io::Error),
+}
+
+impl From<Utf8Error> for DndDataParseError {
+    fn from(e: Utf8Error) -> Self {
+        DndDataParseError::InvalidUtf8(e)
+    }
+}
+
+impl From<io::Error> for DndDataParseError {
+    fn from(e: io::Error) -> Self {
+        DndDataParseError::UnresolvablePath(e)
+    }
+}
+
+/// Parse the `text/uri-list` into the list of local paths.
+pub fn parse_uri_list(data: &[u8]) -> Result<Vec<PathBuf>, DndDataParseError> {
+    if !data.is_empty() {
+        let mut path_list = Vec::new();
+        let decoded = percent_decode(data).decode_utf8()?.into_owned();
+        for uri in decoded.split("\r\n").filter(|u| !u.is_empty()) {
+            // The format is specified as protocol://host/path
+            // However, it's typically simply protocol:///path
+            let path_str = if uri.starts_with("file://") {
+                let path_str = uri.replace("file://", "");
+                if !path_str.starts_with('/') {
+                    // A hostname is specified
+                    // Supporting this case is beyond the scope of my mental health
+                    return Err(DndDataParseError::HostnameSpecified(path_str));
+                }
+                path_str
+            } else {
+                // Only the file protocol is supported
+                return Err(DndDataParseError::UnexpectedProtocol(uri.to_owned()));
+            };
+
+            let path = Path::new(&path_str).canonicalize()?;
+            path_list.push(path);
+        }
+        Ok(path_list)
+    } else {
+        Err(DndDataParseError::EmptyData)
+    }
+}
@@ -0,0 +1,52 @@
+//! The conversion of the touchpad gestures to the winit events.
+
+/// The state of a pinch gesture, turning the scales relative to its start into the deltas winit
+/// reports.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Pinch {
+    /// The latest scale, relative to the start of the pinch.
+    scale: f64,
+}
+
+impl Default for Pinch {
+    fn default() -> Self {
+        Self { scale: 1. }
+    }
+}
+
+impl Pinch {
+    /// Start a new pinch.
+    pub fn begin(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Update the pinch with its `scale` relative to its start and the `rotation` since the
+    /// previous update, in degrees clockwise.
+    ///
+    /// Returns the deltas of the [`PinchGesture`] and [`RotationGesture`] events.
+    ///
+    /// [`PinchGesture`]: crate::event::WindowEvent::PinchGesture
+    /// [`RotationGesture`]: crate::event::WindowEvent::RotationGesture
+    pub fn update(&mut self, scale: f64, rotation: f64) -> (f64, f32) {
+        let scale_delta = scale - self.scale;
+        self.scale = scale;
+        // The rotation is clockwise, while winit's is counterclockwise.
+        (scale_delta, -rotation as f32)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pinch_deltas() {
+        let mut pinch = Pinch::default();
+        assert_eq!(pinch.update(1.5, 10.), (0.5, -10.));
+        assert_eq!(pinch.update(1.25, -5.), (-0.25, 5.));
+
+        // A new pinch starts from the unscaled size.
+        pinch.begin();
+        assert_eq!(pinch.update(0.75, 0.), (-0.25, 0.));
+    }
+}
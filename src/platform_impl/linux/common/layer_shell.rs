@@ -0,0 +1,189 @@
+//! The placement of the layer shell surfaces, for the backends emulating them with regular
+//! windows.
+
+use crate::dpi::{PhysicalPosition, PhysicalSize};
+use crate::platform::layer_shell::LayerAnchor;
+use crate::platform_impl::LayerShellAttributes;
+
+/// Place the layer shell surface of the given `size` on the `monitor`, given by its position and
+/// size, the way the compositor does.
+///
+/// Returns its position and size.
+pub fn geometry(
+    layer_shell: &LayerShellAttributes,
+    monitor: (PhysicalPosition<i32>, PhysicalSize<u32>),
+    scale_factor: f64,
+    size: PhysicalSize<u32>,
+) -> (PhysicalPosition<i32>, PhysicalSize<u32>) {
+    let (position, monitor_size) = monitor;
+    let anchor = layer_shell.anchor;
+    let margins = layer_shell.margins;
+    let margin = |margin: i32| (margin as f64 * scale_factor).round() as i32;
+
+    // Place the surface along one axis, stretching it between the anchored edges.
+    let place = |start: i32, length: u32, size: u32, edges: (bool, bool), margins: (i32, i32)| {
+        let (start_margin, end_margin) = (margin(margins.0), margin(margins.1));
+        let end = start + length as i32;
+        match edges {
+            (true, true) => {
+                let size = (end - end_margin - start - start_margin).max(1) as u32;
+                (start + start_margin, size)
+            },
+            (true, false) => (start + start_margin, size),
+            (false, true) => (end - end_margin - size as i32, size),
+            (false, false) => (start + (length as i32 - size as i32) / 2, size),
+        }
+    };
+
+    let (x, width) = place(
+        position.x,
+        monitor_size.width,
+        size.width,
+        (anchor.contains(LayerAnchor::LEFT), anchor.contains(LayerAnchor::RIGHT)),
+        (margins.left, margins.right),
+    );
+    let (y, height) = place(
+        position.y,
+        monitor_size.height,
+        size.height,
+        (anchor.contains(LayerAnchor::TOP), anchor.contains(LayerAnchor::BOTTOM)),
+        (margins.top, margins.bottom),
+    );
+
+    (PhysicalPosition::new(x, y), PhysicalSize::new(width, height))
+}
+
+/// Compute the `_NET_WM_STRUT_PARTIAL` reserving the exclusive zone of the layer shell surface
+/// placed at `position` with the given `size`.
+///
+/// The zone is reserved along the edge the surface is anchored to, when that edge is
+/// unambiguous, like on Wayland. The struts are relative to the edges of the root window of the
+/// given size, which contains the `monitor`.
+pub fn strut(
+    layer_shell: &LayerShellAttributes,
+    monitor: (PhysicalPosition<i32>, PhysicalSize<u32>),
+    scale_factor: f64,
+    root_size: PhysicalSize<u32>,
+    position: PhysicalPosition<i32>,
+    size: PhysicalSize<u32>,
+) -> Option<[u32; 12]> {
+    if layer_shell.exclusive_zone <= 0 {
+        return None;
+    }
+
+    // The edge is either alone, or along with both of the perpendicular edges.
+    let horizontal_edges = LayerAnchor::LEFT | LayerAnchor::RIGHT;
+    let vertical_edges = LayerAnchor::TOP | LayerAnchor::BOTTOM;
+    let horizontal = layer_shell.anchor & horizontal_edges;
+    let vertical = layer_shell.anchor & vertical_edges;
+    let edge = match (horizontal.bits().count_ones(), vertical.bits().count_ones()) {
+        (1, 0) | (1, 2) => horizontal,
+        (0, 1) | (2, 1) => vertical,
+        _ => return None,
+    };
+
+    let margins = layer_shell.margins;
+    let margin = match edge {
+        LayerAnchor::TOP => margins.top,
+        LayerAnchor::BOTTOM => margins.bottom,
+        LayerAnchor::LEFT => margins.left,
+        _ => margins.right,
+    };
+    let zone = ((layer_shell.exclusive_zone + margin) as f64 * scale_factor).round() as i32;
+
+    let (monitor_position, monitor_size) = monitor;
+    let (root_width, root_height) = (root_size.width as i32, root_size.height as i32);
+    let (x_start, x_end) = (position.x, position.x + size.width as i32 - 1);
+    let (y_start, y_end) = (position.y, position.y + size.height as i32 - 1);
+    let mut strut = [0i32; 12];
+    match edge {
+        LayerAnchor::LEFT => {
+            strut[0] = monitor_position.x + zone;
+            (strut[4], strut[5]) = (y_start, y_end);
+        },
+        LayerAnchor::RIGHT => {
+            strut[1] = root_width - monitor_position.x - monitor_size.width as i32 + zone;
+            (strut[6], strut[7]) = (y_start, y_end);
+        },
+        LayerAnchor::TOP => {
+            strut[2] = monitor_position.y + zone;
+            (strut[8], strut[9]) = (x_start, x_end);
+        },
+        _ => {
+            strut[3] = root_height - monitor_position.y - monitor_size.height as i32 + zone;
+            (strut[10], strut[11]) = (x_start, x_end);
+        },
+    }
+
+    Some(strut.map(|value| value.max(0) as u32))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::platform::layer_shell::{KeyboardInteractivity, Layer, LayerMargins};
+
+    fn panel(anchor: LayerAnchor, exclusive_zone: i32) -> LayerShellAttributes {
+        LayerShellAttributes {
+            layer: Layer::Top,
+            anchor,
+            exclusive_zone,
+            keyboard_interactivity: KeyboardInteractivity::None,
+            margins: LayerMargins { top: 5, right: 0, bottom: 0, left: 10 },
+        }
+    }
+
+    #[test]
+    fn layer_geometry() {
+        // The second monitor, right of the first one.
+        let monitor = (PhysicalPosition::new(1000, 0), PhysicalSize::new(800, 600));
+        let size = PhysicalSize::new(200, 30);
+
+        // Stretched along the top edge, within the margins.
+        let top = panel(LayerAnchor::TOP | LayerAnchor::LEFT | LayerAnchor::RIGHT, 30);
+        let placed = geometry(&top, monitor, 1., size);
+        assert_eq!(placed, (PhysicalPosition::new(1010, 5), PhysicalSize::new(790, 30)));
+        let placed = geometry(&top, monitor, 2., size);
+        assert_eq!(placed, (PhysicalPosition::new(1020, 10), PhysicalSize::new(780, 30)));
+
+        // In the bottom right corner.
+        let corner = panel(LayerAnchor::BOTTOM | LayerAnchor::RIGHT, 0);
+        let placed = geometry(&corner, monitor, 1., size);
+        assert_eq!(placed, (PhysicalPosition::new(1600, 570), size));
+
+        // Centered without any anchor.
+        let centered = panel(LayerAnchor::empty(), 0);
+        let placed = geometry(&centered, monitor, 1., size);
+        assert_eq!(placed, (PhysicalPosition::new(1300, 285), size));
+    }
+
+    #[test]
+    fn layer_strut() {
+        let monitor = (PhysicalPosition::new(1000, 0), PhysicalSize::new(800, 600));
+        let root_size = PhysicalSize::new(1800, 800);
+
+        // The top edge of the second monitor, including the margin.
+        let top = panel(LayerAnchor::TOP | LayerAnchor::LEFT | LayerAnchor::RIGHT, 30);
+        let (position, size) = (PhysicalPosition::new(1010, 5), PhysicalSize::new(790, 30));
+        let expected = [0, 0, 35, 0, 0, 0, 0, 0, 1010, 1799, 0, 0];
+        assert_eq!(strut(&top, monitor, 1., root_size, position, size), Some(expected));
+
+        // The bottom edge, relative to the bottom of the taller root window.
+        let bottom = panel(LayerAnchor::BOTTOM, 30);
+        let (position, size) = (PhysicalPosition::new(1300, 570), PhysicalSize::new(200, 30));
+        let expected = [0, 0, 0, 230, 0, 0, 0, 0, 0, 0, 1300, 1499];
+        assert_eq!(strut(&bottom, monitor, 1., root_size, position, size), Some(expected));
+
+        // The left edge, scaled.
+        let left = panel(LayerAnchor::LEFT | LayerAnchor::TOP | LayerAnchor::BOTTOM, 30);
+        let (position, size) = (PhysicalPosition::new(1020, 10), PhysicalSize::new(60, 590));
+        let expected = [1080, 0, 0, 0, 10, 599, 0, 0, 0, 0, 0, 0];
+        assert_eq!(strut(&left, monitor, 2., root_size, position, size), Some(expected));
+
+        // Without an exclusive zone, or an unambiguous edge.
+        let top = panel(LayerAnchor::TOP, 0);
+        assert_eq!(strut(&top, monitor, 1., root_size, position, size), None);
+        let corner = panel(LayerAnchor::TOP | LayerAnchor::LEFT, 30);
+        assert_eq!(strut(&corner, monitor, 1., root_size, position, size), None);
+    }
+}
@@ -1 +1,10 @@
+pub mod channel;
+#[cfg(any(x11_platform, wayland_platform))]
+pub mod dnd;
+#[cfg(any(x11_platform, wayland_platform))]
+pub mod gesture;
+#[cfg(x11_platform)]
+pub mod layer_shell;
+pub mod popup;
+pub mod timer;
 pub mod xkb;
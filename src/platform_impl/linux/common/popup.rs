@@ -0,0 +1,173 @@
+//! The placement of the popups, for the backends positioning them on their own.
+
+use crate::dpi::{PhysicalPosition, PhysicalSize};
+use crate::window::{PopupAnchor, PopupAttributes, PopupConstraintAdjustment, PopupGravity};
+
+/// Place the popup of the given `size` the way `xdg_positioner` does.
+///
+/// The `parent_position` is the position of the parent's client area, and the `bounds` are the
+/// area the popup should fit into, usually the monitor of the parent, in the same coordinate
+/// space. The returned position is in that space as well.
+pub fn place(
+    popup: &PopupAttributes,
+    parent_position: PhysicalPosition<i32>,
+    scale_factor: f64,
+    size: PhysicalSize<u32>,
+    bounds: Option<(PhysicalPosition<i32>, PhysicalSize<u32>)>,
+) -> (PhysicalPosition<i32>, PhysicalSize<u32>) {
+    let rect_position = popup.anchor_rect.0.to_physical::<i32>(scale_factor);
+    let rect_size = popup.anchor_rect.1.to_physical::<u32>(scale_factor);
+    let (anchor_x, anchor_y) = anchor_sides(popup.anchor);
+    let (gravity_x, gravity_y) = gravity_sides(popup.gravity);
+    let adjustment = popup.constraint_adjustment;
+
+    let (x, width) = place_axis(
+        (parent_position.x + rect_position.x, rect_size.width),
+        (anchor_x, gravity_x),
+        size.width,
+        bounds.map(|(position, size)| (position.x, size.width)),
+        (
+            adjustment.contains(PopupConstraintAdjustment::FLIP_X),
+            adjustment.contains(PopupConstraintAdjustment::SLIDE_X),
+            adjustment.contains(PopupConstraintAdjustment::RESIZE_X),
+        ),
+    );
+    let (y, height) = place_axis(
+        (parent_position.y + rect_position.y, rect_size.height),
+        (anchor_y, gravity_y),
+        size.height,
+        bounds.map(|(position, size)| (position.y, size.height)),
+        (
+            adjustment.contains(PopupConstraintAdjustment::FLIP_Y),
+            adjustment.contains(PopupConstraintAdjustment::SLIDE_Y),
+            adjustment.contains(PopupConstraintAdjustment::RESIZE_Y),
+        ),
+    );
+
+    (PhysicalPosition::new(x, y), PhysicalSize::new(width, height))
+}
+
+/// The sides of the anchor, `-1` for the left or top, `0` for the center, and `1` for the
+/// right or bottom.
+fn anchor_sides(anchor: PopupAnchor) -> (i32, i32) {
+    match anchor {
+        PopupAnchor::None => (0, 0),
+        PopupAnchor::Top => (0, -1),
+        PopupAnchor::Bottom => (0, 1),
+        PopupAnchor::Left => (-1, 0),
+        PopupAnchor::Right => (1, 0),
+        PopupAnchor::TopLeft => (-1, -1),
+        PopupAnchor::BottomLeft => (-1, 1),
+        PopupAnchor::TopRight => (1, -1),
+        PopupAnchor::BottomRight => (1, 1),
+    }
+}
+
+/// The directions of the gravity, like [`anchor_sides`].
+fn gravity_sides(gravity: PopupGravity) -> (i32, i32) {
+    match gravity {
+        PopupGravity::None => (0, 0),
+        PopupGravity::Top => (0, -1),
+        PopupGravity::Bottom => (0, 1),
+        PopupGravity::Left => (-1, 0),
+        PopupGravity::Right => (1, 0),
+        PopupGravity::TopLeft => (-1, -1),
+        PopupGravity::BottomLeft => (-1, 1),
+        PopupGravity::TopRight => (1, -1),
+        PopupGravity::BottomRight => (1, 1),
+    }
+}
+
+/// Place the popup along one axis, returning its start and length.
+fn place_axis(
+    (rect_start, rect_length): (i32, u32),
+    (anchor, gravity): (i32, i32),
+    length: u32,
+    bounds: Option<(i32, u32)>,
+    (flip, slide, resize): (bool, bool, bool),
+) -> (i32, u32) {
+    let start = |anchor: i32, gravity: i32| {
+        let point = rect_start + (anchor + 1) * rect_length as i32 / 2;
+        point + (gravity - 1) * length as i32 / 2
+    };
+
+    let mut position = start(anchor, gravity);
+    let (bounds_start, bounds_end) = match bounds {
+        Some((start, length)) => (start, start + length as i32),
+        None => return (position, length),
+    };
+    let fits = |position: i32| position >= bounds_start && position + length as i32 <= bounds_end;
+
+    if fits(position) {
+        return (position, length);
+    }
+
+    if flip {
+        let flipped = start(-anchor, -gravity);
+        if fits(flipped) {
+            return (flipped, length);
+        }
+    }
+
+    if slide {
+        position = position.min(bounds_end - length as i32).max(bounds_start);
+    }
+
+    let mut length = length;
+    if resize {
+        let end = (position + length as i32).min(bounds_end);
+        position = position.max(bounds_start);
+        length = (end - position).max(1) as u32;
+    }
+
+    (position, length)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dpi::{LogicalPosition, LogicalSize};
+    use crate::window::WindowId;
+
+    fn menu(constraint_adjustment: PopupConstraintAdjustment) -> PopupAttributes {
+        PopupAttributes {
+            parent: WindowId::from(1),
+            anchor_rect: (LogicalPosition::new(10, 20).into(), LogicalSize::new(30, 10).into()),
+            anchor: PopupAnchor::BottomLeft,
+            gravity: PopupGravity::BottomRight,
+            constraint_adjustment,
+        }
+    }
+
+    #[test]
+    fn place_popup() {
+        let parent = PhysicalPosition::new(100, 100);
+        let size = PhysicalSize::new(200, 300);
+        let monitor = Some((PhysicalPosition::new(0, 0), PhysicalSize::new(1000, 500)));
+        let none = PopupConstraintAdjustment::empty();
+
+        // Below the anchor rectangle, aligned to its left.
+        let placed = place(&menu(none), parent, 1., size, monitor);
+        assert_eq!(placed, (PhysicalPosition::new(110, 130), size));
+        let placed = place(&menu(none), parent, 2., size, monitor);
+        assert_eq!(placed, (PhysicalPosition::new(120, 160), size));
+
+        // Centered on the center of the anchor rectangle.
+        let mut centered = menu(none);
+        centered.anchor = PopupAnchor::None;
+        centered.gravity = PopupGravity::None;
+        let placed = place(&centered, parent, 1., size, None);
+        assert_eq!(placed, (PhysicalPosition::new(25, -25), size));
+
+        // Overflowing the bottom of the monitor.
+        let parent = PhysicalPosition::new(100, 400);
+        let placed = place(&menu(PopupConstraintAdjustment::FLIP_Y), parent, 1., size, monitor);
+        assert_eq!(placed, (PhysicalPosition::new(110, 120), size));
+        let placed = place(&menu(PopupConstraintAdjustment::SLIDE_Y), parent, 1., size, monitor);
+        assert_eq!(placed, (PhysicalPosition::new(110, 200), size));
+        let placed = place(&menu(PopupConstraintAdjustment::RESIZE_Y), parent, 1., size, monitor);
+        assert_eq!(placed, (PhysicalPosition::new(110, 430), PhysicalSize::new(200, 70)));
+        let placed = place(&menu(none), parent, 1., size, monitor);
+        assert_eq!(placed, (PhysicalPosition::new(110, 430), size));
+    }
+}
@@ -0,0 +1,94 @@
+//! The timers scheduled by the user, fired from the event loop iterations.
+
+use std::time::{Duration, Instant};
+
+use crate::event_loop::{TimerId, TimerSchedule};
+
+struct Timer {
+    id: TimerId,
+    deadline: Instant,
+    interval: Option<Duration>,
+}
+
+#[derive(Default)]
+pub struct Timers {
+    timers: Vec<Timer>,
+}
+
+impl Timers {
+    pub fn schedule(&mut self, id: TimerId, schedule: TimerSchedule) {
+        let timer = match schedule {
+            TimerSchedule::Deadline(deadline) => Timer { id, deadline, interval: None },
+            TimerSchedule::Interval(interval) => {
+                Timer { id, deadline: Instant::now() + interval, interval: Some(interval) }
+            },
+        };
+        self.timers.push(timer);
+    }
+
+    pub fn cancel(&mut self, id: TimerId) {
+        self.timers.retain(|timer| timer.id != id);
+    }
+
+    /// The earliest deadline among the scheduled timers.
+    pub fn next_deadline(&self) -> Option<Instant> {
+        self.timers.iter().map(|timer| timer.deadline).min()
+    }
+
+    /// Take the timers which have expired by `now`, in the order of their deadlines.
+    ///
+    /// The interval timers are rescheduled, skipping the intervals which were missed entirely.
+    pub fn take_expired(&mut self, now: Instant) -> Vec<TimerId> {
+        let mut expired: Vec<_> = self
+            .timers
+            .iter()
+            .filter(|timer| timer.deadline <= now)
+            .map(|timer| (timer.deadline, timer.id))
+            .collect();
+        expired.sort();
+
+        self.timers.retain_mut(|timer| {
+            if timer.deadline > now {
+                return true;
+            }
+
+            match timer.interval {
+                Some(interval) => {
+                    timer.deadline += interval;
+                    if timer.deadline <= now {
+                        timer.deadline = now + interval;
+                    }
+                    true
+                },
+                None => false,
+            }
+        });
+
+        expired.into_iter().map(|(_, id)| id).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn take_expired_timers() {
+        let mut timers = Timers::default();
+        let now = Instant::now();
+        let (deadline, interval, later) = (TimerId::next(), TimerId::next(), TimerId::next());
+        timers.schedule(later, TimerSchedule::Deadline(now + Duration::from_secs(60)));
+        timers.schedule(interval, TimerSchedule::Interval(Duration::from_millis(10)));
+        timers.schedule(deadline, TimerSchedule::Deadline(now));
+
+        let expired = timers.take_expired(now + Duration::from_millis(35));
+        assert_eq!(expired, [deadline, interval]);
+
+        // The missed intervals are skipped, the deadline timer is gone.
+        assert_eq!(timers.next_deadline(), Some(now + Duration::from_millis(45)));
+        assert!(timers.take_expired(now + Duration::from_millis(40)).is_empty());
+
+        timers.cancel(interval);
+        assert_eq!(timers.next_deadline(), Some(now + Duration::from_secs(60)));
+    }
+}
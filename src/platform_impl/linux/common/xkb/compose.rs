@@ -121,3 +121,55 @@ pub enum ComposeStatus {
     Ignored,
     None,
 }
+
+/// The pending compose sequence, presented to the user as preedit while composing.
+#[derive(Debug, Default)]
+pub struct ComposePreedit {
+    text: String,
+    changed: bool,
+}
+
+impl ComposePreedit {
+    pub fn push(&mut self, segment: &str) {
+        self.text.push_str(segment);
+        self.changed = true;
+    }
+
+    pub fn clear(&mut self) {
+        if !self.text.is_empty() {
+            self.text.clear();
+            self.changed = true;
+        }
+    }
+
+    /// The preedit text if it changed since the last call.
+    pub fn take_update(&mut self) -> Option<String> {
+        std::mem::take(&mut self.changed).then(|| self.text.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compose_preedit_reports_changes_once() {
+        let mut preedit = ComposePreedit::default();
+        assert_eq!(preedit.take_update(), None);
+
+        preedit.push("\u{b7}");
+        preedit.push("'");
+        assert_eq!(preedit.take_update().as_deref(), Some("\u{b7}'"));
+        assert_eq!(preedit.take_update(), None);
+
+        preedit.clear();
+        assert_eq!(preedit.take_update().as_deref(), Some(""));
+    }
+
+    #[test]
+    fn compose_preedit_clear_without_sequence_is_silent() {
+        let mut preedit = ComposePreedit::default();
+        preedit.clear();
+        assert_eq!(preedit.take_update(), None);
+    }
+}
@@ -1,6 +1,6 @@
 //! XKB keymap.
 
-use std::ffi::c_char;
+use std::ffi::{c_char, c_void, CStr};
 use std::ops::Deref;
 use std::ptr::{self, NonNull};
 
@@ -9,6 +9,7 @@ use x11_dl::xlib_xcb::xcb_connection_t;
 #[cfg(wayland_platform)]
 use {memmap2::MmapOptions, std::os::unix::io::OwnedFd};
 
+use tracing::warn;
 use xkb::XKB_MOD_INVALID;
 use xkbcommon_dl::{
     self as xkb, xkb_keycode_t, xkb_keymap, xkb_keymap_compile_flags, xkb_keysym_t,
@@ -19,6 +20,28 @@ use crate::keyboard::{Key, KeyCode, KeyLocation, NamedKey, NativeKey, NativeKeyC
 #[cfg(x11_platform)]
 use crate::platform_impl::common::xkb::XKBXH;
 use crate::platform_impl::common::xkb::{XkbContext, XKBH};
+use crate::utils::Lazy;
+
+type XkbKeymapLayoutGetName =
+    unsafe extern "C" fn(*mut xkb_keymap, xkb_layout_index_t) -> *const c_char;
+
+/// `xkb_keymap_layout_get_name` isn't bound by `xkbcommon-dl`, so look it up in the library it
+/// has already loaded.
+static XKB_KEYMAP_LAYOUT_GET_NAME: Lazy<Option<XkbKeymapLayoutGetName>> = Lazy::new(|| {
+    let layout_get_name = unsafe {
+        [b"libxkbcommon.so.0\0".as_slice(), b"libxkbcommon.so\0"]
+            .iter()
+            .map(|name| libc::dlopen(name.as_ptr().cast(), libc::RTLD_LAZY | libc::RTLD_NOLOAD))
+            .find(|lib| !lib.is_null())
+            .map(|lib| libc::dlsym(lib, b"xkb_keymap_layout_get_name\0".as_ptr().cast()))
+            .filter(|symbol| !symbol.is_null())
+            .map(|symbol| std::mem::transmute::<*mut c_void, XkbKeymapLayoutGetName>(symbol))
+    };
+    if layout_get_name.is_none() {
+        warn!("`xkb_keymap_layout_get_name` wasn't found in libxkbcommon, the layouts are unnamed");
+    }
+    layout_get_name
+});
 
 /// Map the raw X11-style keycode to the `KeyCode` enum.
 ///
@@ -933,6 +956,20 @@ impl XkbKeymap {
         Some(Self::new_inner(keymap, 0))
     }
 
+    /// Compile the keymap in the text format.
+    #[cfg(test)]
+    fn from_string(context: &XkbContext, keymap: &CStr) -> Option<Self> {
+        let keymap = unsafe {
+            (XKBH.xkb_keymap_new_from_string)(
+                context.as_ptr(),
+                keymap.as_ptr(),
+                xkb::xkb_keymap_format::XKB_KEYMAP_FORMAT_TEXT_V1,
+                xkb_keymap_compile_flags::XKB_KEYMAP_COMPILE_NO_FLAGS,
+            )
+        };
+        Some(Self::new_inner(NonNull::new(keymap)?, 0))
+    }
+
     #[cfg(x11_platform)]
     pub fn from_x11_keymap(
         context: &XkbContext,
@@ -999,6 +1036,26 @@ impl XkbKeymap {
     pub fn key_repeats(&mut self, keycode: xkb_keycode_t) -> bool {
         unsafe { (XKBH.xkb_keymap_key_repeats)(self.keymap.as_ptr(), keycode) == 1 }
     }
+
+    /// The names of the layouts, empty for the unnamed ones.
+    pub fn layout_names(&self) -> Vec<String> {
+        let num_layouts = unsafe { (XKBH.xkb_keymap_num_layouts)(self.keymap.as_ptr()) };
+        (0..num_layouts)
+            .map(|layout| {
+                let name = match *XKB_KEYMAP_LAYOUT_GET_NAME {
+                    Some(layout_get_name) => unsafe {
+                        layout_get_name(self.keymap.as_ptr(), layout)
+                    },
+                    None => ptr::null(),
+                };
+                if name.is_null() {
+                    String::new()
+                } else {
+                    unsafe { CStr::from_ptr(name) }.to_string_lossy().into_owned()
+                }
+            })
+            .collect()
+    }
 }
 
 impl Drop for XkbKeymap {
@@ -1042,3 +1099,28 @@ fn mod_index_for_name(keymap: NonNull<xkb_keymap>, name: &[u8]) -> Option<xkb_mo
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn layout_names() {
+        // The third layout is unnamed.
+        let keymap = b"xkb_keymap {
+            xkb_keycodes { minimum = 8; maximum = 255; <AE01> = 10; };
+            xkb_types { type \"ONE_LEVEL\" { modifiers = none; level_name[1] = \"Any\"; }; };
+            xkb_compatibility { };
+            xkb_symbols {
+                name[1] = \"English (US)\";
+                name[2] = \"German\";
+                key <AE01> { [ 1 ], [ 1 ], [ 1 ] };
+            };
+        };\0";
+
+        let context = XkbContext::new().unwrap();
+        let keymap = CStr::from_bytes_with_nul(keymap).unwrap();
+        let keymap = XkbKeymap::from_string(&context, keymap).unwrap();
+        assert_eq!(keymap.layout_names(), ["English (US)", "German", ""]);
+    }
+}
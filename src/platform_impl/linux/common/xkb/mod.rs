@@ -16,14 +16,14 @@ use xkbcommon_dl::{
 use {x11_dl::xlib_xcb::xcb_connection_t, xkbcommon_dl::x11::xkbcommon_x11_handle};
 
 use crate::event::{ElementState, KeyEvent};
-use crate::keyboard::{Key, KeyLocation};
+use crate::keyboard::{Key, KeyLocation, KeyboardLayouts, NamedKey};
 use crate::platform_impl::KeyEventExtra;
 
 mod compose;
 mod keymap;
 mod state;
 
-use compose::{ComposeStatus, XkbComposeState, XkbComposeTable};
+use compose::{ComposePreedit, ComposeStatus, XkbComposeState, XkbComposeTable};
 use keymap::XkbKeymap;
 
 #[cfg(x11_platform)]
@@ -59,6 +59,8 @@ pub struct Context {
     keymap: Option<XkbKeymap>,
     compose_state1: Option<XkbComposeState>,
     compose_state2: Option<XkbComposeState>,
+    compose_preedit: ComposePreedit,
+    reported_layouts: Option<KeyboardLayouts>,
     _compose_table: Option<XkbComposeTable>,
     context: XkbContext,
     scratch_buffer: Vec<u8>,
@@ -87,6 +89,8 @@ impl Context {
             keymap: None,
             compose_state1,
             compose_state2,
+            compose_preedit: Default::default(),
+            reported_layouts: None,
             #[cfg(x11_platform)]
             core_keyboard_id: 0,
             _compose_table: compose_table,
@@ -150,14 +154,57 @@ impl Context {
         self.keymap = keymap;
     }
 
+    /// The layouts of the current keymap and the active one.
+    pub fn keyboard_layouts(&mut self) -> Option<KeyboardLayouts> {
+        let names = self.keymap.as_ref()?.layout_names();
+        let active = self.state.as_mut()?.active_layout() as usize;
+        Some(KeyboardLayouts { names, active })
+    }
+
+    /// The keyboard layouts if they changed since the last call.
+    pub fn take_keyboard_layouts_update(&mut self) -> Option<KeyboardLayouts> {
+        let layouts = self.keyboard_layouts()?;
+        if self.reported_layouts.as_ref() == Some(&layouts) {
+            return None;
+        }
+
+        self.reported_layouts = Some(layouts.clone());
+        Some(layouts)
+    }
+
+    /// The pending compose sequence if it changed since the last call.
+    ///
+    /// An empty string means that the sequence was finished or cancelled.
+    pub fn take_compose_preedit(&mut self) -> Option<String> {
+        self.compose_preedit.take_update()
+    }
+
+    /// Cancel the pending compose sequence, e.g. when the keyboard focus is lost.
+    ///
+    /// The cleared sequence is then reported by [`Self::take_compose_preedit`].
+    pub fn reset_compose(&mut self) {
+        for state in self.compose_state1.iter_mut().chain(self.compose_state2.iter_mut()) {
+            state.reset();
+        }
+        self.compose_preedit.clear();
+    }
+
     /// Key builder context with the user provided xkb state.
     pub fn key_context(&mut self) -> Option<KeyContext<'_>> {
         let state = self.state.as_mut()?;
         let keymap = self.keymap.as_mut()?;
         let compose_state1 = self.compose_state1.as_mut();
         let compose_state2 = self.compose_state2.as_mut();
+        let compose_preedit = &mut self.compose_preedit;
         let scratch_buffer = &mut self.scratch_buffer;
-        Some(KeyContext { state, keymap, compose_state1, compose_state2, scratch_buffer })
+        Some(KeyContext {
+            state,
+            keymap,
+            compose_state1,
+            compose_state2,
+            compose_preedit,
+            scratch_buffer,
+        })
     }
 
     /// Key builder context with the user provided xkb state.
@@ -171,8 +218,16 @@ impl Context {
         let keymap = self.keymap.as_mut()?;
         let compose_state1 = self.compose_state1.as_mut();
         let compose_state2 = self.compose_state2.as_mut();
+        let compose_preedit = &mut self.compose_preedit;
         let scratch_buffer = &mut self.scratch_buffer;
-        Some(KeyContext { state, keymap, compose_state1, compose_state2, scratch_buffer })
+        Some(KeyContext {
+            state,
+            keymap,
+            compose_state1,
+            compose_state2,
+            compose_preedit,
+            scratch_buffer,
+        })
     }
 }
 
@@ -181,6 +236,7 @@ pub struct KeyContext<'a> {
     pub keymap: &'a mut XkbKeymap,
     compose_state1: Option<&'a mut XkbComposeState>,
     compose_state2: Option<&'a mut XkbComposeState>,
+    compose_preedit: &'a mut ComposePreedit,
     scratch_buffer: &'a mut Vec<u8>,
 }
 
@@ -195,6 +251,7 @@ impl KeyContext<'_> {
             KeyEventResults::new(self, keycode, !repeat && state == ElementState::Pressed);
         let physical_key = keymap::raw_keycode_to_physicalkey(keycode);
         let (logical_key, location) = event.key();
+        event.update_compose_preedit(&logical_key);
         let text = event.text();
         let (key_without_modifiers, _) = event.key_without_modifiers();
         let text_with_all_modifiers = event.text_with_all_modifiers();
@@ -236,23 +293,26 @@ struct KeyEventResults<'a, 'b> {
     keycode: u32,
     keysym: u32,
     compose: ComposeStatus,
+    compose_reset: bool,
 }
 
 impl<'a, 'b> KeyEventResults<'a, 'b> {
     fn new(context: &'a mut KeyContext<'b>, keycode: u32, compose: bool) -> Self {
         let keysym = context.state.get_one_sym_raw(keycode);
 
+        let mut compose_reset = false;
         let compose = if let Some(state) = context.compose_state1.as_mut().filter(|_| compose) {
             if RESET_DEAD_KEYS.swap(false, Ordering::SeqCst) {
                 state.reset();
                 context.compose_state2.as_mut().unwrap().reset();
+                compose_reset = true;
             }
             state.feed(keysym)
         } else {
             ComposeStatus::None
         };
 
-        KeyEventResults { context, keycode, keysym, compose }
+        KeyEventResults { context, keycode, keysym, compose, compose_reset }
     }
 
     pub fn key(&mut self) -> (Key, KeyLocation) {
@@ -288,6 +348,28 @@ impl<'a, 'b> KeyEventResults<'a, 'b> {
         }
     }
 
+    /// Track the pending compose sequence, given the key produced by this event.
+    fn update_compose_preedit(&mut self, logical_key: &Key) {
+        if self.compose_reset {
+            self.context.compose_preedit.clear();
+        }
+
+        match self.compose {
+            ComposeStatus::Accepted(xkb_compose_status::XKB_COMPOSE_COMPOSING) => {
+                // Dead keys are shown with their non-combining variant and the compose key with
+                // a middle dot, like GTK does.
+                let segment = match logical_key {
+                    Key::Dead(Some(ch)) => Some(ch.to_string().into()),
+                    Key::Named(NamedKey::Compose) => None,
+                    _ => self.context.keysym_to_utf8_raw(self.keysym),
+                };
+                self.context.compose_preedit.push(segment.as_deref().unwrap_or("\u{b7}"));
+            },
+            ComposeStatus::Accepted(_) => self.context.compose_preedit.clear(),
+            ComposeStatus::Ignored | ComposeStatus::None => (),
+        }
+    }
+
     pub fn key_without_modifiers(&mut self) -> (Key, KeyLocation) {
         // This will become a pointer to an array which libxkbcommon owns, so we don't need to
         // deallocate it.
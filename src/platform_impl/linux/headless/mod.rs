@@ -0,0 +1,578 @@
+//! The headless backend, running the virtual windows without a display server.
+
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::marker::PhantomData;
+use std::mem;
+use std::os::unix::io::{AsFd, AsRawFd, BorrowedFd, OwnedFd, RawFd};
+use std::sync::{mpsc, Arc, Mutex, Weak};
+use std::time::{Duration, Instant};
+
+use calloop::generic::Generic;
+use calloop::{EventLoop as Loop, LoopHandle, RegistrationToken};
+
+use crate::cursor::OnlyCursorImage;
+use crate::error::{EventLoopError, NotSupportedError, OsError as RootOsError};
+use crate::event::{DeviceEvent, DeviceId as RootDeviceId, Event, StartCause, WindowEvent};
+use crate::event_loop::{
+    ActiveEventLoop as RootAEL, ControlFlow, DeviceEvents, EventLoopClosed, Interest, SourceToken,
+    TimerId, TimerSchedule,
+};
+use crate::platform::headless::VirtualMonitor;
+use crate::platform::pump_events::PumpStatus;
+use crate::platform_impl::common::channel::{PeekableReceiver, WakeSender};
+use crate::platform_impl::common::timer::Timers;
+use crate::platform_impl::platform::{min_timeout, WindowId};
+use crate::platform_impl::{
+    ActiveEventLoop as PlatformActiveEventLoop, DeviceId as PlatformDeviceId, OsError,
+    PlatformCustomCursor,
+};
+use crate::window::{CustomCursor as RootCustomCursor, CustomCursorSource};
+
+mod monitor;
+mod window;
+
+pub(crate) use monitor::{MonitorHandle, VideoModeHandle};
+pub(crate) use window::Window;
+use window::WindowState;
+
+/// The events sent to the event loop by the windows, or injected by the user.
+enum Message {
+    Window(WindowId, WindowEvent),
+    Device(DeviceEvent),
+}
+
+/// The virtual monitors, shared with the windows.
+type Monitors = Arc<Mutex<Vec<MonitorHandle>>>;
+
+/// The virtual input device.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct DeviceId;
+
+impl DeviceId {
+    #[allow(unused)]
+    pub const fn dummy() -> Self {
+        DeviceId
+    }
+}
+
+pub struct ActiveEventLoop {
+    control_flow: Cell<ControlFlow>,
+    exit: Cell<Option<i32>>,
+    windows: RefCell<HashMap<WindowId, Weak<Mutex<WindowState>>>>,
+    focused_window: Cell<Option<WindowId>>,
+    monitors: Monitors,
+    sender: WakeSender<Message>,
+    device_events: Cell<DeviceEvents>,
+    timers: RefCell<Timers>,
+    loop_handle: LoopHandle<'static, EventLoopState>,
+    fd_sources: RefCell<HashMap<SourceToken, RegistrationToken>>,
+}
+
+pub struct EventLoop<T: 'static> {
+    loop_running: bool,
+    event_loop: Loop<'static, EventLoopState>,
+    receiver: PeekableReceiver<Message>,
+    user_receiver: PeekableReceiver<T>,
+    user_sender: WakeSender<T>,
+    window_target: RootAEL,
+
+    /// The current state of the event loop.
+    state: EventLoopState,
+}
+
+struct EventLoopState {
+    /// The readiness of the file descriptors registered by the user
+    fd_events: Vec<(SourceToken, Interest)>,
+}
+
+pub struct EventLoopProxy<T: 'static> {
+    user_sender: WakeSender<T>,
+}
+
+impl<T: 'static> Clone for EventLoopProxy<T> {
+    fn clone(&self) -> Self {
+        EventLoopProxy { user_sender: self.user_sender.clone() }
+    }
+}
+
+impl<T: 'static> EventLoop<T> {
+    pub(crate) fn new() -> EventLoop<T> {
+        let event_loop =
+            Loop::<EventLoopState>::try_new().expect("Failed to initialize the event loop");
+        let handle = event_loop.handle();
+
+        let (waker, waker_source) =
+            calloop::ping::make_ping().expect("Failed to create event loop waker");
+        handle
+            .insert_source(waker_source, move |_, _, _| {
+                // No extra handling is required, we just need to wake-up.
+            })
+            .expect("Failed to register the event loop waker source");
+
+        // Create a channel for the window and the injected events.
+        let (sender, channel) = mpsc::channel();
+
+        // Create a channel for sending user events.
+        let (user_sender, user_channel) = mpsc::channel();
+
+        let monitor = MonitorHandle::new(0, VirtualMonitor::default());
+        let window_target = ActiveEventLoop {
+            control_flow: Cell::new(ControlFlow::default()),
+            exit: Cell::new(None),
+            windows: Default::default(),
+            focused_window: Cell::new(None),
+            monitors: Arc::new(Mutex::new(vec![monitor])),
+            sender: WakeSender { sender, waker: waker.clone() },
+            device_events: Default::default(),
+            timers: Default::default(),
+            loop_handle: handle,
+            fd_sources: Default::default(),
+        };
+
+        EventLoop {
+            loop_running: false,
+            event_loop,
+            receiver: PeekableReceiver::from_recv(channel),
+            user_receiver: PeekableReceiver::from_recv(user_channel),
+            user_sender: WakeSender { sender: user_sender, waker },
+            window_target: RootAEL {
+                p: PlatformActiveEventLoop::Headless(window_target),
+                _marker: PhantomData,
+            },
+            state: EventLoopState { fd_events: Vec::new() },
+        }
+    }
+
+    pub fn create_proxy(&self) -> EventLoopProxy<T> {
+        EventLoopProxy { user_sender: self.user_sender.clone() }
+    }
+
+    pub(crate) fn window_target(&self) -> &RootAEL {
+        &self.window_target
+    }
+
+    fn active_event_loop(&self) -> &ActiveEventLoop {
+        match &self.window_target.p {
+            PlatformActiveEventLoop::Headless(window_target) => window_target,
+            #[allow(unreachable_patterns)]
+            _ => unreachable!(),
+        }
+    }
+
+    pub fn run_on_demand<F>(&mut self, mut event_handler: F) -> Result<(), EventLoopError>
+    where
+        F: FnMut(Event<T>, &RootAEL),
+    {
+        loop {
+            match self.pump_events(None, &mut event_handler) {
+                PumpStatus::Exit(0) => {
+                    break Ok(());
+                },
+                PumpStatus::Exit(code) => {
+                    break Err(EventLoopError::ExitFailure(code));
+                },
+                _ => {
+                    continue;
+                },
+            }
+        }
+    }
+
+    pub fn pump_events<F>(&mut self, timeout: Option<Duration>, mut callback: F) -> PumpStatus
+    where
+        F: FnMut(Event<T>, &RootAEL),
+    {
+        if !self.loop_running {
+            self.loop_running = true;
+
+            // run the initial loop iteration
+            self.single_iteration(&mut callback, StartCause::Init);
+        }
+
+        // Consider the possibility that the `StartCause::Init` iteration could
+        // request to Exit.
+        if !self.exiting() {
+            self.poll_events_with_timeout(timeout, &mut callback);
+        }
+        if let Some(code) = self.exit_code() {
+            self.loop_running = false;
+
+            callback(Event::LoopExiting, self.window_target());
+
+            PumpStatus::Exit(code)
+        } else {
+            PumpStatus::Continue
+        }
+    }
+
+    fn has_pending(&mut self) -> bool {
+        self.receiver.has_incoming()
+            || self.user_receiver.has_incoming()
+            || !self.state.fd_events.is_empty()
+    }
+
+    /// Prepare to wait for the events on the event loop file descriptor, returning how long
+    /// the wait may last.
+    pub fn prepare(&mut self) -> Option<Duration> {
+        if !self.loop_running || self.exiting() || self.has_pending() {
+            return Some(Duration::ZERO);
+        }
+
+        self.wait_timeout(Instant::now())
+    }
+
+    /// How long we may wait for the new events according to the control flow and the timers.
+    fn wait_timeout(&self, start: Instant) -> Option<Duration> {
+        let control_flow_timeout = match self.control_flow() {
+            ControlFlow::Wait => None,
+            ControlFlow::Poll => Some(Duration::ZERO),
+            ControlFlow::WaitUntil(wait_deadline) => {
+                Some(wait_deadline.saturating_duration_since(start))
+            },
+        };
+
+        let timers_timeout = self
+            .active_event_loop()
+            .timers
+            .borrow()
+            .next_deadline()
+            .map(|deadline| deadline.saturating_duration_since(start));
+
+        min_timeout(control_flow_timeout, timers_timeout)
+    }
+
+    pub fn poll_events_with_timeout<F>(&mut self, mut timeout: Option<Duration>, mut callback: F)
+    where
+        F: FnMut(Event<T>, &RootAEL),
+    {
+        let start = Instant::now();
+
+        timeout = if self.has_pending() {
+            // If we already have work to do then we don't want to block on the next poll.
+            Some(Duration::ZERO)
+        } else {
+            min_timeout(self.wait_timeout(start), timeout)
+        };
+
+        if let Err(error) =
+            self.event_loop.dispatch(timeout, &mut self.state).map_err(std::io::Error::from)
+        {
+            tracing::error!("Failed to poll for events: {error:?}");
+            let exit_code = error.raw_os_error().unwrap_or(1);
+            self.set_exit_code(exit_code);
+            return;
+        }
+
+        // NB: `StartCause::Init` is handled as a special case and doesn't need
+        // to be considered here
+        let cause = match self.control_flow() {
+            ControlFlow::Poll => StartCause::Poll,
+            ControlFlow::Wait => StartCause::WaitCancelled { start, requested_resume: None },
+            ControlFlow::WaitUntil(deadline) => {
+                if Instant::now() < deadline {
+                    StartCause::WaitCancelled { start, requested_resume: Some(deadline) }
+                } else {
+                    StartCause::ResumeTimeReached { start, requested_resume: deadline }
+                }
+            },
+        };
+
+        // Skip the spurious wake ups, as the other backends do.
+        let timers_expired = self
+            .active_event_loop()
+            .timers
+            .borrow()
+            .next_deadline()
+            .is_some_and(|deadline| deadline <= Instant::now());
+        if !self.has_pending()
+            && !timers_expired
+            && !matches!(&cause, StartCause::ResumeTimeReached { .. } | StartCause::Poll)
+        {
+            return;
+        }
+
+        self.single_iteration(&mut callback, cause);
+    }
+
+    fn single_iteration<F>(&mut self, callback: &mut F, cause: StartCause)
+    where
+        F: FnMut(Event<T>, &RootAEL),
+    {
+        callback(Event::NewEvents(cause), &self.window_target);
+
+        if cause == StartCause::Init {
+            callback(Event::Resumed, &self.window_target);
+        }
+
+        // Fire the expired timers, the timers scheduled by the callbacks fire on later iterations.
+        let expired = self.active_event_loop().timers.borrow_mut().take_expired(Instant::now());
+        for timer_id in expired {
+            callback(Event::TimerFired(timer_id), &self.window_target);
+        }
+
+        // Report the ready file descriptors.
+        for (token, readiness) in mem::take(&mut self.state.fd_events) {
+            callback(Event::FdReady { token, readiness }, &self.window_target);
+        }
+
+        // Deliver the window and device events, the redraws are delivered last.
+        let mut redraws = Vec::new();
+        while let Ok(message) = self.receiver.try_recv() {
+            match message {
+                Message::Window(window_id, WindowEvent::RedrawRequested) => {
+                    if !redraws.contains(&window_id) {
+                        redraws.push(window_id);
+                    }
+                },
+                Message::Window(window_id, event) => {
+                    self.dispatch_window_event(window_id, event, callback)
+                },
+                Message::Device(event) => {
+                    if self.active_event_loop().listens_device_events() {
+                        let device_id = RootDeviceId(PlatformDeviceId::Headless(DeviceId));
+                        callback(Event::DeviceEvent { device_id, event }, &self.window_target);
+                    }
+                },
+            }
+        }
+
+        // Empty the user event buffer
+        while let Ok(event) = self.user_receiver.try_recv() {
+            callback(Event::UserEvent(event), &self.window_target);
+        }
+
+        // Empty the redraw requests
+        for window_id in redraws {
+            if self.active_event_loop().windows.borrow().contains_key(&window_id) {
+                let window_id = crate::window::WindowId(window_id);
+                callback(
+                    Event::WindowEvent { window_id, event: WindowEvent::RedrawRequested },
+                    &self.window_target,
+                );
+            }
+        }
+
+        // This is always the last event we dispatch before poll again
+        callback(Event::AboutToWait, &self.window_target);
+    }
+
+    /// Update the state of the window with the event, then deliver it.
+    fn dispatch_window_event<F>(&self, window_id: WindowId, event: WindowEvent, callback: &mut F)
+    where
+        F: FnMut(Event<T>, &RootAEL),
+    {
+        let target = self.active_event_loop();
+        if event != WindowEvent::Destroyed && !target.windows.borrow().contains_key(&window_id) {
+            return;
+        }
+
+        match event {
+            WindowEvent::Focused(true) => {
+                let unfocused = target.focused_window.replace(Some(window_id));
+                if let Some(unfocused) = unfocused.filter(|&unfocused| unfocused != window_id) {
+                    target.update_window(unfocused, &WindowEvent::Focused(false));
+                    let window_id = crate::window::WindowId(unfocused);
+                    let event = WindowEvent::Focused(false);
+                    callback(Event::WindowEvent { window_id, event }, &self.window_target);
+                }
+            },
+            WindowEvent::Focused(false) | WindowEvent::Destroyed
+                if target.focused_window.get() == Some(window_id) =>
+            {
+                target.focused_window.set(None);
+            },
+            _ => (),
+        }
+
+        if event == WindowEvent::Destroyed {
+            target.windows.borrow_mut().remove(&window_id);
+        } else {
+            target.update_window(window_id, &event);
+        }
+
+        let window_id = crate::window::WindowId(window_id);
+        callback(Event::WindowEvent { window_id, event }, &self.window_target);
+    }
+
+    fn control_flow(&self) -> ControlFlow {
+        self.active_event_loop().control_flow()
+    }
+
+    fn exiting(&self) -> bool {
+        self.active_event_loop().exiting()
+    }
+
+    fn set_exit_code(&self, code: i32) {
+        self.active_event_loop().set_exit_code(code);
+    }
+
+    fn exit_code(&self) -> Option<i32> {
+        self.active_event_loop().exit_code()
+    }
+}
+
+impl<T> AsFd for EventLoop<T> {
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        self.event_loop.as_fd()
+    }
+}
+
+impl<T> AsRawFd for EventLoop<T> {
+    fn as_raw_fd(&self) -> RawFd {
+        self.event_loop.as_raw_fd()
+    }
+}
+
+impl ActiveEventLoop {
+    pub fn create_custom_cursor(&self, cursor: CustomCursorSource) -> RootCustomCursor {
+        RootCustomCursor {
+            inner: PlatformCustomCursor::Headless(OnlyCursorImage(Arc::from(cursor.inner.0))),
+        }
+    }
+
+    #[inline]
+    pub fn available_monitors(&self) -> impl Iterator<Item = MonitorHandle> {
+        self.monitors.lock().unwrap().clone().into_iter()
+    }
+
+    #[inline]
+    pub fn primary_monitor(&self) -> Option<MonitorHandle> {
+        self.monitors.lock().unwrap().first().cloned()
+    }
+
+    #[inline]
+    pub fn listen_device_events(&self, allowed: DeviceEvents) {
+        self.device_events.set(allowed);
+    }
+
+    fn listens_device_events(&self) -> bool {
+        match self.device_events.get() {
+            DeviceEvents::Always => true,
+            DeviceEvents::WhenFocused => self.focused_window.get().is_some(),
+            DeviceEvents::Never => false,
+        }
+    }
+
+    pub fn schedule_timer(&self, timer_id: TimerId, schedule: TimerSchedule) {
+        self.timers.borrow_mut().schedule(timer_id, schedule);
+    }
+
+    pub fn cancel_timer(&self, timer_id: TimerId) {
+        self.timers.borrow_mut().cancel(timer_id);
+    }
+
+    pub fn register_fd(&self, fd: OwnedFd, interest: Interest) -> Result<SourceToken, RootOsError> {
+        let token = SourceToken::next();
+        let source = Generic::new(
+            fd,
+            calloop::Interest {
+                readable: interest.contains(Interest::READABLE),
+                writable: interest.contains(Interest::WRITABLE),
+            },
+            calloop::Mode::Level,
+        );
+        let registration = self
+            .loop_handle
+            .insert_source(source, move |readiness, _, state| {
+                let mut interest = Interest::empty();
+                interest.set(Interest::READABLE, readiness.readable || readiness.error);
+                interest.set(Interest::WRITABLE, readiness.writable);
+                state.fd_events.push((token, interest));
+                Ok(calloop::PostAction::Continue)
+            })
+            .map_err(|_| os_error!(OsError::Misc("failed to register the file descriptor")))?;
+
+        self.fd_sources.borrow_mut().insert(token, registration);
+        Ok(token)
+    }
+
+    pub fn unregister_fd(&self, token: SourceToken) {
+        if let Some(registration) = self.fd_sources.borrow_mut().remove(&token) {
+            self.loop_handle.remove(registration);
+        }
+    }
+
+    pub fn inject_window_event(&self, window_id: WindowId, event: WindowEvent) {
+        let _ = self.sender.send(Message::Window(window_id, event));
+    }
+
+    pub fn inject_device_event(&self, event: DeviceEvent) {
+        let _ = self.sender.send(Message::Device(event));
+    }
+
+    pub fn set_virtual_monitors(&self, monitors: Vec<VirtualMonitor>) {
+        *self.monitors.lock().unwrap() = monitors
+            .into_iter()
+            .enumerate()
+            .map(|(id, monitor)| MonitorHandle::new(id as u32, monitor))
+            .collect();
+    }
+
+    /// Update the state of the window according to the delivered event.
+    fn update_window(&self, window_id: WindowId, event: &WindowEvent) {
+        let window = self.windows.borrow().get(&window_id).and_then(Weak::upgrade);
+        if let Some(window) = window {
+            window.lock().unwrap().update(event);
+        }
+    }
+
+    #[cfg(feature = "rwh_05")]
+    pub fn raw_display_handle_rwh_05(&self) -> rwh_05::RawDisplayHandle {
+        no_raw_handle()
+    }
+
+    #[cfg(feature = "rwh_06")]
+    pub fn raw_display_handle_rwh_06(
+        &self,
+    ) -> Result<rwh_06::RawDisplayHandle, rwh_06::HandleError> {
+        Err(rwh_06::HandleError::NotSupported)
+    }
+
+    pub(crate) fn set_control_flow(&self, control_flow: ControlFlow) {
+        self.control_flow.set(control_flow)
+    }
+
+    pub(crate) fn control_flow(&self) -> ControlFlow {
+        self.control_flow.get()
+    }
+
+    pub(crate) fn exit(&self) {
+        self.exit.set(Some(0))
+    }
+
+    pub(crate) fn clear_exit(&self) {
+        self.exit.set(None)
+    }
+
+    pub(crate) fn exiting(&self) -> bool {
+        self.exit.get().is_some()
+    }
+
+    pub(crate) fn set_exit_code(&self, code: i32) {
+        self.exit.set(Some(code))
+    }
+
+    pub(crate) fn exit_code(&self) -> Option<i32> {
+        self.exit.get()
+    }
+}
+
+impl<T: 'static> EventLoopProxy<T> {
+    pub fn send_event(&self, event: T) -> Result<(), EventLoopClosed<T>> {
+        self.user_sender.send(event)
+    }
+}
+
+/// The error of the operations the headless backend doesn't support.
+pub(crate) fn not_supported() -> NotSupportedError {
+    NotSupportedError::new()
+}
+
+/// The infallible raw handle getters of the older `raw-window-handle` versions can't report that
+/// there are no handles without a display server.
+#[cfg(any(feature = "rwh_04", feature = "rwh_05"))]
+#[cold]
+pub(crate) fn no_raw_handle() -> ! {
+    panic!("the headless backend has no raw window or display handles")
+}
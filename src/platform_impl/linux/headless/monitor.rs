@@ -0,0 +1,115 @@
+use std::sync::Arc;
+
+use crate::dpi::{PhysicalPosition, PhysicalSize};
+use crate::platform::headless::VirtualMonitor;
+use crate::platform_impl::VideoModeHandle as PlatformVideoModeHandle;
+
+#[derive(Debug, Clone)]
+pub struct MonitorHandle {
+    id: u32,
+    monitor: Arc<VirtualMonitor>,
+}
+
+impl PartialEq for MonitorHandle {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+impl Eq for MonitorHandle {}
+
+impl PartialOrd for MonitorHandle {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for MonitorHandle {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.id.cmp(&other.id)
+    }
+}
+
+impl std::hash::Hash for MonitorHandle {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.id.hash(state);
+    }
+}
+
+impl MonitorHandle {
+    pub(crate) fn new(id: u32, monitor: VirtualMonitor) -> Self {
+        Self { id, monitor: Arc::new(monitor) }
+    }
+
+    #[inline]
+    pub fn name(&self) -> Option<String> {
+        Some(self.monitor.name.clone())
+    }
+
+    #[inline]
+    pub fn native_identifier(&self) -> u32 {
+        self.id
+    }
+
+    #[inline]
+    pub fn size(&self) -> PhysicalSize<u32> {
+        self.monitor.size
+    }
+
+    #[inline]
+    pub fn position(&self) -> PhysicalPosition<i32> {
+        self.monitor.position
+    }
+
+    #[inline]
+    pub fn refresh_rate_millihertz(&self) -> Option<u32> {
+        Some(self.monitor.refresh_rate_millihertz)
+    }
+
+    #[inline]
+    pub fn scale_factor(&self) -> f64 {
+        self.monitor.scale_factor
+    }
+
+    #[inline]
+    pub fn video_modes(&self) -> impl Iterator<Item = PlatformVideoModeHandle> {
+        std::iter::once(PlatformVideoModeHandle::Headless(VideoModeHandle {
+            monitor: self.clone(),
+        }))
+    }
+
+    /// Whether the monitor contains the position, in the desktop coordinates.
+    pub(crate) fn contains(&self, position: PhysicalPosition<i32>) -> bool {
+        let PhysicalPosition { x, y } = self.monitor.position;
+        let PhysicalSize { width, height } = self.monitor.size;
+        (x..x + width as i32).contains(&position.x) && (y..y + height as i32).contains(&position.y)
+    }
+}
+
+/// The virtual monitors only have their current mode.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct VideoModeHandle {
+    monitor: MonitorHandle,
+}
+
+impl VideoModeHandle {
+    #[inline]
+    pub fn size(&self) -> PhysicalSize<u32> {
+        self.monitor.size()
+    }
+
+    #[inline]
+    pub fn bit_depth(&self) -> u16 {
+        32
+    }
+
+    #[inline]
+    pub fn refresh_rate_millihertz(&self) -> u32 {
+        self.monitor.monitor.refresh_rate_millihertz
+    }
+
+    #[inline]
+    pub fn monitor(&self) -> MonitorHandle {
+        self.monitor.clone()
+    }
+}
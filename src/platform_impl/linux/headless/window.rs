@@ -0,0 +1,660 @@
+use std::cmp;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, Weak};
+
+use crate::clipboard::{MimeData, Selection};
+use crate::dnd::{DndActions, DragData};
+use crate::dpi::{PhysicalPosition, PhysicalSize, Position, Size};
+use crate::error::{ExternalError, NotSupportedError, OsError as RootOsError};
+use crate::event::{DeviceId as RootDeviceId, Ime, WindowEvent};
+use crate::event_loop::AsyncRequestSerial;
+use crate::platform_impl::common::popup;
+use crate::platform_impl::{
+    DeviceId as PlatformDeviceId, DropResponse, Fullscreen, MonitorHandle as PlatformMonitorHandle,
+    PlatformIcon, VideoModeHandle as PlatformVideoModeHandle,
+};
+use crate::window::{
+    Cursor, CursorGrabMode, ImePurpose, ResizeDirection, Theme, UserAttentionType,
+    WindowAttributes, WindowButtons, WindowLevel,
+};
+
+use super::{
+    not_supported, ActiveEventLoop, DeviceId, Message, MonitorHandle, Monitors, WakeSender,
+    WindowId,
+};
+
+/// The default size of the windows, as on X11.
+const DEFAULT_INNER_SIZE: PhysicalSize<u32> = PhysicalSize::new(800, 600);
+
+static WINDOW_ID: AtomicU64 = AtomicU64::new(1);
+
+/// The state of the virtual window, which is the source of truth for the getters.
+pub(crate) struct WindowState {
+    title: String,
+    visible: bool,
+    position: PhysicalPosition<i32>,
+    size: PhysicalSize<u32>,
+    min_size: Option<Size>,
+    max_size: Option<Size>,
+    resize_increments: Option<Size>,
+    resizable: bool,
+    enabled_buttons: WindowButtons,
+    maximized: bool,
+    minimized: bool,
+    fullscreen: Option<Fullscreen>,
+    /// The geometry to restore when leaving the maximized or fullscreen states.
+    restore_geometry: Option<(PhysicalPosition<i32>, PhysicalSize<u32>)>,
+    decorated: bool,
+    window_level: WindowLevel,
+    theme: Option<Theme>,
+    has_focus: bool,
+    pointer_position: Option<PhysicalPosition<i32>>,
+    ime_allowed: bool,
+}
+
+impl WindowState {
+    /// Update the state according to the delivered event.
+    pub(crate) fn update(&mut self, event: &WindowEvent) {
+        match *event {
+            WindowEvent::Resized(size) => self.size = size,
+            WindowEvent::Moved(position) => self.position = position,
+            WindowEvent::Focused(focused) => self.has_focus = focused,
+            WindowEvent::ThemeChanged(theme) => self.theme = Some(theme),
+            WindowEvent::CursorMoved { position, .. } => {
+                self.pointer_position = Some(position.cast())
+            },
+            WindowEvent::CursorLeft { .. } => self.pointer_position = None,
+            WindowEvent::Ime(Ime::Enabled) => self.ime_allowed = true,
+            WindowEvent::Ime(Ime::Disabled) => self.ime_allowed = false,
+            WindowEvent::PopupDismissed => self.visible = false,
+            _ => (),
+        }
+    }
+}
+
+pub struct Window {
+    id: WindowId,
+    state: Arc<Mutex<WindowState>>,
+    sender: WakeSender<Message>,
+    monitors: Monitors,
+}
+
+impl Window {
+    pub(crate) fn new(
+        event_loop: &ActiveEventLoop,
+        attributes: WindowAttributes,
+    ) -> Result<Self, RootOsError> {
+        let id = WindowId::from(WINDOW_ID.fetch_add(1, Ordering::Relaxed));
+
+        let position = event_loop
+            .primary_monitor()
+            .map(|monitor| monitor.position())
+            .unwrap_or_else(|| PhysicalPosition::new(0, 0));
+        let state = WindowState {
+            title: attributes.title,
+            visible: attributes.visible,
+            position,
+            size: DEFAULT_INNER_SIZE,
+            min_size: attributes.min_inner_size,
+            max_size: attributes.max_inner_size,
+            resize_increments: attributes.resize_increments,
+            resizable: attributes.resizable,
+            enabled_buttons: attributes.enabled_buttons,
+            maximized: false,
+            minimized: false,
+            fullscreen: None,
+            restore_geometry: None,
+            decorated: attributes.decorations,
+            window_level: attributes.window_level,
+            theme: attributes.preferred_theme,
+            has_focus: false,
+            pointer_position: None,
+            ime_allowed: false,
+        };
+
+        let window = Window {
+            id,
+            state: Arc::new(Mutex::new(state)),
+            sender: event_loop.sender.clone(),
+            monitors: event_loop.monitors.clone(),
+        };
+        event_loop.windows.borrow_mut().insert(id, Arc::downgrade(&window.state));
+
+        // Apply the geometry with the scale factor of the monitor the window is on.
+        if let Some(position) = attributes.position {
+            let position = position.to_physical(window.scale_factor());
+            window.state().position = position;
+        }
+        let size = attributes
+            .inner_size
+            .map(|size| size.to_physical(window.scale_factor()))
+            .unwrap_or(DEFAULT_INNER_SIZE);
+        let size = window.constrain_size(size);
+        window.state().size = size;
+
+        // Place the popup next to its parent, on the monitor of the parent.
+        let parent = attributes.popup.as_ref().and_then(|popup| {
+            event_loop.windows.borrow().get(&popup.parent.0).and_then(Weak::upgrade)
+        });
+        if let (Some(popup), Some(parent)) = (attributes.popup.as_ref(), parent) {
+            let parent_position = parent.lock().unwrap().position;
+            let monitor = window.monitor_at(parent_position);
+            let scale_factor = monitor.as_ref().map_or(1.0, |monitor| monitor.scale_factor());
+            let bounds = monitor.map(|monitor| (monitor.position(), monitor.size()));
+            let (position, size) = popup::place(popup, parent_position, scale_factor, size, bounds);
+            let mut state = window.state();
+            state.position = position;
+            state.size = size;
+        }
+
+        if attributes.maximized {
+            window.set_maximized(true);
+        }
+        if let Some(fullscreen) = attributes.fullscreen {
+            window.set_fullscreen(Some(fullscreen.into()));
+        }
+
+        if attributes.visible {
+            if attributes.active {
+                window.send_event(WindowEvent::Focused(true));
+            }
+            window.request_redraw();
+        }
+
+        Ok(window)
+    }
+
+    #[inline]
+    fn state(&self) -> MutexGuard<'_, WindowState> {
+        self.state.lock().unwrap()
+    }
+
+    /// Send the event, delivered on the next iteration of the event loop.
+    fn send_event(&self, event: WindowEvent) {
+        let _ = self.sender.send(Message::Window(self.id, event));
+    }
+
+    /// Clamp the size to the size constraints of the window.
+    fn constrain_size(&self, mut size: PhysicalSize<u32>) -> PhysicalSize<u32> {
+        let scale_factor = self.scale_factor();
+        let (min_size, max_size) = {
+            let state = self.state();
+            (state.min_size, state.max_size)
+        };
+        if let Some(max_size) = max_size.map(|size| size.to_physical::<u32>(scale_factor)) {
+            size.width = cmp::min(size.width, max_size.width);
+            size.height = cmp::min(size.height, max_size.height);
+        }
+        if let Some(min_size) = min_size.map(|size| size.to_physical::<u32>(scale_factor)) {
+            size.width = cmp::max(size.width, min_size.width);
+            size.height = cmp::max(size.height, min_size.height);
+        }
+        size
+    }
+
+    /// Move and resize the window, reporting the changes.
+    fn set_geometry(&self, position: PhysicalPosition<i32>, size: PhysicalSize<u32>) {
+        let (moved, resized) = {
+            let mut state = self.state();
+            let moved = state.position != position;
+            let resized = state.size != size;
+            state.position = position;
+            state.size = size;
+            (moved, resized)
+        };
+
+        if moved {
+            self.send_event(WindowEvent::Moved(position));
+        }
+        if resized {
+            self.send_event(WindowEvent::Resized(size));
+            self.request_redraw();
+        }
+    }
+
+    /// Cover the whole monitor, saving the geometry to restore later.
+    fn cover_monitor(&self, monitor: &MonitorHandle) {
+        {
+            let mut state = self.state();
+            if state.restore_geometry.is_none() {
+                state.restore_geometry = Some((state.position, state.size));
+            }
+        }
+        self.set_geometry(monitor.position(), monitor.size());
+    }
+
+    /// Restore the geometry saved by [`Self::cover_monitor`].
+    fn restore_geometry(&self) {
+        let geometry = {
+            let mut state = self.state();
+            if state.maximized || state.fullscreen.is_some() {
+                return;
+            }
+            state.restore_geometry.take()
+        };
+        if let Some((position, size)) = geometry {
+            self.set_geometry(position, size);
+        }
+    }
+
+    #[inline]
+    pub fn id(&self) -> WindowId {
+        self.id
+    }
+
+    #[inline]
+    pub fn set_title(&self, title: &str) {
+        self.state().title = title.to_owned();
+    }
+
+    #[inline]
+    pub fn title(&self) -> String {
+        self.state().title.clone()
+    }
+
+    #[inline]
+    pub fn set_transparent(&self, _transparent: bool) {}
+
+    #[inline]
+    pub fn set_blur(&self, _blur: bool) {}
+
+    #[inline]
+    pub fn set_modal(&self, _owner: &Window) -> Result<(), RootOsError> {
+        Ok(())
+    }
+
+    #[inline]
+    pub fn set_owner(&self, _owner: Option<&Window>) {}
+
+    #[inline]
+    pub fn set_visible(&self, visible: bool) {
+        let was_visible = std::mem::replace(&mut self.state().visible, visible);
+        if visible && !was_visible {
+            self.request_redraw();
+        }
+    }
+
+    #[inline]
+    pub fn is_visible(&self) -> Option<bool> {
+        Some(self.state().visible)
+    }
+
+    #[inline]
+    pub fn pointer_position(&self) -> Option<PhysicalPosition<i32>> {
+        self.state().pointer_position
+    }
+
+    #[inline]
+    pub fn outer_position(&self) -> Result<PhysicalPosition<i32>, NotSupportedError> {
+        Ok(self.state().position)
+    }
+
+    #[inline]
+    pub fn inner_position(&self) -> Result<PhysicalPosition<i32>, NotSupportedError> {
+        Ok(self.state().position)
+    }
+
+    #[inline]
+    pub fn set_outer_position(&self, position: Position) {
+        let position = position.to_physical(self.scale_factor());
+        let size = self.state().size;
+        self.set_geometry(position, size);
+    }
+
+    #[inline]
+    pub fn inner_size(&self) -> PhysicalSize<u32> {
+        self.state().size
+    }
+
+    #[inline]
+    pub fn outer_size(&self) -> PhysicalSize<u32> {
+        self.state().size
+    }
+
+    #[inline]
+    pub fn request_inner_size(&self, size: Size) -> Option<PhysicalSize<u32>> {
+        let size = self.constrain_size(size.to_physical(self.scale_factor()));
+        let position = self.state().position;
+        self.set_geometry(position, size);
+        Some(size)
+    }
+
+    #[inline]
+    pub(crate) fn request_activation_token(&self) -> Result<AsyncRequestSerial, NotSupportedError> {
+        Err(not_supported())
+    }
+
+    #[inline]
+    pub(crate) fn set_clipboard(
+        &self,
+        _selection: Selection,
+        _data: Vec<MimeData>,
+    ) -> Result<(), NotSupportedError> {
+        Err(not_supported())
+    }
+
+    #[inline]
+    pub(crate) fn request_clipboard(
+        &self,
+        _selection: Selection,
+        _mime_types: Vec<String>,
+    ) -> Result<AsyncRequestSerial, NotSupportedError> {
+        Err(not_supported())
+    }
+
+    #[inline]
+    pub(crate) fn set_drop_response(
+        &self,
+        _response: DropResponse,
+    ) -> Result<(), NotSupportedError> {
+        Err(not_supported())
+    }
+
+    #[inline]
+    pub(crate) fn start_drag(
+        &self,
+        _data: DragData,
+        _allowed_actions: DndActions,
+    ) -> Result<(), NotSupportedError> {
+        Err(not_supported())
+    }
+
+    #[inline]
+    pub fn set_min_inner_size(&self, dimensions: Option<Size>) {
+        self.state().min_size = dimensions;
+        let size = self.inner_size();
+        self.request_inner_size(size.into());
+    }
+
+    #[inline]
+    pub fn set_max_inner_size(&self, dimensions: Option<Size>) {
+        self.state().max_size = dimensions;
+        let size = self.inner_size();
+        self.request_inner_size(size.into());
+    }
+
+    #[inline]
+    pub fn resize_increments(&self) -> Option<PhysicalSize<u32>> {
+        let scale_factor = self.scale_factor();
+        self.state().resize_increments.map(|increments| increments.to_physical(scale_factor))
+    }
+
+    #[inline]
+    pub fn set_resize_increments(&self, increments: Option<Size>) {
+        self.state().resize_increments = increments;
+    }
+
+    #[inline]
+    pub fn set_resizable(&self, resizable: bool) {
+        self.state().resizable = resizable;
+    }
+
+    #[inline]
+    pub fn is_resizable(&self) -> bool {
+        self.state().resizable
+    }
+
+    #[inline]
+    pub fn set_enabled_buttons(&self, buttons: WindowButtons) {
+        self.state().enabled_buttons = buttons;
+    }
+
+    #[inline]
+    pub fn enabled_buttons(&self) -> WindowButtons {
+        self.state().enabled_buttons
+    }
+
+    #[inline]
+    pub fn set_cursor(&self, _cursor: Cursor) {}
+
+    #[inline]
+    pub fn set_cursor_grab(&self, _mode: CursorGrabMode) -> Result<(), ExternalError> {
+        Ok(())
+    }
+
+    #[inline]
+    pub fn set_cursor_visible(&self, _visible: bool) {}
+
+    #[inline]
+    pub fn drag_window(&self) -> Result<(), ExternalError> {
+        Err(ExternalError::NotSupported(not_supported()))
+    }
+
+    #[inline]
+    pub fn drag_resize_window(&self, _direction: ResizeDirection) -> Result<(), ExternalError> {
+        Err(ExternalError::NotSupported(not_supported()))
+    }
+
+    #[inline]
+    pub fn show_window_menu(&self, _position: Position) {}
+
+    #[inline]
+    pub fn set_cursor_hittest(&self, _hittest: bool) -> Result<(), ExternalError> {
+        Ok(())
+    }
+
+    #[inline]
+    pub fn scale_factor(&self) -> f64 {
+        self.current_monitor().map_or(1.0, |monitor| monitor.scale_factor())
+    }
+
+    #[inline]
+    pub fn set_cursor_position(&self, position: Position) -> Result<(), ExternalError> {
+        let position = position.to_physical::<f64>(self.scale_factor());
+        let root_position = {
+            let mut state = self.state();
+            state.pointer_position = Some(position.cast());
+            PhysicalPosition::new(
+                state.position.x as f64 + position.x,
+                state.position.y as f64 + position.y,
+            )
+        };
+        let device_id = RootDeviceId(PlatformDeviceId::Headless(DeviceId));
+        self.send_event(WindowEvent::CursorMoved { device_id, position, root_position });
+        Ok(())
+    }
+
+    #[inline]
+    pub fn set_maximized(&self, maximized: bool) {
+        if std::mem::replace(&mut self.state().maximized, maximized) == maximized {
+            return;
+        }
+
+        if maximized {
+            if let Some(monitor) = self.current_monitor() {
+                self.cover_monitor(&monitor);
+            }
+        } else {
+            self.restore_geometry();
+        }
+    }
+
+    #[inline]
+    pub fn is_maximized(&self) -> bool {
+        self.state().maximized
+    }
+
+    #[inline]
+    pub fn set_minimized(&self, minimized: bool) {
+        if std::mem::replace(&mut self.state().minimized, minimized) != minimized {
+            self.send_event(WindowEvent::Occluded(minimized));
+        }
+    }
+
+    #[inline]
+    pub fn is_minimized(&self) -> Option<bool> {
+        Some(self.state().minimized)
+    }
+
+    #[inline]
+    pub(crate) fn fullscreen(&self) -> Option<Fullscreen> {
+        self.state().fullscreen.clone()
+    }
+
+    #[inline]
+    pub(crate) fn set_fullscreen(&self, fullscreen: Option<Fullscreen>) {
+        let monitor = match &fullscreen {
+            Some(Fullscreen::Exclusive(PlatformVideoModeHandle::Headless(mode))) => {
+                Some(mode.monitor())
+            },
+            Some(Fullscreen::Borderless(Some(PlatformMonitorHandle::Headless(monitor)))) => {
+                Some(monitor.clone())
+            },
+            Some(Fullscreen::Borderless(None)) => self.current_monitor(),
+            #[allow(unreachable_patterns)]
+            Some(_) => {
+                tracing::error!("passed a monitor of another backend to the headless backend");
+                return;
+            },
+            None => None,
+        };
+
+        self.state().fullscreen = fullscreen;
+        match monitor {
+            Some(monitor) => self.cover_monitor(&monitor),
+            None => self.restore_geometry(),
+        }
+    }
+
+    #[inline]
+    pub fn set_decorations(&self, decorations: bool) {
+        self.state().decorated = decorations;
+    }
+
+    #[inline]
+    pub fn is_decorated(&self) -> bool {
+        self.state().decorated
+    }
+
+    #[inline]
+    pub fn set_window_level(&self, level: WindowLevel) {
+        self.state().window_level = level;
+    }
+
+    #[inline]
+    pub(crate) fn set_window_icon(&self, _icon: Option<PlatformIcon>) {}
+
+    #[inline]
+    pub fn set_ime_cursor_area(&self, _position: Position, _size: Size) {}
+
+    #[inline]
+    pub fn set_ime_allowed(&self, allowed: bool) {
+        if std::mem::replace(&mut self.state().ime_allowed, allowed) != allowed {
+            self.send_event(WindowEvent::Ime(if allowed { Ime::Enabled } else { Ime::Disabled }));
+        }
+    }
+
+    #[inline]
+    pub fn set_ime_purpose(&self, _purpose: ImePurpose) {}
+
+    #[inline]
+    pub fn set_ime_surrounding_text(&self, _text: String, _cursor: usize, _anchor: usize) {}
+
+    #[inline]
+    pub fn commit_ime(&self) {}
+
+    #[inline]
+    pub fn focus_window(&self) {
+        let state = self.state();
+        if state.visible && !state.minimized && !state.has_focus {
+            drop(state);
+            self.send_event(WindowEvent::Focused(true));
+        }
+    }
+
+    #[inline]
+    pub fn request_user_attention(&self, _request_type: Option<UserAttentionType>) {}
+
+    #[inline]
+    pub fn request_redraw(&self) {
+        self.send_event(WindowEvent::RedrawRequested);
+    }
+
+    #[inline]
+    pub fn pre_present_notify(&self) {}
+
+    #[inline]
+    pub fn current_monitor(&self) -> Option<MonitorHandle> {
+        let position = self.state().position;
+        self.monitor_at(position)
+    }
+
+    /// The monitor containing the position, or the primary monitor.
+    fn monitor_at(&self, position: PhysicalPosition<i32>) -> Option<MonitorHandle> {
+        let monitors = self.monitors.lock().unwrap();
+        monitors
+            .iter()
+            .find(|monitor| monitor.contains(position))
+            .or_else(|| monitors.first())
+            .cloned()
+    }
+
+    #[inline]
+    pub fn available_monitors(&self) -> Vec<MonitorHandle> {
+        self.monitors.lock().unwrap().clone()
+    }
+
+    #[inline]
+    pub fn primary_monitor(&self) -> Option<MonitorHandle> {
+        self.monitors.lock().unwrap().first().cloned()
+    }
+
+    #[cfg(feature = "rwh_04")]
+    #[inline]
+    pub fn raw_window_handle_rwh_04(&self) -> rwh_04::RawWindowHandle {
+        super::no_raw_handle()
+    }
+
+    #[cfg(feature = "rwh_05")]
+    #[inline]
+    pub fn raw_window_handle_rwh_05(&self) -> rwh_05::RawWindowHandle {
+        super::no_raw_handle()
+    }
+
+    #[cfg(feature = "rwh_05")]
+    #[inline]
+    pub fn raw_display_handle_rwh_05(&self) -> rwh_05::RawDisplayHandle {
+        super::no_raw_handle()
+    }
+
+    #[cfg(feature = "rwh_06")]
+    #[inline]
+    pub fn raw_window_handle_rwh_06(&self) -> Result<rwh_06::RawWindowHandle, rwh_06::HandleError> {
+        Err(rwh_06::HandleError::NotSupported)
+    }
+
+    #[cfg(feature = "rwh_06")]
+    #[inline]
+    pub fn raw_display_handle_rwh_06(
+        &self,
+    ) -> Result<rwh_06::RawDisplayHandle, rwh_06::HandleError> {
+        Err(rwh_06::HandleError::NotSupported)
+    }
+
+    #[inline]
+    pub fn set_theme(&self, theme: Option<Theme>) {
+        let old_theme = std::mem::replace(&mut self.state().theme, theme);
+        if let Some(theme) = theme.filter(|&theme| Some(theme) != old_theme) {
+            self.send_event(WindowEvent::ThemeChanged(theme));
+        }
+    }
+
+    #[inline]
+    pub fn theme(&self) -> Option<Theme> {
+        self.state().theme
+    }
+
+    #[inline]
+    pub fn set_content_protected(&self, _protected: bool) {}
+
+    #[inline]
+    pub fn has_focus(&self) -> bool {
+        self.state().has_focus
+    }
+}
+
+impl Drop for Window {
+    fn drop(&mut self) {
+        self.send_event(WindowEvent::Destroyed);
+    }
+}
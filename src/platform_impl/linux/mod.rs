@@ -4,7 +4,7 @@
 compile_error!("Please select a feature to build for unix: `x11`, `wayland`");
 
 use std::collections::VecDeque;
-use std::os::unix::io::{AsFd, AsRawFd, BorrowedFd, RawFd};
+use std::os::unix::io::{AsFd, AsRawFd, BorrowedFd, OwnedFd, RawFd};
 use std::sync::Arc;
 use std::time::Duration;
 use std::{env, fmt};
@@ -17,13 +17,19 @@ use smol_str::SmolStr;
 
 #[cfg(x11_platform)]
 use self::x11::{X11Error, XConnection, XError, XNotSupported};
+use crate::clipboard::{MimeData, Selection};
+use crate::dnd::{DndActions, DragData};
 use crate::dpi::{PhysicalPosition, PhysicalSize, Position, Size};
 use crate::error::{EventLoopError, ExternalError, NotSupportedError, OsError as RootOsError};
+#[cfg(x11_platform)]
+use crate::event::{ElementState, MouseButton};
 use crate::event_loop::{
     ActiveEventLoop as RootELW, AsyncRequestSerial, ControlFlow, DeviceEvents, EventLoopClosed,
+    Interest, SourceToken, TimerId, TimerSchedule,
 };
 use crate::icon::Icon;
-use crate::keyboard::Key;
+use crate::keyboard::{Key, KeyboardLayouts};
+use crate::platform::layer_shell::{KeyboardInteractivity, Layer, LayerAnchor, LayerMargins};
 use crate::platform::pump_events::PumpStatus;
 #[cfg(x11_platform)]
 use crate::platform::x11::{WindowType as XWindowType, XlibErrorHook};
@@ -32,12 +38,14 @@ use crate::window::{
     ResizeDirection, Theme, UserAttentionType, WindowAttributes, WindowButtons, WindowLevel,
 };
 
+pub(crate) use self::common::dnd::DropResponse;
 pub(crate) use self::common::xkb::{physicalkey_to_scancode, scancode_to_physicalkey};
 pub(crate) use crate::cursor::OnlyCursorImageSource as PlatformCustomCursorSource;
 pub(crate) use crate::icon::RgbaIcon as PlatformIcon;
 pub(crate) use crate::platform_impl::Fullscreen;
 
 pub(crate) mod common;
+pub(crate) mod headless;
 #[cfg(wayland_platform)]
 pub(crate) mod wayland;
 #[cfg(x11_platform)]
@@ -49,12 +57,18 @@ pub(crate) enum Backend {
     X,
     #[cfg(wayland_platform)]
     Wayland,
+    Headless,
 }
 
+/// The backend of the event loop, to deserialize the device identifiers.
+#[cfg(feature = "serde")]
+static ACTIVE_BACKEND: std::sync::Mutex<Option<Backend>> = std::sync::Mutex::new(None);
+
 #[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
 pub(crate) struct PlatformSpecificEventLoopAttributes {
     pub(crate) forced_backend: Option<Backend>,
     pub(crate) any_thread: bool,
+    pub(crate) tablet_tools: bool,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -73,10 +87,20 @@ impl ApplicationName {
 pub struct PlatformSpecificWindowAttributes {
     pub name: Option<ApplicationName>,
     pub activation_token: Option<ActivationToken>,
+    pub layer_shell: Option<LayerShellAttributes>,
     #[cfg(x11_platform)]
     pub x11: X11WindowAttributes,
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LayerShellAttributes {
+    pub layer: Layer,
+    pub anchor: LayerAnchor,
+    pub exclusive_zone: i32,
+    pub keyboard_interactivity: KeyboardInteractivity,
+    pub margins: LayerMargins,
+}
+
 #[derive(Clone, Debug)]
 #[cfg(x11_platform)]
 pub struct X11WindowAttributes {
@@ -98,6 +122,7 @@ impl Default for PlatformSpecificWindowAttributes {
         Self {
             name: None,
             activation_token: None,
+            layer_shell: None,
             #[cfg(x11_platform)]
             x11: X11WindowAttributes {
                 visual_id: None,
@@ -145,7 +170,8 @@ pub(crate) enum Window {
     #[cfg(x11_platform)]
     X(x11::Window),
     #[cfg(wayland_platform)]
-    Wayland(wayland::Window),
+    Wayland(Box<wayland::Window>),
+    Headless(headless::Window),
 }
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -175,6 +201,7 @@ pub enum DeviceId {
     X(x11::DeviceId),
     #[cfg(wayland_platform)]
     Wayland(wayland::DeviceId),
+    Headless(headless::DeviceId),
 }
 
 impl DeviceId {
@@ -184,6 +211,33 @@ impl DeviceId {
         #[cfg(all(not(wayland_platform), x11_platform))]
         return DeviceId::X(x11::DeviceId::dummy());
     }
+
+    #[cfg(feature = "serde")]
+    pub(crate) fn into_raw(self) -> i64 {
+        match self {
+            #[cfg(x11_platform)]
+            DeviceId::X(device_id) => device_id.into_raw(),
+            _ => 0,
+        }
+    }
+
+    // NOTE: Only the X11 devices carry the identifier, the devices of the other backends are
+    // all the same. Without an event loop, the nonzero identifiers are taken as X11 ones.
+    #[cfg(feature = "serde")]
+    pub(crate) fn from_raw(raw: i64) -> Option<Self> {
+        #[cfg(not(x11_platform))]
+        let _ = raw;
+        match *ACTIVE_BACKEND.lock().unwrap_or_else(|e| e.into_inner()) {
+            #[cfg(x11_platform)]
+            Some(Backend::X) => x11::DeviceId::from_raw(raw).map(DeviceId::X),
+            #[cfg(wayland_platform)]
+            Some(Backend::Wayland) => Some(DeviceId::Wayland(wayland::DeviceId::dummy())),
+            Some(Backend::Headless) => Some(DeviceId::Headless(headless::DeviceId::dummy())),
+            #[cfg(x11_platform)]
+            None if raw != 0 => x11::DeviceId::from_raw(raw).map(DeviceId::X),
+            None => Some(Self::dummy()),
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
@@ -192,6 +246,7 @@ pub enum MonitorHandle {
     X(x11::MonitorHandle),
     #[cfg(wayland_platform)]
     Wayland(wayland::MonitorHandle),
+    Headless(headless::MonitorHandle),
 }
 
 /// `x11_or_wayland!(match expr; Enum(foo) => foo.something())`
@@ -200,6 +255,7 @@ pub enum MonitorHandle {
 /// match self {
 ///    Enum::X(foo) => foo.something(),
 ///    Enum::Wayland(foo) => foo.something(),
+///    Enum::Headless(foo) => foo.something(),
 /// }
 /// ```
 /// The result can be converted to another enum by adding `; as AnotherEnum`
@@ -210,6 +266,7 @@ macro_rules! x11_or_wayland {
             $enum::X($($c1)*) => $enum2::X($x),
             #[cfg(wayland_platform)]
             $enum::Wayland($($c1)*) => $enum2::Wayland($x),
+            $enum::Headless($($c1)*) => $enum2::Headless($x),
         }
     };
     (match $what:expr; $enum:ident ( $($c1:tt)* ) => $x:expr) => {
@@ -218,6 +275,7 @@ macro_rules! x11_or_wayland {
             $enum::X($($c1)*) => $x,
             #[cfg(wayland_platform)]
             $enum::Wayland($($c1)*) => $x,
+            $enum::Headless($($c1)*) => $x,
         }
     };
 }
@@ -265,6 +323,7 @@ pub enum VideoModeHandle {
     X(x11::VideoModeHandle),
     #[cfg(wayland_platform)]
     Wayland(wayland::VideoModeHandle),
+    Headless(headless::VideoModeHandle),
 }
 
 impl VideoModeHandle {
@@ -298,12 +357,16 @@ impl Window {
         match *window_target {
             #[cfg(wayland_platform)]
             ActiveEventLoop::Wayland(ref window_target) => {
-                wayland::Window::new(window_target, attribs).map(Window::Wayland)
+                wayland::Window::new(window_target, attribs)
+                    .map(|window| Window::Wayland(Box::new(window)))
             },
             #[cfg(x11_platform)]
             ActiveEventLoop::X(ref window_target) => {
                 x11::Window::new(window_target, attribs).map(Window::X)
             },
+            ActiveEventLoop::Headless(ref window_target) => {
+                headless::Window::new(window_target, attribs).map(Window::Headless)
+            },
         }
     }
 
@@ -332,19 +395,32 @@ impl Window {
 
     #[inline]
     pub fn set_modal(&self, owner: &Window) -> Result<(), RootOsError> {
-        match self {
-            Window::X(s) => {
-                match owner {
-                    Window::X(o) => s.set_modal(o),
-                    Window::Wayland(_) => unreachable!("unsupported"),
-                }
-            }
-            Window::Wayland(s) => {
-                match owner {
-                    Window::X(_) => unreachable!("unsupported"),
-                    Window::Wayland(o) => s.set_modal(o),
-                }
-            }
+        match (self, owner) {
+            #[cfg(x11_platform)]
+            (Window::X(s), Window::X(o)) => s.set_modal(o),
+            #[cfg(wayland_platform)]
+            (Window::Wayland(s), Window::Wayland(o)) => s.set_modal(o),
+            (Window::Headless(s), Window::Headless(o)) => s.set_modal(o),
+            #[allow(unreachable_patterns)]
+            _ => Err(os_error!(OsError::Misc("the owner belongs to another backend"))),
+        }
+    }
+
+    #[inline]
+    pub fn set_owner(&self, owner: Option<&Window>) {
+        match (self, owner) {
+            #[cfg(x11_platform)]
+            (Window::X(s), Some(Window::X(o))) => s.set_owner(Some(o)),
+            #[cfg(x11_platform)]
+            (Window::X(s), None) => s.set_owner(None),
+            #[cfg(wayland_platform)]
+            (Window::Wayland(s), Some(Window::Wayland(o))) => s.set_owner(Some(o)),
+            #[cfg(wayland_platform)]
+            (Window::Wayland(s), None) => s.set_owner(None),
+            (Window::Headless(s), Some(Window::Headless(o))) => s.set_owner(Some(o)),
+            (Window::Headless(s), None) => s.set_owner(None),
+            #[allow(unreachable_patterns)]
+            _ => tracing::warn!("Ignoring the owner which belongs to another backend"),
         }
     }
 
@@ -403,6 +479,41 @@ impl Window {
         x11_or_wayland!(match self; Window(w) => w.request_activation_token())
     }
 
+    #[inline]
+    pub(crate) fn set_clipboard(
+        &self,
+        selection: Selection,
+        data: Vec<MimeData>,
+    ) -> Result<(), NotSupportedError> {
+        x11_or_wayland!(match self; Window(w) => w.set_clipboard(selection, data))
+    }
+
+    #[inline]
+    pub(crate) fn request_clipboard(
+        &self,
+        selection: Selection,
+        mime_types: Vec<String>,
+    ) -> Result<AsyncRequestSerial, NotSupportedError> {
+        x11_or_wayland!(match self; Window(w) => w.request_clipboard(selection, mime_types))
+    }
+
+    #[inline]
+    pub(crate) fn set_drop_response(
+        &self,
+        response: DropResponse,
+    ) -> Result<(), NotSupportedError> {
+        x11_or_wayland!(match self; Window(w) => w.set_drop_response(response))
+    }
+
+    #[inline]
+    pub(crate) fn start_drag(
+        &self,
+        data: DragData,
+        allowed_actions: DndActions,
+    ) -> Result<(), NotSupportedError> {
+        x11_or_wayland!(match self; Window(w) => w.start_drag(data, allowed_actions))
+    }
+
     #[inline]
     pub fn set_min_inner_size(&self, dimensions: Option<Size>) {
         x11_or_wayland!(match self; Window(w) => w.set_min_inner_size(dimensions))
@@ -558,6 +669,11 @@ impl Window {
         x11_or_wayland!(match self; Window(w) => w.set_ime_purpose(purpose))
     }
 
+    #[inline]
+    pub fn set_ime_surrounding_text(&self, text: String, cursor: usize, anchor: usize) {
+        x11_or_wayland!(match self; Window(w) => w.set_ime_surrounding_text(text, cursor, anchor))
+    }
+
     #[inline]
     pub fn commit_ime(&self) {
         x11_or_wayland!(match self; Window(w) => w.commit_ime())
@@ -598,6 +714,9 @@ impl Window {
             Window::Wayland(ref window) => {
                 window.available_monitors().into_iter().map(MonitorHandle::Wayland).collect()
             },
+            Window::Headless(ref window) => {
+                window.available_monitors().into_iter().map(MonitorHandle::Headless).collect()
+            },
         }
     }
 
@@ -660,6 +779,54 @@ impl Window {
     pub fn title(&self) -> String {
         x11_or_wayland!(match self; Window(window) => window.title())
     }
+
+    #[cfg(x11_platform)]
+    #[inline]
+    pub fn inject_key(
+        &self,
+        key: crate::keyboard::PhysicalKey,
+        state: ElementState,
+    ) -> Result<(), ExternalError> {
+        match self {
+            Window::X(w) => w.inject_key(key, state),
+            #[allow(unreachable_patterns)]
+            _ => Err(ExternalError::NotSupported(NotSupportedError::new())),
+        }
+    }
+
+    #[cfg(x11_platform)]
+    #[inline]
+    pub fn inject_pointer_motion(&self, position: Position) -> Result<(), ExternalError> {
+        match self {
+            Window::X(w) => w.inject_pointer_motion(position),
+            #[allow(unreachable_patterns)]
+            _ => Err(ExternalError::NotSupported(NotSupportedError::new())),
+        }
+    }
+
+    #[cfg(x11_platform)]
+    #[inline]
+    pub fn inject_button(
+        &self,
+        button: MouseButton,
+        state: ElementState,
+    ) -> Result<(), ExternalError> {
+        match self {
+            Window::X(w) => w.inject_button(button, state),
+            #[allow(unreachable_patterns)]
+            _ => Err(ExternalError::NotSupported(NotSupportedError::new())),
+        }
+    }
+
+    #[cfg(x11_platform)]
+    #[inline]
+    pub fn inject_scroll(&self, columns: i32, lines: i32) -> Result<(), ExternalError> {
+        match self {
+            Window::X(w) => w.inject_scroll(columns, lines),
+            #[allow(unreachable_patterns)]
+            _ => Err(ExternalError::NotSupported(NotSupportedError::new())),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Eq, PartialEq, Hash)]
@@ -674,6 +841,7 @@ pub(crate) enum PlatformCustomCursor {
     Wayland(wayland::CustomCursor),
     #[cfg(x11_platform)]
     X(x11::CustomCursor),
+    Headless(crate::cursor::OnlyCursorImage),
 }
 
 /// Hooks for X11 errors.
@@ -732,6 +900,7 @@ pub enum EventLoop<T: 'static> {
     Wayland(Box<wayland::EventLoop<T>>),
     #[cfg(x11_platform)]
     X(x11::EventLoop<T>),
+    Headless(headless::EventLoop<T>),
 }
 
 pub enum EventLoopProxy<T: 'static> {
@@ -739,6 +908,7 @@ pub enum EventLoopProxy<T: 'static> {
     X(x11::EventLoopProxy<T>),
     #[cfg(wayland_platform)]
     Wayland(wayland::EventLoopProxy<T>),
+    Headless(headless::EventLoopProxy<T>),
 }
 
 impl<T: 'static> Clone for EventLoopProxy<T> {
@@ -751,7 +921,9 @@ impl<T: 'static> EventLoop<T> {
     pub(crate) fn new(
         attributes: &PlatformSpecificEventLoopAttributes,
     ) -> Result<Self, EventLoopError> {
-        if !attributes.any_thread && !is_main_thread() {
+        // The headless backend doesn't talk to a display server, so it's fine on any thread.
+        let headless = attributes.forced_backend == Some(Backend::Headless);
+        if !attributes.any_thread && !headless && !is_main_thread() {
             panic!(
                 "Initializing the event loop outside of the main thread is a significant \
                  cross-platform compatibility hazard. If you absolutely need to create an \
@@ -797,17 +969,25 @@ impl<T: 'static> EventLoop<T> {
         };
 
         // Create the display based on the backend.
-        match backend {
+        let event_loop = match backend {
             #[cfg(wayland_platform)]
-            Backend::Wayland => EventLoop::new_wayland_any_thread().map_err(Into::into),
+            Backend::Wayland => EventLoop::new_wayland_any_thread(attributes.tablet_tools)?,
             #[cfg(x11_platform)]
-            Backend::X => EventLoop::new_x11_any_thread().map_err(Into::into),
+            Backend::X => EventLoop::new_x11_any_thread()?,
+            Backend::Headless => EventLoop::Headless(headless::EventLoop::new()),
+        };
+
+        #[cfg(feature = "serde")]
+        {
+            *ACTIVE_BACKEND.lock().unwrap_or_else(|e| e.into_inner()) = Some(backend);
         }
+
+        Ok(event_loop)
     }
 
     #[cfg(wayland_platform)]
-    fn new_wayland_any_thread() -> Result<EventLoop<T>, EventLoopError> {
-        wayland::EventLoop::new().map(|evlp| EventLoop::Wayland(Box::new(evlp)))
+    fn new_wayland_any_thread(tablet_tools: bool) -> Result<EventLoop<T>, EventLoopError> {
+        wayland::EventLoop::new(tablet_tools).map(|evlp| EventLoop::Wayland(Box::new(evlp)))
     }
 
     #[cfg(x11_platform)]
@@ -827,7 +1007,15 @@ impl<T: 'static> EventLoop<T> {
         match *self {
             #[cfg(wayland_platform)]
             EventLoop::Wayland(_) => true,
+            _ => false,
+        }
+    }
+
+    #[inline]
+    pub fn is_x11(&self) -> bool {
+        match *self {
             #[cfg(x11_platform)]
+            EventLoop::X(_) => true,
             _ => false,
         }
     }
@@ -857,6 +1045,10 @@ impl<T: 'static> EventLoop<T> {
         x11_or_wayland!(match self; EventLoop(evlp) => evlp.pump_events(timeout, callback))
     }
 
+    pub fn prepare(&mut self) -> Option<Duration> {
+        x11_or_wayland!(match self; EventLoop(evlp) => evlp.prepare())
+    }
+
     pub fn window_target(&self) -> &crate::event_loop::ActiveEventLoop {
         x11_or_wayland!(match self; EventLoop(evlp) => evlp.window_target())
     }
@@ -885,6 +1077,7 @@ pub enum ActiveEventLoop {
     Wayland(wayland::ActiveEventLoop),
     #[cfg(x11_platform)]
     X(x11::ActiveEventLoop),
+    Headless(headless::ActiveEventLoop),
 }
 
 impl ActiveEventLoop {
@@ -893,11 +1086,70 @@ impl ActiveEventLoop {
         match *self {
             #[cfg(wayland_platform)]
             ActiveEventLoop::Wayland(_) => true,
+            _ => false,
+        }
+    }
+
+    #[inline]
+    pub fn is_x11(&self) -> bool {
+        match *self {
             #[cfg(x11_platform)]
+            ActiveEventLoop::X(_) => true,
             _ => false,
         }
     }
 
+    #[inline]
+    pub fn is_headless(&self) -> bool {
+        matches!(self, ActiveEventLoop::Headless(_))
+    }
+
+    #[inline]
+    pub fn inject_window_event(
+        &self,
+        window_id: WindowId,
+        event: crate::event::WindowEvent,
+    ) -> Result<(), NotSupportedError> {
+        match self {
+            ActiveEventLoop::Headless(evlp) => {
+                evlp.inject_window_event(window_id, event);
+                Ok(())
+            },
+            #[allow(unreachable_patterns)]
+            _ => Err(NotSupportedError::new()),
+        }
+    }
+
+    #[inline]
+    pub fn inject_device_event(
+        &self,
+        event: crate::event::DeviceEvent,
+    ) -> Result<(), NotSupportedError> {
+        match self {
+            ActiveEventLoop::Headless(evlp) => {
+                evlp.inject_device_event(event);
+                Ok(())
+            },
+            #[allow(unreachable_patterns)]
+            _ => Err(NotSupportedError::new()),
+        }
+    }
+
+    #[inline]
+    pub fn set_virtual_monitors(
+        &self,
+        monitors: Vec<crate::platform::headless::VirtualMonitor>,
+    ) -> Result<(), NotSupportedError> {
+        match self {
+            ActiveEventLoop::Headless(evlp) => {
+                evlp.set_virtual_monitors(monitors);
+                Ok(())
+            },
+            #[allow(unreachable_patterns)]
+            _ => Err(NotSupportedError::new()),
+        }
+    }
+
     pub fn create_custom_cursor(&self, cursor: CustomCursorSource) -> CustomCursor {
         x11_or_wayland!(match self; ActiveEventLoop(evlp) => evlp.create_custom_cursor(cursor))
     }
@@ -913,6 +1165,9 @@ impl ActiveEventLoop {
             ActiveEventLoop::X(ref evlp) => {
                 evlp.available_monitors().map(MonitorHandle::X).collect()
             },
+            ActiveEventLoop::Headless(ref evlp) => {
+                evlp.available_monitors().map(MonitorHandle::Headless).collect()
+            },
         }
     }
 
@@ -928,6 +1183,26 @@ impl ActiveEventLoop {
         x11_or_wayland!(match self; Self(evlp) => evlp.listen_device_events(allowed))
     }
 
+    #[inline]
+    pub fn schedule_timer(&self, timer_id: TimerId, schedule: TimerSchedule) {
+        x11_or_wayland!(match self; Self(evlp) => evlp.schedule_timer(timer_id, schedule))
+    }
+
+    #[inline]
+    pub fn cancel_timer(&self, timer_id: TimerId) {
+        x11_or_wayland!(match self; Self(evlp) => evlp.cancel_timer(timer_id))
+    }
+
+    #[inline]
+    pub fn register_fd(&self, fd: OwnedFd, interest: Interest) -> Result<SourceToken, RootOsError> {
+        x11_or_wayland!(match self; Self(evlp) => evlp.register_fd(fd, interest))
+    }
+
+    #[inline]
+    pub fn unregister_fd(&self, token: SourceToken) {
+        x11_or_wayland!(match self; Self(evlp) => evlp.unregister_fd(token))
+    }
+
     #[cfg(feature = "rwh_05")]
     #[inline]
     pub fn raw_display_handle_rwh_05(&self) -> rwh_05::RawDisplayHandle {
@@ -939,6 +1214,17 @@ impl ActiveEventLoop {
         None
     }
 
+    #[inline]
+    pub fn keyboard_layouts(&self) -> Option<KeyboardLayouts> {
+        match self {
+            #[cfg(x11_platform)]
+            ActiveEventLoop::X(evlp) => evlp.keyboard_layouts(),
+            #[cfg(wayland_platform)]
+            ActiveEventLoop::Wayland(evlp) => evlp.keyboard_layouts(),
+            ActiveEventLoop::Headless(_) => None,
+        }
+    }
+
     #[cfg(feature = "rwh_06")]
     #[inline]
     pub fn raw_display_handle_rwh_06(
@@ -950,15 +1236,18 @@ impl ActiveEventLoop {
     #[inline]
     pub fn query_pointer(&self, device_id: DeviceId) -> Option<(f32, f32)> {
         match self {
+            #[cfg(wayland_platform)]
             ActiveEventLoop::Wayland(_ael) => {todo!()}
+            #[cfg(x11_platform)]
             ActiveEventLoop::X(ael) => {
                 match device_id {
                     DeviceId::X(d) => {
                         ael.query_pointer(d)
                     }
-                    DeviceId::Wayland(_) => unreachable!()
+                    _ => unreachable!()
                 }
             }
+            ActiveEventLoop::Headless(_) => None,
         }
     }
 
@@ -988,6 +1277,7 @@ impl ActiveEventLoop {
             Self::X(conn) => OwnedDisplayHandle::X(conn.x_connection().clone()),
             #[cfg(wayland_platform)]
             Self::Wayland(conn) => OwnedDisplayHandle::Wayland(conn.connection.clone()),
+            Self::Headless(_) => OwnedDisplayHandle::Headless,
         }
     }
 
@@ -1009,6 +1299,7 @@ pub(crate) enum OwnedDisplayHandle {
     X(Arc<XConnection>),
     #[cfg(wayland_platform)]
     Wayland(wayland_client::Connection),
+    Headless,
 }
 
 impl OwnedDisplayHandle {
@@ -1032,6 +1323,8 @@ impl OwnedDisplayHandle {
                 wayland_handle.display = conn.display().id().as_ptr() as *mut _;
                 wayland_handle.into()
             },
+
+            Self::Headless => headless::no_raw_handle(),
         }
     }
 
@@ -1059,6 +1352,8 @@ impl OwnedDisplayHandle {
                 )
                 .into())
             },
+
+            Self::Headless => Err(rwh_06::HandleError::NotSupported),
         }
     }
 }
@@ -4,13 +4,17 @@ use std::cell::{Cell, RefCell};
 use std::io::Result as IOResult;
 use std::marker::PhantomData;
 use std::mem;
-use std::os::unix::io::{AsFd, AsRawFd, BorrowedFd, RawFd};
+use std::os::unix::io::{AsFd, AsRawFd, BorrowedFd, OwnedFd, RawFd};
 use std::rc::Rc;
 use std::sync::atomic::Ordering;
 use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 
-use sctk::reexports::calloop::Error as CalloopError;
+use sctk::reexports::calloop::generic::Generic;
+use sctk::reexports::calloop::timer::{TimeoutAction, Timer};
+use sctk::reexports::calloop::{
+    Error as CalloopError, Interest as CalloopInterest, Mode, PostAction,
+};
 use sctk::reexports::calloop_wayland_source::WaylandSource;
 use sctk::reexports::client::{globals, Connection, QueueHandle};
 
@@ -18,7 +22,11 @@ use crate::cursor::OnlyCursorImage;
 use crate::dpi::LogicalSize;
 use crate::error::{EventLoopError, OsError as RootOsError};
 use crate::event::{Event, InnerSizeWriter, StartCause, WindowEvent};
-use crate::event_loop::{ActiveEventLoop as RootActiveEventLoop, ControlFlow, DeviceEvents};
+use crate::event_loop::{
+    ActiveEventLoop as RootActiveEventLoop, ControlFlow, DeviceEvents, Interest, SourceToken,
+    TimerId, TimerSchedule,
+};
+use crate::keyboard::KeyboardLayouts;
 use crate::platform::pump_events::PumpStatus;
 use crate::platform_impl::platform::min_timeout;
 use crate::platform_impl::{
@@ -32,6 +40,7 @@ pub mod sink;
 pub use proxy::EventLoopProxy;
 use sink::EventSink;
 
+use super::seat::{DndRequest, SelectionRequest, WinitSeatState};
 use super::state::{WindowCompositorUpdate, WinitState};
 use super::window::state::FrameCallbackState;
 use super::{logical_to_physical_rounded, DeviceId, WaylandError, WindowId};
@@ -71,7 +80,7 @@ pub struct EventLoop<T: 'static> {
 }
 
 impl<T: 'static> EventLoop<T> {
-    pub fn new() -> Result<EventLoop<T>, EventLoopError> {
+    pub fn new(tablet_tools: bool) -> Result<EventLoop<T>, EventLoopError> {
         macro_rules! map_err {
             ($e:expr, $err:expr) => {
                 $e.map_err(|error| os_error!($err(error).into()))
@@ -87,8 +96,9 @@ impl<T: 'static> EventLoop<T> {
         let event_loop =
             map_err!(calloop::EventLoop::<WinitState>::try_new(), WaylandError::Calloop)?;
 
-        let mut winit_state = WinitState::new(&globals, &queue_handle, event_loop.handle())
-            .map_err(|error| os_error!(error))?;
+        let mut winit_state =
+            WinitState::new(&globals, &queue_handle, event_loop.handle(), tablet_tools)
+                .map_err(|error| os_error!(error))?;
 
         // NOTE: do a roundtrip after binding the globals to prevent potential
         // races with the server.
@@ -144,8 +154,36 @@ impl<T: 'static> EventLoop<T> {
             .map_err(|error| error.error);
         map_err!(result, WaylandError::Calloop)?;
 
+        // Setup the selection requests from the windows.
+        let (selection_sender, selection_channel) = calloop::channel::channel();
+        let selection_queue_handle = queue_handle.clone();
+        let result = event_loop
+            .handle()
+            .insert_source(selection_channel, move |event, _, winit_state: &mut WinitState| {
+                if let calloop::channel::Event::Msg(request) = event {
+                    winit_state.handle_selection_request(request, &selection_queue_handle);
+                }
+            })
+            .map_err(|error| error.error);
+        map_err!(result, WaylandError::Calloop)?;
+
+        // Setup the drag and drop requests from the windows.
+        let (dnd_sender, dnd_channel) = calloop::channel::channel();
+        let dnd_queue_handle = queue_handle.clone();
+        let result = event_loop
+            .handle()
+            .insert_source(dnd_channel, move |event, _, winit_state: &mut WinitState| {
+                if let calloop::channel::Event::Msg(request) = event {
+                    winit_state.handle_dnd_request(request, &dnd_queue_handle);
+                }
+            })
+            .map_err(|error| error.error);
+        map_err!(result, WaylandError::Calloop)?;
+
         let window_target = ActiveEventLoop {
             connection: connection.clone(),
+            selection_sender,
+            dnd_sender,
             wayland_dispatcher: wayland_dispatcher.clone(),
             event_loop_awakener,
             queue_handle,
@@ -227,6 +265,38 @@ impl<T: 'static> EventLoop<T> {
         }
     }
 
+    /// Prepare to wait for the events on the event loop file descriptor, returning how long
+    /// the wait may last.
+    pub fn prepare(&mut self) -> Option<Duration> {
+        if !self.loop_running || self.exiting() {
+            return Some(Duration::ZERO);
+        }
+
+        // The requests must be written out before waiting, and the events which were already
+        // read from the socket won't wake up the poll, so pick them up right away.
+        if self.connection.flush().is_err() {
+            self.set_exit_code(1);
+            return Some(Duration::ZERO);
+        }
+        if let Err(error) = self.loop_dispatch(Duration::ZERO) {
+            self.set_exit_code(error.raw_os_error().unwrap_or(1));
+            return Some(Duration::ZERO);
+        }
+
+        if self.with_state(|state| state.dispatched_events) {
+            return Some(Duration::ZERO);
+        }
+
+        // The calloop timers, like the key repeat, don't wake up the event loop's fd.
+        let deadline = self.with_state(|state| {
+            let timers = state.timers.values().map(|&(_, deadline)| deadline);
+            let repeats = state.seats.values().filter_map(WinitSeatState::repeat_deadline);
+            timers.chain(repeats).min()
+        });
+
+        wait_timeout(self.control_flow(), deadline, Instant::now())
+    }
+
     pub fn poll_events_with_timeout<F>(&mut self, mut timeout: Option<Duration>, mut callback: F)
     where
         F: FnMut(Event<T>, &RootActiveEventLoop),
@@ -283,10 +353,14 @@ impl<T: 'static> EventLoop<T> {
                 },
             };
 
-            // Reduce spurious wake-ups.
+            // Reduce spurious wake-ups, unless the caller limited how long we may block.
             let dispatched_events = self.with_state(|state| state.dispatched_events);
             if matches!(cause, StartCause::WaitCancelled { .. }) && !dispatched_events {
-                continue;
+                if timeout.is_none() {
+                    continue;
+                }
+
+                return;
             }
 
             break cause;
@@ -413,6 +487,16 @@ impl<T: 'static> EventLoop<T> {
                     &self.window_target,
                 );
             }
+
+            if compositor_update.popup_dismissed {
+                callback(
+                    Event::WindowEvent {
+                        window_id: crate::window::WindowId(window_id),
+                        event: WindowEvent::PopupDismissed,
+                    },
+                    &self.window_target,
+                );
+            }
         }
 
         // Push the events directly from the window.
@@ -512,8 +596,7 @@ impl<T: 'static> EventLoop<T> {
                 PlatformActiveEventLoop::Wayland(window_target) => {
                     window_target.event_loop_awakener.ping();
                 },
-                #[cfg(x11_platform)]
-                PlatformActiveEventLoop::X(_) => unreachable!(),
+                _ => unreachable!(),
             }
         }
 
@@ -535,7 +618,6 @@ impl<T: 'static> EventLoop<T> {
     fn with_state<'a, U: 'a, F: FnOnce(&'a mut WinitState) -> U>(&'a mut self, callback: F) -> U {
         let state = match &mut self.window_target.p {
             PlatformActiveEventLoop::Wayland(window_target) => window_target.state.get_mut(),
-            #[cfg(x11_platform)]
             _ => unreachable!(),
         };
 
@@ -545,7 +627,6 @@ impl<T: 'static> EventLoop<T> {
     fn loop_dispatch<D: Into<Option<std::time::Duration>>>(&mut self, timeout: D) -> IOResult<()> {
         let state = match &mut self.window_target.p {
             PlatformActiveEventLoop::Wayland(window_target) => window_target.state.get_mut(),
-            #[cfg(feature = "x11")]
             _ => unreachable!(),
         };
 
@@ -558,7 +639,6 @@ impl<T: 'static> EventLoop<T> {
     fn roundtrip(&mut self) -> Result<usize, RootOsError> {
         let state = match &mut self.window_target.p {
             PlatformActiveEventLoop::Wayland(window_target) => window_target.state.get_mut(),
-            #[cfg(feature = "x11")]
             _ => unreachable!(),
         };
 
@@ -586,6 +666,23 @@ impl<T: 'static> EventLoop<T> {
     }
 }
 
+/// How long to wait for the events with the `control_flow`, at most until the `deadline` of the
+/// next timer.
+fn wait_timeout(
+    control_flow: ControlFlow,
+    deadline: Option<Instant>,
+    now: Instant,
+) -> Option<Duration> {
+    let control_flow_timeout = match control_flow {
+        ControlFlow::Wait => None,
+        ControlFlow::Poll => Some(Duration::ZERO),
+        ControlFlow::WaitUntil(wait_deadline) => Some(wait_deadline.saturating_duration_since(now)),
+    };
+    let timers_timeout = deadline.map(|deadline| deadline.saturating_duration_since(now));
+
+    min_timeout(control_flow_timeout, timers_timeout)
+}
+
 impl<T> AsFd for EventLoop<T> {
     fn as_fd(&self) -> BorrowedFd<'_> {
         self.event_loop.as_fd()
@@ -620,6 +717,12 @@ pub struct ActiveEventLoop {
 
     /// Connection to the wayland server.
     pub connection: Connection,
+
+    /// Sender of the selection requests from the windows.
+    pub selection_sender: calloop::channel::Sender<SelectionRequest>,
+
+    /// Sender of the drag and drop requests from the windows.
+    pub dnd_sender: calloop::channel::Sender<DndRequest>,
 }
 
 impl ActiveEventLoop {
@@ -654,6 +757,90 @@ impl ActiveEventLoop {
     #[inline]
     pub fn listen_device_events(&self, _allowed: DeviceEvents) {}
 
+    pub fn keyboard_layouts(&self) -> Option<KeyboardLayouts> {
+        self.state.borrow_mut().seats.values_mut().find_map(|seat| seat.keyboard_layouts())
+    }
+
+    pub fn schedule_timer(&self, timer_id: TimerId, schedule: TimerSchedule) {
+        let (deadline, interval) = match schedule {
+            TimerSchedule::Deadline(deadline) => (deadline, None),
+            TimerSchedule::Interval(interval) => (Instant::now() + interval, Some(interval)),
+        };
+
+        let mut state = self.state.borrow_mut();
+        let timer = Timer::from_deadline(deadline);
+        let token = state.loop_handle.insert_source(timer, move |_, _, state| {
+            state.events_sink.push_timer_event(timer_id);
+            state.dispatched_events = true;
+
+            // Track the next deadline, so it could be reported by `prepare`.
+            match interval {
+                Some(interval) => {
+                    let deadline = Instant::now() + interval;
+                    if let Some((_, next_deadline)) = state.timers.get_mut(&timer_id) {
+                        *next_deadline = deadline;
+                    }
+                    TimeoutAction::ToInstant(deadline)
+                },
+                None => {
+                    state.timers.remove(&timer_id);
+                    TimeoutAction::Drop
+                },
+            }
+        });
+
+        match token {
+            Ok(token) => {
+                state.timers.insert(timer_id, (token, deadline));
+            },
+            Err(err) => tracing::warn!("Failed to schedule the timer: {}", err.error),
+        }
+    }
+
+    pub fn cancel_timer(&self, timer_id: TimerId) {
+        let mut state = self.state.borrow_mut();
+        if let Some((token, _)) = state.timers.remove(&timer_id) {
+            state.loop_handle.remove(token);
+        }
+    }
+
+    pub fn register_fd(&self, fd: OwnedFd, interest: Interest) -> Result<SourceToken, RootOsError> {
+        let token = SourceToken::next();
+        let source = Generic::new(
+            fd,
+            CalloopInterest {
+                readable: interest.contains(Interest::READABLE),
+                writable: interest.contains(Interest::WRITABLE),
+            },
+            Mode::Level,
+        );
+
+        let mut state = self.state.borrow_mut();
+        let registration = state
+            .loop_handle
+            .insert_source(source, move |readiness, _, state| {
+                let mut interest = Interest::empty();
+                interest.set(Interest::READABLE, readiness.readable || readiness.error);
+                interest.set(Interest::WRITABLE, readiness.writable);
+                state.events_sink.push_fd_event(token, interest);
+                state.dispatched_events = true;
+                Ok(PostAction::Continue)
+            })
+            .map_err(|error| {
+                os_error!(OsError::WaylandError(Arc::new(WaylandError::Calloop(error.error))))
+            })?;
+
+        state.fd_sources.insert(token, registration);
+        Ok(token)
+    }
+
+    pub fn unregister_fd(&self, token: SourceToken) {
+        let mut state = self.state.borrow_mut();
+        if let Some(registration) = state.fd_sources.remove(&token) {
+            state.loop_handle.remove(registration);
+        }
+    }
+
     pub(crate) fn create_custom_cursor(&self, cursor: CustomCursorSource) -> RootCustomCursor {
         RootCustomCursor {
             inner: PlatformCustomCursor::Wayland(OnlyCursorImage(Arc::from(cursor.inner.0))),
@@ -684,3 +871,31 @@ impl ActiveEventLoop {
         .into())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pending_repeat_bounds_the_wait() {
+        let now = Instant::now();
+        let repeat = now + Duration::from_millis(600);
+
+        assert_eq!(wait_timeout(ControlFlow::Wait, None, now), None);
+        assert_eq!(
+            wait_timeout(ControlFlow::Wait, Some(repeat), now),
+            Some(Duration::from_millis(600))
+        );
+        assert_eq!(
+            wait_timeout(ControlFlow::WaitUntil(now + Duration::from_secs(1)), Some(repeat), now),
+            Some(Duration::from_millis(600))
+        );
+        assert_eq!(wait_timeout(ControlFlow::Poll, Some(repeat), now), Some(Duration::ZERO));
+
+        // The overdue repeats are dispatched right away.
+        assert_eq!(
+            wait_timeout(ControlFlow::Wait, Some(now), now + Duration::from_millis(5)),
+            Some(Duration::ZERO)
+        );
+    }
+}
@@ -3,6 +3,7 @@
 use std::vec::Drain;
 
 use crate::event::{DeviceEvent, DeviceId as RootDeviceId, Event, WindowEvent};
+use crate::event_loop::{Interest, SourceToken, TimerId};
 use crate::platform_impl::platform::DeviceId as PlatformDeviceId;
 use crate::window::WindowId as RootWindowId;
 
@@ -41,6 +42,18 @@ impl EventSink {
         self.window_events.push(Event::WindowEvent { event, window_id: RootWindowId(window_id) });
     }
 
+    /// Add new timer event to a queue.
+    #[inline]
+    pub fn push_timer_event(&mut self, timer_id: TimerId) {
+        self.window_events.push(Event::TimerFired(timer_id));
+    }
+
+    /// Add new file descriptor event to a queue.
+    #[inline]
+    pub fn push_fd_event(&mut self, token: SourceToken, readiness: Interest) {
+        self.window_events.push(Event::FdReady { token, readiness });
+    }
+
     #[inline]
     pub fn append(&mut self, other: &mut Self) {
         self.window_events.append(&mut other.window_events);
@@ -0,0 +1,485 @@
+//! The clipboard and primary selection handling.
+
+use std::cell::RefCell;
+use std::io::{self, Read, Write};
+use std::iter;
+use std::os::unix::io::{AsFd, AsRawFd};
+use std::sync::{Arc, Mutex};
+
+use ahash::AHashMap;
+
+use calloop::PostAction;
+use tracing::warn;
+
+use sctk::data_device_manager::data_source::{CopyPasteSource, DataSourceHandler};
+use sctk::data_device_manager::{DataDeviceManagerState, ReadPipe, WritePipe};
+use sctk::primary_selection::device::PrimarySelectionDeviceHandler;
+use sctk::primary_selection::selection::{PrimarySelectionSource, PrimarySelectionSourceHandler};
+use sctk::primary_selection::PrimarySelectionManagerState;
+use sctk::reexports::client::backend::ObjectId;
+use sctk::reexports::client::protocol::wl_data_device_manager::DndAction;
+use sctk::reexports::client::protocol::wl_data_source::WlDataSource;
+use sctk::reexports::client::protocol::wl_seat::WlSeat;
+use sctk::reexports::client::{Connection, QueueHandle};
+use sctk::reexports::protocols::wp::primary_selection::zv1::client::zwp_primary_selection_device_v1::ZwpPrimarySelectionDeviceV1;
+use sctk::reexports::protocols::wp::primary_selection::zv1::client::zwp_primary_selection_source_v1::ZwpPrimarySelectionSourceV1;
+
+use crate::clipboard::{negotiate_mime_type, MimeData, Selection, TEXT_MIME_TYPE};
+use crate::event::WindowEvent;
+use crate::event_loop::AsyncRequestSerial;
+use crate::platform_impl::wayland::state::WinitState;
+use crate::platform_impl::wayland::window::WindowState;
+use crate::platform_impl::wayland::WindowId;
+
+use super::WinitSeatState;
+
+/// The request from the window to the event loop.
+#[derive(Debug)]
+pub enum SelectionRequest {
+    Set {
+        window_id: WindowId,
+        selection: Selection,
+        data: Vec<MimeData>,
+    },
+    Get {
+        window_id: WindowId,
+        selection: Selection,
+        mime_types: Vec<String>,
+        serial: AsyncRequestSerial,
+    },
+}
+
+/// The selections offered by the client.
+#[derive(Debug, Default)]
+pub struct SelectionSources {
+    clipboard: Option<(CopyPasteSource, Vec<MimeData>)>,
+    primary: Option<(PrimarySelectionSource, Vec<MimeData>)>,
+}
+
+impl WinitSeatState {
+    /// Create the data devices for the seat.
+    pub fn bind_data_devices(
+        &mut self,
+        seat: &WlSeat,
+        queue_handle: &QueueHandle<WinitState>,
+        data_device_manager: Option<&DataDeviceManagerState>,
+        primary_selection_manager: Option<&PrimarySelectionManagerState>,
+    ) {
+        self.data_device =
+            data_device_manager.map(|manager| manager.get_data_device(queue_handle, seat));
+        self.primary_selection_device = primary_selection_manager
+            .map(|manager| manager.get_selection_device(queue_handle, seat));
+    }
+
+    /// The serial of the latest button press on the seat, required to start the drag.
+    pub(super) fn latest_button_serial(&self) -> u32 {
+        self.pointer
+            .as_ref()
+            .map(|pointer| super::WinitPointerDataExt::winit_data(pointer.pointer()))
+            .map(|data| data.latest_button_serial())
+            .unwrap_or_default()
+    }
+
+    /// The serial of the latest input on the seat, required to set the selection.
+    fn latest_input_serial(&self) -> u32 {
+        let button_serial = self.latest_button_serial();
+
+        // The serials are increasing, but could wrap around.
+        if (button_serial.wrapping_sub(self.latest_key_serial) as i32) > 0 {
+            button_serial
+        } else {
+            self.latest_key_serial
+        }
+    }
+}
+
+impl WinitState {
+    pub fn handle_selection_request(
+        &mut self,
+        request: SelectionRequest,
+        queue_handle: &QueueHandle<Self>,
+    ) {
+        match request {
+            SelectionRequest::Set { window_id, selection, data } => {
+                self.set_selection(window_id, selection, data, queue_handle)
+            },
+            SelectionRequest::Get { window_id, selection, mime_types, serial } => {
+                let data = self.receive_selection(window_id, selection, mime_types, serial);
+                if let Err(err) = data {
+                    warn!("Failed to receive the selection: {err}");
+                    let event = WindowEvent::ClipboardDone { serial, data: None };
+                    self.events_sink.push_window_event(event, window_id);
+                }
+            },
+        }
+    }
+
+    fn set_selection(
+        &mut self,
+        window_id: WindowId,
+        selection: Selection,
+        data: Vec<MimeData>,
+        queue_handle: &QueueHandle<Self>,
+    ) {
+        let seat_state = match selection_seat(&self.windows, &self.seats, window_id) {
+            Some(seat_state) => seat_state,
+            None => return,
+        };
+        let serial = seat_state.latest_input_serial();
+        let sources = &mut self.selection_sources;
+
+        let mime_types = offered_mime_types(&data);
+
+        match selection {
+            Selection::Clipboard => {
+                let (manager, device) =
+                    match (self.data_device_manager.as_ref(), seat_state.data_device.as_ref()) {
+                        (Some(manager), Some(device)) => (manager, device),
+                        _ => return,
+                    };
+
+                if data.is_empty() {
+                    device.unset_selection(serial);
+                    sources.clipboard = None;
+                } else {
+                    let source = manager.create_copy_paste_source(queue_handle, mime_types);
+                    source.set_selection(device, serial);
+                    sources.clipboard = Some((source, data));
+                }
+            },
+            Selection::Primary => {
+                let (manager, device) = match (
+                    self.primary_selection_manager.as_ref(),
+                    seat_state.primary_selection_device.as_ref(),
+                ) {
+                    (Some(manager), Some(device)) => (manager, device),
+                    _ => return,
+                };
+
+                if data.is_empty() {
+                    device.unset_selection(serial);
+                    sources.primary = None;
+                } else {
+                    let source = manager.create_selection_source(queue_handle, mime_types);
+                    source.set_selection(device, serial);
+                    sources.primary = Some((source, data));
+                }
+            },
+        }
+    }
+
+    fn receive_selection(
+        &mut self,
+        window_id: WindowId,
+        selection: Selection,
+        mime_types: Vec<String>,
+        serial: AsyncRequestSerial,
+    ) -> io::Result<()> {
+        let seat_state = selection_seat(&self.windows, &self.seats, window_id);
+
+        let offered = match selection {
+            Selection::Clipboard => seat_state
+                .and_then(|seat_state| seat_state.data_device.as_ref())
+                .and_then(|device| device.data().selection_offer())
+                .map(|offer| (offer.with_mime_types(<[String]>::to_vec), Offer::Clipboard(offer))),
+            Selection::Primary => seat_state
+                .and_then(|seat_state| seat_state.primary_selection_device.as_ref())
+                .and_then(|device| device.data().selection_offer())
+                .map(|offer| (offer.with_mime_types(<[String]>::to_vec), Offer::Primary(offer))),
+        };
+
+        let target = offered.and_then(|(offered, offer)| {
+            let (mime_type, index) = negotiate_mime_type(&mime_types, &offered)?;
+            Some((mime_type.to_owned(), offered[index].clone(), offer))
+        });
+
+        let (mime_type, target, offer) = match target {
+            Some(target) => target,
+            None => {
+                let event = WindowEvent::ClipboardDone { serial, data: None };
+                self.events_sink.push_window_event(event, window_id);
+                return Ok(());
+            },
+        };
+
+        let pipe = match offer {
+            Offer::Clipboard(offer) => offer.receive(target).map_err(|err| match err {
+                sctk::data_device_manager::data_offer::DataOfferError::Io(err) => err,
+                err => io::Error::new(io::ErrorKind::Other, err),
+            })?,
+            Offer::Primary(offer) => offer.receive(target)?,
+        };
+
+        self.read_selection(pipe, window_id, mime_type, serial)
+    }
+
+    /// Read the selection contents from the `pipe` and deliver them to the window.
+    fn read_selection(
+        &mut self,
+        pipe: ReadPipe,
+        window_id: WindowId,
+        mime_type: String,
+        serial: AsyncRequestSerial,
+    ) -> io::Result<()> {
+        self.read_pipe(pipe, move |state, data| {
+            let data = match data {
+                Ok(data) => Some(MimeData::new(mime_type, data)),
+                Err(err) => {
+                    warn!("Failed to read the selection: {err}");
+                    None
+                },
+            };
+
+            let event = WindowEvent::ClipboardDone { serial, data };
+            state.events_sink.push_window_event(event, window_id);
+        })
+    }
+
+    /// Read the contents from the `pipe` without blocking the event loop.
+    ///
+    /// The `callback` is called once all the contents were read.
+    pub(super) fn read_pipe<F>(&mut self, pipe: ReadPipe, callback: F) -> io::Result<()>
+    where
+        F: FnOnce(&mut WinitState, io::Result<Vec<u8>>) + 'static,
+    {
+        set_non_blocking(pipe.as_fd())?;
+
+        let mut callback = Some(callback);
+        let mut data = Vec::new();
+        let mut buffer = [0; 4096];
+        self.loop_handle
+            .insert_source(pipe, move |_, file, state| {
+                let result = loop {
+                    match (&**file).read(&mut buffer) {
+                        Ok(0) => break Ok(std::mem::take(&mut data)),
+                        Ok(len) => data.extend_from_slice(&buffer[..len]),
+                        Err(err) if err.kind() == io::ErrorKind::Interrupted => (),
+                        Err(err) if err.kind() == io::ErrorKind::WouldBlock => {
+                            return PostAction::Continue
+                        },
+                        Err(err) => break Err(err),
+                    }
+                };
+
+                if let Some(callback) = callback.take() {
+                    callback(state, result);
+                    state.dispatched_events = true;
+                }
+
+                PostAction::Remove
+            })
+            .map_err(|err| io::Error::new(io::ErrorKind::Other, err.error))?;
+
+        Ok(())
+    }
+
+    /// Write the contents of the offered selection into the `pipe` without blocking the
+    /// event loop.
+    fn write_selection(&self, pipe: WritePipe, data: Vec<u8>) {
+        if let Err(err) = set_non_blocking(pipe.as_fd()) {
+            warn!("Failed to send the selection: {err}");
+            return;
+        }
+
+        let mut written = 0;
+        let result = self.loop_handle.insert_source(pipe, move |_, file, _| loop {
+            match (&**file).write(&data[written..]) {
+                Ok(len) if written + len < data.len() => written += len,
+                Ok(_) => break PostAction::Remove,
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => (),
+                Err(err) if err.kind() == io::ErrorKind::WouldBlock => break PostAction::Continue,
+                Err(err) => {
+                    warn!("Failed to send the selection: {err}");
+                    break PostAction::Remove;
+                },
+            }
+        });
+
+        if let Err(err) = result {
+            warn!("Failed to send the selection: {}", err.error);
+        }
+    }
+}
+
+/// The seat to perform the selection requests for the given window.
+pub(super) fn selection_seat<'a>(
+    windows: &RefCell<AHashMap<WindowId, Arc<Mutex<WindowState>>>>,
+    seats: &'a AHashMap<ObjectId, WinitSeatState>,
+    window_id: WindowId,
+) -> Option<&'a WinitSeatState> {
+    let windows = windows.borrow();
+    let window = windows.get(&window_id)?.lock().unwrap();
+    seats
+        .iter()
+        .find(|(seat_id, _)| window.has_seat_focus(seat_id))
+        .or_else(|| seats.iter().next())
+        .map(|(_, seat_state)| seat_state)
+}
+
+enum Offer {
+    Clipboard(sctk::data_device_manager::data_offer::SelectionOffer),
+    Primary(sctk::primary_selection::offer::PrimarySelectionOffer),
+}
+
+/// The MIME types to advertise for the `data`, the text is also offered as UTF-8 plain text.
+pub(super) fn offered_mime_types(data: &[MimeData]) -> Vec<&str> {
+    let mut mime_types = Vec::new();
+    for entry in data {
+        let names = entry.is_text().then_some(TEXT_MIME_TYPE);
+        for name in iter::once(entry.mime_type.as_str()).chain(names) {
+            // The X11 target names like `UTF8_STRING` aren't MIME types.
+            if name.contains('/') && !mime_types.contains(&name) {
+                mime_types.push(name);
+            }
+        }
+    }
+    mime_types
+}
+
+/// Find the data offered in the given MIME type.
+pub(super) fn offered_data<'a>(data: &'a [MimeData], mime_type: &str) -> Option<&'a [u8]> {
+    data.iter()
+        .find(|entry| entry.mime_type == mime_type)
+        .or_else(|| {
+            crate::clipboard::is_text_mime_type(mime_type)
+                .then(|| data.iter().find(|entry| entry.is_text()))
+                .flatten()
+        })
+        .map(|entry| entry.data.as_slice())
+}
+
+fn set_non_blocking(fd: std::os::unix::io::BorrowedFd<'_>) -> io::Result<()> {
+    let fd = fd.as_raw_fd();
+    // SAFETY: The file descriptor is valid for the duration of the call.
+    unsafe {
+        let flags = libc::fcntl(fd, libc::F_GETFL);
+        if flags < 0 || libc::fcntl(fd, libc::F_SETFL, flags | libc::O_NONBLOCK) < 0 {
+            return Err(io::Error::last_os_error());
+        }
+    }
+    Ok(())
+}
+
+impl DataSourceHandler for WinitState {
+    fn accept_mime(
+        &mut self,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+        _: &WlDataSource,
+        _: Option<String>,
+    ) {
+    }
+
+    fn send_request(
+        &mut self,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+        source: &WlDataSource,
+        mime: String,
+        fd: WritePipe,
+    ) {
+        let data = match self.selection_sources.clipboard.as_ref() {
+            Some((clipboard, data)) if clipboard.inner() == source => Some(data.as_slice()),
+            _ => self.dnd_source_data(source),
+        };
+
+        if let Some(data) = data.and_then(|data| offered_data(data, &mime)) {
+            self.write_selection(fd, data.to_vec());
+        }
+    }
+
+    fn cancelled(&mut self, _: &Connection, _: &QueueHandle<Self>, source: &WlDataSource) {
+        if self
+            .selection_sources
+            .clipboard
+            .as_ref()
+            .is_some_and(|(clipboard, _)| clipboard.inner() == source)
+        {
+            self.selection_sources.clipboard = None;
+        } else {
+            self.finish_dnd_source(source, false);
+        }
+    }
+
+    fn dnd_dropped(&mut self, _: &Connection, _: &QueueHandle<Self>, _: &WlDataSource) {}
+
+    fn dnd_finished(&mut self, _: &Connection, _: &QueueHandle<Self>, source: &WlDataSource) {
+        self.finish_dnd_source(source, true);
+    }
+
+    fn action(
+        &mut self,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+        source: &WlDataSource,
+        action: DndAction,
+    ) {
+        self.set_dnd_source_action(source, action);
+    }
+}
+
+impl PrimarySelectionDeviceHandler for WinitState {
+    fn selection(
+        &mut self,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+        _: &ZwpPrimarySelectionDeviceV1,
+    ) {
+        // The offer is stored on the device and read on demand.
+    }
+}
+
+impl PrimarySelectionSourceHandler for WinitState {
+    fn send_request(
+        &mut self,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+        source: &ZwpPrimarySelectionSourceV1,
+        mime: String,
+        write_pipe: WritePipe,
+    ) {
+        let data = match self.selection_sources.primary.as_ref() {
+            Some((primary, data)) if primary.inner() == source => offered_data(data, &mime),
+            _ => None,
+        };
+
+        if let Some(data) = data {
+            self.write_selection(write_pipe, data.to_vec());
+        }
+    }
+
+    fn cancelled(
+        &mut self,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+        source: &ZwpPrimarySelectionSourceV1,
+    ) {
+        if self
+            .selection_sources
+            .primary
+            .as_ref()
+            .is_some_and(|(primary, _)| primary.inner() == source)
+        {
+            self.selection_sources.primary = None;
+        }
+    }
+}
+
+sctk::delegate_data_device!(WinitState);
+sctk::delegate_primary_selection!(WinitState);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn offered_mime_types_are_unique_mime_types() {
+        let data = [
+            MimeData::new("text/html", Vec::new()),
+            MimeData::new("UTF8_STRING", Vec::new()),
+            MimeData::new(TEXT_MIME_TYPE, Vec::new()),
+            MimeData::new("text/html", Vec::new()),
+        ];
+        assert_eq!(offered_mime_types(&data), ["text/html", TEXT_MIME_TYPE]);
+    }
+}
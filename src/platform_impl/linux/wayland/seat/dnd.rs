@@ -0,0 +1,446 @@
+//! The drag and drop handling, both as the source and the target.
+
+use tracing::warn;
+
+use sctk::data_device_manager::data_device::DataDeviceHandler;
+use sctk::data_device_manager::data_offer::{DataOfferHandler, DragOffer};
+use sctk::data_device_manager::data_source::DragSource;
+use sctk::reexports::client::protocol::wl_data_device::WlDataDevice;
+use sctk::reexports::client::protocol::wl_data_device_manager::DndAction as WlDndAction;
+use sctk::reexports::client::protocol::wl_data_source::WlDataSource;
+use sctk::reexports::client::protocol::wl_surface::WlSurface;
+use sctk::reexports::client::{Connection, QueueHandle};
+use sctk::shell::WaylandSurface;
+
+use crate::clipboard::MimeData;
+use crate::dnd::{DndAction, DndActions, DropData, URI_LIST_MIME_TYPE};
+use crate::dpi::{LogicalPosition, PhysicalPosition};
+use crate::event::WindowEvent;
+use crate::platform_impl::common::dnd::{parse_uri_list, DropResponse};
+use crate::platform_impl::wayland::state::WinitState;
+use crate::platform_impl::wayland::{self, WindowId};
+
+use super::data_device::{offered_mime_types, selection_seat};
+
+/// The request from the window to the event loop.
+#[derive(Debug)]
+pub enum DndRequest {
+    Respond { window_id: WindowId, response: DropResponse },
+    StartDrag { window_id: WindowId, data: Vec<MimeData>, actions: DndActions },
+}
+
+/// The drag started by one of our windows.
+#[derive(Debug)]
+pub struct DndSource {
+    source: DragSource,
+    window_id: WindowId,
+    data: Vec<MimeData>,
+    /// The action picked by the compositor.
+    action: Option<DndAction>,
+}
+
+/// The drag and drop offer hovering one of our windows.
+#[derive(Debug)]
+pub struct DndOffer {
+    offer: DragOffer,
+    window_id: WindowId,
+    mime_types: Vec<String>,
+    response: DropResponse,
+    /// The action picked by the compositor.
+    selected_action: Option<DndAction>,
+    position: PhysicalPosition<f64>,
+    /// Whether the drop was performed.
+    dropped: bool,
+}
+
+impl DndOffer {
+    /// Whether the files were announced with `HoveredFile`.
+    fn has_files(&self) -> bool {
+        self.mime_types.iter().any(|mime_type| mime_type == URI_LIST_MIME_TYPE)
+    }
+
+    /// Notify the source about the current response.
+    fn update_response(&self) {
+        match self.response.accepted(&self.mime_types) {
+            Some((index, action)) => {
+                let action = wl_action(action);
+                self.offer
+                    .accept_mime_type(self.offer.serial, Some(self.mime_types[index].clone()));
+                self.offer.set_actions(action, action);
+            },
+            None => {
+                self.offer.accept_mime_type(self.offer.serial, None);
+                self.offer.set_actions(WlDndAction::empty(), WlDndAction::empty());
+            },
+        }
+    }
+}
+
+impl WinitState {
+    pub fn handle_dnd_request(&mut self, request: DndRequest, queue_handle: &QueueHandle<Self>) {
+        match request {
+            DndRequest::StartDrag { window_id, data, actions } => {
+                if !self.start_drag(window_id, data, actions, queue_handle) {
+                    let event = WindowEvent::DragFinished { action: None };
+                    self.events_sink.push_window_event(event, window_id);
+                }
+            },
+            DndRequest::Respond { window_id, response } => {
+                let dnd_offer = match self.dnd_offer.as_mut() {
+                    Some(dnd_offer) if dnd_offer.window_id == window_id && !dnd_offer.dropped => {
+                        dnd_offer
+                    },
+                    _ => return,
+                };
+
+                dnd_offer.response = response;
+                dnd_offer.update_response();
+            },
+        }
+    }
+
+    /// Start the drag from the window, following the latest button press on its seat.
+    fn start_drag(
+        &mut self,
+        window_id: WindowId,
+        data: Vec<MimeData>,
+        actions: DndActions,
+        queue_handle: &QueueHandle<Self>,
+    ) -> bool {
+        // Only a single drag can be in progress.
+        if self.dnd_source.is_some() {
+            return false;
+        }
+
+        let surface = match self.windows.borrow().get(&window_id) {
+            Some(window) => window.lock().unwrap().window.wl_surface().clone(),
+            None => return false,
+        };
+        let seat_state = match selection_seat(&self.windows, &self.seats, window_id) {
+            Some(seat_state) => seat_state,
+            None => return false,
+        };
+        let (manager, device) =
+            match (self.data_device_manager.as_ref(), seat_state.data_device.as_ref()) {
+                (Some(manager), Some(device)) => (manager, device),
+                _ => return false,
+            };
+
+        let source = manager.create_drag_and_drop_source(
+            queue_handle,
+            offered_mime_types(&data),
+            wl_actions(actions),
+        );
+        source.start_drag(device, &surface, None, seat_state.latest_button_serial());
+        self.dnd_source = Some(DndSource { source, window_id, data, action: None });
+
+        true
+    }
+
+    /// The data offered by our drag `source`.
+    pub(super) fn dnd_source_data(&self, source: &WlDataSource) -> Option<&[MimeData]> {
+        self.dnd_source
+            .as_ref()
+            .filter(|dnd_source| dnd_source.source.inner() == source)
+            .map(|dnd_source| dnd_source.data.as_slice())
+    }
+
+    pub(super) fn set_dnd_source_action(&mut self, source: &WlDataSource, action: WlDndAction) {
+        if let Some(dnd_source) = self.dnd_source.as_mut() {
+            if dnd_source.source.inner() == source {
+                dnd_source.action = dnd_action(action);
+            }
+        }
+    }
+
+    /// Deliver the result of our drag, the action is only reported when the drop `succeeded`.
+    pub(super) fn finish_dnd_source(&mut self, source: &WlDataSource, succeeded: bool) {
+        let dnd_source = match self.dnd_source.take() {
+            Some(dnd_source) if dnd_source.source.inner() == source => dnd_source,
+            dnd_source => {
+                self.dnd_source = dnd_source;
+                return;
+            },
+        };
+
+        let action = if succeeded { dnd_source.action } else { None };
+        let event = WindowEvent::DragFinished { action };
+        self.events_sink.push_window_event(event, dnd_source.window_id);
+    }
+
+    /// Destroy the offer which won't deliver the drop, notifying the window that the drag left.
+    fn cancel_dnd_offer(&mut self, dnd_offer: DndOffer) {
+        dnd_offer.offer.destroy();
+        self.push_drag_left(dnd_offer.window_id, dnd_offer.has_files());
+    }
+
+    fn push_drag_left(&mut self, window_id: WindowId, has_files: bool) {
+        if has_files {
+            self.events_sink.push_window_event(WindowEvent::HoveredFileCancelled, window_id);
+        }
+        self.events_sink.push_window_event(WindowEvent::DragLeft, window_id);
+    }
+
+    /// The position inside the window in physical pixels.
+    fn dnd_position(&self, window_id: WindowId, x: f64, y: f64) -> PhysicalPosition<f64> {
+        let scale_factor = self
+            .windows
+            .borrow()
+            .get(&window_id)
+            .map(|window| window.lock().unwrap().scale_factor())
+            .unwrap_or(1.);
+        LogicalPosition::new(x, y).to_physical(scale_factor)
+    }
+
+    /// Read the hovering files for the [`WindowEvent::HoveredFile`].
+    fn read_hovered_files(&mut self, offer: &DragOffer, window_id: WindowId) {
+        let pipe = match offer.receive(URI_LIST_MIME_TYPE.to_owned()) {
+            Ok(pipe) => pipe,
+            Err(err) => {
+                warn!("Failed to receive the dropped files: {err}");
+                return;
+            },
+        };
+
+        let inner = offer.inner().clone();
+        let result = self.read_pipe(pipe, move |state, data| {
+            // The drag has left already.
+            if !state.dnd_offer.as_ref().is_some_and(|dnd_offer| dnd_offer.offer.inner() == &inner)
+            {
+                return;
+            }
+
+            match data.map_err(From::from).and_then(|data| parse_uri_list(&data)) {
+                Ok(paths) => {
+                    for path in paths {
+                        state
+                            .events_sink
+                            .push_window_event(WindowEvent::HoveredFile(path), window_id);
+                    }
+                },
+                Err(err) => warn!("Failed to read the dropped files: {err:?}"),
+            }
+        });
+
+        if let Err(err) = result {
+            warn!("Failed to read the dropped files: {err}");
+        }
+    }
+}
+
+impl DataDeviceHandler for WinitState {
+    fn enter(
+        &mut self,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+        data_device: &WlDataDevice,
+        x: f64,
+        y: f64,
+        surface: &WlSurface,
+    ) {
+        let window_id = wayland::make_wid(surface);
+        let offer = self
+            .seats
+            .values()
+            .filter_map(|seat_state| seat_state.data_device.as_ref())
+            .find(|device| device.inner() == data_device)
+            .and_then(|device| device.data().drag_offer());
+        let offer = match offer {
+            Some(offer) if self.windows.borrow().contains_key(&window_id) => offer,
+            Some(offer) => {
+                offer.accept_mime_type(offer.serial, None);
+                return;
+            },
+            None => return,
+        };
+
+        if let Some(dnd_offer) = self.dnd_offer.take() {
+            if !dnd_offer.dropped {
+                self.cancel_dnd_offer(dnd_offer);
+            }
+        }
+
+        let mime_types = offer.with_mime_types(<[String]>::to_vec);
+        let position = self.dnd_position(window_id, x, y);
+        let actions = dnd_actions(offer.source_actions);
+
+        let dnd_offer = DndOffer {
+            offer,
+            window_id,
+            mime_types: mime_types.clone(),
+            response: DropResponse::default(),
+            selected_action: None,
+            position,
+            dropped: false,
+        };
+        dnd_offer.update_response();
+        if dnd_offer.has_files() {
+            self.read_hovered_files(&dnd_offer.offer, window_id);
+        }
+        self.dnd_offer = Some(dnd_offer);
+
+        let event = WindowEvent::DragEntered { mime_types, actions, position };
+        self.events_sink.push_window_event(event, window_id);
+    }
+
+    fn leave(&mut self, _: &Connection, _: &QueueHandle<Self>, _: &WlDataDevice) {
+        // The offer is kept around after the drop, until its contents are delivered.
+        if self.dnd_offer.as_ref().map_or(true, |dnd_offer| dnd_offer.dropped) {
+            return;
+        }
+
+        if let Some(dnd_offer) = self.dnd_offer.take() {
+            self.cancel_dnd_offer(dnd_offer);
+        }
+    }
+
+    fn motion(&mut self, _: &Connection, _: &QueueHandle<Self>, _: &WlDataDevice, x: f64, y: f64) {
+        let window_id = match self.dnd_offer.as_ref() {
+            Some(dnd_offer) if !dnd_offer.dropped => dnd_offer.window_id,
+            _ => return,
+        };
+
+        let position = self.dnd_position(window_id, x, y);
+        if let Some(dnd_offer) = self.dnd_offer.as_mut() {
+            dnd_offer.position = position;
+        }
+
+        self.events_sink.push_window_event(WindowEvent::DragMoved { position }, window_id);
+    }
+
+    fn selection(&mut self, _: &Connection, _: &QueueHandle<Self>, _: &WlDataDevice) {
+        // The offer is stored on the data device and read on demand.
+    }
+
+    fn drop_performed(&mut self, _: &Connection, _: &QueueHandle<Self>, _: &WlDataDevice) {
+        let dnd_offer = match self.dnd_offer.as_mut() {
+            Some(dnd_offer) => dnd_offer,
+            None => return,
+        };
+        dnd_offer.dropped = true;
+
+        let accepted = dnd_offer.response.accepted(&dnd_offer.mime_types).map(|(index, action)| {
+            (dnd_offer.mime_types[index].clone(), dnd_offer.selected_action.unwrap_or(action))
+        });
+        let pipe = accepted.map(|(mime_type, action)| {
+            dnd_offer.offer.receive(mime_type.clone()).map(|pipe| (pipe, mime_type, action))
+        });
+
+        let (pipe, mime_type, action) = match pipe {
+            Some(Ok(pipe)) => pipe,
+            Some(Err(err)) => {
+                warn!("Failed to receive the dropped data: {err}");
+                if let Some(dnd_offer) = self.dnd_offer.take() {
+                    self.cancel_dnd_offer(dnd_offer);
+                }
+                return;
+            },
+            None => {
+                if let Some(dnd_offer) = self.dnd_offer.take() {
+                    self.cancel_dnd_offer(dnd_offer);
+                }
+                return;
+            },
+        };
+
+        let offer = dnd_offer.offer.clone();
+        let window_id = dnd_offer.window_id;
+        let position = dnd_offer.position;
+        let has_files = dnd_offer.has_files();
+        let result = self.read_pipe(pipe, move |state, data| {
+            if state.dnd_offer.as_ref().is_some_and(|dnd_offer| dnd_offer.offer == offer) {
+                state.dnd_offer = None;
+            }
+
+            match data {
+                Ok(data) => {
+                    if mime_type == URI_LIST_MIME_TYPE {
+                        for path in parse_uri_list(&data).unwrap_or_default() {
+                            let event = WindowEvent::DroppedFile(path);
+                            state.events_sink.push_window_event(event, window_id);
+                        }
+                    }
+
+                    let data = DropData::from_mime_data(MimeData::new(mime_type, data));
+                    let event = WindowEvent::DragDropped { data, action, position };
+                    state.events_sink.push_window_event(event, window_id);
+                    offer.finish();
+                },
+                Err(err) => {
+                    warn!("Failed to read the dropped data: {err}");
+                    state.push_drag_left(window_id, has_files);
+                },
+            }
+
+            offer.destroy();
+        });
+
+        if let Err(err) = result {
+            warn!("Failed to read the dropped data: {err}");
+            if let Some(dnd_offer) = self.dnd_offer.take() {
+                self.cancel_dnd_offer(dnd_offer);
+            }
+        }
+    }
+}
+
+impl DataOfferHandler for WinitState {
+    fn source_actions(
+        &mut self,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+        _: &mut DragOffer,
+        _: WlDndAction,
+    ) {
+    }
+
+    fn selected_action(
+        &mut self,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+        offer: &mut DragOffer,
+        action: WlDndAction,
+    ) {
+        if let Some(dnd_offer) = self.dnd_offer.as_mut() {
+            if dnd_offer.offer.inner() == offer.inner() {
+                dnd_offer.selected_action = dnd_action(action);
+            }
+        }
+    }
+}
+
+fn wl_action(action: DndAction) -> WlDndAction {
+    match action {
+        // There's no link action on Wayland.
+        DndAction::Copy | DndAction::Link => WlDndAction::Copy,
+        DndAction::Move => WlDndAction::Move,
+    }
+}
+
+fn wl_actions(actions: DndActions) -> WlDndAction {
+    let mut wl_actions = WlDndAction::empty();
+    wl_actions.set(WlDndAction::Copy, actions.intersects(DndActions::COPY | DndActions::LINK));
+    wl_actions.set(WlDndAction::Move, actions.contains(DndActions::MOVE));
+    if wl_actions.is_empty() {
+        WlDndAction::Copy
+    } else {
+        wl_actions
+    }
+}
+
+fn dnd_action(action: WlDndAction) -> Option<DndAction> {
+    if action.contains(WlDndAction::Copy) {
+        Some(DndAction::Copy)
+    } else if action.contains(WlDndAction::Move) {
+        Some(DndAction::Move)
+    } else {
+        None
+    }
+}
+
+fn dnd_actions(actions: WlDndAction) -> DndActions {
+    let mut dnd_actions = DndActions::empty();
+    dnd_actions.set(DndActions::COPY, actions.contains(WlDndAction::Copy));
+    dnd_actions.set(DndActions::MOVE, actions.contains(WlDndAction::Move));
+    dnd_actions
+}
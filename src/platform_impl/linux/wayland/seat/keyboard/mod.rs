@@ -1,7 +1,8 @@
 //! The keyboard input handling.
 
-use std::sync::Mutex;
-use std::time::Duration;
+use std::cell::RefCell;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
 use calloop::timer::{TimeoutAction, Timer};
 use calloop::{LoopHandle, RegistrationToken};
@@ -13,12 +14,15 @@ use sctk::reexports::client::protocol::wl_keyboard::{
 use sctk::reexports::client::protocol::wl_seat::WlSeat;
 use sctk::reexports::client::{Connection, Dispatch, Proxy, QueueHandle, WEnum};
 
-use crate::event::{ElementState, WindowEvent};
+use ahash::AHashMap;
+
+use crate::event::{ElementState, Ime, WindowEvent};
 use crate::keyboard::ModifiersState;
 
 use crate::platform_impl::common::xkb::Context;
 use crate::platform_impl::wayland::event_loop::sink::EventSink;
 use crate::platform_impl::wayland::state::WinitState;
+use crate::platform_impl::wayland::window::WindowState;
 use crate::platform_impl::wayland::{self, DeviceId, WindowId};
 
 impl Dispatch<WlKeyboard, KeyboardData, WinitState> for WinitState {
@@ -54,6 +58,18 @@ impl Dispatch<WlKeyboard, KeyboardData, WinitState> for WinitState {
                     WlKeymapFormat::XkbV1 => {
                         let context = &mut keyboard_state.xkb_context;
                         context.set_keymap_from_fd(fd, size as usize);
+
+                        let window_id = match *data.window_id.lock().unwrap() {
+                            Some(window_id) => window_id,
+                            None => return,
+                        };
+
+                        if let Some(layouts) = context.take_keyboard_layouts_update() {
+                            state.events_sink.push_window_event(
+                                WindowEvent::KeyboardLayoutChanged(layouts),
+                                window_id,
+                            );
+                        }
                     },
                     _ => unreachable!(),
                 },
@@ -61,8 +77,9 @@ impl Dispatch<WlKeyboard, KeyboardData, WinitState> for WinitState {
                     warn!("unknown keymap format 0x{:x}", value)
                 },
             },
-            WlKeyboardEvent::Enter { surface, .. } => {
+            WlKeyboardEvent::Enter { surface, serial, .. } => {
                 let window_id = wayland::make_wid(&surface);
+                seat_state.latest_key_serial = serial;
 
                 // Mark the window as focused.
                 let was_unfocused = match state.windows.get_mut().get(&window_id) {
@@ -76,10 +93,7 @@ impl Dispatch<WlKeyboard, KeyboardData, WinitState> for WinitState {
                 };
 
                 // Drop the repeat, if there were any.
-                keyboard_state.current_repeat = None;
-                if let Some(token) = keyboard_state.repeat_token.take() {
-                    keyboard_state.loop_handle.remove(token);
-                }
+                keyboard_state.stop_repeat();
 
                 *data.window_id.lock().unwrap() = Some(window_id);
 
@@ -95,24 +109,27 @@ impl Dispatch<WlKeyboard, KeyboardData, WinitState> for WinitState {
                         window_id,
                     );
                 }
+
+                // Deliver the layout changes which happened without focus.
+                if let Some(layouts) = keyboard_state.xkb_context.take_keyboard_layouts_update() {
+                    let event = WindowEvent::KeyboardLayoutChanged(layouts);
+                    state.events_sink.push_window_event(event, window_id);
+                }
             },
             WlKeyboardEvent::Leave { surface, .. } => {
                 let window_id = wayland::make_wid(&surface);
 
                 // NOTE: we should drop the repeat regardless whethere it was for the present
                 // window of for the window which just went gone.
-                keyboard_state.current_repeat = None;
-                if let Some(token) = keyboard_state.repeat_token.take() {
-                    keyboard_state.loop_handle.remove(token);
-                }
+                keyboard_state.stop_repeat();
 
                 // NOTE: The check whether the window exists is essential as we might get a
                 // nil surface, regardless of what protocol says.
-                let focused = match state.windows.get_mut().get(&window_id) {
+                let (focused, ime_allowed) = match state.windows.get_mut().get(&window_id) {
                     Some(window) => {
                         let mut window = window.lock().unwrap();
                         window.remove_seat_focus(&data.seat.id());
-                        window.has_focus()
+                        (window.has_focus(), window.ime_allowed())
                     },
                     None => return,
                 };
@@ -121,6 +138,13 @@ impl Dispatch<WlKeyboard, KeyboardData, WinitState> for WinitState {
                 // anyway.
                 *data.window_id.lock().unwrap() = None;
 
+                // Cancel the compose sequence, the keys finishing it go to another window.
+                keyboard_state.xkb_context.reset_compose();
+                if keyboard_state.xkb_context.take_compose_preedit().is_some() && ime_allowed {
+                    let event = WindowEvent::Ime(Ime::Preedit(String::new(), None));
+                    state.events_sink.push_window_event(event, window_id);
+                }
+
                 if !focused {
                     // Notify that no modifiers are being pressed.
                     state.events_sink.push_window_event(
@@ -131,12 +155,16 @@ impl Dispatch<WlKeyboard, KeyboardData, WinitState> for WinitState {
                     state.events_sink.push_window_event(WindowEvent::Focused(false), window_id);
                 }
             },
-            WlKeyboardEvent::Key { key, state: WEnum::Value(WlKeyState::Pressed), .. } => {
+            WlKeyboardEvent::Key {
+                key, serial, state: WEnum::Value(WlKeyState::Pressed), ..
+            } => {
                 let key = key + 8;
+                seat_state.latest_key_serial = serial;
 
                 key_input(
                     keyboard_state,
                     &mut state.events_sink,
+                    &state.windows,
                     data,
                     key,
                     ElementState::Pressed,
@@ -160,7 +188,10 @@ impl Dispatch<WlKeyboard, KeyboardData, WinitState> for WinitState {
                     keyboard_state.loop_handle.remove(token);
                 }
 
-                let timer = Timer::from_duration(delay);
+                // The deadline is tracked, since the timer doesn't wake up the event loop's fd.
+                let deadline = Instant::now() + delay;
+                keyboard_state.repeat_deadline = Some(deadline);
+                let timer = Timer::from_deadline(deadline);
                 let wl_keyboard = wl_keyboard.clone();
                 keyboard_state.repeat_token = keyboard_state
                     .loop_handle
@@ -189,6 +220,7 @@ impl Dispatch<WlKeyboard, KeyboardData, WinitState> for WinitState {
                         key_input(
                             keyboard_state,
                             &mut state.events_sink,
+                            &state.windows,
                             data,
                             repeat_keycode,
                             ElementState::Pressed,
@@ -197,18 +229,29 @@ impl Dispatch<WlKeyboard, KeyboardData, WinitState> for WinitState {
 
                         // NOTE: the gap could change dynamically while repeat is going.
                         match keyboard_state.repeat_info {
-                            RepeatInfo::Repeat { gap, .. } => TimeoutAction::ToDuration(gap),
-                            RepeatInfo::Disable => TimeoutAction::Drop,
+                            RepeatInfo::Repeat { gap, .. } => {
+                                let deadline = Instant::now() + gap;
+                                keyboard_state.repeat_deadline = Some(deadline);
+                                TimeoutAction::ToInstant(deadline)
+                            },
+                            RepeatInfo::Disable => {
+                                keyboard_state.repeat_deadline = None;
+                                TimeoutAction::Drop
+                            },
                         }
                     })
                     .ok();
             },
-            WlKeyboardEvent::Key { key, state: WEnum::Value(WlKeyState::Released), .. } => {
+            WlKeyboardEvent::Key {
+                key, serial, state: WEnum::Value(WlKeyState::Released), ..
+            } => {
                 let key = key + 8;
+                seat_state.latest_key_serial = serial;
 
                 key_input(
                     keyboard_state,
                     &mut state.events_sink,
+                    &state.windows,
                     data,
                     key,
                     ElementState::Released,
@@ -219,10 +262,7 @@ impl Dispatch<WlKeyboard, KeyboardData, WinitState> for WinitState {
                     && keyboard_state.xkb_context.keymap_mut().unwrap().key_repeats(key)
                     && Some(key) == keyboard_state.current_repeat
                 {
-                    keyboard_state.current_repeat = None;
-                    if let Some(token) = keyboard_state.repeat_token.take() {
-                        keyboard_state.loop_handle.remove(token);
-                    }
+                    keyboard_state.stop_repeat();
                 }
             },
             WlKeyboardEvent::Modifiers {
@@ -250,14 +290,16 @@ impl Dispatch<WlKeyboard, KeyboardData, WinitState> for WinitState {
                     WindowEvent::ModifiersChanged(seat_state.modifiers.into()),
                     window_id,
                 );
+
+                if let Some(layouts) = xkb_context.take_keyboard_layouts_update() {
+                    let event = WindowEvent::KeyboardLayoutChanged(layouts);
+                    state.events_sink.push_window_event(event, window_id);
+                }
             },
             WlKeyboardEvent::RepeatInfo { rate, delay } => {
                 keyboard_state.repeat_info = if rate == 0 {
                     // Stop the repeat once we get a disable event.
-                    keyboard_state.current_repeat = None;
-                    if let Some(repeat_token) = keyboard_state.repeat_token.take() {
-                        keyboard_state.loop_handle.remove(repeat_token);
-                    }
+                    keyboard_state.stop_repeat();
                     RepeatInfo::Disable
                 } else {
                     let gap = Duration::from_micros(1_000_000 / rate as u64);
@@ -290,6 +332,9 @@ pub struct KeyboardState {
 
     /// The current repeat raw key.
     pub current_repeat: Option<u32>,
+
+    /// When the current repeat fires next.
+    pub repeat_deadline: Option<Instant>,
 }
 
 impl KeyboardState {
@@ -301,6 +346,16 @@ impl KeyboardState {
             repeat_info: RepeatInfo::default(),
             repeat_token: None,
             current_repeat: None,
+            repeat_deadline: None,
+        }
+    }
+
+    /// Stop repeating the current key.
+    fn stop_repeat(&mut self) {
+        self.current_repeat = None;
+        self.repeat_deadline = None;
+        if let Some(token) = self.repeat_token.take() {
+            self.loop_handle.remove(token);
         }
     }
 }
@@ -361,6 +416,7 @@ impl KeyboardData {
 fn key_input(
     keyboard_state: &mut KeyboardState,
     event_sink: &mut EventSink,
+    windows: &RefCell<AHashMap<WindowId, Arc<Mutex<WindowState>>>>,
     data: &KeyboardData,
     keycode: u32,
     state: ElementState,
@@ -374,6 +430,18 @@ fn key_input(
     let device_id = crate::event::DeviceId(crate::platform_impl::DeviceId::Wayland(DeviceId));
     if let Some(mut key_context) = keyboard_state.xkb_context.key_context() {
         let event = key_context.process_key_event(keycode, state, repeat);
+
+        // Surface the pending compose sequence to text fields before the key finishing it.
+        let preedit = keyboard_state.xkb_context.take_compose_preedit();
+        let ime_allowed = windows
+            .borrow()
+            .get(&window_id)
+            .is_some_and(|window| window.lock().unwrap().ime_allowed());
+        if let Some(text) = preedit.filter(|_| ime_allowed) {
+            let cursor = (!text.is_empty()).then_some((text.len(), text.len()));
+            event_sink.push_window_event(WindowEvent::Ime(Ime::Preedit(text, cursor)), window_id);
+        }
+
         let event = WindowEvent::KeyboardInput { device_id, event, is_synthetic: false };
         event_sink.push_window_event(event, window_id);
     }
@@ -1,34 +1,46 @@
 //! Seat handling.
 
 use std::sync::Arc;
+use std::time::Instant;
 
 use ahash::AHashMap;
 use tracing::warn;
 
+use sctk::data_device_manager::data_device::DataDevice;
+use sctk::primary_selection::device::PrimarySelectionDevice;
 use sctk::reexports::client::backend::ObjectId;
 use sctk::reexports::client::protocol::wl_seat::WlSeat;
 use sctk::reexports::client::protocol::wl_touch::WlTouch;
 use sctk::reexports::client::{Connection, Proxy, QueueHandle};
 use sctk::reexports::protocols::wp::relative_pointer::zv1::client::zwp_relative_pointer_v1::ZwpRelativePointerV1;
+use sctk::reexports::protocols::wp::tablet::zv2::client::zwp_tablet_seat_v2::ZwpTabletSeatV2;
 use sctk::reexports::protocols::wp::text_input::zv3::client::zwp_text_input_v3::ZwpTextInputV3;
 
 use sctk::seat::pointer::{ThemeSpec, ThemedPointer};
 use sctk::seat::{Capability as SeatCapability, SeatHandler, SeatState};
 
 use crate::event::WindowEvent;
-use crate::keyboard::ModifiersState;
+use crate::keyboard::{KeyboardLayouts, ModifiersState};
 use crate::platform_impl::wayland::state::WinitState;
 
+mod data_device;
+mod dnd;
 mod keyboard;
 mod pointer;
+mod tablet;
 mod text_input;
 mod touch;
 
+pub use data_device::{SelectionRequest, SelectionSources};
+pub use dnd::{DndOffer, DndRequest, DndSource};
+pub use pointer::gestures::PointerGesturesState;
 pub use pointer::relative_pointer::RelativePointerState;
 pub use pointer::{PointerConstraintsState, WinitPointerData, WinitPointerDataExt};
-pub use text_input::{TextInputState, ZwpTextInputV3Ext};
+pub use tablet::TabletManagerState;
+pub use text_input::{SurroundingText, TextInputState, ZwpTextInputV3Ext};
 
 use keyboard::{KeyboardData, KeyboardState};
+use pointer::gestures::PointerGestures;
 use text_input::TextInputData;
 use touch::TouchPoint;
 
@@ -49,6 +61,12 @@ pub struct WinitSeatState {
     /// The relative pointer bound on the seat.
     relative_pointer: Option<ZwpRelativePointerV1>,
 
+    /// The pointer gestures bound on the seat.
+    pointer_gestures: Option<PointerGestures>,
+
+    /// The tablet seat bound on the seat.
+    tablet_seat: Option<ZwpTabletSeatV2>,
+
     /// The keyboard bound on the seat.
     keyboard_state: Option<KeyboardState>,
 
@@ -57,12 +75,31 @@ pub struct WinitSeatState {
 
     /// Whether we have pending modifiers.
     modifiers_pending: bool,
+
+    /// The serial of the latest keyboard event.
+    latest_key_serial: u32,
+
+    /// The data device bound on the seat.
+    data_device: Option<DataDevice>,
+
+    /// The primary selection device bound on the seat.
+    primary_selection_device: Option<PrimarySelectionDevice>,
 }
 
 impl WinitSeatState {
     pub fn new() -> Self {
         Default::default()
     }
+
+    /// The layouts of the keyboard bound on the seat.
+    pub fn keyboard_layouts(&mut self) -> Option<KeyboardLayouts> {
+        self.keyboard_state.as_mut()?.xkb_context.keyboard_layouts()
+    }
+
+    /// When the key held on the keyboard of the seat repeats next.
+    pub fn repeat_deadline(&self) -> Option<Instant> {
+        self.keyboard_state.as_ref()?.repeat_deadline
+    }
 }
 
 impl SeatHandler for WinitState {
@@ -118,6 +155,11 @@ impl SeatHandler for WinitState {
                     )
                 });
 
+                seat_state.pointer_gestures = self
+                    .pointer_gestures
+                    .as_ref()
+                    .map(|manager| manager.get_gestures(themed_pointer.pointer(), queue_handle));
+
                 let themed_pointer = Arc::new(themed_pointer);
 
                 // Register cursor surface.
@@ -171,6 +213,10 @@ impl SeatHandler for WinitState {
                     relative_pointer.destroy();
                 }
 
+                if let Some(pointer_gestures) = seat_state.pointer_gestures.take() {
+                    pointer_gestures.destroy();
+                }
+
                 if let Some(pointer) = seat_state.pointer.take() {
                     let pointer_data = pointer.pointer().winit_data();
 
@@ -198,10 +244,18 @@ impl SeatHandler for WinitState {
     fn new_seat(
         &mut self,
         _connection: &Connection,
-        _queue_handle: &QueueHandle<Self>,
+        queue_handle: &QueueHandle<Self>,
         seat: WlSeat,
     ) {
-        self.seats.insert(seat.id(), WinitSeatState::new());
+        let mut seat_state = WinitSeatState::new();
+        seat_state.bind_data_devices(
+            &seat,
+            queue_handle,
+            self.data_device_manager.as_ref(),
+            self.primary_selection_manager.as_ref(),
+        );
+        seat_state.bind_tablet_seat(&seat, queue_handle, self.tablet_manager.as_ref());
+        self.seats.insert(seat.id(), seat_state);
     }
 
     fn remove_seat(
@@ -210,7 +264,11 @@ impl SeatHandler for WinitState {
         _queue_handle: &QueueHandle<Self>,
         seat: WlSeat,
     ) {
-        let _ = self.seats.remove(&seat.id());
+        if let Some(tablet_seat) =
+            self.seats.remove(&seat.id()).and_then(|seat_state| seat_state.tablet_seat)
+        {
+            tablet_seat.destroy();
+        }
         self.on_keyboard_destroy(&seat.id());
     }
 }
@@ -0,0 +1,255 @@
+//! Pointer gestures.
+
+use std::ops::Deref;
+use std::sync::Mutex;
+
+use sctk::reexports::client::globals::{BindError, GlobalList};
+use sctk::reexports::client::protocol::wl_pointer::WlPointer;
+use sctk::reexports::client::protocol::wl_surface::WlSurface;
+use sctk::reexports::client::{delegate_dispatch, Dispatch};
+use sctk::reexports::client::{Connection, Proxy, QueueHandle};
+use sctk::reexports::protocols::wp::pointer_gestures::zv1::{
+    client::zwp_pointer_gesture_hold_v1::{self, ZwpPointerGestureHoldV1},
+    client::zwp_pointer_gesture_pinch_v1::{self, ZwpPointerGesturePinchV1},
+    client::zwp_pointer_gesture_swipe_v1::{self, ZwpPointerGestureSwipeV1},
+    client::zwp_pointer_gestures_v1::ZwpPointerGesturesV1,
+};
+
+use sctk::globals::GlobalData;
+
+use crate::dpi::{LogicalPosition, PhysicalPosition};
+use crate::event::{TouchPhase, WindowEvent};
+use crate::platform_impl::common::gesture::Pinch;
+use crate::platform_impl::wayland::state::WinitState;
+use crate::platform_impl::wayland::{self, DeviceId, WindowId};
+
+/// Wrapper around the pointer gestures.
+pub struct PointerGesturesState {
+    manager: ZwpPointerGesturesV1,
+}
+
+impl PointerGesturesState {
+    /// Create new pointer gestures manager.
+    pub fn new(
+        globals: &GlobalList,
+        queue_handle: &QueueHandle<WinitState>,
+    ) -> Result<Self, BindError> {
+        let manager = globals.bind(queue_handle, 1..=3, GlobalData)?;
+        Ok(Self { manager })
+    }
+
+    /// Get the gestures of the pointer.
+    pub fn get_gestures(
+        &self,
+        pointer: &WlPointer,
+        queue_handle: &QueueHandle<WinitState>,
+    ) -> PointerGestures {
+        let manager = &self.manager;
+        PointerGestures {
+            swipe: manager.get_swipe_gesture(pointer, queue_handle, GestureData::default()),
+            pinch: manager.get_pinch_gesture(pointer, queue_handle, GestureData::default()),
+            hold: (manager.version() >= 3)
+                .then(|| manager.get_hold_gesture(pointer, queue_handle, GestureData::default())),
+        }
+    }
+}
+
+impl Deref for PointerGesturesState {
+    type Target = ZwpPointerGesturesV1;
+
+    fn deref(&self) -> &Self::Target {
+        &self.manager
+    }
+}
+
+/// The gestures bound on a pointer.
+#[derive(Debug)]
+pub struct PointerGestures {
+    swipe: ZwpPointerGestureSwipeV1,
+    pinch: ZwpPointerGesturePinchV1,
+    hold: Option<ZwpPointerGestureHoldV1>,
+}
+
+impl PointerGestures {
+    pub fn destroy(&self) {
+        self.swipe.destroy();
+        self.pinch.destroy();
+        if let Some(hold) = self.hold.as_ref() {
+            hold.destroy();
+        }
+    }
+}
+
+/// The data of a gesture.
+#[derive(Debug, Default)]
+pub struct GestureData {
+    inner: Mutex<GestureDataInner>,
+}
+
+#[derive(Debug, Default)]
+struct GestureDataInner {
+    /// The window the gesture started on.
+    window_id: Option<WindowId>,
+
+    /// The ongoing pinch.
+    pinch: Pinch,
+}
+
+impl WinitState {
+    fn push_gesture_event(&mut self, window_id: WindowId, event: impl FnOnce(f64) -> WindowEvent) {
+        let scale_factor = match self.windows.get_mut().get(&window_id) {
+            Some(window) => window.lock().unwrap().scale_factor(),
+            None => return,
+        };
+        self.events_sink.push_window_event(event(scale_factor), window_id);
+    }
+}
+
+/// The phase of the gesture once it ended.
+fn end_phase(cancelled: i32) -> TouchPhase {
+    if cancelled == 0 {
+        TouchPhase::Ended
+    } else {
+        TouchPhase::Cancelled
+    }
+}
+
+fn device_id() -> crate::event::DeviceId {
+    crate::event::DeviceId(crate::platform_impl::DeviceId::Wayland(DeviceId))
+}
+
+/// Start the gesture on the `surface`, returning the window it's happening on.
+fn begin(data: &GestureData, surface: &WlSurface) -> WindowId {
+    let window_id = wayland::make_wid(surface);
+    let mut inner = data.inner.lock().unwrap();
+    inner.window_id = Some(window_id);
+    inner.pinch.begin();
+    window_id
+}
+
+impl Dispatch<ZwpPointerGesturesV1, GlobalData, WinitState> for PointerGesturesState {
+    fn event(
+        _state: &mut WinitState,
+        _proxy: &ZwpPointerGesturesV1,
+        _event: <ZwpPointerGesturesV1 as Proxy>::Event,
+        _data: &GlobalData,
+        _conn: &Connection,
+        _qhandle: &QueueHandle<WinitState>,
+    ) {
+    }
+}
+
+impl Dispatch<ZwpPointerGestureSwipeV1, GestureData, WinitState> for PointerGesturesState {
+    fn event(
+        state: &mut WinitState,
+        _proxy: &ZwpPointerGestureSwipeV1,
+        event: <ZwpPointerGestureSwipeV1 as Proxy>::Event,
+        data: &GestureData,
+        _conn: &Connection,
+        _qhandle: &QueueHandle<WinitState>,
+    ) {
+        let (window_id, delta, phase) = match event {
+            zwp_pointer_gesture_swipe_v1::Event::Begin { surface, .. } => {
+                (begin(data, &surface), (0., 0.), TouchPhase::Started)
+            },
+            zwp_pointer_gesture_swipe_v1::Event::Update { dx, dy, .. } => {
+                match data.inner.lock().unwrap().window_id {
+                    Some(window_id) => (window_id, (dx, dy), TouchPhase::Moved),
+                    None => return,
+                }
+            },
+            zwp_pointer_gesture_swipe_v1::Event::End { cancelled, .. } => {
+                match data.inner.lock().unwrap().window_id.take() {
+                    Some(window_id) => (window_id, (0., 0.), end_phase(cancelled)),
+                    None => return,
+                }
+            },
+            _ => return,
+        };
+
+        state.push_gesture_event(window_id, |scale_factor| {
+            let delta = LogicalPosition::<f64>::from(delta).to_physical(scale_factor);
+            WindowEvent::PanGesture { device_id: device_id(), delta, phase }
+        });
+    }
+}
+
+impl Dispatch<ZwpPointerGesturePinchV1, GestureData, WinitState> for PointerGesturesState {
+    fn event(
+        state: &mut WinitState,
+        _proxy: &ZwpPointerGesturePinchV1,
+        event: <ZwpPointerGesturePinchV1 as Proxy>::Event,
+        data: &GestureData,
+        _conn: &Connection,
+        _qhandle: &QueueHandle<WinitState>,
+    ) {
+        let (window_id, scale_delta, rotation_delta, phase) = match event {
+            zwp_pointer_gesture_pinch_v1::Event::Begin { surface, .. } => {
+                (begin(data, &surface), 0., 0., TouchPhase::Started)
+            },
+            zwp_pointer_gesture_pinch_v1::Event::Update { scale, rotation, .. } => {
+                let mut inner = data.inner.lock().unwrap();
+                let window_id = match inner.window_id {
+                    Some(window_id) => window_id,
+                    None => return,
+                };
+                let (scale_delta, rotation_delta) = inner.pinch.update(scale, rotation);
+                (window_id, scale_delta, rotation_delta, TouchPhase::Moved)
+            },
+            zwp_pointer_gesture_pinch_v1::Event::End { cancelled, .. } => {
+                match data.inner.lock().unwrap().window_id.take() {
+                    Some(window_id) => (window_id, 0., 0., end_phase(cancelled)),
+                    None => return,
+                }
+            },
+            _ => return,
+        };
+
+        state.push_gesture_event(window_id, |_| WindowEvent::PinchGesture {
+            device_id: device_id(),
+            delta: scale_delta,
+            phase,
+        });
+        state.push_gesture_event(window_id, |_| WindowEvent::RotationGesture {
+            device_id: device_id(),
+            delta: rotation_delta,
+            phase,
+        });
+    }
+}
+
+impl Dispatch<ZwpPointerGestureHoldV1, GestureData, WinitState> for PointerGesturesState {
+    fn event(
+        state: &mut WinitState,
+        _proxy: &ZwpPointerGestureHoldV1,
+        event: <ZwpPointerGestureHoldV1 as Proxy>::Event,
+        data: &GestureData,
+        _conn: &Connection,
+        _qhandle: &QueueHandle<WinitState>,
+    ) {
+        // The hold is a pan that didn't move, e.g. to stop the kinetic scrolling.
+        let (window_id, phase) = match event {
+            zwp_pointer_gesture_hold_v1::Event::Begin { surface, .. } => {
+                (begin(data, &surface), TouchPhase::Started)
+            },
+            zwp_pointer_gesture_hold_v1::Event::End { cancelled, .. } => {
+                match data.inner.lock().unwrap().window_id.take() {
+                    Some(window_id) => (window_id, end_phase(cancelled)),
+                    None => return,
+                }
+            },
+            _ => return,
+        };
+
+        state.push_gesture_event(window_id, |_| WindowEvent::PanGesture {
+            device_id: device_id(),
+            delta: PhysicalPosition::new(0., 0.),
+            phase,
+        });
+    }
+}
+
+delegate_dispatch!(WinitState: [ZwpPointerGesturesV1: GlobalData] => PointerGesturesState);
+delegate_dispatch!(WinitState: [ZwpPointerGestureSwipeV1: GestureData] => PointerGesturesState);
+delegate_dispatch!(WinitState: [ZwpPointerGesturePinchV1: GestureData] => PointerGesturesState);
+delegate_dispatch!(WinitState: [ZwpPointerGestureHoldV1: GestureData] => PointerGesturesState);
@@ -32,6 +32,7 @@ use crate::event::{ElementState, MouseButton, MouseScrollDelta, TouchPhase, Wind
 use crate::platform_impl::wayland::state::WinitState;
 use crate::platform_impl::wayland::{self, DeviceId, WindowId};
 
+pub mod gestures;
 pub mod relative_pointer;
 
 impl PointerHandler for WinitState {
@@ -0,0 +1,580 @@
+//! Tablet handling.
+
+use std::ops::Deref;
+use std::sync::Mutex;
+
+use cursor_icon::CursorIcon;
+use sctk::compositor::{CompositorState, SurfaceData};
+use sctk::globals::GlobalData;
+use sctk::reexports::client::globals::{BindError, GlobalList};
+use sctk::reexports::client::protocol::wl_seat::WlSeat;
+use sctk::reexports::client::protocol::wl_surface::WlSurface;
+use sctk::reexports::client::{
+    delegate_dispatch, event_created_child, Connection, Dispatch, Proxy, QueueHandle, WEnum,
+};
+use sctk::reexports::protocols::wp::cursor_shape::v1::client::wp_cursor_shape_device_v1::{
+    Shape, WpCursorShapeDeviceV1,
+};
+use sctk::reexports::protocols::wp::cursor_shape::v1::client::wp_cursor_shape_manager_v1::WpCursorShapeManagerV1;
+use sctk::reexports::protocols::wp::tablet::zv2::client::zwp_tablet_manager_v2::ZwpTabletManagerV2;
+use sctk::reexports::protocols::wp::tablet::zv2::client::zwp_tablet_pad_group_v2::{
+    self, ZwpTabletPadGroupV2,
+};
+use sctk::reexports::protocols::wp::tablet::zv2::client::zwp_tablet_pad_ring_v2::ZwpTabletPadRingV2;
+use sctk::reexports::protocols::wp::tablet::zv2::client::zwp_tablet_pad_strip_v2::ZwpTabletPadStripV2;
+use sctk::reexports::protocols::wp::tablet::zv2::client::zwp_tablet_pad_v2::{
+    self, ZwpTabletPadV2,
+};
+use sctk::reexports::protocols::wp::tablet::zv2::client::zwp_tablet_seat_v2::{
+    self, ZwpTabletSeatV2,
+};
+use sctk::reexports::protocols::wp::tablet::zv2::client::zwp_tablet_tool_v2::{
+    ButtonState, Capability, Event as TabletToolEvent, Type as TabletToolType, ZwpTabletToolV2,
+};
+use sctk::reexports::protocols::wp::tablet::zv2::client::zwp_tablet_v2::{self, ZwpTabletV2};
+
+use crate::dpi::{LogicalPosition, PhysicalPosition};
+use crate::event::{
+    ElementState, TabletTool, TabletToolAxes, TabletToolButton, TabletToolKind, WindowEvent,
+};
+use crate::platform_impl::wayland::seat::WinitSeatState;
+use crate::platform_impl::wayland::state::WinitState;
+use crate::platform_impl::wayland::types::cursor::SelectedCursor;
+use crate::platform_impl::wayland::{self, DeviceId};
+
+/// The `BTN_STYLUS` Linux event code.
+const BTN_STYLUS: u32 = 0x14b;
+/// The `BTN_STYLUS2` Linux event code.
+const BTN_STYLUS2: u32 = 0x14c;
+/// The `BTN_STYLUS3` Linux event code.
+const BTN_STYLUS3: u32 = 0x149;
+
+/// The maximum value of the pressure and distance axes.
+const AXIS_MAX: f64 = 65535.;
+
+/// Wrapper around the tablet manager.
+pub struct TabletManagerState {
+    manager: ZwpTabletManagerV2,
+
+    /// The cursor shape manager, to set the named cursors on the tools.
+    cursor_shape_manager: Option<WpCursorShapeManagerV1>,
+}
+
+impl TabletManagerState {
+    /// Create new tablet manager.
+    pub fn new(
+        globals: &GlobalList,
+        queue_handle: &QueueHandle<WinitState>,
+    ) -> Result<Self, BindError> {
+        let manager = globals.bind(queue_handle, 1..=1, GlobalData)?;
+        let cursor_shape_manager = globals.bind(queue_handle, 1..=1, GlobalData).ok();
+        Ok(Self { manager, cursor_shape_manager })
+    }
+}
+
+impl Deref for TabletManagerState {
+    type Target = ZwpTabletManagerV2;
+
+    fn deref(&self) -> &Self::Target {
+        &self.manager
+    }
+}
+
+impl WinitSeatState {
+    /// Get the tablet seat, to receive the events of the tablet tools.
+    pub fn bind_tablet_seat(
+        &mut self,
+        seat: &WlSeat,
+        queue_handle: &QueueHandle<WinitState>,
+        tablet_manager: Option<&TabletManagerState>,
+    ) {
+        self.tablet_seat =
+            tablet_manager.map(|manager| manager.get_tablet_seat(seat, queue_handle, GlobalData));
+    }
+}
+
+/// The data of the tablet tool.
+#[derive(Debug, Default)]
+pub struct TabletToolData {
+    inner: Mutex<TabletToolInner>,
+}
+
+#[derive(Debug, Default)]
+struct TabletToolInner {
+    /// The physical type of the tool.
+    kind: Option<TabletToolKind>,
+
+    /// The hardware serial of the tool.
+    serial: u64,
+
+    /// The axes of the tool, the ones the tool lacks are `None`.
+    axes: TabletToolAxes,
+
+    /// The surface the tool is in proximity of.
+    surface: Option<WlSurface>,
+
+    /// The serial of the latest proximity in, to set the cursor.
+    proximity_serial: u32,
+
+    /// The cursor shape device of the tool, for the named cursors.
+    shape_device: Option<WpCursorShapeDeviceV1>,
+
+    /// The surface of the custom cursors of the tool.
+    cursor_surface: Option<WlSurface>,
+
+    /// The position of the tool on the surface.
+    position: LogicalPosition<f64>,
+
+    /// The events accumulated until the next frame.
+    frame: TabletToolFrame,
+}
+
+/// The events of a tablet tool frame.
+#[derive(Debug, Default)]
+struct TabletToolFrame {
+    proximity_in: bool,
+    proximity_out: bool,
+    motion: bool,
+    tip: Option<ElementState>,
+    buttons: Vec<(TabletToolButton, ElementState)>,
+}
+
+impl TabletToolFrame {
+    /// The events of the frame, in the order they happened.
+    fn events(
+        self,
+        position: PhysicalPosition<f64>,
+        axes: TabletToolAxes,
+    ) -> Vec<crate::event::TabletToolEvent> {
+        let mut events = Vec::new();
+        if self.proximity_in {
+            events.push(crate::event::TabletToolEvent::ProximityIn);
+        }
+        if self.motion {
+            events.push(crate::event::TabletToolEvent::Motion { position, axes });
+        }
+        if let Some(tip) = self.tip {
+            events.push(crate::event::TabletToolEvent::Tip(tip));
+        }
+        for (button, state) in self.buttons {
+            events.push(crate::event::TabletToolEvent::Button { button, state });
+        }
+        if self.proximity_out {
+            events.push(crate::event::TabletToolEvent::ProximityOut);
+        }
+        events
+    }
+}
+
+impl TabletToolInner {
+    /// Set the cursor of the window the tool came into proximity of, `None` hiding it.
+    ///
+    /// The named cursors need `wp_cursor_shape_v1`, the compositor picks the cursor otherwise.
+    fn set_cursor(
+        &mut self,
+        tool: &ZwpTabletToolV2,
+        cursor: Option<&SelectedCursor>,
+        cursor_shape_manager: Option<&WpCursorShapeManagerV1>,
+        compositor_state: &CompositorState,
+        queue_handle: &QueueHandle<WinitState>,
+    ) {
+        let serial = self.proximity_serial;
+        match cursor {
+            None => tool.set_cursor(serial, None, 0, 0),
+            Some(SelectedCursor::Named(icon)) => {
+                if let Some(manager) = cursor_shape_manager {
+                    self.shape_device
+                        .get_or_insert_with(|| {
+                            manager.get_tablet_tool_v2(tool, queue_handle, GlobalData)
+                        })
+                        .set_shape(serial, cursor_shape(*icon));
+                }
+            },
+            Some(SelectedCursor::Custom(cursor)) => {
+                let surface = self
+                    .cursor_surface
+                    .get_or_insert_with(|| compositor_state.create_surface(queue_handle));
+
+                let scale = surface.data::<SurfaceData>().unwrap().scale_factor();
+
+                surface.set_buffer_scale(scale);
+                surface.attach(Some(cursor.buffer.wl_buffer()), 0, 0);
+                if surface.version() >= 4 {
+                    surface.damage_buffer(0, 0, cursor.w, cursor.h);
+                } else {
+                    surface.damage(0, 0, cursor.w / scale, cursor.h / scale);
+                }
+                surface.commit();
+
+                tool.set_cursor(
+                    serial,
+                    Some(surface),
+                    cursor.hotspot_x / scale,
+                    cursor.hotspot_y / scale,
+                );
+            },
+        }
+    }
+}
+
+/// Map the physical type of a tool.
+fn tool_kind(tool_type: WEnum<TabletToolType>) -> TabletToolKind {
+    match tool_type {
+        WEnum::Value(TabletToolType::Pen) => TabletToolKind::Pen,
+        WEnum::Value(TabletToolType::Eraser) => TabletToolKind::Eraser,
+        WEnum::Value(TabletToolType::Brush) => TabletToolKind::Brush,
+        WEnum::Value(TabletToolType::Pencil) => TabletToolKind::Pencil,
+        WEnum::Value(TabletToolType::Airbrush) => TabletToolKind::Airbrush,
+        WEnum::Value(TabletToolType::Finger) => TabletToolKind::Finger,
+        WEnum::Value(TabletToolType::Mouse) => TabletToolKind::Mouse,
+        WEnum::Value(TabletToolType::Lens) => TabletToolKind::Lens,
+        _ => TabletToolKind::Unknown,
+    }
+}
+
+/// Map the Linux event code of a tool button.
+fn tool_button(button: u32) -> TabletToolButton {
+    match button {
+        BTN_STYLUS => TabletToolButton::Primary,
+        BTN_STYLUS2 => TabletToolButton::Secondary,
+        BTN_STYLUS3 => TabletToolButton::Tertiary,
+        button => TabletToolButton::Other(button),
+    }
+}
+
+/// Normalize the value of the pressure and distance axes.
+fn axis_value(value: u32) -> f64 {
+    value as f64 / AXIS_MAX
+}
+
+/// Map a cursor icon to its `wp_cursor_shape_v1` shape.
+fn cursor_shape(icon: CursorIcon) -> Shape {
+    match icon {
+        CursorIcon::Default => Shape::Default,
+        CursorIcon::ContextMenu => Shape::ContextMenu,
+        CursorIcon::Help => Shape::Help,
+        CursorIcon::Pointer => Shape::Pointer,
+        CursorIcon::Progress => Shape::Progress,
+        CursorIcon::Wait => Shape::Wait,
+        CursorIcon::Cell => Shape::Cell,
+        CursorIcon::Crosshair => Shape::Crosshair,
+        CursorIcon::Text => Shape::Text,
+        CursorIcon::VerticalText => Shape::VerticalText,
+        CursorIcon::Alias => Shape::Alias,
+        CursorIcon::Copy => Shape::Copy,
+        CursorIcon::Move => Shape::Move,
+        CursorIcon::NoDrop => Shape::NoDrop,
+        CursorIcon::NotAllowed => Shape::NotAllowed,
+        CursorIcon::Grab => Shape::Grab,
+        CursorIcon::Grabbing => Shape::Grabbing,
+        CursorIcon::EResize => Shape::EResize,
+        CursorIcon::NResize => Shape::NResize,
+        CursorIcon::NeResize => Shape::NeResize,
+        CursorIcon::NwResize => Shape::NwResize,
+        CursorIcon::SResize => Shape::SResize,
+        CursorIcon::SeResize => Shape::SeResize,
+        CursorIcon::SwResize => Shape::SwResize,
+        CursorIcon::WResize => Shape::WResize,
+        CursorIcon::EwResize => Shape::EwResize,
+        CursorIcon::NsResize => Shape::NsResize,
+        CursorIcon::NeswResize => Shape::NeswResize,
+        CursorIcon::NwseResize => Shape::NwseResize,
+        CursorIcon::ColResize => Shape::ColResize,
+        CursorIcon::RowResize => Shape::RowResize,
+        CursorIcon::AllScroll => Shape::AllScroll,
+        CursorIcon::ZoomIn => Shape::ZoomIn,
+        CursorIcon::ZoomOut => Shape::ZoomOut,
+        _ => Shape::Default,
+    }
+}
+
+impl Dispatch<ZwpTabletManagerV2, GlobalData, WinitState> for TabletManagerState {
+    fn event(
+        _state: &mut WinitState,
+        _proxy: &ZwpTabletManagerV2,
+        _event: <ZwpTabletManagerV2 as Proxy>::Event,
+        _data: &GlobalData,
+        _conn: &Connection,
+        _qhandle: &QueueHandle<WinitState>,
+    ) {
+    }
+}
+
+impl Dispatch<ZwpTabletSeatV2, GlobalData, WinitState> for TabletManagerState {
+    fn event(
+        _state: &mut WinitState,
+        _proxy: &ZwpTabletSeatV2,
+        _event: <ZwpTabletSeatV2 as Proxy>::Event,
+        _data: &GlobalData,
+        _conn: &Connection,
+        _qhandle: &QueueHandle<WinitState>,
+    ) {
+        // The tablets, tools and pads are set up by `event_created_child`.
+    }
+
+    event_created_child!(WinitState, ZwpTabletSeatV2, [
+        zwp_tablet_seat_v2::EVT_TABLET_ADDED_OPCODE => (ZwpTabletV2, GlobalData),
+        zwp_tablet_seat_v2::EVT_TOOL_ADDED_OPCODE => (ZwpTabletToolV2, TabletToolData::default()),
+        zwp_tablet_seat_v2::EVT_PAD_ADDED_OPCODE => (ZwpTabletPadV2, GlobalData),
+    ]);
+}
+
+impl Dispatch<ZwpTabletV2, GlobalData, WinitState> for TabletManagerState {
+    fn event(
+        _state: &mut WinitState,
+        tablet: &ZwpTabletV2,
+        event: <ZwpTabletV2 as Proxy>::Event,
+        _data: &GlobalData,
+        _conn: &Connection,
+        _qhandle: &QueueHandle<WinitState>,
+    ) {
+        if let zwp_tablet_v2::Event::Removed = event {
+            tablet.destroy();
+        }
+    }
+}
+
+impl Dispatch<ZwpTabletToolV2, TabletToolData, WinitState> for TabletManagerState {
+    fn event(
+        state: &mut WinitState,
+        tool: &ZwpTabletToolV2,
+        event: <ZwpTabletToolV2 as Proxy>::Event,
+        data: &TabletToolData,
+        _conn: &Connection,
+        queue_handle: &QueueHandle<WinitState>,
+    ) {
+        let mut inner = data.inner.lock().unwrap();
+        match event {
+            TabletToolEvent::Type { tool_type } => inner.kind = Some(tool_kind(tool_type)),
+            TabletToolEvent::HardwareSerial { hardware_serial_hi, hardware_serial_lo } => {
+                inner.serial = (hardware_serial_hi as u64) << 32 | hardware_serial_lo as u64;
+            },
+            TabletToolEvent::Capability { capability } => match capability {
+                WEnum::Value(Capability::Pressure) => inner.axes.pressure = Some(0.),
+                WEnum::Value(Capability::Distance) => inner.axes.distance = Some(0.),
+                WEnum::Value(Capability::Tilt) => inner.axes.tilt = Some((0., 0.)),
+                WEnum::Value(Capability::Rotation) => inner.axes.rotation = Some(0.),
+                WEnum::Value(Capability::Slider) => inner.axes.slider = Some(0.),
+                _ => (),
+            },
+            TabletToolEvent::ProximityIn { serial, surface, .. } => {
+                inner.surface = Some(surface);
+                inner.proximity_serial = serial;
+                inner.frame.proximity_in = true;
+            },
+            TabletToolEvent::ProximityOut => inner.frame.proximity_out = true,
+            TabletToolEvent::Down { .. } => inner.frame.tip = Some(ElementState::Pressed),
+            TabletToolEvent::Up => inner.frame.tip = Some(ElementState::Released),
+            TabletToolEvent::Motion { x, y } => {
+                inner.position = LogicalPosition::new(x, y);
+                inner.frame.motion = true;
+            },
+            TabletToolEvent::Pressure { pressure } => {
+                inner.axes.pressure = Some(axis_value(pressure));
+                inner.frame.motion = true;
+            },
+            TabletToolEvent::Distance { distance } => {
+                inner.axes.distance = Some(axis_value(distance));
+                inner.frame.motion = true;
+            },
+            TabletToolEvent::Tilt { tilt_x, tilt_y } => {
+                inner.axes.tilt = Some((tilt_x, tilt_y));
+                inner.frame.motion = true;
+            },
+            TabletToolEvent::Rotation { degrees } => {
+                inner.axes.rotation = Some(degrees);
+                inner.frame.motion = true;
+            },
+            TabletToolEvent::Slider { position } => {
+                inner.axes.slider = Some(position as f64 / AXIS_MAX);
+                inner.frame.motion = true;
+            },
+            TabletToolEvent::Button { button, state: button_state, .. } => {
+                let button = tool_button(button);
+                let button_state = match button_state {
+                    WEnum::Value(ButtonState::Pressed) => ElementState::Pressed,
+                    _ => ElementState::Released,
+                };
+                inner.frame.buttons.push((button, button_state));
+            },
+            TabletToolEvent::Frame { .. } => {
+                let frame = std::mem::take(&mut inner.frame);
+                let surface = match frame.proximity_out {
+                    true => inner.surface.take(),
+                    false => inner.surface.clone(),
+                };
+                let surface = match surface {
+                    Some(surface) => surface,
+                    None => return,
+                };
+
+                let window_id = wayland::make_wid(&surface);
+                let scale_factor = match state.windows.get_mut().get(&window_id) {
+                    Some(window) => {
+                        let window = window.lock().unwrap();
+                        if frame.proximity_in {
+                            let cursor_shape_manager = state
+                                .tablet_manager
+                                .as_ref()
+                                .and_then(|manager| manager.cursor_shape_manager.as_ref());
+                            inner.set_cursor(
+                                tool,
+                                window.selected_cursor(),
+                                cursor_shape_manager,
+                                &state.compositor_state,
+                                queue_handle,
+                            );
+                        }
+                        window.scale_factor()
+                    },
+                    None => return,
+                };
+
+                let tool = TabletTool {
+                    kind: inner.kind.unwrap_or(TabletToolKind::Unknown),
+                    serial: inner.serial,
+                };
+                for event in frame.events(inner.position.to_physical(scale_factor), inner.axes) {
+                    state.events_sink.push_window_event(
+                        WindowEvent::TabletTool {
+                            device_id: crate::event::DeviceId(
+                                crate::platform_impl::DeviceId::Wayland(DeviceId),
+                            ),
+                            tool,
+                            event,
+                        },
+                        window_id,
+                    );
+                }
+            },
+            TabletToolEvent::Removed => {
+                if let Some(shape_device) = inner.shape_device.take() {
+                    shape_device.destroy();
+                }
+                if let Some(cursor_surface) = inner.cursor_surface.take() {
+                    cursor_surface.destroy();
+                }
+                tool.destroy();
+            },
+            _ => (),
+        }
+    }
+}
+
+impl Dispatch<ZwpTabletPadV2, GlobalData, WinitState> for TabletManagerState {
+    fn event(
+        _state: &mut WinitState,
+        pad: &ZwpTabletPadV2,
+        event: <ZwpTabletPadV2 as Proxy>::Event,
+        _data: &GlobalData,
+        _conn: &Connection,
+        _qhandle: &QueueHandle<WinitState>,
+    ) {
+        // The pads aren't supported, only keep track of their lifetime.
+        if let zwp_tablet_pad_v2::Event::Removed = event {
+            pad.destroy();
+        }
+    }
+
+    event_created_child!(WinitState, ZwpTabletPadV2, [
+        zwp_tablet_pad_v2::EVT_GROUP_OPCODE => (ZwpTabletPadGroupV2, GlobalData),
+    ]);
+}
+
+impl Dispatch<ZwpTabletPadGroupV2, GlobalData, WinitState> for TabletManagerState {
+    fn event(
+        _state: &mut WinitState,
+        _proxy: &ZwpTabletPadGroupV2,
+        _event: <ZwpTabletPadGroupV2 as Proxy>::Event,
+        _data: &GlobalData,
+        _conn: &Connection,
+        _qhandle: &QueueHandle<WinitState>,
+    ) {
+    }
+
+    event_created_child!(WinitState, ZwpTabletPadGroupV2, [
+        zwp_tablet_pad_group_v2::EVT_RING_OPCODE => (ZwpTabletPadRingV2, GlobalData),
+        zwp_tablet_pad_group_v2::EVT_STRIP_OPCODE => (ZwpTabletPadStripV2, GlobalData),
+    ]);
+}
+
+impl Dispatch<ZwpTabletPadRingV2, GlobalData, WinitState> for TabletManagerState {
+    fn event(
+        _state: &mut WinitState,
+        _proxy: &ZwpTabletPadRingV2,
+        _event: <ZwpTabletPadRingV2 as Proxy>::Event,
+        _data: &GlobalData,
+        _conn: &Connection,
+        _qhandle: &QueueHandle<WinitState>,
+    ) {
+    }
+}
+
+impl Dispatch<ZwpTabletPadStripV2, GlobalData, WinitState> for TabletManagerState {
+    fn event(
+        _state: &mut WinitState,
+        _proxy: &ZwpTabletPadStripV2,
+        _event: <ZwpTabletPadStripV2 as Proxy>::Event,
+        _data: &GlobalData,
+        _conn: &Connection,
+        _qhandle: &QueueHandle<WinitState>,
+    ) {
+    }
+}
+
+delegate_dispatch!(WinitState: [ZwpTabletManagerV2: GlobalData] => TabletManagerState);
+delegate_dispatch!(WinitState: [ZwpTabletSeatV2: GlobalData] => TabletManagerState);
+delegate_dispatch!(WinitState: [ZwpTabletV2: GlobalData] => TabletManagerState);
+delegate_dispatch!(WinitState: [ZwpTabletToolV2: TabletToolData] => TabletManagerState);
+delegate_dispatch!(WinitState: [ZwpTabletPadV2: GlobalData] => TabletManagerState);
+delegate_dispatch!(WinitState: [ZwpTabletPadGroupV2: GlobalData] => TabletManagerState);
+delegate_dispatch!(WinitState: [ZwpTabletPadRingV2: GlobalData] => TabletManagerState);
+delegate_dispatch!(WinitState: [ZwpTabletPadStripV2: GlobalData] => TabletManagerState);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tool_kinds_and_buttons_are_mapped() {
+        assert_eq!(tool_kind(WEnum::Value(TabletToolType::Eraser)), TabletToolKind::Eraser);
+        assert_eq!(tool_kind(WEnum::Value(TabletToolType::Airbrush)), TabletToolKind::Airbrush);
+        assert_eq!(tool_kind(WEnum::Unknown(0x1337)), TabletToolKind::Unknown);
+
+        assert_eq!(tool_button(BTN_STYLUS), TabletToolButton::Primary);
+        assert_eq!(tool_button(BTN_STYLUS2), TabletToolButton::Secondary);
+        assert_eq!(tool_button(BTN_STYLUS3), TabletToolButton::Tertiary);
+        assert_eq!(tool_button(0x110), TabletToolButton::Other(0x110));
+
+        assert_eq!(axis_value(0), 0.);
+        assert_eq!(axis_value(65535), 1.);
+
+        assert_eq!(cursor_shape(CursorIcon::NwseResize), Shape::NwseResize);
+    }
+
+    #[test]
+    fn frame_events_are_ordered() {
+        let position = PhysicalPosition::new(4., 2.);
+        let axes = TabletToolAxes { pressure: Some(0.5), ..Default::default() };
+        let frame = TabletToolFrame {
+            proximity_in: true,
+            proximity_out: true,
+            motion: true,
+            tip: Some(ElementState::Pressed),
+            buttons: vec![(TabletToolButton::Primary, ElementState::Released)],
+        };
+        let expected = [
+            crate::event::TabletToolEvent::ProximityIn,
+            crate::event::TabletToolEvent::Motion { position, axes },
+            crate::event::TabletToolEvent::Tip(ElementState::Pressed),
+            crate::event::TabletToolEvent::Button {
+                button: TabletToolButton::Primary,
+                state: ElementState::Released,
+            },
+            crate::event::TabletToolEvent::ProximityOut,
+        ];
+        assert_eq!(frame.events(position, axes), expected);
+
+        assert!(TabletToolFrame::default().events(position, axes).is_empty());
+    }
+}
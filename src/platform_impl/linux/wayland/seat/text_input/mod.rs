@@ -74,6 +74,9 @@ impl Dispatch<ZwpTextInputV3, TextInputData, WinitState> for TextInputState {
                 if window.ime_allowed() {
                     text_input.enable();
                     text_input.set_content_type_by_purpose(window.ime_purpose());
+                    if let Some(surrounding_text) = window.ime_surrounding_text() {
+                        text_input.set_surrounding_text_trimmed(surrounding_text);
+                    }
                     text_input.commit();
                     state.events_sink.push_window_event(WindowEvent::Ime(Ime::Enabled), window_id);
                 }
@@ -115,14 +118,20 @@ impl Dispatch<ZwpTextInputV3, TextInputData, WinitState> for TextInputState {
                 text_input_data.pending_preedit = None;
                 text_input_data.pending_commit = text;
             },
+            TextInputEvent::DeleteSurroundingText { before_length, after_length } => {
+                text_input_data.pending_delete =
+                    Some((before_length as usize, after_length as usize));
+            },
             TextInputEvent::Done { .. } => {
                 let window_id = match text_input_data.surface.as_ref() {
                     Some(surface) => wayland::make_wid(surface),
                     None => return,
                 };
 
-                // Clear preedit, unless all we'll be doing next is sending a new preedit.
+                // Clear preedit, unless all we'll be doing next is sending a new preedit. It's
+                // cleared before the deletion, so the surrounding text doesn't include it.
                 if text_input_data.pending_commit.is_some()
+                    || text_input_data.pending_delete.is_some()
                     || text_input_data.pending_preedit.is_none()
                 {
                     state.events_sink.push_window_event(
@@ -131,6 +140,14 @@ impl Dispatch<ZwpTextInputV3, TextInputData, WinitState> for TextInputState {
                     );
                 }
 
+                // Send `DeleteSurrounding`.
+                if let Some((before_bytes, after_bytes)) = text_input_data.pending_delete.take() {
+                    state.events_sink.push_window_event(
+                        WindowEvent::Ime(Ime::DeleteSurrounding { before_bytes, after_bytes }),
+                        window_id,
+                    );
+                }
+
                 // Send `Commit`.
                 if let Some(text) = text_input_data.pending_commit.take() {
                     state
@@ -149,19 +166,30 @@ impl Dispatch<ZwpTextInputV3, TextInputData, WinitState> for TextInputState {
                     );
                 }
             },
-            TextInputEvent::DeleteSurroundingText { .. } => {
-                // Not handled.
-            },
             _ => {},
         }
     }
 }
 
+/// The maximum length of the surrounding text, in bytes.
+const MAX_SURROUNDING_TEXT_LEN: usize = 4000;
+
 pub trait ZwpTextInputV3Ext {
     fn set_content_type_by_purpose(&self, purpose: ImePurpose);
+
+    /// Set the surrounding text, trimmed around the selection to fit in the protocol.
+    fn set_surrounding_text_trimmed(&self, surrounding_text: &SurroundingText);
 }
 
 impl ZwpTextInputV3Ext for ZwpTextInputV3 {
+    fn set_surrounding_text_trimmed(&self, surrounding_text: &SurroundingText) {
+        let SurroundingText { text, cursor, anchor } = surrounding_text;
+        let (start, end) = trim_surrounding_text(text, *cursor, *anchor);
+        let cursor = cursor.clamp(&start, &end) - start;
+        let anchor = anchor.clamp(&start, &end) - start;
+        self.set_surrounding_text(text[start..end].to_owned(), cursor as i32, anchor as i32);
+    }
+
     fn set_content_type_by_purpose(&self, purpose: ImePurpose) {
         let (hint, purpose) = match purpose {
             ImePurpose::Normal => (ContentHint::None, ContentPurpose::Normal),
@@ -172,6 +200,41 @@ impl ZwpTextInputV3Ext for ZwpTextInputV3 {
     }
 }
 
+/// The range of the `text` to send as the surrounding text, centered on the selection.
+fn trim_surrounding_text(text: &str, cursor: usize, anchor: usize) -> (usize, usize) {
+    if text.len() <= MAX_SURROUNDING_TEXT_LEN {
+        return (0, text.len());
+    }
+
+    // Keep only the cursor when the selection doesn't fit.
+    let (mut start, mut end) = (cursor.min(anchor), cursor.max(anchor));
+    if end - start > MAX_SURROUNDING_TEXT_LEN {
+        (start, end) = (cursor, cursor);
+    }
+
+    // Share the room left between both sides, unless one of them reaches an end of the text.
+    let margin = (MAX_SURROUNDING_TEXT_LEN - (end - start)) / 2;
+    start = start.saturating_sub(margin).min(text.len() - MAX_SURROUNDING_TEXT_LEN);
+    end = start + MAX_SURROUNDING_TEXT_LEN;
+
+    while !text.is_char_boundary(start) {
+        start += 1;
+    }
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+
+    (start, end)
+}
+
+/// The text around the cursor, with the cursor and the anchor of the selection.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SurroundingText {
+    pub text: String,
+    pub cursor: usize,
+    pub anchor: usize,
+}
+
 /// The Data associated with the text input.
 #[derive(Default)]
 pub struct TextInputData {
@@ -188,6 +251,9 @@ pub struct TextInputDataInner {
 
     /// The preedit to submit on `done`.
     pending_preedit: Option<Preedit>,
+
+    /// The lengths of the text to delete before and after the cursor on `done`.
+    pending_delete: Option<(usize, usize)>,
 }
 
 /// The state of the preedit.
@@ -199,3 +265,32 @@ struct Preedit {
 
 delegate_dispatch!(WinitState: [ZwpTextInputManagerV3: GlobalData] => TextInputState);
 delegate_dispatch!(WinitState: [ZwpTextInputV3: TextInputData] => TextInputState);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn trim_surrounding_text_around_selection() {
+        let short = "hello";
+        assert_eq!(trim_surrounding_text(short, 2, 2), (0, 5));
+
+        // Centered on the selection.
+        let long = "a".repeat(10000);
+        assert_eq!(trim_surrounding_text(&long, 5000, 5000), (3000, 7000));
+        assert_eq!(trim_surrounding_text(&long, 5100, 4900), (3000, 7000));
+
+        // Shifted inside the text near its ends.
+        assert_eq!(trim_surrounding_text(&long, 100, 100), (0, 4000));
+        assert_eq!(trim_surrounding_text(&long, 9900, 9950), (6000, 10000));
+
+        // Only the cursor is kept when the selection doesn't fit.
+        assert_eq!(trim_surrounding_text(&long, 9000, 0), (6000, 10000));
+
+        // The range stays on the boundaries of the characters.
+        let wide = "é".repeat(5000);
+        let (start, end) = trim_surrounding_text(&wide, 5000, 5000);
+        assert!(wide.is_char_boundary(start) && wide.is_char_boundary(end));
+        assert!(start <= 5000 && end >= 5000 && end - start <= MAX_SURROUNDING_TEXT_LEN);
+    }
+}
@@ -9,8 +9,8 @@ use sctk::reexports::client::{Connection, Proxy, QueueHandle};
 
 use sctk::seat::touch::{TouchData, TouchHandler};
 
-use crate::dpi::LogicalPosition;
-use crate::event::{Touch, TouchPhase, WindowEvent};
+use crate::dpi::{LogicalPosition, LogicalSize};
+use crate::event::{Event, Touch, TouchContact, TouchPhase, WindowEvent};
 
 use crate::platform_impl::wayland::state::WinitState;
 use crate::platform_impl::wayland::{self, DeviceId};
@@ -43,7 +43,8 @@ impl TouchHandler for WinitState {
 
         // Update the state of the point.
         let location = LogicalPosition::<f64>::from(position);
-        seat_state.touch_map.insert(id, TouchPoint { surface, location });
+        let touch_point = TouchPoint { surface, location, shape: None, orientation: None };
+        seat_state.touch_map.insert(id, touch_point);
 
         self.events_sink.push_window_event(
             WindowEvent::Touch(Touch {
@@ -53,6 +54,7 @@ impl TouchHandler for WinitState {
                 phase: TouchPhase::Started,
                 location: location.to_physical(scale_factor),
                 force: None,
+                contact: None,
                 id: id as u64,
             }),
             window_id,
@@ -96,6 +98,7 @@ impl TouchHandler for WinitState {
                 phase: TouchPhase::Ended,
                 location: touch_point.location.to_physical(scale_factor),
                 force: None,
+                contact: touch_point.contact(scale_factor),
                 id: id as u64,
             }),
             window_id,
@@ -141,6 +144,7 @@ impl TouchHandler for WinitState {
                 phase: TouchPhase::Moved,
                 location: touch_point.location.to_physical(scale_factor),
                 force: None,
+                contact: touch_point.contact(scale_factor),
                 id: id as u64,
             }),
             window_id,
@@ -173,6 +177,7 @@ impl TouchHandler for WinitState {
                     phase: TouchPhase::Cancelled,
                     location,
                     force: None,
+                    contact: touch_point.contact(scale_factor),
                     id: id as u64,
                 }),
                 window_id,
@@ -184,16 +189,89 @@ impl TouchHandler for WinitState {
         &mut self,
         _: &Connection,
         _: &QueueHandle<Self>,
-        _: &WlTouch,
-        _: i32,
-        _: f64,
-        _: f64,
+        touch: &WlTouch,
+        id: i32,
+        major: f64,
+        minor: f64,
     ) {
-        // Blank.
+        self.update_touch_contact(touch, id, |touch_point| {
+            touch_point.shape = Some(LogicalSize::new(major, minor))
+        });
     }
 
-    fn orientation(&mut self, _: &Connection, _: &QueueHandle<Self>, _: &WlTouch, _: i32, _: f64) {
-        // Blank.
+    fn orientation(
+        &mut self,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+        touch: &WlTouch,
+        id: i32,
+        orientation: f64,
+    ) {
+        self.update_touch_contact(touch, id, |touch_point| {
+            touch_point.orientation = Some(orientation)
+        });
+    }
+}
+
+impl WinitState {
+    /// Update the contact of the touch point from the `shape` and `orientation` events.
+    fn update_touch_contact(
+        &mut self,
+        touch: &WlTouch,
+        id: i32,
+        update: impl FnOnce(&mut TouchPoint),
+    ) {
+        let seat_state = match self.seats.get_mut(&touch.seat().id()) {
+            Some(seat_state) => seat_state,
+            None => {
+                warn!("Received wl_touch shape without seat");
+                return;
+            },
+        };
+
+        let touch_point = match seat_state.touch_map.get_mut(&id) {
+            Some(touch_point) => touch_point,
+            None => return,
+        };
+
+        let window_id = wayland::make_wid(&touch_point.surface);
+        let scale_factor = match self.windows.get_mut().get(&window_id) {
+            Some(window) => window.lock().unwrap().scale_factor(),
+            None => return,
+        };
+
+        update(touch_point);
+        let contact = touch_point.contact(scale_factor);
+
+        // The shape comes after the `down` or `motion` of the same frame, amend their event.
+        let root_window_id = crate::window::WindowId(window_id);
+        let pending =
+            self.events_sink.window_events.iter_mut().rev().find_map(|event| match event {
+                Event::WindowEvent { window_id, event: WindowEvent::Touch(event) }
+                    if *window_id == root_window_id && event.id == id as u64 =>
+                {
+                    Some(event)
+                },
+                _ => None,
+            });
+        if let Some(event) = pending {
+            event.contact = contact;
+            return;
+        }
+
+        self.events_sink.push_window_event(
+            WindowEvent::Touch(Touch {
+                device_id: crate::event::DeviceId(crate::platform_impl::DeviceId::Wayland(
+                    DeviceId,
+                )),
+                phase: TouchPhase::Moved,
+                location: touch_point.location.to_physical(scale_factor),
+                force: None,
+                contact,
+                id: id as u64,
+            }),
+            window_id,
+        );
     }
 }
 
@@ -205,6 +283,23 @@ pub struct TouchPoint {
 
     /// The location of the point on the surface.
     pub location: LogicalPosition<f64>,
+
+    /// The major and minor axes of the contact ellipse on the surface.
+    pub shape: Option<LogicalSize<f64>>,
+
+    /// The orientation of the contact ellipse, in degrees.
+    pub orientation: Option<f64>,
+}
+
+impl TouchPoint {
+    fn contact(&self, scale_factor: f64) -> Option<TouchContact> {
+        let shape = self.shape?.to_physical::<f64>(scale_factor);
+        Some(TouchContact {
+            major: shape.width,
+            minor: shape.height,
+            orientation: self.orientation,
+        })
+    }
 }
 
 pub trait TouchDataExt {
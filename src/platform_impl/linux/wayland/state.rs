@@ -1,10 +1,11 @@
 use std::cell::RefCell;
 use std::sync::atomic::Ordering;
 use std::sync::{Arc, Mutex};
+use std::time::Instant;
 
 use ahash::AHashMap;
 
-use sctk::reexports::calloop::LoopHandle;
+use sctk::reexports::calloop::{LoopHandle, RegistrationToken};
 use sctk::reexports::client::backend::ObjectId;
 use sctk::reexports::client::globals::GlobalList;
 use sctk::reexports::client::protocol::wl_output::WlOutput;
@@ -12,10 +13,14 @@ use sctk::reexports::client::protocol::wl_surface::WlSurface;
 use sctk::reexports::client::{Connection, Proxy, QueueHandle};
 
 use sctk::compositor::{CompositorHandler, CompositorState};
+use sctk::data_device_manager::DataDeviceManagerState;
 use sctk::output::{OutputHandler, OutputState};
+use sctk::primary_selection::PrimarySelectionManagerState;
 use sctk::registry::{ProvidesRegistryState, RegistryState};
 use sctk::seat::pointer::ThemedPointer;
 use sctk::seat::SeatState;
+use sctk::shell::wlr_layer::{LayerShell, LayerShellHandler, LayerSurface, LayerSurfaceConfigure};
+use sctk::shell::xdg::popup::{Popup, PopupConfigure, PopupHandler};
 use sctk::shell::xdg::window::{Window, WindowConfigure, WindowHandler};
 use sctk::shell::xdg::XdgShell;
 use sctk::shell::WaylandSurface;
@@ -23,16 +28,19 @@ use sctk::shm::slot::SlotPool;
 use sctk::shm::{Shm, ShmHandler};
 use sctk::subcompositor::SubcompositorState;
 
+use crate::event_loop::{SourceToken, TimerId};
 use crate::platform_impl::wayland::event_loop::sink::EventSink;
 use crate::platform_impl::wayland::output::MonitorHandle;
 use crate::platform_impl::wayland::seat::{
-    PointerConstraintsState, RelativePointerState, TextInputState, WinitPointerData,
-    WinitPointerDataExt, WinitSeatState,
+    DndOffer, DndSource, PointerConstraintsState, PointerGesturesState, RelativePointerState,
+    SelectionSources, TabletManagerState, TextInputState, WinitPointerData, WinitPointerDataExt,
+    WinitSeatState,
 };
 use crate::platform_impl::wayland::types::kwin_blur::KWinBlurManager;
 use crate::platform_impl::wayland::types::wp_fractional_scaling::FractionalScalingManager;
 use crate::platform_impl::wayland::types::wp_viewporter::ViewporterState;
 use crate::platform_impl::wayland::types::xdg_activation::XdgActivationState;
+use crate::platform_impl::wayland::types::xdg_dialog::XdgDialogManager;
 use crate::platform_impl::wayland::window::{WindowRequests, WindowState};
 use crate::platform_impl::wayland::{WaylandError, WindowId};
 use crate::platform_impl::OsError;
@@ -63,6 +71,9 @@ pub struct WinitState {
     /// The XDG shell that is used for windows.
     pub xdg_shell: XdgShell,
 
+    /// The layer shell that is used for panels, docks, and overlays.
+    pub layer_shell: Option<LayerShell>,
+
     /// The currently present windows.
     pub windows: RefCell<AHashMap<WindowId, Arc<Mutex<WindowState>>>>,
 
@@ -75,6 +86,9 @@ pub struct WinitState {
     /// The update for the `windows` coming from the compositor.
     pub window_compositor_updates: Vec<WindowCompositorUpdate>,
 
+    /// The popups holding the grab, the last one is the topmost.
+    pub popup_grabs: Vec<WindowId>,
+
     /// Currently handled seats.
     pub seats: AHashMap<ObjectId, WinitSeatState>,
 
@@ -94,9 +108,30 @@ pub struct WinitState {
     /// Xdg activation.
     pub xdg_activation: Option<XdgActivationState>,
 
+    /// Data device manager to handle the clipboard.
+    pub data_device_manager: Option<DataDeviceManagerState>,
+
+    /// Primary selection manager.
+    pub primary_selection_manager: Option<PrimarySelectionManagerState>,
+
+    /// The selections offered by the client.
+    pub selection_sources: SelectionSources,
+
+    /// The drag and drop offer hovering one of the windows.
+    pub dnd_offer: Option<DndOffer>,
+
+    /// The drag started by one of our windows.
+    pub dnd_source: Option<DndSource>,
+
     /// Relative pointer.
     pub relative_pointer: Option<RelativePointerState>,
 
+    /// Pointer gestures, to receive the touchpad gestures.
+    pub pointer_gestures: Option<PointerGesturesState>,
+
+    /// Tablet manager, to receive the events of the tablet tools when they're requested.
+    pub tablet_manager: Option<TabletManagerState>,
+
     /// Pointer constraints to handle pointer locking and confining.
     pub pointer_constraints: Option<Arc<PointerConstraintsState>>,
 
@@ -109,9 +144,18 @@ pub struct WinitState {
     /// KWin blur manager.
     pub kwin_blur_manager: Option<KWinBlurManager>,
 
+    /// XDG dialog manager.
+    pub xdg_dialog_manager: Option<XdgDialogManager>,
+
     /// Loop handle to re-register event sources, such as keyboard repeat.
     pub loop_handle: LoopHandle<'static, Self>,
 
+    /// The timers scheduled by the user, along with their next deadlines.
+    pub timers: AHashMap<TimerId, (RegistrationToken, Instant)>,
+
+    /// The file descriptors registered by the user.
+    pub fd_sources: AHashMap<SourceToken, RegistrationToken>,
+
     /// Whether we have dispatched events to the user thus we want to
     /// send `AboutToWait` and normally wakeup the user.
     pub dispatched_events: bool,
@@ -122,6 +166,7 @@ impl WinitState {
         globals: &GlobalList,
         queue_handle: &QueueHandle<Self>,
         loop_handle: LoopHandle<'static, WinitState>,
+        tablet_tools: bool,
     ) -> Result<Self, OsError> {
         let registry_state = RegistryState::new(globals);
         let compositor_state =
@@ -143,9 +188,24 @@ impl WinitState {
 
         let seat_state = SeatState::new(globals, queue_handle);
 
+        let data_device_manager = DataDeviceManagerState::bind(globals, queue_handle).ok();
+        let primary_selection_manager =
+            PrimarySelectionManagerState::bind(globals, queue_handle).ok();
+        // Binding the tablet seats stops the pointer emulation of the tablet tools.
+        let tablet_manager =
+            tablet_tools.then(|| TabletManagerState::new(globals, queue_handle).ok()).flatten();
+
         let mut seats = AHashMap::default();
         for seat in seat_state.seats() {
-            seats.insert(seat.id(), WinitSeatState::new());
+            let mut winit_seat = WinitSeatState::new();
+            winit_seat.bind_data_devices(
+                &seat,
+                queue_handle,
+                data_device_manager.as_ref(),
+                primary_selection_manager.as_ref(),
+            );
+            winit_seat.bind_tablet_seat(&seat, queue_handle, tablet_manager.as_ref());
+            seats.insert(seat.id(), winit_seat);
         }
 
         let (viewporter_state, fractional_scaling_manager) =
@@ -168,20 +228,30 @@ impl WinitState {
             custom_cursor_pool,
 
             xdg_shell: XdgShell::bind(globals, queue_handle).map_err(WaylandError::Bind)?,
+            layer_shell: LayerShell::bind(globals, queue_handle).ok(),
             xdg_activation: XdgActivationState::bind(globals, queue_handle).ok(),
+            data_device_manager,
+            primary_selection_manager,
+            selection_sources: Default::default(),
+            dnd_offer: None,
+            dnd_source: None,
 
             windows: Default::default(),
             window_requests: Default::default(),
             window_compositor_updates: Vec::new(),
+            popup_grabs: Vec::new(),
             window_events_sink: Default::default(),
             viewporter_state,
             fractional_scaling_manager,
             kwin_blur_manager: KWinBlurManager::new(globals, queue_handle).ok(),
+            xdg_dialog_manager: XdgDialogManager::new(globals, queue_handle).ok(),
 
             seats,
             text_input_state: TextInputState::new(globals, queue_handle).ok(),
 
             relative_pointer: RelativePointerState::new(globals, queue_handle).ok(),
+            pointer_gestures: PointerGesturesState::new(globals, queue_handle).ok(),
+            tablet_manager,
             pointer_constraints: PointerConstraintsState::new(globals, queue_handle)
                 .map(Arc::new)
                 .ok(),
@@ -190,6 +260,8 @@ impl WinitState {
             monitors: Arc::new(Mutex::new(monitors)),
             events_sink: EventSink::new(),
             loop_handle,
+            timers: Default::default(),
+            fd_sources: Default::default(),
             // Make it true by default.
             dispatched_events: true,
         })
@@ -239,6 +311,18 @@ impl WinitState {
     }
 
     pub fn queue_close(updates: &mut Vec<WindowCompositorUpdate>, window_id: WindowId) {
+        Self::compositor_update(updates, window_id).close_window = true;
+    }
+
+    pub fn queue_popup_dismissed(updates: &mut Vec<WindowCompositorUpdate>, window_id: WindowId) {
+        Self::compositor_update(updates, window_id).popup_dismissed = true;
+    }
+
+    /// Get the pending update of the given window.
+    fn compositor_update(
+        updates: &mut Vec<WindowCompositorUpdate>,
+        window_id: WindowId,
+    ) -> &mut WindowCompositorUpdate {
         let pos = if let Some(pos) = updates.iter().position(|update| update.window_id == window_id)
         {
             pos
@@ -247,7 +331,22 @@ impl WinitState {
             updates.len() - 1
         };
 
-        updates[pos].close_window = true;
+        &mut updates[pos]
+    }
+
+    /// Ask the window to redraw after its configure.
+    fn configured(&mut self, window_id: WindowId) {
+        // NOTE: configure demands wl_surface::commit, however winit doesn't commit on behalf of the
+        // users, since it can break a lot of things, thus it'll ask users to redraw instead.
+        self.window_requests
+            .get_mut()
+            .get(&window_id)
+            .unwrap()
+            .redraw_requested
+            .store(true, Ordering::Relaxed);
+
+        // Manually mark that we've got an event, since configure may not generate a resize.
+        self.dispatched_events = true;
     }
 }
 
@@ -273,17 +372,8 @@ impl WindowHandler for WinitState {
     ) {
         let window_id = super::make_wid(window.wl_surface());
 
-        let pos = if let Some(pos) =
-            self.window_compositor_updates.iter().position(|update| update.window_id == window_id)
-        {
-            pos
-        } else {
-            self.window_compositor_updates.push(WindowCompositorUpdate::new(window_id));
-            self.window_compositor_updates.len() - 1
-        };
-
         // Populate the configure to the window.
-        self.window_compositor_updates[pos].resized |= self
+        Self::compositor_update(&mut self.window_compositor_updates, window_id).resized |= self
             .windows
             .get_mut()
             .get_mut(&window_id)
@@ -292,17 +382,71 @@ impl WindowHandler for WinitState {
             .unwrap()
             .configure(configure, &self.shm, &self.subcompositor_state);
 
-        // NOTE: configure demands wl_surface::commit, however winit doesn't commit on behalf of the
-        // users, since it can break a lot of things, thus it'll ask users to redraw instead.
-        self.window_requests
+        self.configured(window_id);
+    }
+}
+
+impl PopupHandler for WinitState {
+    fn configure(
+        &mut self,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+        popup: &Popup,
+        configure: PopupConfigure,
+    ) {
+        let window_id = super::make_wid(popup.wl_surface());
+
+        // Populate the configure to the popup.
+        Self::compositor_update(&mut self.window_compositor_updates, window_id).resized |= self
+            .windows
             .get_mut()
-            .get(&window_id)
+            .get_mut(&window_id)
+            .expect("got configure for dead popup.")
+            .lock()
             .unwrap()
-            .redraw_requested
-            .store(true, Ordering::Relaxed);
+            .configure_popup(configure);
 
-        // Manually mark that we've got an event, since configure may not generate a resize.
-        self.dispatched_events = true;
+        self.configured(window_id);
+    }
+
+    fn done(&mut self, _: &Connection, _: &QueueHandle<Self>, popup: &Popup) {
+        let window_id = super::make_wid(popup.wl_surface());
+        Self::queue_popup_dismissed(&mut self.window_compositor_updates, window_id);
+
+        // The popups above the dismissed one are dismissed along with it.
+        if let Some(index) = self.popup_grabs.iter().position(|&id| id == window_id) {
+            self.popup_grabs.truncate(index);
+        }
+    }
+}
+
+impl LayerShellHandler for WinitState {
+    fn closed(&mut self, _: &Connection, _: &QueueHandle<Self>, layer: &LayerSurface) {
+        let window_id = super::make_wid(layer.wl_surface());
+        Self::queue_close(&mut self.window_compositor_updates, window_id);
+    }
+
+    fn configure(
+        &mut self,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+        layer: &LayerSurface,
+        configure: LayerSurfaceConfigure,
+        _serial: u32,
+    ) {
+        let window_id = super::make_wid(layer.wl_surface());
+
+        // Populate the configure to the layer surface.
+        Self::compositor_update(&mut self.window_compositor_updates, window_id).resized |= self
+            .windows
+            .get_mut()
+            .get_mut(&window_id)
+            .expect("got configure for dead layer surface.")
+            .lock()
+            .unwrap()
+            .configure_layer(configure);
+
+        self.configured(window_id);
     }
 }
 
@@ -418,11 +562,20 @@ pub struct WindowCompositorUpdate {
 
     /// Close the window.
     pub close_window: bool,
+
+    /// The popup was dismissed.
+    pub popup_dismissed: bool,
 }
 
 impl WindowCompositorUpdate {
     fn new(window_id: WindowId) -> Self {
-        Self { window_id, resized: false, scale_changed: false, close_window: false }
+        Self {
+            window_id,
+            resized: false,
+            scale_changed: false,
+            close_window: false,
+            popup_dismissed: false,
+        }
     }
 }
 
@@ -433,3 +586,5 @@ sctk::delegate_registry!(WinitState);
 sctk::delegate_shm!(WinitState);
 sctk::delegate_xdg_shell!(WinitState);
 sctk::delegate_xdg_window!(WinitState);
+sctk::delegate_xdg_popup!(WinitState);
+sctk::delegate_layer!(WinitState);
@@ -9,37 +9,47 @@ use sctk::reexports::client::{Proxy, QueueHandle};
 
 use sctk::compositor::{CompositorState, Region, SurfaceData};
 use sctk::reexports::protocols::xdg::activation::v1::client::xdg_activation_v1::XdgActivationV1;
-use sctk::shell::xdg::window::{Window as SctkWindow, WindowDecorations};
+use sctk::shell::wlr_layer::{LayerShell, LayerSurface, SurfaceKind};
+use sctk::shell::xdg::popup::Popup;
+use sctk::shell::xdg::window::WindowDecorations;
+use sctk::shell::xdg::XdgPositioner;
 use sctk::shell::WaylandSurface;
 
 use tracing::warn;
 
-use crate::dpi::{LogicalSize, PhysicalPosition, PhysicalSize, Position, Size};
+use crate::clipboard::{MimeData, Selection};
+use crate::dnd::{DndActions, DragData};
+use crate::dpi::{LogicalPosition, LogicalSize, PhysicalPosition, PhysicalSize, Position, Size};
 use crate::error::{ExternalError, NotSupportedError, OsError as RootOsError};
 use crate::event::{Ime, WindowEvent};
 use crate::event_loop::AsyncRequestSerial;
+use crate::platform::layer_shell::{KeyboardInteractivity, Layer, LayerAnchor};
 use crate::platform_impl::{
-    Fullscreen, MonitorHandle as PlatformMonitorHandle, OsError, PlatformIcon,
+    DropResponse, Fullscreen, LayerShellAttributes, MonitorHandle as PlatformMonitorHandle,
+    OsError, PlatformIcon,
 };
 use crate::window::{
-    Cursor, CursorGrabMode, ImePurpose, ResizeDirection, Theme, UserAttentionType,
+    Cursor, CursorGrabMode, ImePurpose, PopupAttributes, ResizeDirection, Theme, UserAttentionType,
     WindowAttributes, WindowButtons, WindowLevel,
 };
 
 use super::event_loop::sink::EventSink;
 use super::output::MonitorHandle;
+use super::seat::{DndRequest, SelectionRequest, SurroundingText};
 use super::state::WinitState;
 use super::types::xdg_activation::XdgActivationTokenData;
 use super::{ActiveEventLoop, WaylandError, WindowId};
 
+mod shell;
 pub(crate) mod state;
 
+pub use shell::ShellSurface;
 pub use state::WindowState;
 
 /// The Wayland window.
 pub struct Window {
-    /// Reference to the underlying SCTK window.
-    window: SctkWindow,
+    /// Reference to the underlying SCTK window or popup.
+    window: ShellSurface,
 
     /// Window id.
     window_id: WindowId,
@@ -74,6 +84,18 @@ pub struct Window {
 
     /// The event sink to deliver synthetic events.
     window_events_sink: Arc<Mutex<EventSink>>,
+
+    /// Sender of the selection requests to the event loop.
+    selection_sender: calloop::channel::Sender<SelectionRequest>,
+
+    /// Whether the data device, used by the clipboard and drag and drop, is available.
+    has_data_device: bool,
+
+    /// Whether the primary selection is available.
+    has_primary_selection: bool,
+
+    /// Sender of the drag and drop requests to the event loop.
+    dnd_sender: calloop::channel::Sender<DndRequest>,
 }
 
 impl Window {
@@ -90,6 +112,8 @@ impl Window {
         let compositor = state.compositor_state.clone();
         let xdg_activation =
             state.xdg_activation.as_ref().map(|activation_state| activation_state.global().clone());
+        let has_data_device = state.data_device_manager.is_some();
+        let has_primary_selection = state.primary_selection_manager.is_some();
         let display = event_loop_window_target.connection.display();
 
         let size: Size = attributes.inner_size.unwrap_or(LogicalSize::new(800., 600.).into());
@@ -102,8 +126,38 @@ impl Window {
             WindowDecorations::RequestClient
         };
 
-        let window =
-            state.xdg_shell.create_window(surface.clone(), default_decorations, &queue_handle);
+        let layer_shell = attributes.platform_specific.layer_shell;
+        let window = match (attributes.popup.as_ref(), layer_shell, state.layer_shell.as_ref()) {
+            (Some(popup), ..) => {
+                let grab = attributes.active;
+                let popup =
+                    Self::create_popup(&mut state, popup, size, &surface, &queue_handle, grab)?;
+                ShellSurface::Popup(popup)
+            },
+            (None, Some(layer_attributes), Some(layer_shell)) => {
+                let namespace =
+                    attributes.platform_specific.name.as_ref().map(|name| &name.general);
+                ShellSurface::Layer(Self::create_layer_surface(
+                    layer_shell,
+                    &layer_attributes,
+                    namespace,
+                    size,
+                    &surface,
+                    &queue_handle,
+                ))
+            },
+            (None, layer_shell, _) => {
+                if layer_shell.is_some() {
+                    warn!("Layer shell unavailable, creating a regular window instead");
+                }
+
+                ShellSurface::Toplevel(state.xdg_shell.create_window(
+                    surface.clone(),
+                    default_decorations,
+                    &queue_handle,
+                ))
+            },
+        };
 
         let mut window_state = WindowState::new(
             event_loop_window_target.connection.clone(),
@@ -123,13 +177,22 @@ impl Window {
         window_state.set_decorate(attributes.decorations);
 
         // Set the app_id.
-        if let Some(name) = attributes.platform_specific.name.map(|name| name.general) {
-            window.set_app_id(name);
+        let name = attributes.platform_specific.name.map(|name| name.general);
+        if let Some((name, toplevel)) = name.zip(window.toplevel()) {
+            toplevel.set_app_id(name);
         }
 
         // Set the window title.
         window_state.set_title(attributes.title);
 
+        // Set the owner window.
+        let owner = attributes.owner.and_then(|owner| state.windows.get_mut().get(&owner.0));
+        if let Some((owner, toplevel)) = owner.zip(window.toplevel()) {
+            if let Some(owner) = owner.lock().unwrap().window.toplevel() {
+                toplevel.set_parent(Some(owner));
+            }
+        }
+
         // Set the min and max sizes. We must set the hints upon creating a window, so
         // we use the default `1.` scaling...
         let min_size = attributes.min_inner_size.map(|size| size.to_logical(1.));
@@ -141,21 +204,21 @@ impl Window {
         window_state.set_resizable(attributes.resizable);
 
         // Set startup mode.
-        match attributes.fullscreen.map(Into::into) {
-            Some(Fullscreen::Exclusive(_)) => {
+        match (attributes.fullscreen.map(Into::into), window.toplevel()) {
+            (_, None) => (),
+            (Some(Fullscreen::Exclusive(_)), _) => {
                 warn!("`Fullscreen::Exclusive` is ignored on Wayland");
             },
             #[cfg_attr(not(x11_platform), allow(clippy::bind_instead_of_map))]
-            Some(Fullscreen::Borderless(monitor)) => {
+            (Some(Fullscreen::Borderless(monitor)), Some(window)) => {
                 let output = monitor.and_then(|monitor| match monitor {
                     PlatformMonitorHandle::Wayland(monitor) => Some(monitor.proxy),
-                    #[cfg(x11_platform)]
-                    PlatformMonitorHandle::X(_) => None,
+                    _ => None,
                 });
 
                 window.set_fullscreen(output.as_ref())
             },
-            _ if attributes.maximized => window.set_maximized(),
+            (_, Some(window)) if attributes.maximized => window.set_maximized(),
             _ => (),
         };
 
@@ -221,8 +284,149 @@ impl Window {
             event_loop_awakener,
             window_requests,
             window_events_sink,
+            selection_sender: event_loop_window_target.selection_sender.clone(),
+            dnd_sender: event_loop_window_target.dnd_sender.clone(),
+            has_data_device,
+            has_primary_selection,
         })
     }
+
+    /// Create the popup placed relative to its parent window.
+    fn create_popup(
+        state: &mut WinitState,
+        attributes: &PopupAttributes,
+        size: Size,
+        surface: &WlSurface,
+        queue_handle: &QueueHandle<WinitState>,
+        grab: bool,
+    ) -> Result<Popup, RootOsError> {
+        let parent_id = attributes.parent.0;
+        let parent = match state.windows.get_mut().get(&parent_id) {
+            Some(parent) => parent.clone(),
+            None => return Err(os_error!(OsError::Misc("the parent of the popup was not found"))),
+        };
+        let parent = parent.lock().unwrap();
+
+        // The grabbing popups must be stacked, each on top of the previous one.
+        if grab {
+            let windows = state.windows.get_mut();
+            state.popup_grabs.retain(|window_id| windows.contains_key(window_id));
+            match state.popup_grabs.last() {
+                Some(&topmost) if topmost != parent_id => {
+                    let msg = "the parent of the grabbing popup isn't the topmost grabbing popup";
+                    return Err(os_error!(OsError::Misc(msg)));
+                },
+                None if matches!(parent.window, ShellSurface::Popup(_)) => {
+                    let msg = "the parent popup of the grabbing popup doesn't hold the grab";
+                    return Err(os_error!(OsError::Misc(msg)));
+                },
+                _ => (),
+            }
+        }
+
+        // The positioner works in the surface local coordinates of the parent.
+        let scale_factor = parent.scale_factor();
+        let size: LogicalSize<i32> = size.to_logical(scale_factor);
+        let rect_position: LogicalPosition<i32> = attributes.anchor_rect.0.to_logical(scale_factor);
+        let rect_size: LogicalSize<i32> = attributes.anchor_rect.1.to_logical(scale_factor);
+
+        // The xdg shell is bound on startup, thus it can't go missing.
+        let map_err = |_| os_error!(OsError::Misc("failed to create the popup"));
+        let positioner = XdgPositioner::new(&state.xdg_shell).map_err(map_err)?;
+        positioner.set_size(size.width.max(1), size.height.max(1));
+        positioner.set_anchor_rect(
+            rect_position.x,
+            rect_position.y,
+            rect_size.width.max(1),
+            rect_size.height.max(1),
+        );
+        positioner.set_anchor(attributes.anchor.into());
+        positioner.set_gravity(attributes.gravity.into());
+        positioner.set_constraint_adjustment(attributes.constraint_adjustment.into());
+
+        let popup = Popup::from_surface(
+            parent.window.xdg_surface(),
+            &positioner,
+            queue_handle,
+            surface.clone(),
+            &state.xdg_shell,
+        )
+        .map_err(map_err)?;
+
+        // The popups of the layer surfaces are parented through the layer shell.
+        if let ShellSurface::Layer(layer) = &parent.window {
+            layer.get_popup(popup.xdg_popup());
+        }
+
+        // The grab must be taken before the initial commit.
+        if grab && parent.grab_popup(popup.xdg_popup()) {
+            state.popup_grabs.push(super::make_wid(surface));
+        }
+
+        Ok(popup)
+    }
+
+    /// Create the layer surface placed by the compositor.
+    fn create_layer_surface(
+        layer_shell: &LayerShell,
+        attributes: &LayerShellAttributes,
+        namespace: Option<&String>,
+        size: Size,
+        surface: &WlSurface,
+        queue_handle: &QueueHandle<WinitState>,
+    ) -> LayerSurface {
+        let layer = layer_shell.create_layer_surface(
+            queue_handle,
+            surface.clone(),
+            attributes.layer.into(),
+            namespace,
+            None,
+        );
+
+        // The size must be set before the initial commit, thus use the default `1.` scaling. The
+        // axes stretched between the opposite edges are sized by the compositor.
+        let size: LogicalSize<u32> = size.to_logical(1.);
+        let anchor = attributes.anchor;
+        let stretched = |edges| anchor.contains(edges);
+        let width = if stretched(LayerAnchor::LEFT | LayerAnchor::RIGHT) { 0 } else { size.width };
+        let height =
+            if stretched(LayerAnchor::TOP | LayerAnchor::BOTTOM) { 0 } else { size.height };
+        layer.set_size(width, height);
+
+        let margins = attributes.margins;
+        layer.set_anchor(anchor.into());
+        layer.set_exclusive_zone(attributes.exclusive_zone);
+        layer.set_keyboard_interactivity(Self::keyboard_interactivity(&layer, attributes).into());
+        layer.set_margin(margins.top, margins.right, margins.bottom, margins.left);
+
+        layer
+    }
+
+    /// The keyboard interactivity supported by the layer shell, the focus on demand requires the
+    /// version 4 and falls back to the exclusive focus above the regular windows.
+    fn keyboard_interactivity(
+        layer: &LayerSurface,
+        attributes: &LayerShellAttributes,
+    ) -> KeyboardInteractivity {
+        let version = match layer.kind() {
+            SurfaceKind::Wlr(wlr_layer) => wlr_layer.version(),
+            _ => 4,
+        };
+        match attributes.keyboard_interactivity {
+            KeyboardInteractivity::OnDemand if version < 4 => {
+                let fallback = match attributes.layer {
+                    Layer::Top | Layer::Overlay => KeyboardInteractivity::Exclusive,
+                    Layer::Background | Layer::Bottom => KeyboardInteractivity::None,
+                };
+                warn!(
+                    "The on demand keyboard interactivity requires zwlr_layer_shell_v1 version 4, \
+                     using {fallback:?} instead"
+                );
+                fallback
+            },
+            keyboard_interactivity => keyboard_interactivity,
+        }
+    }
 }
 
 impl Window {
@@ -346,8 +550,10 @@ impl Window {
     }
 
     #[inline]
-    pub fn set_modal(&self, _owner_window: &Self) -> Result<(), RootOsError> {
-        //TODO support modal
+    pub fn set_modal(&self, owner_window: &Self) -> Result<(), RootOsError> {
+        if let Some(owner_window) = owner_window.window.toplevel() {
+            self.window_state.lock().unwrap().set_modal(owner_window);
+        }
         Ok(())
     }
 
@@ -403,6 +609,13 @@ impl Window {
         self.window_state.lock().unwrap().scale_factor()
     }
 
+    #[inline]
+    pub fn set_owner(&self, owner_window: Option<&Self>) {
+        if let Some(window) = self.window.toplevel() {
+            window.set_parent(owner_window.and_then(|owner_window| owner_window.window.toplevel()));
+        }
+    }
+
     #[inline]
     pub fn set_blur(&self, blur: bool) {
         self.window_state.lock().unwrap().set_blur(blur);
@@ -432,7 +645,9 @@ impl Window {
             return;
         }
 
-        self.window.set_minimized();
+        if let Some(window) = self.window.toplevel() {
+            window.set_minimized();
+        }
     }
 
     #[inline]
@@ -448,10 +663,15 @@ impl Window {
 
     #[inline]
     pub fn set_maximized(&self, maximized: bool) {
+        let window = match self.window.toplevel() {
+            Some(window) => window,
+            None => return,
+        };
+
         if maximized {
-            self.window.set_maximized()
+            window.set_maximized()
         } else {
-            self.window.unset_maximized()
+            window.unset_maximized()
         }
     }
 
@@ -476,6 +696,11 @@ impl Window {
 
     #[inline]
     pub(crate) fn set_fullscreen(&self, fullscreen: Option<Fullscreen>) {
+        let window = match self.window.toplevel() {
+            Some(window) => window,
+            None => return,
+        };
+
         match fullscreen {
             Some(Fullscreen::Exclusive(_)) => {
                 warn!("`Fullscreen::Exclusive` is ignored on Wayland");
@@ -484,13 +709,12 @@ impl Window {
             Some(Fullscreen::Borderless(monitor)) => {
                 let output = monitor.and_then(|monitor| match monitor {
                     PlatformMonitorHandle::Wayland(monitor) => Some(monitor.proxy),
-                    #[cfg(x11_platform)]
-                    PlatformMonitorHandle::X(_) => None,
+                    _ => None,
                 });
 
-                self.window.set_fullscreen(output.as_ref())
+                window.set_fullscreen(output.as_ref())
             },
-            None => self.window.unset_fullscreen(),
+            None => window.unset_fullscreen(),
         }
     }
 
@@ -551,6 +775,71 @@ impl Window {
         Ok(serial)
     }
 
+    pub fn set_clipboard(
+        &self,
+        selection: Selection,
+        data: Vec<MimeData>,
+    ) -> Result<(), NotSupportedError> {
+        if !self.has_selection(selection) {
+            return Err(NotSupportedError::new());
+        }
+
+        let request = SelectionRequest::Set { window_id: self.window_id, selection, data };
+        self.selection_sender.send(request).expect("selection channel should never be closed");
+        Ok(())
+    }
+
+    pub fn request_clipboard(
+        &self,
+        selection: Selection,
+        mime_types: Vec<String>,
+    ) -> Result<AsyncRequestSerial, NotSupportedError> {
+        if !self.has_selection(selection) {
+            return Err(NotSupportedError::new());
+        }
+
+        let serial = AsyncRequestSerial::get();
+        let request =
+            SelectionRequest::Get { window_id: self.window_id, selection, mime_types, serial };
+        self.selection_sender.send(request).expect("selection channel should never be closed");
+        Ok(serial)
+    }
+
+    pub fn set_drop_response(&self, response: DropResponse) -> Result<(), NotSupportedError> {
+        if !self.has_data_device {
+            return Err(NotSupportedError::new());
+        }
+
+        let request = DndRequest::Respond { window_id: self.window_id, response };
+        self.dnd_sender.send(request).expect("drag and drop channel should never be closed");
+        Ok(())
+    }
+
+    pub fn start_drag(
+        &self,
+        data: DragData,
+        allowed_actions: DndActions,
+    ) -> Result<(), NotSupportedError> {
+        if !self.has_data_device {
+            return Err(NotSupportedError::new());
+        }
+
+        let request = DndRequest::StartDrag {
+            window_id: self.window_id,
+            data: data.items,
+            actions: allowed_actions,
+        };
+        self.dnd_sender.send(request).expect("drag and drop channel should never be closed");
+        Ok(())
+    }
+
+    fn has_selection(&self, selection: Selection) -> bool {
+        match selection {
+            Selection::Clipboard => self.has_data_device,
+            Selection::Primary => self.has_primary_selection,
+        }
+    }
+
     #[inline]
     pub fn set_cursor_grab(&self, mode: CursorGrabMode) -> Result<(), ExternalError> {
         self.window_state.lock().unwrap().set_cursor_grab(mode)
@@ -617,6 +906,12 @@ impl Window {
         self.window_state.lock().unwrap().set_ime_purpose(purpose);
     }
 
+    #[inline]
+    pub fn set_ime_surrounding_text(&self, text: String, cursor: usize, anchor: usize) {
+        let surrounding_text = SurroundingText { text, cursor, anchor };
+        self.window_state.lock().unwrap().set_ime_surrounding_text(surrounding_text);
+    }
+
     #[inline]
     pub fn commit_ime(&self) {}
 
@@ -0,0 +1,54 @@
+//! The role of the window surface.
+
+use sctk::reexports::client::protocol::wl_surface::WlSurface;
+use sctk::reexports::protocols::xdg::shell::client::xdg_surface::XdgSurface as XdgSurfaceProxy;
+
+use sctk::shell::wlr_layer::LayerSurface;
+use sctk::shell::xdg::popup::Popup;
+use sctk::shell::xdg::window::Window;
+use sctk::shell::xdg::XdgSurface;
+use sctk::shell::WaylandSurface;
+
+/// The shell surface backing the window.
+#[derive(Debug, Clone)]
+pub enum ShellSurface {
+    /// The regular window.
+    Toplevel(Window),
+
+    /// The popup window, placed relative to its parent.
+    Popup(Popup),
+
+    /// The layer shell surface, placed by the compositor on the edges of an output.
+    Layer(LayerSurface),
+}
+
+impl ShellSurface {
+    /// The underlying SCTK window, unless the surface is a popup or a layer.
+    #[inline]
+    pub fn toplevel(&self) -> Option<&Window> {
+        match self {
+            Self::Toplevel(window) => Some(window),
+            Self::Popup(_) | Self::Layer(_) => None,
+        }
+    }
+
+    /// The xdg surface, unless the surface is a layer.
+    #[inline]
+    pub fn xdg_surface(&self) -> Option<&XdgSurfaceProxy> {
+        match self {
+            Self::Toplevel(window) => Some(window.xdg_surface()),
+            Self::Popup(popup) => Some(popup.xdg_surface()),
+            Self::Layer(_) => None,
+        }
+    }
+}
+
+impl WaylandSurface for ShellSurface {
+    fn wl_surface(&self) -> &WlSurface {
+        match self {
+            Self::Toplevel(window) => window.wl_surface(),
+            Self::Popup(popup) => popup.wl_surface(),
+            Self::Layer(layer) => layer.wl_surface(),
+        }
+    }
+}
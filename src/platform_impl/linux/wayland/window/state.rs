@@ -18,12 +18,21 @@ use sctk::reexports::csd_frame::{
 use sctk::reexports::protocols::wp::fractional_scale::v1::client::wp_fractional_scale_v1::WpFractionalScaleV1;
 use sctk::reexports::protocols::wp::text_input::zv3::client::zwp_text_input_v3::ZwpTextInputV3;
 use sctk::reexports::protocols::wp::viewporter::client::wp_viewport::WpViewport;
+use sctk::reexports::protocols::xdg::dialog::v1::client::xdg_dialog_v1::XdgDialogV1;
+use sctk::reexports::protocols::xdg::shell::client::xdg_popup::XdgPopup;
+use sctk::reexports::protocols::xdg::shell::client::xdg_positioner::{
+    Anchor as XdgAnchor, ConstraintAdjustment as XdgConstraintAdjustment, Gravity as XdgGravity,
+};
 use sctk::reexports::protocols::xdg::shell::client::xdg_toplevel::ResizeEdge as XdgResizeEdge;
 
 use sctk::compositor::{CompositorState, Region, SurfaceData, SurfaceDataExt};
 use sctk::seat::pointer::{PointerDataExt, ThemedPointer};
+use sctk::shell::wlr_layer::{
+    Anchor as SctkLayerAnchor, KeyboardInteractivity as SctkKeyboardInteractivity,
+    Layer as SctkLayer, LayerSurfaceConfigure,
+};
+use sctk::shell::xdg::popup::PopupConfigure;
 use sctk::shell::xdg::window::{DecorationMode, Window, WindowConfigure};
-use sctk::shell::xdg::XdgSurface;
 use sctk::shell::WaylandSurface;
 use sctk::shm::slot::SlotPool;
 use sctk::shm::Shm;
@@ -33,17 +42,25 @@ use wayland_protocols_plasma::blur::client::org_kde_kwin_blur::OrgKdeKwinBlur;
 use crate::cursor::CustomCursor as RootCustomCursor;
 use crate::dpi::{LogicalPosition, LogicalSize, PhysicalSize, Size};
 use crate::error::{ExternalError, NotSupportedError};
+use crate::platform::layer_shell::{KeyboardInteractivity, Layer, LayerAnchor};
 use crate::platform_impl::wayland::logical_to_physical_rounded;
 use crate::platform_impl::wayland::types::cursor::{CustomCursor, SelectedCursor};
 use crate::platform_impl::wayland::types::kwin_blur::KWinBlurManager;
+use crate::platform_impl::wayland::types::xdg_dialog::XdgDialogManager;
 use crate::platform_impl::{PlatformCustomCursor, WindowId};
-use crate::window::{CursorGrabMode, CursorIcon, ImePurpose, ResizeDirection, Theme};
+use crate::window::{
+    CursorGrabMode, CursorIcon, ImePurpose, PopupAnchor, PopupConstraintAdjustment, PopupGravity,
+    ResizeDirection, Theme,
+};
 
 use crate::platform_impl::wayland::seat::{
-    PointerConstraintsState, WinitPointerData, WinitPointerDataExt, ZwpTextInputV3Ext,
+    PointerConstraintsState, SurroundingText, WinitPointerData, WinitPointerDataExt,
+    ZwpTextInputV3Ext,
 };
 use crate::platform_impl::wayland::state::{WindowCompositorUpdate, WinitState};
 
+use super::ShellSurface;
+
 #[cfg(feature = "sctk-adwaita")]
 pub type WinitFrame = sctk_adwaita::AdwaitaFrame<WinitState>;
 #[cfg(not(feature = "sctk-adwaita"))]
@@ -66,6 +83,9 @@ pub struct WindowState {
     /// The last received configure.
     pub last_configure: Option<WindowConfigure>,
 
+    /// Whether the popup or the layer surface received its initial configure.
+    surface_configured: bool,
+
     /// The pointers observed on the window.
     pub pointers: Vec<Weak<ThemedPointer<WinitPointerData>>>,
 
@@ -112,6 +132,9 @@ pub struct WindowState {
     /// The current IME purpose.
     ime_purpose: ImePurpose,
 
+    /// The text around the cursor, for the IME.
+    ime_surrounding_text: Option<SurroundingText>,
+
     /// The text inputs observed on the window.
     text_inputs: Vec<ZwpTextInputV3>,
 
@@ -144,14 +167,16 @@ pub struct WindowState {
     fractional_scale: Option<WpFractionalScaleV1>,
     blur: Option<OrgKdeKwinBlur>,
     blur_manager: Option<KWinBlurManager>,
+    dialog: Option<XdgDialogV1>,
+    dialog_manager: Option<XdgDialogManager>,
 
     /// Whether the client side decorations have pending move operations.
     ///
     /// The value is the serial of the event triggered moved.
     has_pending_move: Option<u32>,
 
-    /// The underlying SCTK window.
-    pub window: Window,
+    /// The underlying SCTK window or popup.
+    pub window: ShellSurface,
 
     // NOTE: The spec says that destroying parent(`window` in our case), will unmap the
     // subsurfaces. Thus to achieve atomic unmap of the client, drop the decorations
@@ -168,7 +193,7 @@ impl WindowState {
         queue_handle: &QueueHandle<WinitState>,
         winit_state: &WinitState,
         initial_size: Size,
-        window: Window,
+        window: ShellSurface,
         theme: Option<Theme>,
     ) -> Self {
         let compositor = winit_state.compositor_state.clone();
@@ -185,6 +210,8 @@ impl WindowState {
         Self {
             blur: None,
             blur_manager: winit_state.kwin_blur_manager.clone(),
+            dialog: None,
+            dialog_manager: winit_state.xdg_dialog_manager.clone(),
             compositor,
             connection,
             csd_fails: false,
@@ -199,7 +226,9 @@ impl WindowState {
             has_pending_move: None,
             ime_allowed: false,
             ime_purpose: ImePurpose::Normal,
+            ime_surrounding_text: None,
             last_configure: None,
+            surface_configured: false,
             max_inner_size: None,
             min_inner_size: MIN_WINDOW_SIZE,
             pointer_constraints,
@@ -273,13 +302,15 @@ impl WindowState {
             self.stateless_size = self.size;
         }
 
-        if let Some(subcompositor) = subcompositor.as_ref().filter(|_| {
-            configure.decoration_mode == DecorationMode::Client
-                && self.frame.is_none()
-                && !self.csd_fails
-        }) {
+        if let Some((subcompositor, window)) =
+            subcompositor.as_ref().zip(self.window.toplevel()).filter(|_| {
+                configure.decoration_mode == DecorationMode::Client
+                    && self.frame.is_none()
+                    && !self.csd_fails
+            })
+        {
             match WinitFrame::new(
-                &self.window,
+                window,
                 shm,
                 #[cfg(feature = "sctk-adwaita")]
                 self.compositor.clone(),
@@ -364,6 +395,60 @@ impl WindowState {
         }
     }
 
+    /// Apply the configure of the popup, returning whether it was resized.
+    pub fn configure_popup(&mut self, configure: PopupConfigure) -> bool {
+        self.configure_surface(configure.width.max(1) as u32, configure.height.max(1) as u32)
+    }
+
+    /// Apply the configure of the layer surface, returning whether it was resized.
+    pub fn configure_layer(&mut self, configure: LayerSurfaceConfigure) -> bool {
+        let (width, height) = configure.new_size;
+        self.configure_surface(width, height)
+    }
+
+    /// Resize the surface sized by the compositor, the zero sizes are left up to us.
+    fn configure_surface(&mut self, width: u32, height: u32) -> bool {
+        if let Some(initial_size) = self.initial_size.take() {
+            self.size = initial_size.to_logical(self.scale_factor());
+        }
+
+        let width = if width == 0 { self.size.width } else { width };
+        let height = if height == 0 { self.size.height } else { height };
+        let new_size = LogicalSize::new(width, height);
+        let initial_configure = !std::mem::replace(&mut self.surface_configured, true);
+
+        if initial_configure || new_size != self.inner_size() {
+            self.resize(new_size);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Grab the input for the popup, with the latest button press on this window.
+    ///
+    /// Only the button presses are tracked, so the popups opened by touch don't get the grab.
+    /// Returns whether the grab was requested.
+    pub fn grab_popup(&self, popup: &XdgPopup) -> bool {
+        let latest = self
+            .pointers
+            .iter()
+            .filter_map(Weak::upgrade)
+            .map(|pointer| {
+                let data = pointer.pointer().winit_data();
+                (data.latest_button_serial(), data.seat().clone())
+            })
+            .max_by_key(|(serial, _)| *serial);
+
+        match latest {
+            Some((serial, seat)) => {
+                popup.grab(&seat, serial);
+                true
+            },
+            None => false,
+        }
+    }
+
     /// Compute the bounds for the inner size of the surface.
     fn inner_size_bounds(
         &self,
@@ -392,7 +477,10 @@ impl WindowState {
 
     /// Start interacting drag resize.
     pub fn drag_resize_window(&self, direction: ResizeDirection) -> Result<(), ExternalError> {
-        let xdg_toplevel = self.window.xdg_toplevel();
+        let xdg_toplevel = match self.window.toplevel() {
+            Some(window) => window.xdg_toplevel(),
+            None => return Err(ExternalError::NotSupported(NotSupportedError::new())),
+        };
 
         // TODO(kchibisov) handle touch serials.
         self.apply_on_pointer(|_, data| {
@@ -406,7 +494,10 @@ impl WindowState {
 
     /// Start the window drag.
     pub fn drag_window(&self) -> Result<(), ExternalError> {
-        let xdg_toplevel = self.window.xdg_toplevel();
+        let xdg_toplevel = match self.window.toplevel() {
+            Some(window) => window.xdg_toplevel(),
+            None => return Err(ExternalError::NotSupported(NotSupportedError::new())),
+        };
         // TODO(kchibisov) handle touch serials.
         self.apply_on_pointer(|_, data| {
             let serial = data.latest_button_serial();
@@ -429,10 +520,11 @@ impl WindowState {
         window_id: WindowId,
         updates: &mut Vec<WindowCompositorUpdate>,
     ) -> Option<bool> {
+        let window = self.window.toplevel()?;
         match self.frame.as_mut()?.on_click(timestamp, click, pressed)? {
-            FrameAction::Minimize => self.window.set_minimized(),
-            FrameAction::Maximize => self.window.set_maximized(),
-            FrameAction::UnMaximize => self.window.unset_maximized(),
+            FrameAction::Minimize => window.set_minimized(),
+            FrameAction::Maximize => window.set_maximized(),
+            FrameAction::UnMaximize => window.unset_maximized(),
             FrameAction::Close => WinitState::queue_close(updates, window_id),
             FrameAction::Move => self.has_pending_move = Some(serial),
             FrameAction::Resize(edge) => {
@@ -448,9 +540,9 @@ impl WindowState {
                     ResizeEdge::BottomRight => XdgResizeEdge::BottomRight,
                     _ => return None,
                 };
-                self.window.resize(seat, serial, edge);
+                window.resize(seat, serial, edge);
             },
-            FrameAction::ShowMenu(x, y) => self.window.show_window_menu(seat, serial, (x, y)),
+            FrameAction::ShowMenu(x, y) => window.show_window_menu(seat, serial, (x, y)),
             _ => (),
         };
 
@@ -480,7 +572,9 @@ impl WindowState {
             // If we have a cursor change, that means that cursor is over the decorations,
             // so try to apply move.
             if let Some(serial) = cursor.is_some().then_some(serial).flatten() {
-                self.window.move_(seat, serial);
+                if let Some(window) = self.window.toplevel() {
+                    window.move_(seat, serial);
+                }
                 None
             } else {
                 cursor
@@ -543,7 +637,7 @@ impl WindowState {
     /// Whether the window received initial configure event from the compositor.
     #[inline]
     pub fn is_configured(&self) -> bool {
-        self.last_configure.is_some()
+        self.last_configure.is_some() || self.surface_configured
     }
 
     #[inline]
@@ -667,12 +761,14 @@ impl WindowState {
         self.reload_transparency_hint();
 
         // Set the window geometry.
-        self.window.xdg_surface().set_window_geometry(
-            x,
-            y,
-            outer_size.width as i32,
-            outer_size.height as i32,
-        );
+        if let Some(xdg_surface) = self.window.xdg_surface() {
+            xdg_surface.set_window_geometry(
+                x,
+                y,
+                outer_size.width as i32,
+                outer_size.height as i32,
+            );
+        }
 
         // Update the target viewport, this is used if and only if fractional scaling is in use.
         if let Some(viewport) = self.viewport.as_ref() {
@@ -702,13 +798,17 @@ impl WindowState {
         })
     }
 
+    /// The cursor of the window, `None` when it's hidden.
+    pub fn selected_cursor(&self) -> Option<&SelectedCursor> {
+        self.cursor_visible.then_some(&self.selected_cursor)
+    }
+
     /// Set the custom cursor icon.
     pub(crate) fn set_custom_cursor(&mut self, cursor: RootCustomCursor) {
         let cursor = match cursor {
             RootCustomCursor { inner: PlatformCustomCursor::Wayland(cursor) } => cursor.0,
-            #[cfg(x11_platform)]
-            RootCustomCursor { inner: PlatformCustomCursor::X(_) } => {
-                tracing::error!("passed a X11 cursor to Wayland backend");
+            _ => {
+                tracing::error!("passed a cursor of another backend to Wayland backend");
                 return;
             },
         };
@@ -770,7 +870,9 @@ impl WindowState {
             .unwrap_or(size);
 
         self.min_inner_size = size;
-        self.window.set_min_size(Some(size.into()));
+        if let Some(window) = self.window.toplevel() {
+            window.set_min_size(Some(size.into()));
+        }
     }
 
     /// Set maximum inner window size.
@@ -783,7 +885,9 @@ impl WindowState {
         });
 
         self.max_inner_size = size;
-        self.window.set_max_size(size.map(Into::into));
+        if let Some(window) = self.window.toplevel() {
+            window.set_max_size(size.map(Into::into));
+        }
     }
 
     /// Set the CSD theme.
@@ -862,11 +966,16 @@ impl WindowState {
     }
 
     pub fn show_window_menu(&self, position: LogicalPosition<u32>) {
+        let window = match self.window.toplevel() {
+            Some(window) => window,
+            None => return,
+        };
+
         // TODO(kchibisov) handle touch serials.
         self.apply_on_pointer(|_, data| {
             let serial = data.latest_button_serial();
             let seat = data.seat();
-            self.window.show_window_menu(seat, serial, position.into());
+            window.show_window_menu(seat, serial, position.into());
         });
     }
 
@@ -917,12 +1026,17 @@ impl WindowState {
 
         self.decorate = decorate;
 
+        let window = match self.window.toplevel() {
+            Some(window) => window,
+            None => return,
+        };
+
         match self.last_configure.as_ref().map(|configure| configure.decoration_mode) {
             Some(DecorationMode::Server) if !self.decorate => {
                 // To disable decorations we should request client and hide the frame.
-                self.window.request_decoration_mode(Some(DecorationMode::Client))
+                window.request_decoration_mode(Some(DecorationMode::Client))
             },
-            _ if self.decorate => self.window.request_decoration_mode(Some(DecorationMode::Server)),
+            _ if self.decorate => window.request_decoration_mode(Some(DecorationMode::Server)),
             _ => (),
         }
 
@@ -939,6 +1053,12 @@ impl WindowState {
         self.seat_focus.insert(seat);
     }
 
+    /// Whether the window has the keyboard focus from the given seat.
+    #[inline]
+    pub fn has_seat_focus(&self, seat: &ObjectId) -> bool {
+        self.seat_focus.contains(seat)
+    }
+
     /// Remove seat focus from the window.
     #[inline]
     pub fn remove_seat_focus(&mut self, seat: &ObjectId) {
@@ -955,6 +1075,9 @@ impl WindowState {
             if allowed {
                 text_input.enable();
                 text_input.set_content_type_by_purpose(self.ime_purpose);
+                if let Some(surrounding_text) = self.ime_surrounding_text.as_ref() {
+                    text_input.set_surrounding_text_trimmed(surrounding_text);
+                }
             } else {
                 text_input.disable();
             }
@@ -992,6 +1115,29 @@ impl WindowState {
         self.ime_purpose
     }
 
+    /// Set the text around the cursor for the IME.
+    pub fn set_ime_surrounding_text(&mut self, surrounding_text: SurroundingText) {
+        let SurroundingText { text, cursor, anchor } = &surrounding_text;
+        if !text.is_char_boundary(*cursor) || !text.is_char_boundary(*anchor) {
+            warn!("The IME cursor and anchor must be on character boundaries of the text");
+            return;
+        }
+
+        if self.ime_allowed {
+            for text_input in &self.text_inputs {
+                text_input.set_surrounding_text_trimmed(&surrounding_text);
+                text_input.commit();
+            }
+        }
+
+        self.ime_surrounding_text = Some(surrounding_text);
+    }
+
+    /// Get the text around the cursor for the IME.
+    pub fn ime_surrounding_text(&self) -> Option<&SurroundingText> {
+        self.ime_surrounding_text.as_ref()
+    }
+
     /// Set the scale factor for the given window.
     #[inline]
     pub fn set_scale_factor(&mut self, scale_factor: f64) {
@@ -1024,6 +1170,31 @@ impl WindowState {
         }
     }
 
+    /// Make the window a modal dialog of the `parent` window.
+    ///
+    /// The window is kept above its parent, and the compositor is asked to treat it as modal
+    /// when it supports the xdg-dialog protocol.
+    pub fn set_modal(&mut self, parent: &Window) {
+        let window = match self.window.toplevel() {
+            Some(window) => window,
+            None => return,
+        };
+        window.set_parent(Some(parent));
+
+        if self.dialog.is_none() {
+            if let Some(dialog_manager) = self.dialog_manager.as_ref() {
+                let dialog = dialog_manager.dialog(window.xdg_toplevel(), &self.queue_handle);
+                self.dialog = Some(dialog);
+            } else {
+                info!("Dialog manager unavailable, unable to make the window modal");
+            }
+        }
+
+        if let Some(dialog) = self.dialog.as_ref() {
+            dialog.set_modal();
+        }
+    }
+
     /// Set the window title to a new value.
     ///
     /// This will automatically truncate the title to something meaningful.
@@ -1043,7 +1214,9 @@ impl WindowState {
             frame.set_title(&title);
         }
 
-        self.window.set_title(&title);
+        if let Some(window) = self.window.toplevel() {
+            window.set_title(&title);
+        }
         self.title = title;
     }
 
@@ -1079,6 +1252,10 @@ impl WindowState {
 
 impl Drop for WindowState {
     fn drop(&mut self) {
+        if let Some(dialog) = self.dialog.take() {
+            dialog.destroy();
+        }
+
         if let Some(blur) = self.blur.take() {
             blur.release();
         }
@@ -1139,6 +1316,73 @@ impl From<ResizeDirection> for XdgResizeEdge {
     }
 }
 
+impl From<PopupAnchor> for XdgAnchor {
+    fn from(value: PopupAnchor) -> Self {
+        match value {
+            PopupAnchor::None => XdgAnchor::None,
+            PopupAnchor::Top => XdgAnchor::Top,
+            PopupAnchor::Bottom => XdgAnchor::Bottom,
+            PopupAnchor::Left => XdgAnchor::Left,
+            PopupAnchor::Right => XdgAnchor::Right,
+            PopupAnchor::TopLeft => XdgAnchor::TopLeft,
+            PopupAnchor::BottomLeft => XdgAnchor::BottomLeft,
+            PopupAnchor::TopRight => XdgAnchor::TopRight,
+            PopupAnchor::BottomRight => XdgAnchor::BottomRight,
+        }
+    }
+}
+
+impl From<PopupGravity> for XdgGravity {
+    fn from(value: PopupGravity) -> Self {
+        match value {
+            PopupGravity::None => XdgGravity::None,
+            PopupGravity::Top => XdgGravity::Top,
+            PopupGravity::Bottom => XdgGravity::Bottom,
+            PopupGravity::Left => XdgGravity::Left,
+            PopupGravity::Right => XdgGravity::Right,
+            PopupGravity::TopLeft => XdgGravity::TopLeft,
+            PopupGravity::BottomLeft => XdgGravity::BottomLeft,
+            PopupGravity::TopRight => XdgGravity::TopRight,
+            PopupGravity::BottomRight => XdgGravity::BottomRight,
+        }
+    }
+}
+
+impl From<PopupConstraintAdjustment> for XdgConstraintAdjustment {
+    fn from(value: PopupConstraintAdjustment) -> Self {
+        // The flags have the values of the protocol.
+        XdgConstraintAdjustment::from_bits_truncate(value.bits())
+    }
+}
+
+impl From<Layer> for SctkLayer {
+    fn from(value: Layer) -> Self {
+        match value {
+            Layer::Background => SctkLayer::Background,
+            Layer::Bottom => SctkLayer::Bottom,
+            Layer::Top => SctkLayer::Top,
+            Layer::Overlay => SctkLayer::Overlay,
+        }
+    }
+}
+
+impl From<LayerAnchor> for SctkLayerAnchor {
+    fn from(value: LayerAnchor) -> Self {
+        // The flags have the values of the protocol.
+        SctkLayerAnchor::from_bits_truncate(value.bits())
+    }
+}
+
+impl From<KeyboardInteractivity> for SctkKeyboardInteractivity {
+    fn from(value: KeyboardInteractivity) -> Self {
+        match value {
+            KeyboardInteractivity::None => SctkKeyboardInteractivity::None,
+            KeyboardInteractivity::Exclusive => SctkKeyboardInteractivity::Exclusive,
+            KeyboardInteractivity::OnDemand => SctkKeyboardInteractivity::OnDemand,
+        }
+    }
+}
+
 // NOTE: Rust doesn't allow `From<Option<Theme>>`.
 #[cfg(feature = "sctk-adwaita")]
 fn into_sctk_adwaita_config(theme: Option<Theme>) -> sctk_adwaita::FrameConfig {
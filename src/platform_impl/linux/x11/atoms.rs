@@ -13,7 +13,7 @@ macro_rules! atom_manager {
 
         /// Indices into the `Atoms` struct.
         #[derive(Copy, Clone, Debug)]
-        #[allow(non_camel_case_types)]
+        #[allow(non_camel_case_types, clippy::upper_case_acronyms)]
         pub enum AtomName {
             $($name,)*
         }
@@ -57,6 +57,7 @@ atom_manager! {
     _NET_WM_STATE_MAXIMIZED_HORZ,
     _NET_WM_STATE_MAXIMIZED_VERT,
     _NET_WM_STATE_MODAL,
+    _NET_WM_STRUT_PARTIAL,
     _NET_WM_WINDOW_TYPE,
 
     // Activation atoms.
@@ -82,18 +83,41 @@ atom_manager! {
 
     // Drag-N-Drop Atoms
     XdndAware,
+    XdndProxy,
     XdndEnter,
     XdndLeave,
     XdndDrop,
     XdndPosition,
     XdndStatus,
-    XdndActionPrivate,
+    XdndActionCopy,
+    XdndActionMove,
+    XdndActionLink,
     XdndSelection,
     XdndFinished,
     XdndTypeList,
     TextUriList: b"text/uri-list",
     None: b"None",
 
+    // XInput valuator labels.
+    AbsPressure: b"Abs Pressure",
+    AbsDistance: b"Abs Distance",
+    AbsTiltX: b"Abs Tilt X",
+    AbsTiltY: b"Abs Tilt Y",
+    AbsRotaryZ: b"Abs Rotary Z",
+    AbsThrottle: b"Abs Throttle",
+    AbsMtPositionX: b"Abs MT Position X",
+    AbsMtPositionY: b"Abs MT Position Y",
+    AbsMtTouchMajor: b"Abs MT Touch Major",
+    AbsMtTouchMinor: b"Abs MT Touch Minor",
+    AbsMtOrientation: b"Abs MT Orientation",
+    AbsMtPressure: b"Abs MT Pressure",
+
+    // Selection atoms.
+    CLIPBOARD,
+    INCR,
+    TARGETS,
+    _WINIT_SELECTION,
+
     // Miscellaneous Atoms
     _GTK_THEME_VARIANT,
     _MOTIF_WM_HINTS,
@@ -0,0 +1,621 @@
+//! The clipboard and primary selection handling, following the ICCCM selection protocol.
+
+use std::collections::{HashMap, VecDeque};
+use std::mem;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use x11rb::connection::RequestConnection;
+use x11rb::protocol::xproto::{self, ConnectionExt};
+
+use super::atoms::*;
+use super::{X11Error, XConnection};
+use crate::clipboard::{negotiate_mime_type, MimeData, Selection, TEXT_MIME_TYPES};
+use crate::event_loop::AsyncRequestSerial;
+
+/// How long the other client may stay silent during a selection transfer before we give up.
+const TRANSFER_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// The request from the window to the event loop.
+#[derive(Debug)]
+pub enum ClipboardRequest {
+    Set {
+        window: xproto::Window,
+        selection: Selection,
+        data: Vec<MimeData>,
+    },
+    Get {
+        window: xproto::Window,
+        selection: Selection,
+        mime_types: Vec<String>,
+        serial: AsyncRequestSerial,
+    },
+}
+
+/// The transfer which was finished and should be delivered to the user.
+#[derive(Debug)]
+pub struct TransferDone {
+    pub serial: AsyncRequestSerial,
+    pub data: Option<MimeData>,
+}
+
+/// The selection owned by one of our windows.
+struct OwnedSelection {
+    window: xproto::Window,
+    /// The advertised targets along with the index of the data they map to.
+    targets: Vec<(xproto::Atom, usize)>,
+    data: Vec<MimeData>,
+}
+
+struct Transfer {
+    selection: xproto::Atom,
+    serial: AsyncRequestSerial,
+    mime_types: Vec<String>,
+    stage: TransferStage,
+    /// When the in flight transfer is considered abandoned by the owner.
+    deadline: Instant,
+}
+
+/// The data sent to other client in chunks with the `INCR` mechanism.
+struct OutgoingTransfer {
+    requestor: xproto::Window,
+    property: xproto::Atom,
+    target: xproto::Atom,
+    data: Vec<u8>,
+    /// How much of the data was already written.
+    offset: usize,
+    /// The event mask to restore on the requestor once the transfer is over.
+    restore_mask: Option<xproto::EventMask>,
+    /// When the transfer is considered abandoned by the requestor.
+    deadline: Instant,
+}
+
+enum TransferStage {
+    /// Waiting for the list of targets offered by the owner.
+    Targets,
+    /// Waiting for the data in the given MIME type.
+    Data(String),
+    /// Receiving the data in chunks with the `INCR` mechanism.
+    Incremental(String, Vec<u8>),
+}
+
+enum Step {
+    Continue,
+    Done(Option<MimeData>),
+}
+
+pub struct Clipboard {
+    xconn: Arc<XConnection>,
+    /// The selections owned by our windows.
+    owned: HashMap<xproto::Atom, OwnedSelection>,
+    /// The transfers queued per requesting window, the front one is in flight.
+    transfers: HashMap<xproto::Window, VecDeque<Transfer>>,
+    /// The `INCR` transfers of our selections to other clients.
+    outgoing: Vec<OutgoingTransfer>,
+}
+
+impl Clipboard {
+    pub fn new(xconn: Arc<XConnection>) -> Self {
+        Self { xconn, owned: HashMap::new(), transfers: HashMap::new(), outgoing: Vec::new() }
+    }
+
+    pub fn handle_request(&mut self, request: ClipboardRequest) -> Result<(), X11Error> {
+        match request {
+            ClipboardRequest::Set { window, selection, data } => {
+                self.set_selection(window, self.selection_atom(selection), data)?;
+                Ok(())
+            },
+            ClipboardRequest::Get { window, selection, mime_types, serial } => {
+                let selection = self.selection_atom(selection);
+                let deadline = Instant::now() + TRANSFER_TIMEOUT;
+                let transfer = Transfer {
+                    selection,
+                    serial,
+                    mime_types,
+                    stage: TransferStage::Targets,
+                    deadline,
+                };
+
+                let queue = self.transfers.entry(window).or_default();
+                queue.push_back(transfer);
+                if queue.len() == 1 {
+                    convert_selection(&self.xconn, window, selection, self.xconn.atoms()[TARGETS])?;
+                }
+
+                Ok(())
+            },
+        }
+    }
+
+    /// Answer the `SelectionRequest` from other client.
+    ///
+    /// `own_requestor` tells whether the requestor is one of our windows.
+    pub fn handle_selection_request(
+        &mut self,
+        event: &xproto::SelectionRequestEvent,
+        own_requestor: bool,
+    ) -> Result<(), X11Error> {
+        // Obsolete clients don't specify the property to store the data into.
+        let property = if event.property == x11rb::NONE { event.target } else { event.property };
+
+        // The requestor waits for the notification, refuse the request when the data couldn't be
+        // written.
+        let replied = match self.write_selection(event, property, own_requestor) {
+            Ok(replied) => replied,
+            Err(err) => {
+                tracing::warn!("Failed to write the selection: {err}");
+                false
+            },
+        };
+
+        let notify = xproto::SelectionNotifyEvent {
+            response_type: xproto::SELECTION_NOTIFY_EVENT,
+            sequence: 0,
+            time: event.time,
+            requestor: event.requestor,
+            selection: event.selection,
+            target: event.target,
+            property: if replied { property } else { x11rb::NONE },
+        };
+        self.xconn
+            .xcb_connection()
+            .send_event(false, event.requestor, xproto::EventMask::NO_EVENT, notify)?
+            .ignore_error();
+        self.xconn.flush_requests()?;
+
+        Ok(())
+    }
+
+    /// Write the requested selection into the `property` of the requestor, returning whether
+    /// the request is answered.
+    fn write_selection(
+        &mut self,
+        event: &xproto::SelectionRequestEvent,
+        property: xproto::Atom,
+        own_requestor: bool,
+    ) -> Result<bool, X11Error> {
+        let atoms = self.xconn.atoms();
+        let owned = self.owned.get(&event.selection).filter(|owned| owned.window == event.owner);
+        let replied = match owned {
+            Some(owned) if event.target == atoms[TARGETS] => {
+                let mut targets = vec![atoms[TARGETS]];
+                targets.extend(owned.targets.iter().map(|&(target, _)| target));
+                self.xconn
+                    .change_property(
+                        event.requestor,
+                        property,
+                        xproto::AtomEnum::ATOM.into(),
+                        xproto::PropMode::REPLACE,
+                        &targets,
+                    )?
+                    .ignore_error();
+                true
+            },
+            Some(owned) => {
+                match owned.targets.iter().find(|&&(target, _)| target == event.target) {
+                    Some(&(target, index)) => {
+                        let data = &owned.data[index].data;
+                        if data.len() > self.max_chunk_len() {
+                            let data = data.clone();
+                            let requestor = event.requestor;
+                            self.start_outgoing(requestor, property, target, data, own_requestor)?;
+                        } else {
+                            self.xconn
+                                .change_property(
+                                    event.requestor,
+                                    property,
+                                    target,
+                                    xproto::PropMode::REPLACE,
+                                    data,
+                                )?
+                                .ignore_error();
+                        }
+                        true
+                    },
+                    None => false,
+                }
+            },
+            None => false,
+        };
+
+        Ok(replied)
+    }
+
+    /// Forget the selection taken over by other client.
+    pub fn handle_selection_clear(&mut self, window: xproto::Window, selection: xproto::Atom) {
+        if self.owned.get(&selection).is_some_and(|owned| owned.window == window) {
+            self.owned.remove(&selection);
+        }
+    }
+
+    /// Handle the `SelectionNotify` sent in response to our conversion request.
+    pub fn handle_selection_notify(
+        &mut self,
+        window: xproto::Window,
+        selection: xproto::Atom,
+        property: xproto::Atom,
+    ) -> Option<TransferDone> {
+        let transfer = self.transfers.get(&window)?.front()?;
+        if transfer.selection != selection {
+            return None;
+        }
+
+        let step = if property == x11rb::NONE {
+            Ok(Step::Done(None))
+        } else {
+            self.advance_transfer(window, property)
+        };
+
+        self.finish_step(window, step)
+    }
+
+    /// Handle the `PropertyNotify` on our window, used by the `INCR` transfers.
+    pub fn handle_property_notify(
+        &mut self,
+        window: xproto::Window,
+        property: xproto::Atom,
+        state: xproto::Property,
+    ) -> Option<TransferDone> {
+        if property != self.xconn.atoms()[_WINIT_SELECTION] || state != xproto::Property::NEW_VALUE
+        {
+            return None;
+        }
+
+        let transfer = self.transfers.get(&window)?.front()?;
+        if !matches!(transfer.stage, TransferStage::Incremental(..)) {
+            return None;
+        }
+
+        let step = self.advance_transfer(window, property);
+        self.finish_step(window, step)
+    }
+
+    /// Handle the `PropertyNotify` on the requestor of the `INCR` transfer, the deletion of the
+    /// property asks for the next chunk.
+    ///
+    /// Returns `true` when the event belonged to one of the outgoing transfers.
+    pub fn handle_requestor_property_notify(
+        &mut self,
+        window: xproto::Window,
+        property: xproto::Atom,
+        state: xproto::Property,
+    ) -> bool {
+        if state != xproto::Property::DELETE {
+            return false;
+        }
+
+        let index = match self
+            .outgoing
+            .iter()
+            .position(|transfer| transfer.requestor == window && transfer.property == property)
+        {
+            Some(index) => index,
+            None => return false,
+        };
+
+        let max_len = self.max_chunk_len();
+        let transfer = &mut self.outgoing[index];
+        let end = transfer.data.len().min(transfer.offset + max_len);
+        let chunk = &transfer.data[transfer.offset..end];
+        let finished = chunk.is_empty();
+        transfer.offset = end;
+        transfer.deadline = Instant::now() + TRANSFER_TIMEOUT;
+
+        // The empty chunk marks the end of the transfer.
+        let result = self
+            .xconn
+            .change_property(window, property, transfer.target, xproto::PropMode::REPLACE, chunk)
+            .and_then(|cookie| {
+                cookie.ignore_error();
+                self.xconn.flush_requests().map_err(X11Error::from)
+            });
+        if let Err(err) = result {
+            tracing::warn!("Failed to send the selection chunk: {err}");
+            self.finish_outgoing(index);
+        } else if finished {
+            self.finish_outgoing(index);
+        }
+
+        true
+    }
+
+    /// The closest moment when one of the transfers times out.
+    pub fn next_deadline(&self) -> Option<Instant> {
+        let incoming = self.transfers.values().filter_map(|queue| queue.front());
+        incoming
+            .map(|transfer| transfer.deadline)
+            .chain(self.outgoing.iter().map(|transfer| transfer.deadline))
+            .min()
+    }
+
+    /// Abandon the transfers which timed out, returning the failed transfers of our windows.
+    pub fn take_expired(&mut self, now: Instant) -> Vec<(xproto::Window, TransferDone)> {
+        while let Some(index) = self.outgoing.iter().position(|transfer| transfer.deadline <= now) {
+            tracing::warn!("The selection transfer to other client timed out");
+            self.finish_outgoing(index);
+        }
+
+        let expired: Vec<_> = self
+            .transfers
+            .iter()
+            .filter(|(_, queue)| queue.front().is_some_and(|transfer| transfer.deadline <= now))
+            .map(|(&window, _)| window)
+            .collect();
+
+        let mut done = Vec::new();
+        for window in expired {
+            tracing::warn!("The selection transfer from other client timed out");
+            if let Some(transfer) = self.finish_step(window, Ok(Step::Done(None))) {
+                done.push((window, transfer));
+            }
+        }
+
+        done
+    }
+
+    /// Own the `selection` with the given `data`, used for the `XdndSelection`.
+    ///
+    /// Returns the advertised targets when the selection was taken.
+    pub fn own_selection(
+        &mut self,
+        window: xproto::Window,
+        selection: xproto::Atom,
+        data: Vec<MimeData>,
+    ) -> Result<Option<Vec<xproto::Atom>>, X11Error> {
+        if !self.set_selection(window, selection, data)? {
+            return Ok(None);
+        }
+
+        let owned = self.owned.get(&selection);
+        Ok(owned.map(|owned| owned.targets.iter().map(|&(target, _)| target).collect()))
+    }
+
+    /// Drop all the state associated with the destroyed window.
+    pub fn remove_window(&mut self, window: xproto::Window) {
+        self.owned.retain(|_, owned| owned.window != window);
+        self.transfers.remove(&window);
+    }
+
+    /// Start sending the `data` in chunks with the `INCR` mechanism.
+    fn start_outgoing(
+        &mut self,
+        requestor: xproto::Window,
+        property: xproto::Atom,
+        target: xproto::Atom,
+        data: Vec<u8>,
+        own_requestor: bool,
+    ) -> Result<(), X11Error> {
+        // Watch the deletion of the property on the requestor. The event masks are per client, our
+        // windows already select the property changes while we select nothing on the others.
+        let restore_mask = if own_requestor {
+            None
+        } else {
+            let aux = xproto::ChangeWindowAttributesAux::new()
+                .event_mask(xproto::EventMask::PROPERTY_CHANGE);
+            self.xconn.xcb_connection().change_window_attributes(requestor, &aux)?.ignore_error();
+            Some(xproto::EventMask::NO_EVENT)
+        };
+
+        let len = u32::try_from(data.len()).unwrap_or(u32::MAX);
+        self.xconn
+            .change_property(
+                requestor,
+                property,
+                self.xconn.atoms()[INCR],
+                xproto::PropMode::REPLACE,
+                &[len],
+            )?
+            .ignore_error();
+
+        // The requestor may ask for the same property again, replacing the stale transfer.
+        self.outgoing
+            .retain(|transfer| transfer.requestor != requestor || transfer.property != property);
+        self.outgoing.push(OutgoingTransfer {
+            requestor,
+            property,
+            target,
+            data,
+            offset: 0,
+            restore_mask,
+            deadline: Instant::now() + TRANSFER_TIMEOUT,
+        });
+
+        Ok(())
+    }
+
+    fn finish_outgoing(&mut self, index: usize) {
+        let transfer = self.outgoing.swap_remove(index);
+        let still_used =
+            self.outgoing.iter().any(|outgoing| outgoing.requestor == transfer.requestor);
+        if let (Some(event_mask), false) = (transfer.restore_mask, still_used) {
+            let aux = xproto::ChangeWindowAttributesAux::new().event_mask(event_mask);
+            let _ = self
+                .xconn
+                .xcb_connection()
+                .change_window_attributes(transfer.requestor, &aux)
+                .map(|cookie| cookie.ignore_error());
+            let _ = self.xconn.flush_requests();
+        }
+    }
+
+    /// The largest chunk of data we can put into a property with a single request.
+    fn max_chunk_len(&self) -> usize {
+        // Leave some room for the request header.
+        self.xconn.xcb_connection().maximum_request_bytes() - 32
+    }
+
+    fn set_selection(
+        &mut self,
+        window: xproto::Window,
+        selection: xproto::Atom,
+        data: Vec<MimeData>,
+    ) -> Result<bool, X11Error> {
+        let conn = self.xconn.xcb_connection();
+        let timestamp = self.xconn.timestamp();
+
+        if data.is_empty() {
+            if self.owned.get(&selection).is_some_and(|owned| owned.window == window) {
+                self.owned.remove(&selection);
+                conn.set_selection_owner(x11rb::NONE, selection, timestamp)?.ignore_error();
+                self.xconn.flush_requests()?;
+            }
+            return Ok(false);
+        }
+
+        let mut targets = Vec::new();
+        for (index, entry) in data.iter().enumerate() {
+            let mut names = vec![entry.mime_type.as_str()];
+            if entry.is_text() {
+                names.extend(TEXT_MIME_TYPES);
+            }
+
+            for name in names {
+                let target = conn.intern_atom(false, name.as_bytes())?.reply()?.atom;
+                if !targets.iter().any(|&(existing, _)| existing == target) {
+                    targets.push((target, index));
+                }
+            }
+        }
+
+        conn.set_selection_owner(window, selection, timestamp)?.ignore_error();
+        let owner = conn.get_selection_owner(selection)?.reply()?.owner;
+        if owner != window {
+            tracing::warn!("Failed to take the ownership of the selection");
+            return Ok(false);
+        }
+
+        self.owned.insert(selection, OwnedSelection { window, targets, data });
+
+        Ok(true)
+    }
+
+    fn advance_transfer(
+        &mut self,
+        window: xproto::Window,
+        property: xproto::Atom,
+    ) -> Result<Step, X11Error> {
+        let xconn = &self.xconn;
+        let atoms = xconn.atoms();
+        let conn = xconn.xcb_connection();
+
+        let transfer = match self.transfers.get_mut(&window).and_then(VecDeque::front_mut) {
+            Some(transfer) => transfer,
+            None => return Ok(Step::Done(None)),
+        };
+
+        let reply = conn
+            .get_property(true, window, property, xproto::AtomEnum::ANY, 0, u32::MAX / 4)?
+            .reply()?;
+
+        match &mut transfer.stage {
+            TransferStage::Targets => {
+                let offered: Vec<xproto::Atom> = match reply.value32() {
+                    Some(offered) => offered.collect(),
+                    None => return Ok(Step::Done(None)),
+                };
+
+                let cookies = offered
+                    .iter()
+                    .map(|&atom| conn.get_atom_name(atom))
+                    .collect::<Result<Vec<_>, _>>()?;
+                let mut names = Vec::with_capacity(cookies.len());
+                for cookie in cookies {
+                    names.push(String::from_utf8_lossy(&cookie.reply()?.name).into_owned());
+                }
+
+                let target = negotiate_mime_type(&transfer.mime_types, &names)
+                    .map(|(mime_type, index)| (mime_type.to_owned(), offered[index]));
+
+                match target {
+                    Some((mime_type, target)) => {
+                        transfer.stage = TransferStage::Data(mime_type);
+                        convert_selection(xconn, window, transfer.selection, target)?;
+                        Ok(Step::Continue)
+                    },
+                    None => Ok(Step::Done(None)),
+                }
+            },
+            TransferStage::Data(mime_type) if reply.type_ == atoms[INCR] => {
+                // The data will arrive in chunks with `PropertyNotify`, which is started by
+                // the deletion of the property above.
+                let mime_type = mem::take(mime_type);
+                transfer.stage = TransferStage::Incremental(mime_type, Vec::new());
+                xconn.flush_requests()?;
+                Ok(Step::Continue)
+            },
+            TransferStage::Data(mime_type) => {
+                Ok(Step::Done(Some(MimeData::new(mem::take(mime_type), reply.value))))
+            },
+            TransferStage::Incremental(mime_type, data) => {
+                if reply.value.is_empty() {
+                    Ok(Step::Done(Some(MimeData::new(mem::take(mime_type), mem::take(data)))))
+                } else {
+                    data.extend_from_slice(&reply.value);
+                    Ok(Step::Continue)
+                }
+            },
+        }
+    }
+
+    fn finish_step(
+        &mut self,
+        window: xproto::Window,
+        step: Result<Step, X11Error>,
+    ) -> Option<TransferDone> {
+        let data = match step {
+            Ok(Step::Continue) => {
+                // The owner is still responsive, so give it more time.
+                let queue = self.transfers.get_mut(&window)?;
+                queue.front_mut()?.deadline = Instant::now() + TRANSFER_TIMEOUT;
+                return None;
+            },
+            Ok(Step::Done(data)) => data,
+            Err(err) => {
+                tracing::warn!("Failed to transfer the selection: {err}");
+                None
+            },
+        };
+
+        let queue = self.transfers.get_mut(&window)?;
+        let transfer = queue.pop_front()?;
+
+        // Start the next queued transfer.
+        match queue.front_mut() {
+            Some(next) => {
+                next.deadline = Instant::now() + TRANSFER_TIMEOUT;
+                let targets = self.xconn.atoms()[TARGETS];
+                if let Err(err) = convert_selection(&self.xconn, window, next.selection, targets) {
+                    tracing::warn!("Failed to request the selection: {err}");
+                }
+            },
+            None => {
+                self.transfers.remove(&window);
+            },
+        }
+
+        Some(TransferDone { serial: transfer.serial, data })
+    }
+
+    fn selection_atom(&self, selection: Selection) -> xproto::Atom {
+        match selection {
+            Selection::Clipboard => self.xconn.atoms()[CLIPBOARD],
+            Selection::Primary => xproto::AtomEnum::PRIMARY.into(),
+        }
+    }
+}
+
+/// Ask the selection owner to convert the selection into our transfer property.
+fn convert_selection(
+    xconn: &XConnection,
+    window: xproto::Window,
+    selection: xproto::Atom,
+    target: xproto::Atom,
+) -> Result<(), X11Error> {
+    let property = xconn.atoms()[_WINIT_SELECTION];
+    xconn
+        .xcb_connection()
+        .convert_selection(window, selection, target, property, xconn.timestamp())?
+        .ignore_error();
+    xconn.flush_requests()?;
+    Ok(())
+}
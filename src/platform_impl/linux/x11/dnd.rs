@@ -1,41 +1,90 @@
-use std::io;
+use std::collections::HashMap;
 use std::os::raw::*;
-use std::path::{Path, PathBuf};
-use std::str::Utf8Error;
+use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 
-use percent_encoding::percent_decode;
 use x11rb::protocol::xproto::{self, ConnectionExt};
 
 use super::atoms::AtomName::None as DndNone;
 use super::atoms::*;
 use super::{util, CookieResultExt, X11Error, XConnection};
+use crate::clipboard::MimeData;
+use crate::dnd::{DndAction, DndActions, URI_LIST_MIME_TYPE};
+use crate::dpi::PhysicalPosition;
+use crate::platform_impl::common::dnd::{parse_uri_list, DndDataParseError, DropResponse};
 
 #[derive(Debug, Clone, Copy)]
 pub enum DndState {
-    Accepted,
+    Accepted(DndAction),
     Rejected,
 }
 
+/// The request from the window to the event loop.
 #[derive(Debug)]
-pub enum DndDataParseError {
-    EmptyData,
-    InvalidUtf8(#[allow(dead_code)] Utf8Error),
-    HostnameSpecified(#[allow(dead_code)] String),
-    UnexpectedProtocol(#[allow(dead_code)] String),
-    UnresolvablePath(#[allow(dead_code)] io::Error),
+pub enum DndRequest {
+    Respond { window: xproto::Window, response: DropResponse },
+    StartDrag { window: xproto::Window, data: Vec<MimeData>, actions: DndActions },
 }
 
-impl From<Utf8Error> for DndDataParseError {
-    fn from(e: Utf8Error) -> Self {
-        DndDataParseError::InvalidUtf8(e)
-    }
+/// The version of the XDND protocol we implement.
+const XDND_VERSION: u32 = 5;
+
+/// How long the target may take to answer the drop with `XdndFinished`.
+pub const FINISH_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The XDND aware window under the pointer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DropTarget {
+    pub window: xproto::Window,
+    /// The window receiving the messages, which differs from the `window` with `XdndProxy`.
+    pub proxy: xproto::Window,
+    pub version: u32,
+}
+
+/// The drag started by one of our windows, the data is offered through the `XdndSelection`.
+#[derive(Debug)]
+pub struct DragSource {
+    pub window: xproto::Window,
+    pub type_list: Vec<xproto::Atom>,
+    /// The action requested from the target.
+    pub action: DndAction,
+    /// The XDND aware window under the pointer.
+    pub target: Option<DropTarget>,
+    /// The action accepted by the target.
+    pub accepted: Option<DndAction>,
+    /// When we stop waiting for `XdndFinished`, set once the drop was sent.
+    pub finish_deadline: Option<Instant>,
+    /// The drop targets of the windows already looked up during the drag.
+    pub targets: HashMap<xproto::Window, Option<DropTarget>>,
 }
 
-impl From<io::Error> for DndDataParseError {
-    fn from(e: io::Error) -> Self {
-        DndDataParseError::UnresolvablePath(e)
+impl DragSource {
+    pub fn new(window: xproto::Window, type_list: Vec<xproto::Atom>, action: DndAction) -> Self {
+        Self {
+            window,
+            type_list,
+            action,
+            target: None,
+            accepted: None,
+            finish_deadline: None,
+            targets: HashMap::new(),
+        }
     }
+
+    /// Whether the drop was sent, waiting for `XdndFinished`.
+    pub fn dropped(&self) -> bool {
+        self.finish_deadline.is_some()
+    }
+}
+
+/// The drop accepted by the window, waiting for the data.
+#[derive(Debug)]
+pub struct PendingDrop {
+    pub source_window: xproto::Window,
+    pub target: xproto::Atom,
+    pub mime_type: String,
+    pub action: DndAction,
 }
 
 pub struct Dnd {
@@ -43,22 +92,121 @@ pub struct Dnd {
     // Populated by XdndEnter event handler
     pub version: Option<c_long>,
     pub type_list: Option<Vec<xproto::Atom>>,
+    pub mime_types: Vec<String>,
     // Populated by XdndPosition event handler
     pub source_window: Option<xproto::Window>,
+    pub target_window: Option<xproto::Window>,
+    pub position: PhysicalPosition<f64>,
     // Populated by SelectionNotify event handler (triggered by XdndPosition event handler)
     pub result: Option<Result<Vec<PathBuf>, DndDataParseError>>,
+    // Populated by the window
+    pub response: DropResponse,
+    // Populated by XdndDrop event handler
+    pub pending_drop: Option<PendingDrop>,
+    // Populated when one of our windows starts the drag
+    pub drag_source: Option<DragSource>,
 }
 
 impl Dnd {
     pub fn new(xconn: Arc<XConnection>) -> Result<Self, X11Error> {
-        Ok(Dnd { xconn, version: None, type_list: None, source_window: None, result: None })
+        Ok(Dnd {
+            xconn,
+            version: None,
+            type_list: None,
+            mime_types: Vec::new(),
+            source_window: None,
+            target_window: None,
+            position: PhysicalPosition::default(),
+            result: None,
+            response: DropResponse::default(),
+            pending_drop: None,
+            drag_source: None,
+        })
     }
 
     pub fn reset(&mut self) {
         self.version = None;
         self.type_list = None;
+        self.mime_types = Vec::new();
         self.source_window = None;
+        self.target_window = None;
         self.result = None;
+        self.response = DropResponse::default();
+        self.pending_drop = None;
+    }
+
+    /// Look up the names of the offered types.
+    pub fn set_type_list(&mut self, type_list: Vec<xproto::Atom>) {
+        let conn = self.xconn.xcb_connection();
+        self.mime_types = type_list
+            .iter()
+            .map(|&atom| {
+                conn.get_atom_name(atom)
+                    .ok()
+                    .and_then(|cookie| cookie.reply().ok())
+                    .map(|reply| String::from_utf8_lossy(&reply.name).into_owned())
+                    .unwrap_or_default()
+            })
+            .collect();
+        self.type_list = Some(type_list);
+    }
+
+    /// Whether the dragged files are announced with `HoveredFile`.
+    pub fn has_files(&self) -> bool {
+        self.mime_types.iter().any(|mime_type| mime_type == URI_LIST_MIME_TYPE)
+    }
+
+    /// The target and the MIME type accepted by the window, along with the action.
+    pub fn accepted(&self) -> Option<(xproto::Atom, &str, DndAction)> {
+        let type_list = self.type_list.as_ref()?;
+        let (index, action) = self.response.accepted(&self.mime_types)?;
+        Some((type_list[index], &self.mime_types[index], action))
+    }
+
+    pub fn action_atom(&self, action: DndAction) -> xproto::Atom {
+        let atoms = self.xconn.atoms();
+        match action {
+            DndAction::Copy => atoms[XdndActionCopy],
+            DndAction::Move => atoms[XdndActionMove],
+            DndAction::Link => atoms[XdndActionLink],
+        }
+    }
+
+    pub fn action_from_atom(&self, atom: xproto::Atom) -> Option<DndAction> {
+        let atoms = self.xconn.atoms();
+        if atom == atoms[XdndActionCopy] {
+            Some(DndAction::Copy)
+        } else if atom == atoms[XdndActionMove] {
+            Some(DndAction::Move)
+        } else if atom == atoms[XdndActionLink] {
+            Some(DndAction::Link)
+        } else {
+            None
+        }
+    }
+
+    pub fn actions_from_atom(&self, atom: xproto::Atom) -> DndActions {
+        self.action_from_atom(atom).map_or(DndActions::COPY, DndActions::from)
+    }
+
+    /// Update the response of the window, the source is notified right away.
+    pub fn set_response(&mut self, window: xproto::Window, response: DropResponse) {
+        if self.target_window != Some(window) || self.pending_drop.is_some() {
+            return;
+        }
+
+        self.response = response;
+        if let Some(source_window) = self.source_window {
+            let state = match self.accepted() {
+                Some((.., action)) => DndState::Accepted(action),
+                None => DndState::Rejected,
+            };
+            unsafe {
+                if let Err(err) = self.send_status(window, source_window, state) {
+                    tracing::warn!("Failed to send `XdndStatus` message: {err}");
+                }
+            }
+        }
     }
 
     pub unsafe fn send_status(
@@ -69,7 +217,7 @@ impl Dnd {
     ) -> Result<(), X11Error> {
         let atoms = self.xconn.atoms();
         let (accepted, action) = match state {
-            DndState::Accepted => (1, atoms[XdndActionPrivate]),
+            DndState::Accepted(action) => (1, self.action_atom(action)),
             DndState::Rejected => (0, atoms[DndNone]),
         };
         self.xconn
@@ -93,7 +241,7 @@ impl Dnd {
     ) -> Result<(), X11Error> {
         let atoms = self.xconn.atoms();
         let (accepted, action) = match state {
-            DndState::Accepted => (1, atoms[XdndActionPrivate]),
+            DndState::Accepted(action) => (1, self.action_atom(action)),
             DndState::Rejected => (0, atoms[DndNone]),
         };
         self.xconn
@@ -109,6 +257,144 @@ impl Dnd {
         Ok(())
     }
 
+    /// Find the XDND aware window under the pointer.
+    ///
+    /// The properties of the windows are only read once per drag, the `targets` keep the results.
+    pub fn find_target(
+        &self,
+        targets: &mut HashMap<xproto::Window, Option<DropTarget>>,
+        root: xproto::Window,
+        x: i16,
+        y: i16,
+    ) -> Option<DropTarget> {
+        let conn = self.xconn.xcb_connection();
+
+        // Descend from the root, the top-level windows are usually reparented into the frames.
+        let mut window = root;
+        for _ in 0..32 {
+            let child = conn.translate_coordinates(root, window, x, y).ok()?.reply().ok()?.child;
+            if child == x11rb::NONE {
+                return None;
+            }
+
+            let target = *targets.entry(child).or_insert_with(|| self.drop_target(child));
+            if target.is_some() {
+                return target;
+            }
+
+            window = child;
+        }
+
+        None
+    }
+
+    /// The drop target for the `window` when it's XDND aware, honouring its `XdndProxy`.
+    fn drop_target(&self, window: xproto::Window) -> Option<DropTarget> {
+        let atoms = self.xconn.atoms();
+
+        // The proxy must point to itself, otherwise it's a leftover of a dead client.
+        let proxy = self
+            .proxy_window(window)
+            .filter(|&proxy| self.proxy_window(proxy) == Some(proxy))
+            .unwrap_or(window);
+
+        let version = self
+            .xconn
+            .get_property::<u32>(proxy, atoms[XdndAware], xproto::AtomEnum::ATOM.into())
+            .ok()?
+            .first()
+            .copied()?;
+
+        Some(DropTarget { window, proxy, version: version.min(XDND_VERSION) })
+    }
+
+    fn proxy_window(&self, window: xproto::Window) -> Option<xproto::Window> {
+        let atoms = self.xconn.atoms();
+        self.xconn
+            .get_property::<u32>(window, atoms[XdndProxy], xproto::AtomEnum::WINDOW.into())
+            .ok()?
+            .first()
+            .copied()
+    }
+
+    pub fn send_enter(&self, source: &DragSource, target: &DropTarget) -> Result<(), X11Error> {
+        let atoms = self.xconn.atoms();
+        let more_types = (source.type_list.len() > 3) as u32;
+        let types = |index: usize| source.type_list.get(index).copied().unwrap_or_default();
+        self.xconn
+            .send_client_msg(target.window, target.proxy, atoms[XdndEnter], None, [
+                source.window,
+                (target.version << 24) | more_types,
+                types(0),
+                types(1),
+                types(2),
+            ])?
+            .ignore_error();
+        self.xconn.flush_requests()?;
+
+        Ok(())
+    }
+
+    pub fn send_position(
+        &self,
+        source: &DragSource,
+        target: &DropTarget,
+        (x, y): (i16, i16),
+        time: xproto::Timestamp,
+    ) -> Result<(), X11Error> {
+        let atoms = self.xconn.atoms();
+        let position = ((x as u16 as u32) << 16) | y as u16 as u32;
+        self.xconn
+            .send_client_msg(target.window, target.proxy, atoms[XdndPosition], None, [
+                source.window,
+                0,
+                position,
+                time,
+                self.action_atom(source.action),
+            ])?
+            .ignore_error();
+        self.xconn.flush_requests()?;
+
+        Ok(())
+    }
+
+    pub fn send_leave(&self, source: &DragSource, target: &DropTarget) -> Result<(), X11Error> {
+        let atoms = self.xconn.atoms();
+        self.xconn
+            .send_client_msg(target.window, target.proxy, atoms[XdndLeave], None, [
+                source.window,
+                0,
+                0,
+                0,
+                0,
+            ])?
+            .ignore_error();
+        self.xconn.flush_requests()?;
+
+        Ok(())
+    }
+
+    pub fn send_drop(
+        &self,
+        source: &DragSource,
+        target: &DropTarget,
+        time: xproto::Timestamp,
+    ) -> Result<(), X11Error> {
+        let atoms = self.xconn.atoms();
+        self.xconn
+            .send_client_msg(target.window, target.proxy, atoms[XdndDrop], None, [
+                source.window,
+                0,
+                time,
+                0,
+                0,
+            ])?
+            .ignore_error();
+        self.xconn.flush_requests()?;
+
+        Ok(())
+    }
+
     pub unsafe fn get_type_list(
         &self,
         source_window: xproto::Window,
@@ -121,17 +407,16 @@ impl Dnd {
         )
     }
 
-    pub unsafe fn convert_selection(&self, window: xproto::Window, time: xproto::Timestamp) {
+    pub unsafe fn convert_selection(
+        &self,
+        window: xproto::Window,
+        target: xproto::Atom,
+        time: xproto::Timestamp,
+    ) {
         let atoms = self.xconn.atoms();
         self.xconn
             .xcb_connection()
-            .convert_selection(
-                window,
-                atoms[XdndSelection],
-                atoms[TextUriList],
-                atoms[XdndSelection],
-                time,
-            )
+            .convert_selection(window, atoms[XdndSelection], target, atoms[XdndSelection], time)
             .expect_then_ignore_error("Failed to send XdndSelection event")
     }
 
@@ -143,32 +428,25 @@ impl Dnd {
         self.xconn.get_property(window, atoms[XdndSelection], atoms[TextUriList])
     }
 
+    /// Read the dropped data of any type.
+    pub fn read_drop_data(&self, window: xproto::Window) -> Result<Vec<u8>, X11Error> {
+        let atoms = self.xconn.atoms();
+        let reply = self
+            .xconn
+            .xcb_connection()
+            .get_property(
+                true,
+                window,
+                atoms[XdndSelection],
+                xproto::AtomEnum::ANY,
+                0,
+                u32::MAX / 4,
+            )?
+            .reply()?;
+        Ok(reply.value)
+    }
+
     pub fn parse_data(&self, data: &mut [c_uchar]) -> Result<Vec<PathBuf>, DndDataParseError> {
-        if !data.is_empty() {
-            let mut path_list = Vec::new();
-            let decoded = percent_decode(data).decode_utf8()?.into_owned();
-            for uri in decoded.split("\r\n").filter(|u| !u.is_empty()) {
-                // The format is specified as protocol://host/path
-                // However, it's typically simply protocol:///path
-                let path_str = if uri.starts_with("file://") {
-                    let path_str = uri.replace("file://", "");
-                    if !path_str.starts_with('/') {
-                        // A hostname is specified
-                        // Supporting this case is beyond the scope of my mental health
-                        return Err(DndDataParseError::HostnameSpecified(path_str));
-                    }
-                    path_str
-                } else {
-                    // Only the file protocol is supported
-                    return Err(DndDataParseError::UnexpectedProtocol(uri.to_owned()));
-                };
-
-                let path = Path::new(&path_str).canonicalize()?;
-                path_list.push(path);
-            }
-            Ok(path_list)
-        } else {
-            Err(DndDataParseError::EmptyData)
-        }
+        parse_uri_list(data)
     }
 }
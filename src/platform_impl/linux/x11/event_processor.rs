@@ -3,15 +3,17 @@ use std::collections::{HashMap, VecDeque};
 use std::os::raw::{c_char, c_int, c_long, c_ulong};
 use std::slice;
 use std::sync::{Arc, Mutex};
+use std::time::Instant;
 
 use x11_dl::xinput2::{
     self, XIDeviceEvent, XIEnterEvent, XIFocusInEvent, XIFocusOutEvent, XIHierarchyEvent,
     XILeaveEvent, XIModifierState, XIRawEvent,
 };
 use x11_dl::xlib::{
-    self, Display as XDisplay, Window as XWindow, XAnyEvent, XClientMessageEvent, XConfigureEvent,
-    XDestroyWindowEvent, XEvent, XExposeEvent, XKeyEvent, XMapEvent, XPropertyEvent,
-    XReparentEvent, XSelectionEvent, XVisibilityEvent, XkbAnyEvent, XkbStateRec,
+    self, Display as XDisplay, Window as XWindow, XAnyEvent, XButtonEvent, XClientMessageEvent,
+    XConfigureEvent, XDestroyWindowEvent, XEvent, XExposeEvent, XKeyEvent, XMapEvent,
+    XPropertyEvent, XReparentEvent, XSelectionClearEvent, XSelectionEvent, XSelectionRequestEvent,
+    XVisibilityEvent, XkbAnyEvent, XkbStateRec,
 };
 use x11rb::protocol::xinput;
 use x11rb::protocol::xkb::ID as XkbId;
@@ -19,13 +21,16 @@ use x11rb::protocol::xproto::{self, ConnectionExt as _, ModMask};
 use x11rb::x11_utils::{ExtensionInformation, Serialize};
 use xkbcommon_dl::xkb_mod_mask_t;
 
+use crate::clipboard::MimeData;
+use crate::dnd::{DndAction, DndActions, DropData, URI_LIST_MIME_TYPE};
 use crate::dpi::{PhysicalPosition, PhysicalSize};
 use crate::event::{
     DeviceEvent, ElementState, Event, Ime, InnerSizeWriter, MouseButton, MouseScrollDelta,
-    RawKeyEvent, Touch, TouchPhase, WindowEvent,
+    RawKeyEvent, TabletToolButton, TabletToolEvent, Touch, TouchPhase, WindowEvent,
 };
 use crate::event_loop::ActiveEventLoop as RootAEL;
 use crate::keyboard::ModifiersState;
+use crate::platform_impl::common::gesture::Pinch;
 use crate::platform_impl::common::xkb::{self, XkbState};
 use crate::platform_impl::platform::common::xkb::Context;
 use crate::platform_impl::platform::x11::ime::{ImeEvent, ImeEventReceiver, ImeRequest};
@@ -34,18 +39,20 @@ use crate::platform_impl::platform::ActiveEventLoop as PlatformActiveEventLoop;
 use crate::platform_impl::x11::atoms::*;
 use crate::platform_impl::x11::util::cookie::GenericEventCookie;
 use crate::platform_impl::x11::{
-    mkdid, mkwid, util, CookieResultExt, Device, DeviceId, DeviceInfo, Dnd, DndState, ImeReceiver,
-    ScrollOrientation, UnownedWindow, WindowId,
+    ffi, mkdid, mkwid, util, Clipboard, CookieResultExt, Device, DeviceId, DeviceInfo, Dnd,
+    DndRequest, DndState, DragSource, ImeReceiver, PendingDrop, ScrollOrientation, UnownedWindow,
+    WindowId, X11Error, FINISH_TIMEOUT,
 };
 
 /// The maximum amount of X modifiers to replay.
 pub const MAX_MOD_REPLAY_LEN: usize = 32;
 
 /// The X11 documentation states: "Keycodes lie in the inclusive range `[8, 255]`".
-const KEYCODE_OFFSET: u8 = 8;
+pub(super) const KEYCODE_OFFSET: u8 = 8;
 
 pub struct EventProcessor {
     pub dnd: Dnd,
+    pub clipboard: Clipboard,
     pub ime_receiver: ImeReceiver,
     pub ime_event_receiver: ImeEventReceiver,
     pub randr_event_offset: u8,
@@ -72,6 +79,8 @@ pub struct EventProcessor {
     pub xfiltered_modifiers: VecDeque<u8>,
     pub xmodmap: util::ModifierKeymap,
     pub is_composing: bool,
+    /// The ongoing pinch gesture.
+    pub pinch: Pinch,
 }
 
 impl EventProcessor {
@@ -103,27 +112,37 @@ impl EventProcessor {
         // Drain IME events.
         while let Ok((window, event)) = self.ime_event_receiver.try_recv() {
             let window_id = mkwid(window as xproto::Window);
-            let event = match event {
-                ImeEvent::Enabled => WindowEvent::Ime(Ime::Enabled),
+            let (text, cursor, segments) = match event {
+                ImeEvent::Enabled => {
+                    let event = WindowEvent::Ime(Ime::Enabled);
+                    callback(&self.target, Event::WindowEvent { window_id, event });
+                    continue;
+                },
                 ImeEvent::Start => {
                     self.is_composing = true;
-                    WindowEvent::Ime(Ime::Preedit("".to_owned(), None))
+                    ("".to_owned(), None, Vec::new())
                 },
-                ImeEvent::Update(text, position) if self.is_composing => {
-                    WindowEvent::Ime(Ime::Preedit(text, Some((position, position))))
+                ImeEvent::Update(text, position, segments) if self.is_composing => {
+                    (text, Some((position, position)), segments)
                 },
                 ImeEvent::End => {
                     self.is_composing = false;
                     // Issue empty preedit on `Done`.
-                    WindowEvent::Ime(Ime::Preedit(String::new(), None))
+                    (String::new(), None, Vec::new())
                 },
                 ImeEvent::Disabled => {
                     self.is_composing = false;
-                    WindowEvent::Ime(Ime::Disabled)
+                    let event = WindowEvent::Ime(Ime::Disabled);
+                    callback(&self.target, Event::WindowEvent { window_id, event });
+                    continue;
                 },
                 _ => continue,
             };
 
+            // The styled preedit follows the plain one.
+            let event = WindowEvent::Ime(Ime::Preedit(text.clone(), cursor));
+            callback(&self.target, Event::WindowEvent { window_id, event });
+            let event = WindowEvent::Ime(Ime::StyledPreedit { text, cursor, segments });
             callback(&self.target, Event::WindowEvent { window_id, event });
         }
     }
@@ -187,6 +206,8 @@ impl EventProcessor {
         match event_type {
             xlib::ClientMessage => self.client_message(xev.as_ref(), &mut callback),
             xlib::SelectionNotify => self.selection_notify(xev.as_ref(), &mut callback),
+            xlib::SelectionRequest => self.selection_request(xev.as_ref()),
+            xlib::SelectionClear => self.selection_clear(xev.as_ref()),
             xlib::ConfigureNotify => self.configure_notify(xev.as_ref(), &mut callback),
             xlib::ReparentNotify => self.reparent_notify(xev.as_ref()),
             xlib::MapNotify => self.map_notify(xev.as_ref(), &mut callback),
@@ -194,6 +215,7 @@ impl EventProcessor {
             xlib::PropertyNotify => self.property_notify(xev.as_ref(), &mut callback),
             xlib::VisibilityNotify => self.visibility_notify(xev.as_ref(), &mut callback),
             xlib::Expose => self.expose(xev.as_ref(), &mut callback),
+            xlib::ButtonPress => self.popup_button_press(xev.as_ref(), &mut callback),
             // Note that in compose/pre-edit sequences, we'll always receive KeyRelease events.
             ty @ xlib::KeyPress | ty @ xlib::KeyRelease => {
                 let state = if ty == xlib::KeyPress {
@@ -230,6 +252,9 @@ impl EventProcessor {
                             &mut callback,
                         );
                         self.xinput2_button_input(xev, state, &mut callback);
+                        if state == ElementState::Released {
+                            self.drag_release(xev, &mut callback);
+                        }
                     },
                     xinput2::XI_Motion => {
                         let xev: &XIDeviceEvent = unsafe { xev.as_event() };
@@ -239,6 +264,7 @@ impl EventProcessor {
                             false,
                             &mut callback,
                         );
+                        self.drag_motion(xev);
                         self.xinput2_mouse_motion(xev, &mut callback);
                     },
                     xinput2::XI_Enter => {
@@ -274,6 +300,18 @@ impl EventProcessor {
                         let xev: &XIDeviceEvent = unsafe { xev.as_event() };
                         self.xinput2_touch(xev, phase, &mut callback);
                     },
+                    ffi::XI_GesturePinchBegin
+                    | ffi::XI_GesturePinchUpdate
+                    | ffi::XI_GesturePinchEnd => {
+                        let xev: &ffi::XIGesturePinchEvent = unsafe { xev.as_event() };
+                        self.xinput2_gesture_pinch(xev, evtype, &mut callback);
+                    },
+                    ffi::XI_GestureSwipeBegin
+                    | ffi::XI_GestureSwipeUpdate
+                    | ffi::XI_GestureSwipeEnd => {
+                        let xev: &ffi::XIGestureSwipeEvent = unsafe { xev.as_event() };
+                        self.xinput2_gesture_swipe(xev, evtype, &mut callback);
+                    },
                     xinput2::XI_RawButtonPress | xinput2::XI_RawButtonRelease => {
                         let state = match evtype {
                             xinput2::XI_RawButtonPress => ElementState::Pressed,
@@ -325,6 +363,34 @@ impl EventProcessor {
         result != 0
     }
 
+    /// The closest moment when one of the pending transfers times out.
+    pub fn next_deadline(&self) -> Option<Instant> {
+        let drag_deadline = self.dnd.drag_source.as_ref().and_then(|source| source.finish_deadline);
+        self.clipboard.next_deadline().into_iter().chain(drag_deadline).min()
+    }
+
+    /// Abandon the transfers which timed out, reporting their failure.
+    pub fn handle_expired<T: 'static, F>(&mut self, now: Instant, mut callback: F)
+    where
+        F: FnMut(&RootAEL, Event<T>),
+    {
+        for (window, done) in self.clipboard.take_expired(now) {
+            let event = WindowEvent::ClipboardDone { serial: done.serial, data: done.data };
+            callback(&self.target, Event::WindowEvent { window_id: mkwid(window), event });
+        }
+
+        let drag_expired = self
+            .dnd
+            .drag_source
+            .as_ref()
+            .and_then(|source| source.finish_deadline)
+            .is_some_and(|deadline| deadline <= now);
+        if let Some(source) = drag_expired.then(|| self.dnd.drag_source.take()).flatten() {
+            tracing::warn!("The drop target didn't answer with `XdndFinished`");
+            self.finish_drag(source, None, &mut callback);
+        }
+    }
+
     pub unsafe fn poll_one_event(&mut self, event_ptr: *mut XEvent) -> bool {
         let window_target = Self::window_target(&self.target);
         // This function is used to poll and remove a single event
@@ -358,8 +424,9 @@ impl EventProcessor {
         let window_target = Self::window_target(&self.target);
         let mut devices = self.devices.borrow_mut();
         if let Some(info) = DeviceInfo::get(&window_target.xconn, device as _) {
+            let atoms = window_target.xconn.atoms();
             for info in info.iter() {
-                devices.insert(DeviceId(info.deviceid as _), Device::new(info));
+                devices.insert(DeviceId(info.deviceid as _), Device::new(info, atoms));
             }
         }
     }
@@ -395,7 +462,6 @@ impl EventProcessor {
     pub fn window_target(window_target: &RootAEL) -> &ActiveEventLoop {
         match &window_target.p {
             PlatformActiveEventLoop::X(target) => target,
-            #[cfg(wayland_platform)]
             _ => unreachable!(),
         }
     }
@@ -404,7 +470,6 @@ impl EventProcessor {
     pub fn window_target_mut(window_target: &mut RootAEL) -> &mut ActiveEventLoop {
         match &mut window_target.p {
             PlatformActiveEventLoop::X(target) => target,
-            #[cfg(wayland_platform)]
             _ => unreachable!(),
         }
     }
@@ -451,6 +516,46 @@ impl EventProcessor {
             return;
         }
 
+        if xev.message_type == atoms[XdndStatus] as c_ulong {
+            let target_window = xev.data.get_long(0) as xproto::Window;
+            let accepted = xev.data.get_long(1) & 1 == 1;
+            let action = self.dnd.action_from_atom(xev.data.get_long(4) as xproto::Atom);
+            if let Some(source) = self.dnd.drag_source.as_mut() {
+                if source.target.is_some_and(|target| target.window == target_window) {
+                    source.accepted = accepted.then(|| action.unwrap_or(source.action));
+                }
+            }
+            return;
+        }
+
+        if xev.message_type == atoms[XdndFinished] as c_ulong {
+            let target_window = xev.data.get_long(0) as xproto::Window;
+            let source = match self.dnd.drag_source.take() {
+                Some(source)
+                    if source.dropped()
+                        && source.target.is_some_and(|target| target.window == target_window) =>
+                {
+                    source
+                },
+                source => {
+                    self.dnd.drag_source = source;
+                    return;
+                },
+            };
+
+            // The result of the drop is only reported since version 5.
+            let action = match source.target {
+                Some(target) if target.version >= 5 => {
+                    let accepted = xev.data.get_long(1) & 1 == 1;
+                    let action = self.dnd.action_from_atom(xev.data.get_long(2) as xproto::Atom);
+                    accepted.then(|| action.or(source.accepted).unwrap_or(source.action))
+                },
+                _ => source.accepted,
+            };
+            self.finish_drag(source, action, &mut callback);
+            return;
+        }
+
         if xev.message_type == atoms[XdndEnter] as c_ulong {
             let source_window = xev.data.get_long(0) as xproto::Window;
             let flags = xev.data.get_long(1);
@@ -458,14 +563,15 @@ impl EventProcessor {
             self.dnd.version = Some(version);
             let has_more_types = flags - (flags & (c_long::MAX - 1)) == 1;
             if !has_more_types {
-                let type_list = vec![
+                let type_list = [
                     xev.data.get_long(2) as xproto::Atom,
                     xev.data.get_long(3) as xproto::Atom,
                     xev.data.get_long(4) as xproto::Atom,
                 ];
-                self.dnd.type_list = Some(type_list);
+                let type_list = type_list.into_iter().filter(|&atom| atom != 0).collect();
+                self.dnd.set_type_list(type_list);
             } else if let Ok(more_types) = unsafe { self.dnd.get_type_list(source_window) } {
-                self.dnd.type_list = Some(more_types);
+                self.dnd.set_type_list(more_types);
             }
             return;
         }
@@ -475,9 +581,7 @@ impl EventProcessor {
             // over our window. We emit HoveredFile in response; while the macOS backend
             // does that upon a drag entering, XDND doesn't have access to the actual drop
             // data until this event. For parity with other platforms, we only emit
-            // `HoveredFile` the first time, though if winit's API is later extended to
-            // supply position updates with `HoveredFile` or another event, implementing
-            // that here would be trivial.
+            // `HoveredFile` the first time.
 
             let source_window = xev.data.get_long(0) as xproto::Window;
 
@@ -485,35 +589,40 @@ impl EventProcessor {
             // where `shift = mem::size_of::<c_short>() * 8`
             // Note that coordinates are in "desktop space", not "window space"
             // (in X11 parlance, they're root window coordinates)
-            // let packed_coordinates = xev.data.get_long(2);
-            // let shift = mem::size_of::<libc::c_short>() * 8;
-            // let x = packed_coordinates >> shift;
-            // let y = packed_coordinates & !(x << shift);
+            let packed_coordinates = xev.data.get_long(2);
+            let x = (packed_coordinates >> 16) as i16;
+            let y = packed_coordinates as i16;
+            let position = wt
+                .xconn
+                .xcb_connection()
+                .translate_coordinates(wt.root, window, x, y)
+                .ok()
+                .and_then(|cookie| cookie.reply().ok())
+                .map(|reply| PhysicalPosition::new(reply.dst_x as f64, reply.dst_y as f64))
+                .unwrap_or(self.dnd.position);
+            self.dnd.position = position;
 
             // By our own state flow, `version` should never be `None` at this point.
             let version = self.dnd.version.unwrap_or(5);
 
-            // Action is specified in versions 2 and up, though we don't need it anyway.
-            // let action = xev.data.get_long(4);
-
-            let accepted = if let Some(ref type_list) = self.dnd.type_list {
-                type_list.contains(&atoms[TextUriList])
+            // Action is specified in versions 2 and up.
+            let actions = if version >= 2 {
+                self.dnd.actions_from_atom(xev.data.get_long(4) as xproto::Atom)
             } else {
-                false
+                DndActions::COPY
             };
 
-            if !accepted {
-                unsafe {
-                    self.dnd
-                        .send_status(window, source_window, DndState::Rejected)
-                        .expect("Failed to send `XdndStatus` message.");
-                }
-                self.dnd.reset();
-                return;
-            }
+            let event = if self.dnd.target_window != Some(window) {
+                self.dnd.target_window = Some(window);
+                let mime_types = self.dnd.mime_types.clone();
+                WindowEvent::DragEntered { mime_types, actions, position }
+            } else {
+                WindowEvent::DragMoved { position }
+            };
+            callback(&self.target, Event::WindowEvent { window_id, event });
 
             self.dnd.source_window = Some(source_window);
-            if self.dnd.result.is_none() {
+            if self.dnd.has_files() && self.dnd.result.is_none() {
                 let time = if version >= 1 {
                     xev.data.get_long(3) as xproto::Timestamp
                 } else {
@@ -526,52 +635,80 @@ impl EventProcessor {
 
                 // This results in the `SelectionNotify` event below
                 unsafe {
-                    self.dnd.convert_selection(window, time);
+                    self.dnd.convert_selection(window, atoms[TextUriList], time);
                 }
             }
 
+            let state = match self.dnd.accepted() {
+                Some((.., action)) => DndState::Accepted(action),
+                None => DndState::Rejected,
+            };
             unsafe {
                 self.dnd
-                    .send_status(window, source_window, DndState::Accepted)
+                    .send_status(window, source_window, state)
                     .expect("Failed to send `XdndStatus` message.");
             }
             return;
         }
 
         if xev.message_type == atoms[XdndDrop] as c_ulong {
-            let (source_window, state) = if let Some(source_window) = self.dnd.source_window {
-                if let Some(Ok(ref path_list)) = self.dnd.result {
-                    for path in path_list {
-                        let event = Event::WindowEvent {
-                            window_id,
-                            event: WindowEvent::DroppedFile(path.clone()),
-                        };
-                        callback(&self.target, event);
+            let source_window = xev.data.get_long(0) as xproto::Window;
+            let accepted = self
+                .dnd
+                .accepted()
+                .map(|(target, mime_type, action)| (target, mime_type.to_owned(), action));
+
+            match accepted {
+                Some((target, mime_type, action)) => {
+                    let time = if self.dnd.version.unwrap_or(5) >= 1 {
+                        xev.data.get_long(2) as xproto::Timestamp
+                    } else {
+                        x11rb::CURRENT_TIME
+                    };
+                    wt.xconn.set_timestamp(time);
+
+                    // The drop is finished once the data arrives with `SelectionNotify`.
+                    self.dnd.pending_drop =
+                        Some(PendingDrop { source_window, target, mime_type, action });
+                    unsafe {
+                        self.dnd.convert_selection(window, target, time);
+                    }
+                },
+                None => {
+                    unsafe {
+                        self.dnd
+                            .send_finished(window, source_window, DndState::Rejected)
+                            .expect("Failed to send `XdndFinished` message.");
                     }
-                }
-                (source_window, DndState::Accepted)
-            } else {
-                // `source_window` won't be part of our DND state if we already rejected the drop in
-                // our `XdndPosition` handler.
-                let source_window = xev.data.get_long(0) as xproto::Window;
-                (source_window, DndState::Rejected)
-            };
 
-            unsafe {
-                self.dnd
-                    .send_finished(window, source_window, state)
-                    .expect("Failed to send `XdndFinished` message.");
+                    self.dnd_leave(window_id, &mut callback);
+                },
             }
-
-            self.dnd.reset();
             return;
         }
 
         if xev.message_type == atoms[XdndLeave] as c_ulong {
-            self.dnd.reset();
+            self.dnd_leave(window_id, &mut callback);
+        }
+    }
+
+    /// Forget the drag over the window, telling it that the drag left without a drop.
+    fn dnd_leave<T: 'static, F>(&mut self, window_id: crate::window::WindowId, mut callback: F)
+    where
+        F: FnMut(&RootAEL, Event<T>),
+    {
+        let entered = self.dnd.target_window.is_some();
+        let has_files = self.dnd.has_files();
+        self.dnd.reset();
+
+        if has_files {
             let event = Event::WindowEvent { window_id, event: WindowEvent::HoveredFileCancelled };
             callback(&self.target, event);
         }
+        if entered {
+            let event = Event::WindowEvent { window_id, event: WindowEvent::DragLeft };
+            callback(&self.target, event);
+        }
     }
 
     fn selection_notify<T: 'static, F>(&mut self, xev: &XSelectionEvent, mut callback: F)
@@ -587,7 +724,69 @@ impl EventProcessor {
         // Set the timestamp.
         wt.xconn.set_timestamp(xev.time as xproto::Timestamp);
 
-        if xev.property != atoms[XdndSelection] as c_ulong {
+        if xev.selection != atoms[XdndSelection] as c_ulong {
+            let done = self.clipboard.handle_selection_notify(
+                window,
+                xev.selection as xproto::Atom,
+                xev.property as xproto::Atom,
+            );
+            if let Some(done) = done {
+                let event = WindowEvent::ClipboardDone { serial: done.serial, data: done.data };
+                callback(&self.target, Event::WindowEvent { window_id, event });
+            }
+            return;
+        }
+
+        if let Some(pending_drop) = self.dnd.pending_drop.take() {
+            if xev.target as xproto::Atom != pending_drop.target {
+                self.dnd.pending_drop = Some(pending_drop);
+                return;
+            }
+
+            let data = if xev.property == atoms[XdndSelection] as c_ulong {
+                self.dnd
+                    .read_drop_data(window)
+                    .map_err(|err| tracing::warn!("Failed to read the dropped data: {err}"))
+                    .ok()
+            } else {
+                None
+            };
+
+            let state = match data {
+                Some(data) => {
+                    if pending_drop.mime_type == URI_LIST_MIME_TYPE {
+                        for path in self.dnd.parse_data(&mut data.clone()).unwrap_or_default() {
+                            let event = WindowEvent::DroppedFile(path);
+                            callback(&self.target, Event::WindowEvent { window_id, event });
+                        }
+                    }
+
+                    let data =
+                        DropData::from_mime_data(MimeData::new(pending_drop.mime_type, data));
+                    let action = pending_drop.action;
+                    let position = self.dnd.position;
+                    let event = WindowEvent::DragDropped { data, action, position };
+                    callback(&self.target, Event::WindowEvent { window_id, event });
+                    DndState::Accepted(action)
+                },
+                None => DndState::Rejected,
+            };
+
+            unsafe {
+                self.dnd
+                    .send_finished(window, pending_drop.source_window, state)
+                    .expect("Failed to send `XdndFinished` message.");
+            }
+
+            match state {
+                DndState::Accepted(_) => self.dnd.reset(),
+                DndState::Rejected => self.dnd_leave(window_id, &mut callback),
+            }
+            return;
+        }
+
+        // The drag has already left.
+        if xev.property != atoms[XdndSelection] as c_ulong || self.dnd.source_window.is_none() {
             return;
         }
 
@@ -608,6 +807,189 @@ impl EventProcessor {
         }
     }
 
+    /// Handle the drag and drop request from the window.
+    pub fn handle_dnd_request<T: 'static, F>(&mut self, request: DndRequest, mut callback: F)
+    where
+        F: FnMut(&RootAEL, Event<T>),
+    {
+        match request {
+            DndRequest::Respond { window, response } => self.dnd.set_response(window, response),
+            DndRequest::StartDrag { window, data, actions } => {
+                let started = self.start_drag(window, data, actions).unwrap_or_else(|err| {
+                    tracing::warn!("Failed to start the drag: {err}");
+                    false
+                });
+                if !started {
+                    let event = WindowEvent::DragFinished { action: None };
+                    callback(&self.target, Event::WindowEvent { window_id: mkwid(window), event });
+                }
+            },
+        }
+    }
+
+    /// Offer the data through the `XdndSelection`, the drag follows the implicit pointer grab.
+    fn start_drag(
+        &mut self,
+        window: xproto::Window,
+        data: Vec<MimeData>,
+        actions: DndActions,
+    ) -> Result<bool, X11Error> {
+        // Only a single drag can be in progress.
+        if self.dnd.drag_source.is_some() {
+            return Ok(false);
+        }
+
+        let wt = Self::window_target(&self.target);
+        let atoms = wt.xconn.atoms();
+        let type_list = match self.clipboard.own_selection(window, atoms[XdndSelection], data)? {
+            Some(type_list) => type_list,
+            None => return Ok(false),
+        };
+
+        wt.xconn
+            .change_property(
+                window,
+                atoms[XdndTypeList],
+                xproto::AtomEnum::ATOM.into(),
+                xproto::PropMode::REPLACE,
+                &type_list,
+            )?
+            .ignore_error();
+
+        let action = [DndAction::Copy, DndAction::Move, DndAction::Link]
+            .into_iter()
+            .find(|&action| actions.contains(action.into()))
+            .unwrap_or(DndAction::Copy);
+        self.dnd.drag_source = Some(DragSource::new(window, type_list, action));
+
+        Ok(true)
+    }
+
+    /// Track the target under the pointer while dragging.
+    fn drag_motion(&mut self, event: &XIDeviceEvent) {
+        let mut source = match self.dnd.drag_source.take() {
+            Some(source) if !source.dropped() => source,
+            source => {
+                self.dnd.drag_source = source;
+                return;
+            },
+        };
+
+        let wt = Self::window_target(&self.target);
+        let position = (event.root_x as i16, event.root_y as i16);
+        let target = self.dnd.find_target(&mut source.targets, wt.root, position.0, position.1);
+        let result = (|| {
+            if target != source.target {
+                if let Some(target) = source.target {
+                    self.dnd.send_leave(&source, &target)?;
+                }
+
+                source.target = target;
+                source.accepted = None;
+                if let Some(target) = target {
+                    self.dnd.send_enter(&source, &target)?;
+                }
+            }
+
+            if let Some(target) = source.target {
+                self.dnd.send_position(&source, &target, position, event.time as _)?;
+            }
+
+            Ok::<_, X11Error>(())
+        })();
+
+        if let Err(err) = result {
+            tracing::warn!("Failed to update the drag target: {err}");
+        }
+
+        self.dnd.drag_source = Some(source);
+    }
+
+    /// Drop on the target when it accepts the drag, cancel the drag otherwise.
+    fn drag_release<T: 'static, F>(&mut self, event: &XIDeviceEvent, mut callback: F)
+    where
+        F: FnMut(&RootAEL, Event<T>),
+    {
+        let mut source = match self.dnd.drag_source.take() {
+            Some(source) if !source.dropped() => source,
+            source => {
+                self.dnd.drag_source = source;
+                return;
+            },
+        };
+
+        match source.target {
+            Some(target) if source.accepted.is_some() => {
+                match self.dnd.send_drop(&source, &target, event.time as _) {
+                    Ok(()) => {
+                        source.finish_deadline = Some(Instant::now() + FINISH_TIMEOUT);
+                        self.dnd.drag_source = Some(source);
+                    },
+                    Err(err) => {
+                        tracing::warn!("Failed to send `XdndDrop` message: {err}");
+                        self.finish_drag(source, None, &mut callback);
+                    },
+                }
+            },
+            target => {
+                if let Some(target) = target {
+                    if let Err(err) = self.dnd.send_leave(&source, &target) {
+                        tracing::warn!("Failed to send `XdndLeave` message: {err}");
+                    }
+                }
+                self.finish_drag(source, None, &mut callback);
+            },
+        }
+    }
+
+    fn finish_drag<T: 'static, F>(
+        &mut self,
+        source: DragSource,
+        action: Option<DndAction>,
+        mut callback: F,
+    ) where
+        F: FnMut(&RootAEL, Event<T>),
+    {
+        let wt = Self::window_target(&self.target);
+        let atoms = wt.xconn.atoms();
+        if let Err(err) =
+            self.clipboard.own_selection(source.window, atoms[XdndSelection], Vec::new())
+        {
+            tracing::warn!("Failed to release the `XdndSelection`: {err}");
+        }
+
+        let event = WindowEvent::DragFinished { action };
+        callback(&self.target, Event::WindowEvent { window_id: mkwid(source.window), event });
+    }
+
+    fn selection_request(&mut self, xev: &XSelectionRequestEvent) {
+        let wt = Self::window_target(&self.target);
+
+        // Set the timestamp.
+        wt.xconn.set_timestamp(xev.time as xproto::Timestamp);
+
+        let event = xproto::SelectionRequestEvent {
+            response_type: xproto::SELECTION_REQUEST_EVENT,
+            sequence: 0,
+            time: xev.time as xproto::Timestamp,
+            owner: xev.owner as xproto::Window,
+            requestor: xev.requestor as xproto::Window,
+            selection: xev.selection as xproto::Atom,
+            target: xev.target as xproto::Atom,
+            property: xev.property as xproto::Atom,
+        };
+
+        let own_requestor = self.window_exists(event.requestor);
+        if let Err(err) = self.clipboard.handle_selection_request(&event, own_requestor) {
+            tracing::warn!("Failed to answer the selection request: {err}");
+        }
+    }
+
+    fn selection_clear(&mut self, xev: &XSelectionClearEvent) {
+        self.clipboard
+            .handle_selection_clear(xev.window as xproto::Window, xev.selection as xproto::Atom);
+    }
+
     fn configure_notify<T: 'static, F>(&self, xev: &XConfigureEvent, mut callback: F)
     where
         F: FnMut(&RootAEL, Event<T>),
@@ -810,6 +1192,9 @@ impl EventProcessor {
         let window = xev.window as xproto::Window;
         let window_id = mkwid(window);
 
+        // The popups can only be grabbed once they're viewable.
+        self.with_window(window, |window| window.grab_popup());
+
         // NOTE: Re-issue the focus state when mapping the window.
         //
         // The purpose of it is to deliver initial focused state of the newly created
@@ -821,7 +1206,30 @@ impl EventProcessor {
         callback(&self.target, event);
     }
 
-    fn destroy_notify<T: 'static, F>(&self, xev: &XDestroyWindowEvent, mut callback: F)
+    /// Handle the core button press, which is reported to the popup holding the pointer grab.
+    ///
+    /// The windows of the application don't select the core button events, so the clicks on
+    /// them may be reported to the popup as well, and they don't dismiss it.
+    fn popup_button_press<T: 'static, F>(&self, xev: &XButtonEvent, mut callback: F)
+    where
+        F: FnMut(&RootAEL, Event<T>),
+    {
+        let window = xev.window as xproto::Window;
+        let root = xev.root as xproto::Window;
+        if self.window_at(root, xev.x_root as i16, xev.y_root as i16).is_some() {
+            return;
+        }
+
+        let dismissed =
+            self.with_window(window, |window| window.dismiss_popup(xev.x, xev.y)).unwrap_or(false);
+        if dismissed {
+            let event =
+                Event::WindowEvent { window_id: mkwid(window), event: WindowEvent::PopupDismissed };
+            callback(&self.target, event);
+        }
+    }
+
+    fn destroy_notify<T: 'static, F>(&mut self, xev: &XDestroyWindowEvent, mut callback: F)
     where
         F: FnMut(&RootAEL, Event<T>),
     {
@@ -834,6 +1242,8 @@ impl EventProcessor {
         // cleanup again here.
         wt.windows.borrow_mut().remove(&WindowId(window as _));
 
+        self.clipboard.remove_window(window);
+
         // Since all XIM stuff needs to happen from the same thread, we destroy the input
         // context here instead of when dropping the window.
         if let Some(ime) = wt.ime.as_ref() {
@@ -852,11 +1262,22 @@ impl EventProcessor {
         let wt = Self::window_target(&self.target);
         let atoms = wt.x_connection().atoms();
         let atom = xev.atom as xproto::Atom;
+        let window = xev.window as xproto::Window;
+        let state = xproto::Property::from(xev.state as u8);
+
+        if self.clipboard.handle_requestor_property_notify(window, atom, state) {
+            return;
+        }
 
         if atom == xproto::Atom::from(xproto::AtomEnum::RESOURCE_MANAGER)
             || atom == atoms[_XSETTINGS_SETTINGS]
         {
             self.process_dpi_change(&mut callback);
+        } else if atom == atoms[_WINIT_SELECTION] {
+            if let Some(done) = self.clipboard.handle_property_notify(window, atom, state) {
+                let event = WindowEvent::ClipboardDone { serial: done.serial, data: done.data };
+                callback(&self.target, Event::WindowEvent { window_id: mkwid(window), event });
+            }
         }
     }
 
@@ -974,6 +1395,24 @@ impl EventProcessor {
 
             if let Some(mut key_processor) = self.xkb_context.key_context() {
                 let event = key_processor.process_key_event(keycode, state, repeat);
+
+                // Surface the pending compose sequence to text fields before the key finishing
+                // it.
+                let wt = Self::window_target(&self.target);
+                let ime_allowed = wt
+                    .ime
+                    .as_ref()
+                    .is_some_and(|ime| ime.borrow().is_ime_allowed(window as XWindow));
+                let preedit = self.xkb_context.take_compose_preedit();
+                if let Some(text) = preedit.filter(|_| ime_allowed) {
+                    let cursor = (!text.is_empty()).then_some((text.len(), text.len()));
+                    let event = Event::WindowEvent {
+                        window_id,
+                        event: WindowEvent::Ime(Ime::Preedit(text, cursor)),
+                    };
+                    callback(&self.target, event);
+                }
+
                 let event = Event::WindowEvent {
                     window_id,
                     event: WindowEvent::KeyboardInput { device_id, event, is_synthetic: false },
@@ -1064,7 +1503,7 @@ impl EventProcessor {
             return;
         }
 
-        let event = match event.detail as u32 {
+        let window_event = match event.detail as u32 {
             xlib::Button1 => {
                 WindowEvent::MouseInput { device_id, state, button: MouseButton::Left }
             },
@@ -1097,8 +1536,33 @@ impl EventProcessor {
             x => WindowEvent::MouseInput { device_id, state, button: MouseButton::Other(x as u16) },
         };
 
-        let event = Event::WindowEvent { window_id, event };
-        callback(&self.target, event);
+        let window_event = Event::WindowEvent { window_id, event: window_event };
+        callback(&self.target, window_event);
+
+        // The tablet tools report their tip and barrel buttons as the mouse buttons.
+        let tablet_event = match event.detail as u32 {
+            4..=7 => return,
+            xlib::Button1 => TabletToolEvent::Tip(state),
+            xlib::Button2 => TabletToolEvent::Button { button: TabletToolButton::Primary, state },
+            xlib::Button3 => TabletToolEvent::Button { button: TabletToolButton::Secondary, state },
+            x => TabletToolEvent::Button { button: TabletToolButton::Other(x), state },
+        };
+
+        let window = event.event as xproto::Window;
+        let mut events = Vec::new();
+        let mut devices = self.devices.borrow_mut();
+        if let Some(tablet_tool) = devices
+            .get_mut(&DeviceId(event.sourceid as xinput::DeviceId))
+            .and_then(|device| device.tablet_tool.as_mut())
+        {
+            tablet_tool.enter(window, device_id, &mut events);
+            events.push(tablet_tool.event(window, device_id, tablet_event));
+        }
+        drop(devices);
+
+        for event in events {
+            callback(&self.target, event);
+        }
     }
 
     fn xinput2_mouse_motion<T: 'static, F>(&self, event: &XIDeviceEvent, mut callback: F)
@@ -1138,12 +1602,24 @@ impl EventProcessor {
             slice::from_raw_parts(event.valuators.mask, event.valuators.mask_len as usize)
         };
         let mut devices = self.devices.borrow_mut();
-        let physical_device = match devices.get_mut(&DeviceId(event.sourceid as xinput::DeviceId)) {
+        let source_id = DeviceId(event.sourceid as xinput::DeviceId);
+
+        // The tablet tools sharing the pointer with the moving device left the proximity.
+        let mut events = Vec::new();
+        for (_, device) in devices
+            .iter_mut()
+            .filter(|(id, device)| **id != source_id && device.attachment == event.deviceid)
+        {
+            if let Some(tablet_tool) = device.tablet_tool.as_mut() {
+                tablet_tool.leave(device_id, &mut events);
+            }
+        }
+
+        let physical_device = match devices.get_mut(&source_id) {
             Some(device) => device,
             None => return,
         };
 
-        let mut events = Vec::new();
         let mut value = event.valuators.values;
         for i in 0..event.valuators.mask_len * 8 {
             if !xinput2::XIMaskIsSet(mask, i) {
@@ -1152,6 +1628,12 @@ impl EventProcessor {
 
             let x = unsafe { *value };
 
+            if let Some(tablet_tool) = physical_device.tablet_tool.as_mut() {
+                if let Some((_, axis)) = tablet_tool.axes.iter().find(|(axis, _)| *axis == i) {
+                    axis.update(&mut tablet_tool.values, x);
+                }
+            }
+
             let event = if let Some(&mut (_, ref mut info)) =
                 physical_device.scroll_axes.iter_mut().find(|&&mut (axis, _)| axis == i as _)
             {
@@ -1175,6 +1657,13 @@ impl EventProcessor {
             value = unsafe { value.offset(1) };
         }
 
+        if let Some(tablet_tool) = physical_device.tablet_tool.as_mut() {
+            let position = PhysicalPosition::new(event.event_x, event.event_y);
+            let motion = TabletToolEvent::Motion { position, axes: tablet_tool.values };
+            tablet_tool.enter(window, device_id, &mut events);
+            events.push(tablet_tool.event(window, device_id, motion));
+        }
+
         for event in events {
             callback(&self.target, event);
         }
@@ -1239,11 +1728,26 @@ impl EventProcessor {
         // Leave, FocusIn, and FocusOut can be received by a window that's already
         // been destroyed, which the user presumably doesn't want to deal with.
         if self.window_exists(window) {
+            let device_id = mkdid(event.deviceid as xinput::DeviceId);
+
+            let mut events = Vec::new();
+            let mut devices = self.devices.borrow_mut();
+            if let Some(tablet_tool) = devices
+                .get_mut(&DeviceId(event.sourceid as xinput::DeviceId))
+                .and_then(|device| device.tablet_tool.as_mut())
+                .filter(|tablet_tool| tablet_tool.proximity == Some(window))
+            {
+                tablet_tool.leave(device_id, &mut events);
+            }
+            drop(devices);
+
+            for event in events {
+                callback(&self.target, event);
+            }
+
             let event = Event::WindowEvent {
                 window_id: mkwid(window),
-                event: WindowEvent::CursorLeft {
-                    device_id: mkdid(event.deviceid as xinput::DeviceId),
-                },
+                event: WindowEvent::CursorLeft { device_id },
             };
             callback(&self.target, event);
         }
@@ -1352,6 +1856,18 @@ impl EventProcessor {
             // window regains focus.
             self.held_key_press = None;
 
+            // Cancel the compose sequence, the keys finishing it go to another window.
+            self.xkb_context.reset_compose();
+            let ime_allowed =
+                wt.ime.as_ref().is_some_and(|ime| ime.borrow().is_ime_allowed(window as XWindow));
+            if self.xkb_context.take_compose_preedit().is_some() && ime_allowed {
+                let event = Event::WindowEvent {
+                    window_id,
+                    event: WindowEvent::Ime(Ime::Preedit(String::new(), None)),
+                };
+                callback(&self.target, event);
+            }
+
             if let Some(window) = self.with_window(window, Arc::clone) {
                 window.shared_state_lock().has_focus = false;
             }
@@ -1395,13 +1911,26 @@ impl EventProcessor {
                 callback(&self.target, event);
             }
 
+            let root = wt.xconn.default_root();
+            let root_size = (root.width_in_pixels as f64, root.height_in_pixels as f64);
+            let (force, contact) = match self
+                .devices
+                .borrow_mut()
+                .get_mut(&DeviceId(xev.sourceid as xinput::DeviceId))
+                .and_then(|device| device.touch.as_mut())
+            {
+                Some(touch) => touch.update(xev, phase, root_size),
+                None => (None, None),
+            };
+
             let event = Event::WindowEvent {
                 window_id,
                 event: WindowEvent::Touch(Touch {
                     device_id: mkdid(xev.deviceid as xinput::DeviceId),
                     phase,
                     location,
-                    force: None, // TODO
+                    force,
+                    contact,
                     id,
                 }),
             };
@@ -1409,6 +1938,76 @@ impl EventProcessor {
         }
     }
 
+    fn xinput2_gesture_pinch<T: 'static, F>(
+        &mut self,
+        xev: &ffi::XIGesturePinchEvent,
+        evtype: c_int,
+        mut callback: F,
+    ) where
+        F: FnMut(&RootAEL, Event<T>),
+    {
+        let wt = Self::window_target(&self.target);
+
+        // Set the timestamp.
+        wt.xconn.set_timestamp(xev.time as xproto::Timestamp);
+
+        let window = xev.event as xproto::Window;
+        if !self.window_exists(window) {
+            return;
+        }
+
+        let (scale_delta, rotation_delta, phase) = match evtype {
+            ffi::XI_GesturePinchBegin => {
+                self.pinch.begin();
+                (0., 0., TouchPhase::Started)
+            },
+            ffi::XI_GesturePinchUpdate => {
+                let (scale_delta, rotation_delta) = self.pinch.update(xev.scale, xev.delta_angle);
+                (scale_delta, rotation_delta, TouchPhase::Moved)
+            },
+            _ => (0., 0., gesture_end_phase(xev.flags & ffi::XIGesturePinchEventCancelled)),
+        };
+
+        let window_id = mkwid(window);
+        let device_id = mkdid(xev.deviceid as xinput::DeviceId);
+        let event = WindowEvent::PinchGesture { device_id, delta: scale_delta, phase };
+        callback(&self.target, Event::WindowEvent { window_id, event });
+        let event = WindowEvent::RotationGesture { device_id, delta: rotation_delta, phase };
+        callback(&self.target, Event::WindowEvent { window_id, event });
+    }
+
+    fn xinput2_gesture_swipe<T: 'static, F>(
+        &self,
+        xev: &ffi::XIGestureSwipeEvent,
+        evtype: c_int,
+        mut callback: F,
+    ) where
+        F: FnMut(&RootAEL, Event<T>),
+    {
+        let wt = Self::window_target(&self.target);
+
+        // Set the timestamp.
+        wt.xconn.set_timestamp(xev.time as xproto::Timestamp);
+
+        let window = xev.event as xproto::Window;
+        if !self.window_exists(window) {
+            return;
+        }
+
+        let (delta, phase) = match evtype {
+            ffi::XI_GestureSwipeBegin => ((0., 0.), TouchPhase::Started),
+            ffi::XI_GestureSwipeUpdate => ((xev.delta_x, xev.delta_y), TouchPhase::Moved),
+            _ => ((0., 0.), gesture_end_phase(xev.flags & ffi::XIGestureSwipeEventCancelled)),
+        };
+
+        let event = WindowEvent::PanGesture {
+            device_id: mkdid(xev.deviceid as xinput::DeviceId),
+            delta: PhysicalPosition::new(delta.0 as f32, delta.1 as f32),
+            phase,
+        };
+        callback(&self.target, Event::WindowEvent { window_id: mkwid(window), event });
+    }
+
     fn xinput2_raw_button_input<T: 'static, F>(
         &self,
         xev: &XIRawEvent,
@@ -1569,30 +2168,29 @@ impl EventProcessor {
                     self.xkb_context.set_keymap_from_x11(xcb);
                     self.xmodmap.reload_from_x_connection(&wt.xconn);
 
-                    let window_id = match self.active_window.map(super::mkwid) {
-                        Some(window_id) => window_id,
-                        None => return,
-                    };
-
-                    if let Some(state) = self.xkb_context.state_mut() {
-                        let mods = state.modifiers().into();
-                        self.send_modifiers(window_id, mods, true, &mut callback);
+                    if let Some(window_id) = self.active_window.map(super::mkwid) {
+                        if let Some(state) = self.xkb_context.state_mut() {
+                            let mods = state.modifiers().into();
+                            self.send_modifiers(window_id, mods, true, &mut callback);
+                        }
                     }
+
+                    self.update_keyboard_layouts(&mut callback);
                 }
             },
             xlib::XkbMapNotify => {
                 let xcb = wt.xconn.xcb_connection().get_raw_xcb_connection();
                 self.xkb_context.set_keymap_from_x11(xcb);
                 self.xmodmap.reload_from_x_connection(&wt.xconn);
-                let window_id = match self.active_window.map(super::mkwid) {
-                    Some(window_id) => window_id,
-                    None => return,
-                };
 
-                if let Some(state) = self.xkb_context.state_mut() {
-                    let mods = state.modifiers().into();
-                    self.send_modifiers(window_id, mods, true, &mut callback);
+                if let Some(window_id) = self.active_window.map(super::mkwid) {
+                    if let Some(state) = self.xkb_context.state_mut() {
+                        let mods = state.modifiers().into();
+                        self.send_modifiers(window_id, mods, true, &mut callback);
+                    }
                 }
+
+                self.update_keyboard_layouts(&mut callback);
             },
             xlib::XkbStateNotify => {
                 let xev = unsafe { &*(xev as *const _ as *const xlib::XkbStateNotifyEvent) };
@@ -1610,19 +2208,42 @@ impl EventProcessor {
                         xev.locked_group as u32,
                     );
 
-                    let window_id = match self.active_window.map(super::mkwid) {
-                        Some(window_id) => window_id,
-                        None => return,
-                    };
-
                     let mods = state.modifiers().into();
-                    self.send_modifiers(window_id, mods, true, &mut callback);
+                    if let Some(window_id) = self.active_window.map(super::mkwid) {
+                        self.send_modifiers(window_id, mods, true, &mut callback);
+                    }
+
+                    self.update_keyboard_layouts(&mut callback);
                 }
             },
             _ => {},
         }
     }
 
+    /// Report the changes of the keyboard layouts to the focused window.
+    fn update_keyboard_layouts<T: 'static, F>(&mut self, mut callback: F)
+    where
+        F: FnMut(&RootAEL, Event<T>),
+    {
+        let mut layouts = match self.xkb_context.take_keyboard_layouts_update() {
+            Some(layouts) => layouts,
+            None => return,
+        };
+
+        let wt = Self::window_target(&self.target);
+        wt.xconn.name_keyboard_layouts(&mut layouts);
+        *wt.keyboard_layouts.borrow_mut() = Some(layouts.clone());
+
+        let window_id = match self.active_window.map(super::mkwid) {
+            Some(window_id) => window_id,
+            None => return,
+        };
+
+        let event =
+            Event::WindowEvent { window_id, event: WindowEvent::KeyboardLayoutChanged(layouts) };
+        callback(&self.target, event);
+    }
+
     pub fn update_mods_from_xinput2_event<T: 'static, F>(
         &mut self,
         mods: &XIModifierState,
@@ -1865,6 +2486,39 @@ impl EventProcessor {
     fn window_exists(&self, window_id: xproto::Window) -> bool {
         self.with_window(window_id, |_| ()).is_some()
     }
+
+    /// The window of the application at the position relative to the root window, unless the
+    /// windows of the other clients are on top of it.
+    fn window_at(&self, root: xproto::Window, x: i16, y: i16) -> Option<xproto::Window> {
+        let xconn = Self::window_target(&self.target).x_connection();
+        let mut window = root;
+        loop {
+            // Descend through the frames of the window manager down to the client window.
+            let child = xconn
+                .xcb_connection()
+                .translate_coordinates(root, window, x, y)
+                .ok()?
+                .reply()
+                .ok()?
+                .child;
+            if child == x11rb::NONE {
+                return None;
+            }
+            if self.window_exists(child) {
+                return Some(child);
+            }
+            window = child;
+        }
+    }
+}
+
+/// The phase of the gesture once it ended.
+fn gesture_end_phase(cancelled: c_int) -> TouchPhase {
+    if cancelled == 0 {
+        TouchPhase::Ended
+    } else {
+        TouchPhase::Cancelled
+    }
 }
 
 fn is_first_touch(first: &mut Option<u64>, num: &mut u32, id: u64, phase: TouchPhase) -> bool {
@@ -1,11 +1,12 @@
 use std::ffi::CStr;
 use std::os::raw::c_short;
 use std::sync::Arc;
-use std::{mem, ptr};
+use std::{mem, ptr, slice};
 
 use x11_dl::xlib::{XIMCallback, XIMPreeditCaretCallbackStruct, XIMPreeditDrawCallbackStruct};
 
 use super::{ffi, util, XConnection, XError};
+use crate::event::{PreeditSegment, PreeditStyle};
 use crate::platform_impl::platform::x11::ime::input_method::{InputMethod, Style, XIMStyle};
 use crate::platform_impl::platform::x11::ime::{ImeEvent, ImeEventSender};
 
@@ -37,6 +38,7 @@ extern "C" fn preedit_start_callback(
     let client_data = unsafe { &mut *(client_data as *mut ImeContextClientData) };
 
     client_data.text.clear();
+    client_data.feedback.clear();
     client_data.cursor_pos = 0;
     client_data
         .event_sender
@@ -55,6 +57,7 @@ extern "C" fn preedit_done_callback(
 
     // Drop text buffer and reset cursor position on done.
     client_data.text = Vec::new();
+    client_data.feedback = Vec::new();
     client_data.cursor_pos = 0;
 
     client_data
@@ -67,6 +70,41 @@ fn calc_byte_position(text: &[char], pos: usize) -> usize {
     text.iter().take(pos).fold(0, |byte_pos, text| byte_pos + text.len_utf8())
 }
 
+/// Group the characters of the preedit drawn with the same style into segments.
+fn preedit_segments(text: &[char], feedback: &[ffi::XIMFeedback]) -> Vec<PreeditSegment> {
+    let mut segments: Vec<PreeditSegment> = Vec::new();
+    let mut byte_pos = 0;
+    for (ch, &feedback) in text.iter().zip(feedback) {
+        let start = byte_pos;
+        byte_pos += ch.len_utf8();
+
+        let mut style = PreeditStyle::empty();
+        style.set(PreeditStyle::REVERSE, feedback & ffi::XIMReverse != 0);
+        style.set(PreeditStyle::UNDERLINE, feedback & ffi::XIMUnderline != 0);
+        style.set(PreeditStyle::HIGHLIGHT, feedback & ffi::XIMHighlight != 0);
+        if style.is_empty() {
+            continue;
+        }
+
+        match segments.last_mut() {
+            Some(last) if last.end == start && last.style == style => last.end = byte_pos,
+            _ => segments.push(PreeditSegment { start, end: byte_pos, style }),
+        }
+    }
+
+    segments
+}
+
+impl ImeContextClientData {
+    /// Send the current preedit.
+    fn send_update(&self) {
+        let cursor_byte_pos = calc_byte_position(&self.text, self.cursor_pos);
+        let segments = preedit_segments(&self.text, &self.feedback);
+        let event = ImeEvent::Update(self.text.iter().collect(), cursor_byte_pos, segments);
+        self.event_sender.send((self.window, event)).expect("failed to send preedit update event");
+    }
+}
+
 /// Preedit text information to be drawn inline by the client.
 extern "C" fn preedit_draw_callback(
     _xim: ffi::XIM,
@@ -90,37 +128,51 @@ extern "C" fn preedit_draw_callback(
     }
 
     // NULL indicate text deletion
-    let mut new_chars = if call_data.text.is_null() {
-        Vec::new()
+    let (mut new_chars, new_feedback) = if call_data.text.is_null() {
+        (Vec::new(), &[][..])
     } else {
         let xim_text = unsafe { &mut *(call_data.text) };
         if xim_text.encoding_is_wchar > 0 {
             return;
         }
 
+        let new_feedback = if xim_text.feedback.is_null() {
+            &[][..]
+        } else {
+            unsafe { slice::from_raw_parts(xim_text.feedback, xim_text.length as usize) }
+        };
+
         let new_text = unsafe { xim_text.string.multi_byte };
 
+        // NULL string indicate that only the styles changed.
         if new_text.is_null() {
+            let end = (chg_range.start + new_feedback.len()).min(client_data.feedback.len());
+            let new_feedback = &new_feedback[..end - chg_range.start];
+            client_data.feedback[chg_range.start..end].copy_from_slice(new_feedback);
+            client_data.send_update();
             return;
         }
 
         let new_text = unsafe { CStr::from_ptr(new_text) };
 
-        String::from(new_text.to_str().expect("Invalid UTF-8 String from IME")).chars().collect()
+        let new_text = String::from(new_text.to_str().expect("Invalid UTF-8 String from IME"));
+        (new_text.chars().collect::<Vec<_>>(), new_feedback)
     };
+
+    // Unstyled characters when the styles are missing.
+    let mut new_feedback: Vec<_> =
+        (0..new_chars.len()).map(|i| new_feedback.get(i).copied().unwrap_or(0)).collect();
+    let mut old_feedback_tail = client_data.feedback.split_off(chg_range.end);
+    client_data.feedback.truncate(chg_range.start);
+    client_data.feedback.append(&mut new_feedback);
+    client_data.feedback.append(&mut old_feedback_tail);
+
     let mut old_text_tail = client_data.text.split_off(chg_range.end);
     client_data.text.truncate(chg_range.start);
     client_data.text.append(&mut new_chars);
     client_data.text.append(&mut old_text_tail);
-    let cursor_byte_pos = calc_byte_position(&client_data.text, client_data.cursor_pos);
 
-    client_data
-        .event_sender
-        .send((
-            client_data.window,
-            ImeEvent::Update(client_data.text.iter().collect(), cursor_byte_pos),
-        ))
-        .expect("failed to send preedit update event");
+    client_data.send_update();
 }
 
 /// Handling of cursor movements in preedit text.
@@ -134,15 +186,7 @@ extern "C" fn preedit_caret_callback(
 
     if call_data.direction == ffi::XIMCaretDirection::XIMAbsolutePosition {
         client_data.cursor_pos = call_data.position as usize;
-        let cursor_byte_pos = calc_byte_position(&client_data.text, client_data.cursor_pos);
-
-        client_data
-            .event_sender
-            .send((
-                client_data.window,
-                ImeEvent::Update(client_data.text.iter().collect(), cursor_byte_pos),
-            ))
-            .expect("failed to send preedit update event");
+        client_data.send_update();
     }
 }
 
@@ -173,6 +217,8 @@ struct ImeContextClientData {
     window: ffi::Window,
     event_sender: ImeEventSender,
     text: Vec<char>,
+    // The style of each character of the text.
+    feedback: Vec<ffi::XIMFeedback>,
     cursor_pos: usize,
 }
 
@@ -202,6 +248,7 @@ impl ImeContext {
             window,
             event_sender,
             text: Vec::new(),
+            feedback: Vec::new(),
             cursor_pos: 0,
         }));
 
@@ -374,3 +421,43 @@ impl ImeContext {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn preedit_segments_split_on_style_changes() {
+        let text: Vec<char> = "aé漢字bc".chars().collect();
+        let feedback = [
+            ffi::XIMUnderline,
+            ffi::XIMUnderline,
+            ffi::XIMReverse,
+            ffi::XIMReverse | ffi::XIMHighlight,
+            0,
+            ffi::XIMUnderline,
+        ];
+        let expected = [
+            PreeditSegment { start: 0, end: 3, style: PreeditStyle::UNDERLINE },
+            PreeditSegment { start: 3, end: 6, style: PreeditStyle::REVERSE },
+            PreeditSegment {
+                start: 6,
+                end: 9,
+                style: PreeditStyle::REVERSE | PreeditStyle::HIGHLIGHT,
+            },
+            PreeditSegment { start: 10, end: 11, style: PreeditStyle::UNDERLINE },
+        ];
+        assert_eq!(preedit_segments(&text, &feedback), expected);
+    }
+
+    #[test]
+    fn preedit_segments_skip_unstyled_text() {
+        let text: Vec<char> = "abc".chars().collect();
+        assert!(preedit_segments(&text, &[0, 0, 0]).is_empty());
+        // The input method may not report the feedback of every character.
+        assert_eq!(
+            preedit_segments(&text, &[ffi::XIMHighlight]),
+            [PreeditSegment { start: 0, end: 1, style: PreeditStyle::HIGHLIGHT }]
+        );
+    }
+}
@@ -17,13 +17,14 @@ pub use self::context::ImeContextCreationError;
 use self::inner::{close_im, ImeInner};
 use self::input_method::PotentialInputMethods;
 use super::{ffi, util, XConnection, XError};
+use crate::event::PreeditSegment;
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum ImeEvent {
     Enabled,
     Start,
-    Update(String, usize),
+    Update(String, usize, Vec<PreeditSegment>),
     End,
     Disabled,
 }
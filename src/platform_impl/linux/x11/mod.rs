@@ -2,18 +2,17 @@ use std::cell::{Cell, RefCell};
 use std::collections::{HashMap, HashSet, VecDeque};
 use std::ffi::CStr;
 use std::marker::PhantomData;
-use std::mem::MaybeUninit;
+use std::mem::{self, MaybeUninit};
 use std::ops::Deref;
 use std::os::raw::*;
-use std::os::unix::io::{AsFd, AsRawFd, BorrowedFd, RawFd};
-use std::sync::mpsc::{self, Receiver, Sender, TryRecvError};
+use std::os::unix::io::{AsFd, AsRawFd, BorrowedFd, OwnedFd, RawFd};
+use std::sync::mpsc::{self, Sender};
 use std::sync::{Arc, Weak};
 use std::time::{Duration, Instant};
 use std::{fmt, ptr, slice, str};
 
 use calloop::generic::Generic;
-use calloop::ping::Ping;
-use calloop::{EventLoop as Loop, Readiness};
+use calloop::{EventLoop as Loop, LoopHandle, Readiness, RegistrationToken};
 use libc::{setlocale, LC_CTYPE};
 use tracing::warn;
 
@@ -26,9 +25,18 @@ use x11rb::x11_utils::X11Error as LogicalError;
 use x11rb::xcb_ffi::ReplyOrIdError;
 
 use crate::error::{EventLoopError, OsError as RootOsError};
-use crate::event::{Event, StartCause, WindowEvent};
-use crate::event_loop::{ActiveEventLoop as RootAEL, ControlFlow, DeviceEvents, EventLoopClosed};
+use crate::event::{
+    Event, Force, StartCause, TabletTool, TabletToolAxes, TabletToolEvent, TabletToolKind,
+    TouchContact, TouchPhase, WindowEvent,
+};
+use crate::event_loop::{
+    ActiveEventLoop as RootAEL, ControlFlow, DeviceEvents, EventLoopClosed, Interest, SourceToken,
+    TimerId, TimerSchedule,
+};
+use crate::keyboard::KeyboardLayouts;
 use crate::platform::pump_events::PumpStatus;
+use crate::platform_impl::common::channel::{PeekableReceiver, WakeSender};
+use crate::platform_impl::common::timer::Timers;
 use crate::platform_impl::common::xkb::Context;
 use crate::platform_impl::platform::{min_timeout, WindowId};
 use crate::platform_impl::{x11, ActiveEventLoop as PlatformActiveEventLoop, OsError, PlatformCustomCursor};
@@ -36,6 +44,7 @@ use crate::window::{CustomCursor as RootCustomCursor, CustomCursorSource, Window
 
 mod activation;
 mod atoms;
+mod clipboard;
 mod dnd;
 mod event_processor;
 pub mod ffi;
@@ -49,7 +58,8 @@ mod xsettings;
 pub use util::CustomCursor;
 
 use atoms::*;
-use dnd::{Dnd, DndState};
+use clipboard::{Clipboard, ClipboardRequest};
+use dnd::{Dnd, DndRequest, DndState, DragSource, PendingDrop, FINISH_TIMEOUT};
 use event_processor::{EventProcessor, MAX_MOD_REPLAY_LEN};
 use ime::{Ime, ImeCreationError, ImeReceiver, ImeRequest, ImeSender};
 pub(crate) use monitor::{MonitorHandle, VideoModeHandle};
@@ -66,63 +76,6 @@ type X11rbConnection = x11rb::xcb_ffi::XCBConnection;
 
 type X11Source = Generic<BorrowedFd<'static>>;
 
-struct WakeSender<T> {
-    sender: Sender<T>,
-    waker: Ping,
-}
-
-impl<T> Clone for WakeSender<T> {
-    fn clone(&self) -> Self {
-        Self { sender: self.sender.clone(), waker: self.waker.clone() }
-    }
-}
-
-impl<T> WakeSender<T> {
-    pub fn send(&self, t: T) -> Result<(), EventLoopClosed<T>> {
-        let res = self.sender.send(t).map_err(|e| EventLoopClosed(e.0));
-        if res.is_ok() {
-            self.waker.ping();
-        }
-        res
-    }
-}
-
-struct PeekableReceiver<T> {
-    recv: Receiver<T>,
-    first: Option<T>,
-}
-
-impl<T> PeekableReceiver<T> {
-    pub fn from_recv(recv: Receiver<T>) -> Self {
-        Self { recv, first: None }
-    }
-
-    pub fn has_incoming(&mut self) -> bool {
-        if self.first.is_some() {
-            return true;
-        }
-
-        match self.recv.try_recv() {
-            Ok(v) => {
-                self.first = Some(v);
-                true
-            },
-            Err(TryRecvError::Empty) => false,
-            Err(TryRecvError::Disconnected) => {
-                warn!("Channel was disconnected when checking incoming");
-                false
-            },
-        }
-    }
-
-    pub fn try_recv(&mut self) -> Result<T, TryRecvError> {
-        if let Some(first) = self.first.take() {
-            return Ok(first);
-        }
-        self.recv.try_recv()
-    }
-}
-
 pub struct ActiveEventLoop {
     xconn: Arc<XConnection>,
     wm_delete_window: xproto::Atom,
@@ -135,7 +88,14 @@ pub struct ActiveEventLoop {
     windows: RefCell<HashMap<WindowId, Weak<UnownedWindow>>>,
     redraw_sender: WakeSender<WindowId>,
     activation_sender: WakeSender<ActivationToken>,
+    clipboard_sender: WakeSender<ClipboardRequest>,
+    dnd_sender: WakeSender<DndRequest>,
     device_events: Cell<DeviceEvents>,
+    timers: RefCell<Timers>,
+    loop_handle: LoopHandle<'static, EventLoopState>,
+    fd_sources: RefCell<HashMap<SourceToken, RegistrationToken>>,
+    xi2_gestures: bool,
+    keyboard_layouts: RefCell<Option<KeyboardLayouts>>,
 }
 
 pub struct EventLoop<T: 'static> {
@@ -146,6 +106,8 @@ pub struct EventLoop<T: 'static> {
     redraw_receiver: PeekableReceiver<WindowId>,
     user_receiver: PeekableReceiver<T>,
     activation_receiver: PeekableReceiver<ActivationToken>,
+    clipboard_receiver: PeekableReceiver<ClipboardRequest>,
+    dnd_receiver: PeekableReceiver<DndRequest>,
     user_sender: Sender<T>,
 
     /// The current state of the event loop.
@@ -157,6 +119,8 @@ type ActivationToken = (WindowId, crate::event_loop::AsyncRequestSerial);
 struct EventLoopState {
     /// The latest readiness state for the x11 file descriptor
     x11_readiness: Readiness,
+    /// The readiness of the file descriptors registered by the user
+    fd_events: Vec<(SourceToken, Interest)>,
 }
 
 pub struct EventLoopProxy<T: 'static> {
@@ -180,6 +144,8 @@ impl<T: 'static> EventLoop<T> {
         let dnd = Dnd::new(Arc::clone(&xconn))
             .expect("Failed to call XInternAtoms when initializing drag and drop");
 
+        let clipboard = Clipboard::new(Arc::clone(&xconn));
+
         let (ime_sender, ime_receiver) = mpsc::channel();
         let (ime_event_sender, ime_event_receiver) = mpsc::channel();
         // Input methods will open successfully without setting the locale, but it won't be
@@ -230,13 +196,16 @@ impl<T: 'static> EventLoop<T> {
             .expect("X server missing XKB extension");
 
         // Check for XInput2 support.
-        xconn
+        let xi2_version = xconn
             .xcb_connection()
-            .xinput_xi_query_version(2, 3)
+            .xinput_xi_query_version(2, 4)
             .expect("Failed to send XInput2 query version request")
             .reply()
             .expect("Error while checking for XInput2 query version reply");
 
+        // The gesture events are only known by servers supporting XInput 2.4.
+        let xi2_gestures = (xi2_version.major_version, xi2_version.minor_version) >= (2, 4);
+
         xconn.update_cached_wm_info(root);
 
         // Create an event loop.
@@ -273,11 +242,22 @@ impl<T: 'static> EventLoop<T> {
         // Create a channel for sending activation tokens.
         let (activation_token_sender, activation_token_channel) = mpsc::channel();
 
+        // Create a channel for the clipboard requests.
+        let (clipboard_sender, clipboard_channel) = mpsc::channel();
+
+        // Create a channel for the drag and drop requests.
+        let (dnd_sender, dnd_channel) = mpsc::channel();
+
         // Create a channel for sending user events.
         let (user_sender, user_channel) = mpsc::channel();
 
-        let xkb_context =
+        let mut xkb_context =
             Context::from_x11_xkb(xconn.xcb_connection().get_raw_xcb_connection()).unwrap();
+        let mut keyboard_layouts = xkb_context.take_keyboard_layouts_update();
+        if let Some(layouts) = keyboard_layouts.as_mut() {
+            xconn.name_keyboard_layouts(layouts);
+        }
+        let keyboard_layouts = RefCell::new(keyboard_layouts);
 
         let mut xmodmap = util::ModifierKeymap::new();
         xmodmap.reload_from_x_connection(&xconn);
@@ -300,7 +280,20 @@ impl<T: 'static> EventLoop<T> {
                 sender: activation_token_sender, // not used again so no clone
                 waker: waker.clone(),
             },
+            clipboard_sender: WakeSender {
+                sender: clipboard_sender, // not used again so no clone
+                waker: waker.clone(),
+            },
+            dnd_sender: WakeSender {
+                sender: dnd_sender, // not used again so no clone
+                waker: waker.clone(),
+            },
             device_events: Default::default(),
+            timers: Default::default(),
+            loop_handle: handle.clone(),
+            fd_sources: Default::default(),
+            xi2_gestures,
+            keyboard_layouts,
         };
 
         // Set initial device event filter.
@@ -312,6 +305,7 @@ impl<T: 'static> EventLoop<T> {
         let event_processor = EventProcessor {
             target: root_window_target,
             dnd,
+            clipboard,
             devices: Default::default(),
             randr_event_offset,
             ime_receiver,
@@ -327,6 +321,7 @@ impl<T: 'static> EventLoop<T> {
             active_window: None,
             modifiers: Default::default(),
             is_composing: false,
+            pinch: Default::default(),
         };
 
         // Register for device hotplug events
@@ -337,7 +332,7 @@ impl<T: 'static> EventLoop<T> {
             .select_xinput_events(
                 root,
                 ALL_DEVICES,
-                x11rb::protocol::xinput::XIEventMask::HIERARCHY,
+                &[x11rb::protocol::xinput::XIEventMask::HIERARCHY],
             )
             .expect_then_ignore_error("Failed to register for XInput2 device hotplug events");
 
@@ -359,9 +354,11 @@ impl<T: 'static> EventLoop<T> {
             event_processor,
             redraw_receiver: PeekableReceiver::from_recv(redraw_channel),
             activation_receiver: PeekableReceiver::from_recv(activation_token_channel),
+            clipboard_receiver: PeekableReceiver::from_recv(clipboard_channel),
+            dnd_receiver: PeekableReceiver::from_recv(dnd_channel),
             user_receiver: PeekableReceiver::from_recv(user_channel),
             user_sender,
-            state: EventLoopState { x11_readiness: Readiness::EMPTY },
+            state: EventLoopState { x11_readiness: Readiness::EMPTY, fd_events: Vec::new() },
         }
     }
 
@@ -436,6 +433,43 @@ impl<T: 'static> EventLoop<T> {
         self.event_processor.poll()
             || self.user_receiver.has_incoming()
             || self.redraw_receiver.has_incoming()
+            || self.clipboard_receiver.has_incoming()
+            || self.dnd_receiver.has_incoming()
+            || !self.state.fd_events.is_empty()
+    }
+
+    /// Prepare to wait for the events on the event loop file descriptor, returning how long
+    /// the wait may last.
+    pub fn prepare(&mut self) -> Option<Duration> {
+        if !self.loop_running || self.exiting() || self.has_pending() {
+            return Some(Duration::ZERO);
+        }
+
+        self.wait_timeout(Instant::now())
+    }
+
+    /// How long we may wait for the new events according to the control flow and the timers.
+    fn wait_timeout(&self, start: Instant) -> Option<Duration> {
+        let control_flow_timeout = match self.control_flow() {
+            ControlFlow::Wait => None,
+            ControlFlow::Poll => Some(Duration::ZERO),
+            ControlFlow::WaitUntil(wait_deadline) => {
+                Some(wait_deadline.saturating_duration_since(start))
+            },
+        };
+
+        let window_target = EventProcessor::window_target(&self.event_processor.target);
+        let timers_timeout = window_target
+            .timers
+            .borrow()
+            .next_deadline()
+            .map(|deadline| deadline.saturating_duration_since(start));
+        let transfers_timeout = self
+            .event_processor
+            .next_deadline()
+            .map(|deadline| deadline.saturating_duration_since(start));
+
+        min_timeout(min_timeout(control_flow_timeout, timers_timeout), transfers_timeout)
     }
 
     pub fn poll_events_with_timeout<F>(&mut self, mut timeout: Option<Duration>, mut callback: F)
@@ -450,15 +484,7 @@ impl<T: 'static> EventLoop<T> {
             // If we already have work to do then we don't want to block on the next poll.
             Some(Duration::ZERO)
         } else {
-            let control_flow_timeout = match self.control_flow() {
-                ControlFlow::Wait => None,
-                ControlFlow::Poll => Some(Duration::ZERO),
-                ControlFlow::WaitUntil(wait_deadline) => {
-                    Some(wait_deadline.saturating_duration_since(start))
-                },
-            };
-
-            min_timeout(control_flow_timeout, timeout)
+            min_timeout(self.wait_timeout(start), timeout)
         };
 
         self.state.x11_readiness = Readiness::EMPTY;
@@ -493,7 +519,16 @@ impl<T: 'static> EventLoop<T> {
         // have any pending `_receiver` events and if not we return without
         // running a loop iteration.
         // If we don't have any pending `_receiver`
+        let window_target = EventProcessor::window_target(&self.event_processor.target);
+        let deadline_reached = window_target
+            .timers
+            .borrow()
+            .next_deadline()
+            .into_iter()
+            .chain(self.event_processor.next_deadline())
+            .any(|deadline| deadline <= Instant::now());
         if !self.has_pending()
+            && !deadline_reached
             && !matches!(&cause, StartCause::ResumeTimeReached { .. } | StartCause::Poll)
         {
             return;
@@ -514,6 +549,18 @@ impl<T: 'static> EventLoop<T> {
             callback(Event::Resumed, &self.event_processor.target);
         }
 
+        // Fire the expired timers, the timers scheduled by the callbacks fire on later iterations.
+        let window_target = EventProcessor::window_target(&self.event_processor.target);
+        let expired = window_target.timers.borrow_mut().take_expired(Instant::now());
+        for timer_id in expired {
+            callback(Event::TimerFired(timer_id), &self.event_processor.target);
+        }
+
+        // Report the ready file descriptors.
+        for (token, readiness) in mem::take(&mut self.state.fd_events) {
+            callback(Event::FdReady { token, readiness }, &self.event_processor.target);
+        }
+
         // Process all pending events
         self.drain_events(callback);
 
@@ -541,6 +588,23 @@ impl<T: 'static> EventLoop<T> {
             }
         }
 
+        // Forward the clipboard requests, the results are delivered with the X11 events.
+        while let Ok(request) = self.clipboard_receiver.try_recv() {
+            if let Err(err) = self.event_processor.clipboard.handle_request(request) {
+                tracing::warn!("Failed to handle the clipboard request: {err}");
+            }
+        }
+
+        // Give up on the transfers the other clients stopped answering.
+        self.event_processor
+            .handle_expired(Instant::now(), |window_target, event| callback(event, window_target));
+
+        // Forward the drag and drop requests.
+        while let Ok(request) = self.dnd_receiver.try_recv() {
+            self.event_processor
+                .handle_dnd_request(request, |window_target, event| callback(event, window_target));
+        }
+
         // Empty the user event buffer
         {
             while let Ok(event) = self.user_receiver.try_recv() {
@@ -650,6 +714,49 @@ impl ActiveEventLoop {
         self.device_events.set(allowed);
     }
 
+    pub fn keyboard_layouts(&self) -> Option<KeyboardLayouts> {
+        self.keyboard_layouts.borrow().clone()
+    }
+
+    pub fn schedule_timer(&self, timer_id: TimerId, schedule: TimerSchedule) {
+        self.timers.borrow_mut().schedule(timer_id, schedule);
+    }
+
+    pub fn cancel_timer(&self, timer_id: TimerId) {
+        self.timers.borrow_mut().cancel(timer_id);
+    }
+
+    pub fn register_fd(&self, fd: OwnedFd, interest: Interest) -> Result<SourceToken, RootOsError> {
+        let token = SourceToken::next();
+        let source = Generic::new(
+            fd,
+            calloop::Interest {
+                readable: interest.contains(Interest::READABLE),
+                writable: interest.contains(Interest::WRITABLE),
+            },
+            calloop::Mode::Level,
+        );
+        let registration = self
+            .loop_handle
+            .insert_source(source, move |readiness, _, state| {
+                let mut interest = Interest::empty();
+                interest.set(Interest::READABLE, readiness.readable || readiness.error);
+                interest.set(Interest::WRITABLE, readiness.writable);
+                state.fd_events.push((token, interest));
+                Ok(calloop::PostAction::Continue)
+            })
+            .map_err(|_| os_error!(OsError::Misc("failed to register the file descriptor")))?;
+
+        self.fd_sources.borrow_mut().insert(token, registration);
+        Ok(token)
+    }
+
+    pub fn unregister_fd(&self, token: SourceToken) {
+        if let Some(registration) = self.fd_sources.borrow_mut().remove(&token) {
+            self.loop_handle.remove(registration);
+        }
+    }
+
     /// Update the device event based on window focus.
     pub fn update_listen_device_events(&self, focus: bool) {
         let device_events = self.device_events.get() == DeviceEvents::Always
@@ -665,7 +772,7 @@ impl ActiveEventLoop {
         }
 
         self.xconn
-            .select_xinput_events(self.root, ALL_MASTER_DEVICES, mask)
+            .select_xinput_events(self.root, ALL_MASTER_DEVICES, &[mask])
             .expect_then_ignore_error("Failed to update device event filter");
     }
 
@@ -778,6 +885,16 @@ impl DeviceId {
     pub const fn dummy() -> Self {
         DeviceId(0)
     }
+
+    #[cfg(feature = "serde")]
+    pub(crate) fn into_raw(self) -> i64 {
+        self.0 as i64
+    }
+
+    #[cfg(feature = "serde")]
+    pub(crate) fn from_raw(raw: i64) -> Option<Self> {
+        xinput::DeviceId::try_from(raw).ok().map(DeviceId)
+    }
 }
 
 pub(crate) struct Window(Arc<UnownedWindow>);
@@ -978,6 +1095,10 @@ fn mkdid(w: xinput::DeviceId) -> crate::event::DeviceId {
 pub struct Device {
     _name: String,
     scroll_axes: Vec<(i32, ScrollAxis)>,
+    // The tablet tool behind the device, for the pens and the erasers.
+    tablet_tool: Option<TabletToolDevice>,
+    // The shape and pressure of the touches, for the touchscreens.
+    touch: Option<TouchDevice>,
     // For master devices, this is the paired device (pointer <-> keyboard).
     // For slave devices, this is the master.
     attachment: c_int,
@@ -996,10 +1117,329 @@ enum ScrollOrientation {
     Horizontal,
 }
 
+#[derive(Debug, Clone)]
+struct TabletToolDevice {
+    kind: TabletToolKind,
+    axes: Vec<(i32, TabletAxis)>,
+    // The values of the axes, as of the latest event of the tool.
+    values: TabletToolAxes,
+    // The window the tool is in proximity of.
+    proximity: Option<xproto::Window>,
+}
+
+#[derive(Debug, Copy, Clone)]
+struct TabletAxis {
+    kind: TabletAxisKind,
+    min: f64,
+    max: f64,
+    /// The units per radian of the tilt, or `0` when unknown.
+    resolution: f64,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum TabletAxisKind {
+    Pressure,
+    Distance,
+    TiltX,
+    TiltY,
+    Rotation,
+    Slider,
+}
+
+impl TabletToolDevice {
+    fn new(name: &str, info: &ffi::XIDeviceInfo, atoms: &Atoms) -> Option<Self> {
+        let mut axes = Vec::new();
+        let mut values = TabletToolAxes::default();
+        for &class_ptr in Device::classes(info) {
+            let ty = unsafe { (*class_ptr)._type };
+            if ty != ffi::XIValuatorClass {
+                continue;
+            }
+
+            let info = unsafe { &*(class_ptr as *const ffi::XIValuatorClassInfo) };
+            let label = info.label as xproto::Atom;
+            let kind = match label {
+                label if label == atoms[AbsPressure] => TabletAxisKind::Pressure,
+                label if label == atoms[AbsDistance] => TabletAxisKind::Distance,
+                label if label == atoms[AbsTiltX] => TabletAxisKind::TiltX,
+                label if label == atoms[AbsTiltY] => TabletAxisKind::TiltY,
+                label if label == atoms[AbsRotaryZ] => TabletAxisKind::Rotation,
+                label if label == atoms[AbsThrottle] => TabletAxisKind::Slider,
+                _ => continue,
+            };
+            let resolution = info.resolution as f64;
+            let axis = TabletAxis { kind, min: info.min, max: info.max, resolution };
+            axis.update(&mut values, info.value);
+            axes.push((info.number, axis));
+        }
+
+        // Only the tools sensing the pressure are told apart from the mice.
+        if !axes.iter().any(|(_, axis)| axis.kind == TabletAxisKind::Pressure) {
+            return None;
+        }
+
+        // XInput2 has no notion of tools, the drivers put the type in the name of the device.
+        let name = name.to_lowercase();
+        let kind = if name.contains("eraser") {
+            TabletToolKind::Eraser
+        } else if name.contains("airbrush") {
+            TabletToolKind::Airbrush
+        } else if name.contains("pencil") {
+            TabletToolKind::Pencil
+        } else if name.contains("brush") {
+            TabletToolKind::Brush
+        } else if name.contains("cursor") || name.contains("mouse") {
+            TabletToolKind::Mouse
+        } else if name.contains("lens") {
+            TabletToolKind::Lens
+        } else {
+            TabletToolKind::Pen
+        };
+
+        Some(TabletToolDevice { kind, axes, values, proximity: None })
+    }
+
+    fn event<T>(
+        &self,
+        window: xproto::Window,
+        device_id: crate::event::DeviceId,
+        event: TabletToolEvent,
+    ) -> Event<T> {
+        // The drivers don't expose the serial of the tool in the events.
+        let tool = TabletTool { kind: self.kind, serial: 0 };
+        Event::WindowEvent {
+            window_id: mkwid(window),
+            event: WindowEvent::TabletTool { device_id, tool, event },
+        }
+    }
+
+    // Bring the tool into proximity of the `window`, XInput2 doesn't report the proximity.
+    fn enter<T>(
+        &mut self,
+        window: xproto::Window,
+        device_id: crate::event::DeviceId,
+        events: &mut Vec<Event<T>>,
+    ) {
+        if self.proximity == Some(window) {
+            return;
+        }
+
+        if let Some(previous) = self.proximity.replace(window) {
+            events.push(self.event(previous, device_id, TabletToolEvent::ProximityOut));
+        }
+        events.push(self.event(window, device_id, TabletToolEvent::ProximityIn));
+    }
+
+    fn leave<T>(&mut self, device_id: crate::event::DeviceId, events: &mut Vec<Event<T>>) {
+        if let Some(window) = self.proximity.take() {
+            events.push(self.event(window, device_id, TabletToolEvent::ProximityOut));
+        }
+    }
+}
+
+impl TabletAxis {
+    fn update(&self, axes: &mut TabletToolAxes, value: f64) {
+        let normalized =
+            if self.max > self.min { (value - self.min) / (self.max - self.min) } else { 0.0 };
+        match self.kind {
+            TabletAxisKind::Pressure => axes.pressure = Some(normalized),
+            TabletAxisKind::Distance => axes.distance = Some(normalized),
+            TabletAxisKind::TiltX => {
+                axes.tilt = Some((self.tilt_degrees(value), axes.tilt.unwrap_or_default().1))
+            },
+            TabletAxisKind::TiltY => {
+                axes.tilt = Some((axes.tilt.unwrap_or_default().0, self.tilt_degrees(value)))
+            },
+            TabletAxisKind::Rotation => axes.rotation = Some(normalized * 360.0),
+            TabletAxisKind::Slider => axes.slider = Some(normalized * 2.0 - 1.0),
+        }
+    }
+
+    /// Convert the raw tilt to degrees.
+    ///
+    /// The drivers give the resolution in units per radian, like evdev, e.g. `57` for the
+    /// `-64..=63` range of the Wacom tablets. Without it, the range is taken as the whole tilt.
+    fn tilt_degrees(&self, value: f64) -> f64 {
+        let degrees = if self.resolution > 0.0 {
+            (value / self.resolution).to_degrees()
+        } else if self.max > self.min {
+            (value - (self.min + self.max) / 2.0) / (self.max - self.min) * 180.0
+        } else {
+            0.0
+        };
+        degrees.clamp(-90.0, 90.0)
+    }
+}
+
+#[derive(Debug, Clone)]
+struct TouchDevice {
+    axes: Vec<(i32, TouchAxis)>,
+    // The values of the axes for the ongoing touches, as of their latest event.
+    touches: HashMap<u64, TouchValues>,
+}
+
+#[derive(Debug, Copy, Clone)]
+struct TouchAxis {
+    kind: TouchAxisKind,
+    min: f64,
+    max: f64,
+    /// The units per meter, or `0` when unknown.
+    resolution: f64,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum TouchAxisKind {
+    PositionX,
+    PositionY,
+    Major,
+    Minor,
+    Orientation,
+    Pressure,
+}
+
+#[derive(Debug, Default, Copy, Clone)]
+struct TouchValues {
+    major: Option<f64>,
+    minor: Option<f64>,
+    orientation: Option<f64>,
+    pressure: Option<f64>,
+}
+
+impl TouchDevice {
+    fn new(info: &ffi::XIDeviceInfo, atoms: &Atoms) -> Option<Self> {
+        if !Device::touch_device(info) {
+            return None;
+        }
+
+        let mut axes = Vec::new();
+        for &class_ptr in Device::classes(info) {
+            let ty = unsafe { (*class_ptr)._type };
+            if ty != ffi::XIValuatorClass {
+                continue;
+            }
+
+            let info = unsafe { &*(class_ptr as *const ffi::XIValuatorClassInfo) };
+            let label = info.label as xproto::Atom;
+            let kind = match label {
+                label if label == atoms[AbsMtPositionX] => TouchAxisKind::PositionX,
+                label if label == atoms[AbsMtPositionY] => TouchAxisKind::PositionY,
+                label if label == atoms[AbsMtTouchMajor] => TouchAxisKind::Major,
+                label if label == atoms[AbsMtTouchMinor] => TouchAxisKind::Minor,
+                label if label == atoms[AbsMtOrientation] => TouchAxisKind::Orientation,
+                label if label == atoms[AbsMtPressure] => TouchAxisKind::Pressure,
+                _ => continue,
+            };
+            let resolution = info.resolution as f64;
+            axes.push((info.number, TouchAxis { kind, min: info.min, max: info.max, resolution }));
+        }
+
+        let position = |axis: &TouchAxis| {
+            matches!(axis.kind, TouchAxisKind::PositionX | TouchAxisKind::PositionY)
+        };
+        if axes.iter().all(|(_, axis)| position(axis)) {
+            return None;
+        }
+
+        Some(TouchDevice { axes, touches: HashMap::new() })
+    }
+
+    /// Update the touch from the valuators of its event, returning its force and contact.
+    ///
+    /// The touchscreen is mapped on the whole root window of `root_size` pixels.
+    fn update(
+        &mut self,
+        event: &ffi::XIDeviceEvent,
+        phase: TouchPhase,
+        root_size: (f64, f64),
+    ) -> (Option<Force>, Option<TouchContact>) {
+        let id = event.detail as u64;
+        let mut values = match phase {
+            TouchPhase::Started => TouchValues::default(),
+            _ => self.touches.get(&id).copied().unwrap_or_default(),
+        };
+
+        let mask = unsafe {
+            slice::from_raw_parts(event.valuators.mask, event.valuators.mask_len as usize)
+        };
+        let mut value = event.valuators.values;
+        for i in 0..event.valuators.mask_len * 8 {
+            if !ffi::XIMaskIsSet(mask, i) {
+                continue;
+            }
+
+            let x = unsafe { *value };
+            if let Some((_, axis)) = self.axes.iter().find(|(axis, _)| *axis == i) {
+                match axis.kind {
+                    TouchAxisKind::PositionX | TouchAxisKind::PositionY => {},
+                    TouchAxisKind::Major => values.major = Some(self.position_units(axis, x)),
+                    TouchAxisKind::Minor => values.minor = Some(self.position_units(axis, x)),
+                    // The kernel reports a quarter turn clockwise as the maximum.
+                    TouchAxisKind::Orientation if axis.max > 0.0 => {
+                        values.orientation = Some(x / axis.max * 90.0)
+                    },
+                    TouchAxisKind::Orientation => {},
+                    TouchAxisKind::Pressure if axis.max > axis.min => {
+                        values.pressure = Some((x - axis.min) / (axis.max - axis.min))
+                    },
+                    TouchAxisKind::Pressure => {},
+                }
+            }
+
+            value = unsafe { value.offset(1) };
+        }
+
+        match phase {
+            TouchPhase::Started | TouchPhase::Moved => self.touches.insert(id, values),
+            TouchPhase::Ended | TouchPhase::Cancelled => self.touches.remove(&id),
+        };
+
+        (values.pressure.map(Force::Normalized), self.contact(&values, root_size))
+    }
+
+    fn position_axis(&self, kind: TouchAxisKind) -> Option<&TouchAxis> {
+        self.axes.iter().map(|(_, axis)| axis).find(|axis| axis.kind == kind)
+    }
+
+    /// Convert the value of the ellipse axis to the units of the position, which are the same
+    /// unless the resolutions tell otherwise.
+    fn position_units(&self, axis: &TouchAxis, value: f64) -> f64 {
+        match self.position_axis(TouchAxisKind::PositionX) {
+            Some(position) if position.resolution > 0.0 && axis.resolution > 0.0 => {
+                value * position.resolution / axis.resolution
+            },
+            _ => value,
+        }
+    }
+
+    /// The contact ellipse in pixels, the touchscreen being mapped on the whole root window of
+    /// `root_size` pixels.
+    fn contact(&self, values: &TouchValues, root_size: (f64, f64)) -> Option<TouchContact> {
+        let scale = |kind, root_length: f64| {
+            self.position_axis(kind)
+                .filter(|axis| axis.max > axis.min)
+                .map(|axis| root_length / (axis.max - axis.min))
+        };
+        let scale_x = scale(TouchAxisKind::PositionX, root_size.0).unwrap_or(1.0);
+        let scale_y = scale(TouchAxisKind::PositionY, root_size.1).unwrap_or(scale_x);
+
+        // Each axis of the ellipse is scaled along its direction, the major one being along the
+        // y-axis without any orientation.
+        let (sin, cos) = values.orientation.unwrap_or(0.0).to_radians().sin_cos();
+        let major = values.major?;
+        Some(TouchContact {
+            major: major * (scale_x * sin).hypot(scale_y * cos),
+            minor: values.minor.unwrap_or(major) * (scale_x * cos).hypot(scale_y * sin),
+            orientation: values.orientation,
+        })
+    }
+}
+
 impl Device {
-    fn new(info: &ffi::XIDeviceInfo) -> Self {
+    fn new(info: &ffi::XIDeviceInfo, atoms: &Atoms) -> Self {
         let name = unsafe { CStr::from_ptr(info.name).to_string_lossy() };
         let mut scroll_axes = Vec::new();
+        let mut tablet_tool = None;
+        let mut touch = None;
 
         if Device::physical_device(info) {
             // Identify scroll axes
@@ -1018,10 +1458,23 @@ impl Device {
                     }));
                 }
             }
+
+            if info._use != ffi::XISlaveKeyboard {
+                // The touchscreens may report the pressure of the emulated pointer as well.
+                touch = TouchDevice::new(info, atoms);
+                if !Device::touch_device(info) {
+                    tablet_tool = TabletToolDevice::new(&name, info, atoms);
+                }
+            }
         }
 
-        let mut device =
-            Device { _name: name.into_owned(), scroll_axes, attachment: info.attachment };
+        let mut device = Device {
+            _name: name.into_owned(),
+            scroll_axes,
+            tablet_tool,
+            touch,
+            attachment: info.attachment,
+        };
         device.reset_scroll_position(info);
         device
     }
@@ -1049,6 +1502,13 @@ impl Device {
             || info._use == ffi::XIFloatingSlave
     }
 
+    #[inline]
+    fn touch_device(info: &ffi::XIDeviceInfo) -> bool {
+        Device::classes(info)
+            .iter()
+            .any(|&class_ptr| unsafe { (*class_ptr)._type } == ffi::XITouchClass)
+    }
+
     #[inline]
     fn classes(info: &ffi::XIDeviceInfo) -> &[*const ffi::XIAnyClassInfo] {
         unsafe {
@@ -1065,3 +1525,56 @@ impl Device {
 fn xinput_fp1616_to_float(fp: xinput::Fp1616) -> f64 {
     (fp as f64) / ((1 << 16) as f64)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tablet_tilt_degrees() {
+        let axis =
+            |min, max, resolution| TabletAxis { kind: TabletAxisKind::TiltX, min, max, resolution };
+
+        // The Wacom range, in units per radian.
+        let wacom = axis(-64.0, 63.0, 57.0);
+        assert_eq!(wacom.tilt_degrees(0.0), 0.0);
+        assert!((wacom.tilt_degrees(57.0) - 180.0 / std::f64::consts::PI).abs() < 1e-9);
+        assert_eq!(wacom.tilt_degrees(-1000.0), -90.0);
+
+        // The range spans the whole tilt without a resolution.
+        let unknown = axis(0.0, 1000.0, 0.0);
+        assert_eq!(unknown.tilt_degrees(500.0), 0.0);
+        assert_eq!(unknown.tilt_degrees(0.0), -90.0);
+        assert_eq!(unknown.tilt_degrees(750.0), 45.0);
+    }
+
+    #[test]
+    fn touch_contact_scale() {
+        let axis = |kind, max, resolution| TouchAxis { kind, min: 0.0, max, resolution };
+        // A 4000x1000 units touchscreen on a 2000x1000 pixels root window, with the touch size
+        // in half the units of the position.
+        let touch = TouchDevice {
+            axes: vec![
+                (0, axis(TouchAxisKind::PositionX, 4000.0, 20.0)),
+                (1, axis(TouchAxisKind::PositionY, 1000.0, 20.0)),
+                (2, axis(TouchAxisKind::Major, 100.0, 10.0)),
+            ],
+            touches: HashMap::new(),
+        };
+        let root_size = (2000.0, 1000.0);
+        let (_, major_axis) = &touch.axes[2];
+        assert_eq!(touch.position_units(major_axis, 10.0), 20.0);
+
+        // The major axis is along the y-axis, the minor one along the x-axis.
+        let values = TouchValues { major: Some(20.0), minor: Some(10.0), ..Default::default() };
+        let contact = touch.contact(&values, root_size).unwrap();
+        assert_eq!((contact.major, contact.minor), (20.0, 5.0));
+
+        // A quarter turn swaps them.
+        let values = TouchValues { orientation: Some(90.0), ..values };
+        let contact = touch.contact(&values, root_size).unwrap();
+        assert!((contact.major - 10.0).abs() < 1e-9 && (contact.minor - 10.0).abs() < 1e-9);
+
+        assert!(touch.contact(&TouchValues::default(), root_size).is_none());
+    }
+}
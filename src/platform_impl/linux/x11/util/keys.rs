@@ -1,7 +1,10 @@
 use std::iter::Enumerate;
 use std::slice::Iter;
 
+use x11rb::protocol::xkb::{self, ConnectionExt as _};
+
 use super::*;
+use crate::keyboard::KeyboardLayouts;
 
 pub struct Keymap {
     keys: [u8; 32],
@@ -68,6 +71,48 @@ impl XConnection {
 
         Keymap { keys }
     }
+
+    /// The names of the layouts of the core keyboard, from its XKB group names.
+    pub fn xkb_group_names(&self) -> Result<Vec<String>, X11Error> {
+        let conn = self.xcb_connection();
+        let reply = conn
+            .xkb_get_names(xkb::ID::USE_CORE_KBD.into(), xkb::NameDetail::GROUP_NAMES)?
+            .reply()?;
+        reply
+            .value_list
+            .groups
+            .unwrap_or_default()
+            .into_iter()
+            .map(|atom| {
+                if atom == x11rb::NONE {
+                    return Ok(String::new());
+                }
+                let name = conn.get_atom_name(atom)?.reply()?.name;
+                Ok(String::from_utf8_lossy(&name).into_owned())
+            })
+            .collect()
+    }
+
+    /// Fill the names of the unnamed `layouts` from the XKB group names, for the libxkbcommon
+    /// builds not naming them.
+    pub fn name_keyboard_layouts(&self, layouts: &mut KeyboardLayouts) {
+        if layouts.names.iter().all(|name| !name.is_empty()) {
+            return;
+        }
+
+        let group_names = match self.xkb_group_names() {
+            Ok(group_names) => group_names,
+            Err(err) => {
+                tracing::warn!("failed to get the XKB group names: {err}");
+                return;
+            },
+        };
+        for (name, group_name) in layouts.names.iter_mut().zip(group_names) {
+            if name.is_empty() {
+                *name = group_name;
+            }
+        }
+    }
 }
 
 fn first_bit(b: u8) -> u8 {
@@ -19,6 +19,7 @@ mod randr;
 mod window_property;
 mod wm;
 mod xmodmap;
+mod xtest;
 
 pub use self::cursor::*;
 pub use self::geometry::*;
@@ -0,0 +1,38 @@
+//! Synthetic input with the XTEST extension.
+
+use x11rb::protocol::xtest::{self, ConnectionExt as _};
+
+use super::*;
+
+impl XConnection {
+    /// Fake the input event with XTEST, waiting until the server has processed it.
+    ///
+    /// The coordinates are only used by the motion events, relative to the `root` window.
+    pub fn fake_input(
+        &self,
+        event_type: u8,
+        detail: u8,
+        root: xproto::Window,
+        root_x: i16,
+        root_y: i16,
+    ) -> Result<(), X11Error> {
+        if !self.has_xtest() {
+            return Err(X11Error::MissingExtension(xtest::X11_EXTENSION_NAME));
+        }
+
+        self.xcb_connection()
+            .xtest_fake_input(
+                event_type,
+                detail,
+                x11rb::CURRENT_TIME,
+                root,
+                root_x,
+                root_y,
+                x11rb::NONE as u8,
+            )?
+            .check()?;
+
+        // The events generated by the server are queued along the way.
+        self.sync_with_server().map_err(Into::into)
+    }
+}
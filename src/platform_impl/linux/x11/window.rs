@@ -12,14 +12,22 @@ use x11rb::protocol::shape::SK;
 use x11rb::protocol::xfixes::{ConnectionExt, RegionWrapper};
 use x11rb::protocol::xproto::{self, AtomEnum, ConnectionExt as _, Rectangle, Window};
 use x11rb::protocol::{randr, xinput};
+use crate::clipboard::{MimeData, Selection};
 use crate::cursor::{Cursor, CustomCursor as RootCustomCursor};
+use crate::dnd::{DndActions, DragData};
 use crate::dpi::{PhysicalPosition, PhysicalSize, Position, Size};
 use crate::error::{ExternalError, NotSupportedError, OsError as RootOsError};
-use crate::event::{Event, InnerSizeWriter, WindowEvent};
+use crate::event::{ElementState, Event, InnerSizeWriter, MouseButton, WindowEvent};
 use crate::event_loop::AsyncRequestSerial;
+use crate::keyboard::PhysicalKey;
+use crate::platform::layer_shell::{KeyboardInteractivity, Layer};
 use crate::platform::x11::WindowType;
+use crate::platform_impl::common::channel::WakeSender;
+use crate::platform_impl::common::dnd::DropResponse;
+use crate::platform_impl::common::{layer_shell, popup};
+use crate::platform_impl::common::xkb::physicalkey_to_scancode;
 use crate::platform_impl::x11::atoms::*;
-use crate::platform_impl::x11::{xinput_fp1616_to_float, DeviceId, MonitorHandle as X11MonitorHandle, WakeSender, X11Error};
+use crate::platform_impl::x11::{xinput_fp1616_to_float, DeviceId, MonitorHandle as X11MonitorHandle, X11Error};
 use crate::platform_impl::{
     Fullscreen, MonitorHandle as PlatformMonitorHandle, OsError, PlatformCustomCursor,
     PlatformIcon, VideoModeHandle as PlatformVideoModeHandle,
@@ -29,9 +37,11 @@ use crate::window::{
     WindowButtons, WindowLevel,
 };
 
+use super::event_processor::KEYCODE_OFFSET;
 use super::util::{self, SelectedCursor};
 use super::{
-    ffi, ActiveEventLoop, CookieResultExt, ImeRequest, ImeSender, VoidCookie, WindowId, XConnection,
+    ffi, ActiveEventLoop, ClipboardRequest, CookieResultExt, DndRequest, ImeRequest, ImeSender,
+    VoidCookie, WindowId, XConnection,
 };
 
 #[derive(Debug)]
@@ -61,6 +71,8 @@ pub struct SharedState {
     pub has_focus: bool,
     // Use `Option` to not apply hittest logic when it was never requested.
     pub cursor_hittest: Option<bool>,
+    /// Whether the window is a popup taking the pointer and keyboard grab while it's mapped.
+    pub popup_grab: bool,
 }
 
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
@@ -99,6 +111,7 @@ impl SharedState {
             base_size: None,
             has_focus: false,
             cursor_hittest: None,
+            popup_grab: window_attributes.popup.is_some() && window_attributes.active,
         })
     }
 }
@@ -122,6 +135,8 @@ pub struct UnownedWindow {
     pub shared_state: Mutex<SharedState>,
     redraw_sender: WakeSender<WindowId>,
     activation_sender: WakeSender<super::ActivationToken>,
+    clipboard_sender: WakeSender<ClipboardRequest>,
+    dnd_sender: WakeSender<DndRequest>,
 }
 
 macro_rules! leap {
@@ -218,6 +233,42 @@ impl UnownedWindow {
             dimensions
         };
 
+        // Place the popup next to its parent, on the monitor of the parent.
+        let (position, dimensions) = match window_attrs.popup.as_ref() {
+            Some(popup) => {
+                let parent = popup.parent.0 .0 as xproto::Window;
+                let coords = leap!(xconn.translate_coords(parent, root));
+                let parent_position =
+                    PhysicalPosition::<i32>::new(coords.dst_x.into(), coords.dst_y.into());
+                let monitor = leap!(xconn.available_monitors()).into_iter().find(|monitor| {
+                    monitor.rect.contains_point(parent_position.x.into(), parent_position.y.into())
+                });
+                let scale_factor =
+                    monitor.as_ref().map_or(scale_factor, |monitor| monitor.scale_factor());
+                let bounds = monitor.map(|monitor| (monitor.position(), monitor.size()));
+                let (position, size) =
+                    popup::place(popup, parent_position, scale_factor, dimensions.into(), bounds);
+                (Some(position), size.into())
+            },
+            None => (position, dimensions),
+        };
+
+        // Place the layer shell fallback on the edges of the primary monitor, like the compositor.
+        let layer_shell = match window_attrs.platform_specific.layer_shell {
+            Some(layer_shell) => Some((layer_shell, leap!(xconn.primary_monitor()))),
+            None => None,
+        };
+        let (position, dimensions) = match layer_shell.as_ref() {
+            Some((attributes, monitor)) => {
+                let bounds = (monitor.position(), monitor.size());
+                let scale_factor = monitor.scale_factor();
+                let (position, size) =
+                    layer_shell::geometry(attributes, bounds, scale_factor, dimensions.into());
+                (Some(position), size.into())
+            },
+            None => (position, dimensions),
+        };
+
         // An iterator over the visuals matching screen id combined with their depths.
         let mut all_visuals = screen
             .allowed_depths
@@ -272,7 +323,9 @@ impl UnownedWindow {
 
             aux = aux.event_mask(event_mask).border_pixel(0);
 
-            if window_attrs.platform_specific.x11.override_redirect {
+            // The popups are placed by us, and not by the window manager.
+            if window_attrs.platform_specific.x11.override_redirect || window_attrs.popup.is_some()
+            {
                 aux = aux.override_redirect(true as u32);
             }
 
@@ -349,6 +402,8 @@ impl UnownedWindow {
             shared_state: SharedState::new(guessed_monitor, &window_attrs),
             redraw_sender: event_loop.redraw_sender.clone(),
             activation_sender: event_loop.activation_sender.clone(),
+            clipboard_sender: event_loop.clipboard_sender.clone(),
+            dnd_sender: event_loop.dnd_sender.clone(),
         };
 
         // Title must be set before mapping. Some tiling window managers (i.e. i3) use the window
@@ -366,6 +421,12 @@ impl UnownedWindow {
             window.embed_window()?;
         }
 
+        if let Some(owner) =
+            window_attrs.popup.as_ref().map(|popup| popup.parent).or(window_attrs.owner)
+        {
+            window.set_owner_raw(Some(owner.0 .0 as xproto::Window))?;
+        }
+
         if let Some(t) = window_attrs.platform_specific.x11.modal_owner {
             window.set_modal_raw(t)?;
         }
@@ -418,8 +479,52 @@ impl UnownedWindow {
                 flusher.ignore_error()
             }
 
-            leap!(window.set_window_types(window_attrs.platform_specific.x11.x11_window_types))
-                .ignore_error();
+            // Hint the compositor about the popups which didn't choose their type.
+            let mut window_types = window_attrs.platform_specific.x11.x11_window_types;
+            if window_attrs.popup.is_some() && window_types == [WindowType::Normal] {
+                let popup_type =
+                    if window_attrs.active { WindowType::PopupMenu } else { WindowType::Tooltip };
+                window_types = vec![popup_type];
+            }
+            if let Some((attributes, monitor)) = layer_shell.as_ref() {
+                if window_types == [WindowType::Normal] {
+                    let layer_type = match attributes.layer {
+                        Layer::Background => WindowType::Desktop,
+                        _ => WindowType::Dock,
+                    };
+                    window_types = vec![layer_type];
+                }
+
+                let bounds = (monitor.position(), monitor.size());
+                let root_size = PhysicalSize::new(
+                    screen.width_in_pixels as u32,
+                    screen.height_in_pixels as u32,
+                );
+                let strut = layer_shell::strut(
+                    attributes,
+                    bounds,
+                    monitor.scale_factor(),
+                    root_size,
+                    position.unwrap(),
+                    dimensions.into(),
+                );
+                if let Some(strut) = strut {
+                    leap!(xconn.change_property(
+                        window.xwindow,
+                        atoms[_NET_WM_STRUT_PARTIAL],
+                        xproto::Atom::from(xproto::AtomEnum::CARDINAL),
+                        xproto::PropMode::REPLACE,
+                        &strut,
+                    ))
+                    .ignore_error();
+                }
+
+                let mut wm_hints = WmHints::new();
+                wm_hints.input =
+                    Some(attributes.keyboard_interactivity != KeyboardInteractivity::None);
+                leap!(wm_hints.set(xconn.xcb_connection(), window.xwindow)).ignore_error();
+            }
+            leap!(window.set_window_types(window_types)).ignore_error();
 
             // Set size hints.
             let mut min_inner_size =
@@ -502,7 +607,15 @@ impl UnownedWindow {
                 | xinput::XIEventMask::TOUCH_BEGIN
                 | xinput::XIEventMask::TOUCH_UPDATE
                 | xinput::XIEventMask::TOUCH_END;
-            leap!(xconn.select_xinput_events(window.xwindow, super::ALL_MASTER_DEVICES, mask))
+            let mut mask = vec![mask];
+            if event_loop.xi2_gestures {
+                // The gesture events don't fit in the first word of the mask.
+                let gestures = (ffi::XI_GesturePinchBegin..=ffi::XI_GestureSwipeEnd)
+                    .fold(0u64, |mask, evtype| mask | (1 << evtype));
+                mask[0] |= xinput::XIEventMask::from(gestures as u32);
+                mask.push(xinput::XIEventMask::from((gestures >> 32) as u32));
+            }
+            leap!(xconn.select_xinput_events(window.xwindow, super::ALL_MASTER_DEVICES, &mask))
                 .ignore_error();
 
             // Set visibility (map window)
@@ -553,7 +666,14 @@ impl UnownedWindow {
                 }
             }
 
-            leap!(window.set_window_level_inner(window_attrs.window_level)).ignore_error();
+            let window_level = window_attrs.window_level;
+            let layer = layer_shell.as_ref().map(|(attributes, _)| attributes.layer);
+            let window_level = match layer {
+                Some(Layer::Background | Layer::Bottom) => WindowLevel::AlwaysOnBottom,
+                Some(Layer::Top | Layer::Overlay) => WindowLevel::AlwaysOnTop,
+                None => window_level,
+            };
+            leap!(window.set_window_level_inner(window_level)).ignore_error();
         }
 
         window.set_cursor(window_attrs.cursor);
@@ -595,22 +715,113 @@ impl UnownedWindow {
             &[atoms[_NET_WM_STATE_MODAL]],
         ))
         .check());
-        leap!(leap!(self.xconn.change_property(
-            self.xwindow,
-            atoms[WM_TRANSIENT_FOR],
-            AtomEnum::WINDOW.into(),
-            xproto::PropMode::REPLACE,
-            &[owner_window],
-        ))
-        .check());
-
-        Ok(())
+        self.set_owner_raw(Some(owner_window))
     }
 
     pub fn set_modal(&self, owner_window: &Self) -> Result<(), RootOsError> {
         self.set_modal_raw(owner_window.xwindow)
     }
 
+    pub fn set_owner_raw(&self, owner_window: Option<xproto::Window>) -> Result<(), RootOsError> {
+        let atoms = self.xconn.atoms();
+
+        match owner_window {
+            Some(owner_window) => leap!(leap!(self.xconn.change_property(
+                self.xwindow,
+                atoms[WM_TRANSIENT_FOR],
+                AtomEnum::WINDOW.into(),
+                xproto::PropMode::REPLACE,
+                &[owner_window],
+            ))
+            .check()),
+            None => leap!(leap!(self
+                .xconn
+                .xcb_connection()
+                .delete_property(self.xwindow, atoms[WM_TRANSIENT_FOR]))
+            .check()),
+        }
+
+        Ok(())
+    }
+
+    #[inline]
+    pub fn set_owner(&self, owner_window: Option<&Self>) {
+        if let Err(err) = self.set_owner_raw(owner_window.map(|owner_window| owner_window.xwindow))
+        {
+            warn!("Failed to set the owner of the window: {err}");
+        }
+    }
+
+    /// Take the pointer and keyboard grab of a popup, once it's mapped.
+    pub(super) fn grab_popup(&self) {
+        if !self.shared_state_lock().popup_grab {
+            return;
+        }
+
+        // The events for the other windows of the application are reported as usual, and the
+        // core button presses to the popup, which hit-tests them against the windows of the
+        // application.
+        let pointer_grab = self
+            .xconn
+            .xcb_connection()
+            .grab_pointer(
+                true,
+                self.xwindow,
+                xproto::EventMask::BUTTON_PRESS
+                    | xproto::EventMask::BUTTON_RELEASE
+                    | xproto::EventMask::ENTER_WINDOW
+                    | xproto::EventMask::LEAVE_WINDOW
+                    | xproto::EventMask::POINTER_MOTION,
+                xproto::GrabMode::ASYNC,
+                xproto::GrabMode::ASYNC,
+                x11rb::NONE,
+                x11rb::NONE,
+                x11rb::CURRENT_TIME,
+            )
+            .map_err(X11Error::from)
+            .and_then(|cookie| cookie.reply().map_err(X11Error::from))
+            .map(|reply| reply.status);
+        let keyboard_grab = self
+            .xconn
+            .xcb_connection()
+            .grab_keyboard(
+                false,
+                self.xwindow,
+                x11rb::CURRENT_TIME,
+                xproto::GrabMode::ASYNC,
+                xproto::GrabMode::ASYNC,
+            )
+            .map_err(X11Error::from)
+            .and_then(|cookie| cookie.reply().map_err(X11Error::from))
+            .map(|reply| reply.status);
+
+        for (device, grab) in [("pointer", pointer_grab), ("keyboard", keyboard_grab)] {
+            match grab {
+                Ok(xproto::GrabStatus::SUCCESS) => (),
+                Ok(status) => warn!("Failed to grab the {device} for the popup: {status:?}"),
+                Err(err) => warn!("Failed to grab the {device} for the popup: {err}"),
+            }
+        }
+    }
+
+    /// Dismiss the popup when the click at the given position is outside of it.
+    ///
+    /// Returns whether the popup was dismissed.
+    pub(super) fn dismiss_popup(&self, x: i32, y: i32) -> bool {
+        if !self.shared_state_lock().popup_grab {
+            return false;
+        }
+
+        let (width, height) = self.inner_size_physical();
+        if (0..width as i32).contains(&x) && (0..height as i32).contains(&y) {
+            return false;
+        }
+
+        // Unmapping the popup releases its grab.
+        self.set_visible(false);
+        true
+    }
+
     pub(super) fn shared_state_lock(&self) -> MutexGuard<'_, SharedState> {
         self.shared_state.lock().unwrap()
     }
@@ -788,7 +999,6 @@ impl UnownedWindow {
                     Fullscreen::Borderless(None) => {
                         (None, self.shared_state_lock().last_monitor.clone())
                     },
-                    #[cfg(wayland_platform)]
                     _ => unreachable!(),
                 };
 
@@ -1519,9 +1729,8 @@ impl UnownedWindow {
 
                 *self.selected_cursor.lock().unwrap() = SelectedCursor::Custom(cursor);
             },
-            #[cfg(wayland_platform)]
-            Cursor::Custom(RootCustomCursor { inner: PlatformCustomCursor::Wayland(_) }) => {
-                tracing::error!("passed a Wayland cursor to X11 backend")
+            Cursor::Custom(_) => {
+                tracing::error!("passed a cursor of another backend to X11 backend")
             },
         }
     }
@@ -1666,6 +1875,71 @@ impl UnownedWindow {
         self.set_cursor_position_physical(x, y)
     }
 
+    fn fake_input(&self, event_type: u8, detail: u8, x: i16, y: i16) -> Result<(), ExternalError> {
+        self.xconn.fake_input(event_type, detail, self.root, x, y).map_err(|error| match error {
+            X11Error::MissingExtension(_) => ExternalError::NotSupported(NotSupportedError::new()),
+            error => ExternalError::Os(os_error!(OsError::XError(error.into()))),
+        })
+    }
+
+    /// Press or release the key with XTEST, delivered to the focused window.
+    pub fn inject_key(&self, key: PhysicalKey, state: ElementState) -> Result<(), ExternalError> {
+        let keycode = physicalkey_to_scancode(key)
+            .and_then(|scancode| u8::try_from(scancode + KEYCODE_OFFSET as u32).ok())
+            .ok_or(ExternalError::Ignored)?;
+        let event_type = match state {
+            ElementState::Pressed => xproto::KEY_PRESS_EVENT,
+            ElementState::Released => xproto::KEY_RELEASE_EVENT,
+        };
+        self.fake_input(event_type, keycode, 0, 0)
+    }
+
+    /// Move the pointer with XTEST, the position is relative to the window.
+    pub fn inject_pointer_motion(&self, position: Position) -> Result<(), ExternalError> {
+        let PhysicalPosition { x, y } = position.to_physical::<i32>(self.scale_factor());
+        let (window_x, window_y) = self.inner_position_physical();
+        let x = i16::try_from(window_x.saturating_add(x)).map_err(|_| ExternalError::Ignored)?;
+        let y = i16::try_from(window_y.saturating_add(y)).map_err(|_| ExternalError::Ignored)?;
+        self.fake_input(xproto::MOTION_NOTIFY_EVENT, 0, x, y)
+    }
+
+    /// Press or release the pointer button with XTEST.
+    pub fn inject_button(
+        &self,
+        button: MouseButton,
+        state: ElementState,
+    ) -> Result<(), ExternalError> {
+        let button = match button {
+            MouseButton::Left => 1,
+            MouseButton::Middle => 2,
+            MouseButton::Right => 3,
+            MouseButton::Back => 8,
+            MouseButton::Forward => 9,
+            MouseButton::Other(button) => {
+                u8::try_from(button).map_err(|_| ExternalError::Ignored)?
+            },
+        };
+        let event_type = match state {
+            ElementState::Pressed => xproto::BUTTON_PRESS_EVENT,
+            ElementState::Released => xproto::BUTTON_RELEASE_EVENT,
+        };
+        self.fake_input(event_type, button, 0, 0)
+    }
+
+    /// Scroll with XTEST, clicking the wheel buttons once per line or column.
+    pub fn inject_scroll(&self, columns: i32, lines: i32) -> Result<(), ExternalError> {
+        let horizontal = if columns > 0 { 6 } else { 7 };
+        let vertical = if lines > 0 { 4 } else { 5 };
+        let clicks = std::iter::repeat(horizontal)
+            .take(columns.unsigned_abs() as usize)
+            .chain(std::iter::repeat(vertical).take(lines.unsigned_abs() as usize));
+        for button in clicks {
+            self.fake_input(xproto::BUTTON_PRESS_EVENT, button, 0, 0)?;
+            self.fake_input(xproto::BUTTON_RELEASE_EVENT, button, 0, 0)?;
+        }
+        Ok(())
+    }
+
     #[inline]
     pub fn set_cursor_hittest(&self, hittest: bool) -> Result<(), ExternalError> {
         let mut rectangles: Vec<Rectangle> = Vec::new();
@@ -1784,6 +2058,9 @@ impl UnownedWindow {
     #[inline]
     pub fn set_ime_purpose(&self, _purpose: ImePurpose) {}
 
+    #[inline]
+    pub fn set_ime_surrounding_text(&self, _text: String, _cursor: usize, _anchor: usize) {}
+
     #[inline]
     pub fn commit_ime(&self) {}
 
@@ -1869,6 +2146,55 @@ impl UnownedWindow {
         Ok(serial)
     }
 
+    #[inline]
+    pub fn set_clipboard(
+        &self,
+        selection: Selection,
+        data: Vec<MimeData>,
+    ) -> Result<(), NotSupportedError> {
+        self.clipboard_sender
+            .send(ClipboardRequest::Set { window: self.xwindow, selection, data })
+            .expect("clipboard channel should never be closed");
+        Ok(())
+    }
+
+    #[inline]
+    pub fn request_clipboard(
+        &self,
+        selection: Selection,
+        mime_types: Vec<String>,
+    ) -> Result<AsyncRequestSerial, NotSupportedError> {
+        let serial = AsyncRequestSerial::get();
+        self.clipboard_sender
+            .send(ClipboardRequest::Get { window: self.xwindow, selection, mime_types, serial })
+            .expect("clipboard channel should never be closed");
+        Ok(serial)
+    }
+
+    #[inline]
+    pub fn set_drop_response(&self, response: DropResponse) -> Result<(), NotSupportedError> {
+        self.dnd_sender
+            .send(DndRequest::Respond { window: self.xwindow, response })
+            .expect("drag and drop channel should never be closed");
+        Ok(())
+    }
+
+    #[inline]
+    pub fn start_drag(
+        &self,
+        data: DragData,
+        allowed_actions: DndActions,
+    ) -> Result<(), NotSupportedError> {
+        self.dnd_sender
+            .send(DndRequest::StartDrag {
+                window: self.xwindow,
+                data: data.items,
+                actions: allowed_actions,
+            })
+            .expect("drag and drop channel should never be closed");
+        Ok(())
+    }
+
     #[inline]
     pub fn id(&self) -> WindowId {
         WindowId(self.xwindow as _)
@@ -9,9 +9,10 @@ use crate::window::CursorIcon;
 use super::atoms::Atoms;
 use super::ffi;
 use super::monitor::MonitorHandle;
-use x11rb::connection::Connection;
+use x11rb::connection::{Connection, RequestConnection};
 use x11rb::protocol::randr::ConnectionExt as _;
 use x11rb::protocol::xproto::{self, ConnectionExt};
+use x11rb::protocol::xtest;
 use x11rb::resource_manager;
 use x11rb::xcb_ffi::XCBConnection;
 
@@ -55,6 +56,9 @@ pub struct XConnection {
     /// Atom for the XSettings screen.
     xsettings_screen: Option<xproto::Atom>,
 
+    /// Whether the server has the XTEST extension.
+    has_xtest: bool,
+
     pub latest_error: Mutex<Option<XError>>,
     pub cursor_cache: Mutex<HashMap<Option<CursorIcon>, ffi::Cursor>>,
 }
@@ -118,6 +122,10 @@ impl XConnection {
             tracing::warn!("error setting XSETTINGS; Xft options won't reload automatically")
         }
 
+        // Check for XTEST once, it's only used to synthesize the input.
+        let has_xtest =
+            xcb.extension_information(xtest::X11_EXTENSION_NAME).ok().flatten().is_some();
+
         // Fetch atoms.
         let atoms = Atoms::new(&xcb)
             .map_err(|e| XNotSupported::XcbConversionError(Arc::new(e)))?
@@ -139,6 +147,7 @@ impl XConnection {
             cursor_cache: Default::default(),
             randr_version: (randr_version.major_version, randr_version.minor_version),
             xsettings_screen,
+            has_xtest,
         })
     }
 
@@ -184,6 +193,12 @@ impl XConnection {
         self.randr_version
     }
 
+    /// Whether the server has the XTEST extension, checked when connecting.
+    #[inline]
+    pub fn has_xtest(&self) -> bool {
+        self.has_xtest
+    }
+
     /// Get the underlying XCB connection.
     #[inline]
     pub fn xcb_connection(&self) -> &XCBConnection {
@@ -30,8 +30,10 @@ use super::observer::setup_control_flow_observers;
 use crate::error::EventLoopError;
 use crate::event::{Event};
 use crate::event_loop::{
-    ActiveEventLoop as RootWindowTarget, ControlFlow, DeviceEvents, EventLoopClosed,
+    ActiveEventLoop as RootWindowTarget, ControlFlow, DeviceEvents, EventLoopClosed, TimerId,
+    TimerSchedule,
 };
+use crate::keyboard::KeyboardLayouts;
 use crate::platform::macos::ActivationPolicy;
 use crate::platform::pump_events::PumpStatus;
 use crate::platform_impl::DeviceId;
@@ -103,6 +105,10 @@ impl ActiveEventLoop {
     #[inline]
     pub fn listen_device_events(&self, _allowed: DeviceEvents) {}
 
+    pub fn schedule_timer(&self, _timer_id: TimerId, _schedule: TimerSchedule) {}
+
+    pub fn cancel_timer(&self, _timer_id: TimerId) {}
+
     #[cfg(feature = "rwh_05")]
     #[inline]
     pub fn raw_display_handle_rwh_05(&self) -> rwh_05::RawDisplayHandle {
@@ -120,6 +126,11 @@ impl ActiveEventLoop {
         }
     }
 
+    #[inline]
+    pub fn keyboard_layouts(&self) -> Option<KeyboardLayouts> {
+        None
+    }
+
     #[cfg(feature = "rwh_06")]
     #[inline]
     pub fn raw_display_handle_rwh_06(
@@ -16,7 +16,10 @@ use crate::cursor::Cursor;
 use crate::dpi::{PhysicalPosition, PhysicalSize, Position, Size};
 use crate::error::{self, EventLoopError};
 use crate::event::{self, Force, InnerSizeWriter, StartCause};
-use crate::event_loop::{self, ActiveEventLoop as RootAEL, ControlFlow, DeviceEvents};
+use crate::event_loop::{
+    self, ActiveEventLoop as RootAEL, ControlFlow, DeviceEvents, TimerId, TimerSchedule,
+};
+use crate::keyboard::KeyboardLayouts;
 use crate::window::{
     self, CursorGrabMode, CustomCursor, CustomCursorSource, Fullscreen, ImePurpose,
     ResizeDirection, Theme, WindowButtons, WindowLevel,
@@ -139,6 +142,7 @@ impl<T: 'static> EventLoop<T> {
                                 location: position,
                                 id: pointer.id as u64,
                                 force: Some(Force::Normalized(pointer.force as f64)),
+                                contact: None,
                             }),
                         };
                         if let Some(ref mut h) = *self.event_loop.borrow_mut() {
@@ -424,8 +428,16 @@ impl ActiveEventLoop {
         None
     }
 
+    pub fn keyboard_layouts(&self) -> Option<KeyboardLayouts> {
+        None
+    }
+
     pub fn listen_device_events(&self, _allowed: DeviceEvents) {}
 
+    pub fn schedule_timer(&self, _timer_id: TimerId, _schedule: TimerSchedule) {}
+
+    pub fn cancel_timer(&self, _timer_id: TimerId) {}
+
     pub fn set_control_flow(&self, control_flow: ControlFlow) {
         self.control_flow.set(control_flow)
     }
@@ -507,6 +519,16 @@ impl DeviceId {
     pub const fn dummy() -> Self {
         DeviceId(0)
     }
+
+    #[cfg(feature = "serde")]
+    pub(crate) fn into_raw(self) -> i64 {
+        self.0 as i64
+    }
+
+    #[cfg(feature = "serde")]
+    pub(crate) fn from_raw(raw: i64) -> Option<Self> {
+        i32::try_from(raw).ok().map(DeviceId)
+    }
 }
 
 #[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
@@ -656,6 +678,8 @@ impl Window {
 
     pub fn set_ime_purpose(&self, _purpose: ImePurpose) {}
 
+    pub fn set_ime_surrounding_text(&self, _text: String, _cursor: usize, _anchor: usize) {}
+
     pub fn commit_ime(&self) {}
 
     pub fn focus_window(&self) {}
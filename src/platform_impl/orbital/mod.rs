@@ -126,6 +126,16 @@ impl DeviceId {
     pub const fn dummy() -> Self {
         DeviceId
     }
+
+    #[cfg(feature = "serde")]
+    pub(crate) fn into_raw(self) -> i64 {
+        0
+    }
+
+    #[cfg(feature = "serde")]
+    pub(crate) fn from_raw(_raw: i64) -> Option<Self> {
+        Some(DeviceId)
+    }
 }
 
 #[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
@@ -5,4 +5,14 @@ impl DeviceId {
     pub const fn dummy() -> Self {
         Self(0)
     }
+
+    #[cfg(feature = "serde")]
+    pub(crate) fn into_raw(self) -> i64 {
+        self.0 as i64
+    }
+
+    #[cfg(feature = "serde")]
+    pub(crate) fn from_raw(raw: i64) -> Option<Self> {
+        i32::try_from(raw).ok().map(Self)
+    }
 }
@@ -16,8 +16,8 @@ use super::{backend, runner};
 use crate::event::{
     DeviceId as RootDeviceId, ElementState, Event, KeyEvent, Touch, TouchPhase, WindowEvent,
 };
-use crate::event_loop::{ControlFlow, DeviceEvents};
-use crate::keyboard::ModifiersState;
+use crate::event_loop::{ControlFlow, DeviceEvents, TimerId, TimerSchedule};
+use crate::keyboard::{KeyboardLayouts, ModifiersState};
 use crate::platform::web::{CustomCursorFuture, PollStrategy, WaitUntilStrategy};
 use crate::platform_impl::platform::cursor::CustomCursor;
 use crate::platform_impl::platform::r#async::Waker;
@@ -306,6 +306,7 @@ impl ActiveEventLoop {
                                 device_id: RootDeviceId(DeviceId(device_id)),
                                 phase: TouchPhase::Moved,
                                 force: Some(force),
+                                contact: None,
                                 location,
                             }),
                         },
@@ -412,6 +413,7 @@ impl ActiveEventLoop {
                                 device_id: RootDeviceId(DeviceId(device_id)),
                                 phase: TouchPhase::Started,
                                 force: Some(force),
+                                contact: None,
                                 location,
                             }),
                         },
@@ -480,6 +482,7 @@ impl ActiveEventLoop {
                                 device_id: RootDeviceId(DeviceId(device_id)),
                                 phase: TouchPhase::Ended,
                                 force: Some(force),
+                                contact: None,
                                 location,
                             }),
                         },
@@ -521,6 +524,7 @@ impl ActiveEventLoop {
                     device_id: RootDeviceId(DeviceId(device_id)),
                     phase: TouchPhase::Cancelled,
                     force: Some(force),
+                    contact: None,
                     location,
                 }),
             });
@@ -614,6 +618,10 @@ impl ActiveEventLoop {
         self.runner.listen_device_events(allowed)
     }
 
+    pub fn schedule_timer(&self, _timer_id: TimerId, _schedule: TimerSchedule) {}
+
+    pub fn cancel_timer(&self, _timer_id: TimerId) {}
+
     pub fn system_theme(&self) -> Option<Theme> {
         backend::is_dark_mode(self.runner.window()).map(|is_dark_mode| {
             if is_dark_mode {
@@ -624,6 +632,10 @@ impl ActiveEventLoop {
         })
     }
 
+    pub fn keyboard_layouts(&self) -> Option<KeyboardLayouts> {
+        None
+    }
+
     pub(crate) fn set_control_flow(&self, control_flow: ControlFlow) {
         self.runner.set_control_flow(control_flow)
     }
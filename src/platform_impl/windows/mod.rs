@@ -70,6 +70,16 @@ impl DeviceId {
     pub const fn dummy() -> Self {
         DeviceId(0)
     }
+
+    #[cfg(feature = "serde")]
+    pub(crate) fn into_raw(self) -> i64 {
+        self.0 as i64
+    }
+
+    #[cfg(feature = "serde")]
+    pub(crate) fn from_raw(raw: i64) -> Option<Self> {
+        u32::try_from(raw).ok().map(DeviceId)
+    }
 }
 
 impl DeviceId {
@@ -37,7 +37,7 @@ use windows_sys::Win32::UI::WindowsAndMessaging::{
     SetCursor, SetCursorPos, SetForegroundWindow, SetMenuDefaultItem, SetWindowDisplayAffinity,
     SetWindowPlacement, SetWindowPos, SetWindowTextW, TrackPopupMenu, CS_HREDRAW, CS_VREDRAW,
     CW_USEDEFAULT, FLASHWINFO, FLASHW_ALL, FLASHW_STOP, FLASHW_TIMERNOFG, FLASHW_TRAY,
-    GWLP_HINSTANCE, HTBOTTOM, HTBOTTOMLEFT, HTBOTTOMRIGHT, HTCAPTION, HTLEFT, HTRIGHT, HTTOP,
+    GWLP_HINSTANCE, GWLP_HWNDPARENT, HTBOTTOM, HTBOTTOMLEFT, HTBOTTOMRIGHT, HTCAPTION, HTLEFT, HTRIGHT, HTTOP,
     HTTOPLEFT, HTTOPRIGHT, MENU_ITEM_STATE, MFS_DISABLED, MFS_ENABLED, MF_BYCOMMAND, NID_READY,
     PM_NOREMOVE, SC_CLOSE, SC_MAXIMIZE, SC_MINIMIZE, SC_MOVE, SC_RESTORE, SC_SIZE, SM_DIGITIZER,
     SWP_ASYNCWINDOWPOS, SWP_NOACTIVATE, SWP_NOSIZE, SWP_NOZORDER, TPM_LEFTALIGN, TPM_RETURNCMD,
@@ -922,6 +922,13 @@ impl Window {
         unsafe { EnableWindow(self.hwnd(), enabled.into()) };
     }
 
+    #[inline]
+    pub fn set_owner(&self, owner: Option<&Window>) {
+        // NOTE: Despite its name, `GWLP_HWNDPARENT` is the owner of the top-level windows.
+        let owner = owner.map_or(0, |owner| owner.hwnd());
+        unsafe { super::set_window_long(self.hwnd(), GWLP_HWNDPARENT, owner) };
+    }
+
     #[inline]
     pub fn set_taskbar_icon(&self, taskbar_icon: Option<Icon>) {
         if let Some(ref taskbar_icon) = taskbar_icon {
@@ -955,6 +962,9 @@ impl Window {
     #[inline]
     pub fn set_ime_purpose(&self, _purpose: ImePurpose) {}
 
+    #[inline]
+    pub fn set_ime_surrounding_text(&self, _text: String, _cursor: usize, _anchor: usize) {}
+
     #[inline]
     pub fn commit_ime(&self) {}
 
@@ -1357,7 +1367,8 @@ unsafe fn init(
     window_flags.set(WindowFlags::CLOSABLE, true);
     window_flags.set(WindowFlags::CLIP_CHILDREN, attributes.platform_specific.clip_children);
 
-    let mut fallback_parent = || match attributes.platform_specific.owner {
+    let owner = attributes.owner.map(|owner| HWND::from(owner.0));
+    let mut fallback_parent = || match attributes.platform_specific.owner.or(owner) {
         Some(parent) => {
             window_flags.set(WindowFlags::POPUP, true);
             Some(parent)
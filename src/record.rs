@@ -0,0 +1,453 @@
+//! Recording and replaying the event stream.
+//!
+//! The [`Recorder`] wraps an [`ApplicationHandler`] and hands every [`Event`] it receives to a
+//! sink as a [`RecordedEvent`], along with the time elapsed since the recording started. With
+//! the `serde` feature, the records implement `Serialize` and `Deserialize`, so the sink can
+//! write them to a file with any serde format, e.g. as JSON lines.
+//!
+//! ```no_run
+//! use std::sync::mpsc;
+//!
+//! use deft_winit::application::ApplicationHandler;
+//! use deft_winit::event::WindowEvent;
+//! use deft_winit::event_loop::{ActiveEventLoop, EventLoop};
+//! use deft_winit::record::{RecordedEvent, Recorder};
+//! use deft_winit::window::WindowId;
+//!
+//! struct App;
+//!
+//! impl ApplicationHandler for App {
+//!     fn resumed(&mut self, _event_loop: &ActiveEventLoop) {}
+//!
+//!     fn window_event(&mut self, _: &ActiveEventLoop, _: WindowId, _: WindowEvent) {}
+//! }
+//!
+//! let (sender, receiver) = mpsc::channel::<RecordedEvent<()>>();
+//! std::thread::spawn(move || {
+//!     for _record in receiver {
+//!         // Keep the record in the trace.
+//!     }
+//! });
+//!
+//! let event_loop = EventLoop::new().unwrap();
+//! let recorder = Recorder::new(App, move |record: &RecordedEvent<()>| {
+//!     let _ = sender.send(record.clone());
+//! });
+//! event_loop.run_app(recorder).unwrap();
+//! ```
+//!
+//! The [`Replayer`] feeds the deserialized records back into an [`ApplicationHandler`], either
+//! with the original timing or as fast as possible, see [`ReplayTiming`]. The events of the
+//! running event loop are dropped, the application only sees the recorded ones, and the event
+//! loop exits once all of them were delivered.
+//!
+//! ```no_run
+//! # use deft_winit::application::ApplicationHandler;
+//! # use deft_winit::event::WindowEvent;
+//! # use deft_winit::event_loop::{ActiveEventLoop, EventLoop};
+//! # use deft_winit::window::WindowId;
+//! # struct App;
+//! # impl ApplicationHandler for App {
+//! #     fn resumed(&mut self, _event_loop: &ActiveEventLoop) {}
+//! #     fn window_event(&mut self, _: &ActiveEventLoop, _: WindowId, _: WindowEvent) {}
+//! # }
+//! use deft_winit::record::{RecordedEvent, ReplayTiming, Replayer};
+//!
+//! // The records collected by the recorder.
+//! let trace: Vec<RecordedEvent<()>> = Vec::new();
+//!
+//! let event_loop = EventLoop::new().unwrap();
+//! event_loop.run_app(Replayer::new(App, trace, ReplayTiming::Original)).unwrap();
+//! ```
+//!
+//! The window IDs differ between the runs, thus the recorded ones are mapped to the windows
+//! created by the application during the replay, in the order of their first event. The
+//! replay waits until the application has created the window a recorded event is targeting,
+//! up to the timeout set with [`Replayer::with_window_timeout`], then drops the events of the
+//! missing windows. The device IDs are mapped the same way to the devices of the live events,
+//! and to [`DeviceId::dummy`] until the replay has seen enough of them.
+//!
+//! See the [event module](crate::event#serialization) for the details of the serialized
+//! representation.
+
+use std::collections::HashMap;
+use std::iter::Peekable;
+#[cfg(not(web_platform))]
+use std::time::{Duration, Instant};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+#[cfg(web_platform)]
+use web_time::{Duration, Instant};
+
+use crate::application::ApplicationHandler;
+use crate::event::{DeviceEvent, DeviceId, Event, StartCause, WindowEvent};
+use crate::event_loop::{
+    dispatch_event_for_app, ActiveEventLoop, ControlFlow, Interest, SourceToken, TimerId,
+};
+use crate::window::WindowId;
+
+/// An [`Event`] along with the time it was received at.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct RecordedEvent<T: 'static> {
+    /// The time elapsed since the start of the recording.
+    pub time: Duration,
+    /// The recorded event.
+    pub event: Event<T>,
+}
+
+/// The [`ApplicationHandler`] recording the events delivered to the wrapped application.
+///
+/// See the [module-level docs](self) for more information.
+#[derive(Debug)]
+pub struct Recorder<A, F> {
+    app: A,
+    sink: F,
+    start: Instant,
+}
+
+impl<A, F> Recorder<A, F> {
+    /// Wrap the `app`, passing each event to the `sink` before the application handles it.
+    ///
+    /// The time of the events is relative to the creation of the recorder.
+    pub fn new(app: A, sink: F) -> Self {
+        Self { app, sink, start: Instant::now() }
+    }
+
+    /// The wrapped application.
+    pub fn app(&self) -> &A {
+        &self.app
+    }
+
+    /// The wrapped application.
+    pub fn app_mut(&mut self) -> &mut A {
+        &mut self.app
+    }
+
+    /// Unwrap the application.
+    pub fn into_inner(self) -> A {
+        self.app
+    }
+
+    fn record<T: 'static>(&mut self, event_loop: &ActiveEventLoop, event: Event<T>)
+    where
+        A: ApplicationHandler<T>,
+        F: FnMut(&RecordedEvent<T>),
+    {
+        let record = RecordedEvent { time: self.start.elapsed(), event };
+        (self.sink)(&record);
+        dispatch_event_for_app(&mut self.app, event_loop, record.event);
+    }
+}
+
+impl<T: 'static, A, F> ApplicationHandler<T> for Recorder<A, F>
+where
+    A: ApplicationHandler<T>,
+    F: FnMut(&RecordedEvent<T>),
+{
+    fn new_events(&mut self, event_loop: &ActiveEventLoop, cause: StartCause) {
+        self.record(event_loop, Event::NewEvents(cause));
+    }
+
+    fn resumed(&mut self, event_loop: &ActiveEventLoop) {
+        self.record(event_loop, Event::Resumed);
+    }
+
+    fn user_event(&mut self, event_loop: &ActiveEventLoop, event: T) {
+        self.record(event_loop, Event::UserEvent(event));
+    }
+
+    fn window_event(
+        &mut self,
+        event_loop: &ActiveEventLoop,
+        window_id: WindowId,
+        event: WindowEvent,
+    ) {
+        self.record(event_loop, Event::WindowEvent { window_id, event });
+    }
+
+    fn device_event(
+        &mut self,
+        event_loop: &ActiveEventLoop,
+        device_id: DeviceId,
+        event: DeviceEvent,
+    ) {
+        self.record(event_loop, Event::DeviceEvent { device_id, event });
+    }
+
+    fn timer_fired(&mut self, event_loop: &ActiveEventLoop, timer_id: TimerId) {
+        self.record(event_loop, Event::TimerFired(timer_id));
+    }
+
+    fn fd_ready(&mut self, event_loop: &ActiveEventLoop, token: SourceToken, readiness: Interest) {
+        self.record(event_loop, Event::FdReady { token, readiness });
+    }
+
+    fn about_to_wait(&mut self, event_loop: &ActiveEventLoop) {
+        self.record(event_loop, Event::AboutToWait);
+    }
+
+    fn suspended(&mut self, event_loop: &ActiveEventLoop) {
+        self.record(event_loop, Event::Suspended);
+    }
+
+    fn exiting(&mut self, event_loop: &ActiveEventLoop) {
+        self.record(event_loop, Event::LoopExiting);
+    }
+
+    fn memory_warning(&mut self, event_loop: &ActiveEventLoop) {
+        self.record(event_loop, Event::MemoryWarning);
+    }
+
+    fn reopen(&mut self, event_loop: &ActiveEventLoop, has_visible: bool) {
+        self.record(event_loop, Event::Reopen { has_visible });
+    }
+}
+
+/// The pace at which the [`Replayer`] delivers the recorded events.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ReplayTiming {
+    /// Deliver the events at the time they were recorded at, relative to the start of the
+    /// replay.
+    Original,
+
+    /// Deliver the events as fast as possible.
+    ///
+    /// The event loop still gets to process the requests of the application after each
+    /// recorded [`Event::AboutToWait`].
+    AsFastAsPossible,
+}
+
+/// How long the [`Replayer`] waits by default for the application to create a window.
+const DEFAULT_WINDOW_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// The [`ApplicationHandler`] replaying the recorded events into the wrapped application.
+///
+/// See the [module-level docs](self) for more information.
+pub struct Replayer<A, I: Iterator> {
+    app: A,
+    events: Peekable<I>,
+    timing: ReplayTiming,
+    start: Option<Instant>,
+    /// The live windows, in the order of their first event.
+    windows: Vec<WindowId>,
+    /// The recorded windows, mapped to the index of the live ones.
+    recorded_windows: HashMap<WindowId, usize>,
+    /// The live devices, in the order of their first event.
+    devices: Vec<DeviceId>,
+    /// The recorded devices, mapped to the index of the live ones.
+    recorded_devices: HashMap<DeviceId, usize>,
+    window_timeout: Duration,
+    /// When the replay started waiting for a window.
+    waiting_since: Option<Instant>,
+    exiting: bool,
+}
+
+impl<A, I: Iterator> Replayer<A, I> {
+    /// Wrap the `app`, replaying the `events` with the given `timing`.
+    pub fn new<E>(app: A, events: E, timing: ReplayTiming) -> Self
+    where
+        E: IntoIterator<IntoIter = I>,
+    {
+        Self {
+            app,
+            events: events.into_iter().peekable(),
+            timing,
+            start: None,
+            windows: Vec::new(),
+            recorded_windows: HashMap::new(),
+            devices: Vec::new(),
+            recorded_devices: HashMap::new(),
+            window_timeout: DEFAULT_WINDOW_TIMEOUT,
+            waiting_since: None,
+            exiting: false,
+        }
+    }
+
+    /// Set how long the replay waits for the application to create the window a recorded event
+    /// is targeting, before dropping the event.
+    ///
+    /// The default is 5 seconds.
+    pub fn with_window_timeout(mut self, timeout: Duration) -> Self {
+        self.window_timeout = timeout;
+        self
+    }
+
+    /// The wrapped application.
+    pub fn app(&self) -> &A {
+        &self.app
+    }
+
+    /// The wrapped application.
+    pub fn app_mut(&mut self) -> &mut A {
+        &mut self.app
+    }
+
+    /// Unwrap the application.
+    pub fn into_inner(self) -> A {
+        self.app
+    }
+
+    /// Map the recorded window to the live one, when the application has created it.
+    fn map_window(&mut self, window_id: WindowId) -> Option<WindowId> {
+        let next = self.recorded_windows.len();
+        let index = *self.recorded_windows.entry(window_id).or_insert(next);
+        self.windows.get(index).copied()
+    }
+
+    /// Map the recorded device to the live one, or to the dummy device when there are not
+    /// enough live devices yet.
+    fn map_device(&mut self, device_id: DeviceId) -> DeviceId {
+        let next = self.recorded_devices.len();
+        let index = *self.recorded_devices.entry(device_id).or_insert(next);
+        self.devices.get(index).copied().unwrap_or_else(DeviceId::dummy)
+    }
+
+    /// Keep the live device, in the order of its first event.
+    fn register_device(&mut self, device_id: DeviceId) {
+        if !self.devices.contains(&device_id) {
+            self.devices.push(device_id);
+        }
+    }
+
+    fn replay<T: 'static>(&mut self, event_loop: &ActiveEventLoop)
+    where
+        A: ApplicationHandler<T>,
+        I: Iterator<Item = RecordedEvent<T>>,
+    {
+        let start = *self.start.get_or_insert_with(Instant::now);
+        while let Some(record) = self.events.peek() {
+            if event_loop.exiting() {
+                return;
+            }
+
+            if self.timing == ReplayTiming::Original {
+                let deadline = start + record.time;
+                if deadline > Instant::now() {
+                    event_loop.set_control_flow(ControlFlow::WaitUntil(deadline));
+                    return;
+                }
+            }
+
+            let recorded_window_id = match record.event {
+                Event::WindowEvent { window_id, .. } => Some(window_id),
+                _ => None,
+            };
+            let live_window_id = match recorded_window_id {
+                Some(window_id) => match self.map_window(window_id) {
+                    Some(window_id) => {
+                        self.waiting_since = None;
+                        Some(window_id)
+                    },
+                    None => {
+                        // Wait for the application to create the window, and drop the events
+                        // of the windows still missing after the timeout.
+                        let waiting_since = *self.waiting_since.get_or_insert_with(Instant::now);
+                        let deadline = waiting_since + self.window_timeout;
+                        if deadline > Instant::now() {
+                            event_loop.set_control_flow(ControlFlow::WaitUntil(deadline));
+                            return;
+                        }
+
+                        tracing::warn!("Dropping the recorded event of a window never created");
+                        self.events.next();
+                        continue;
+                    },
+                },
+                None => None,
+            };
+
+            let mut event = self.events.next().unwrap().event;
+            match &mut event {
+                Event::WindowEvent { window_id, event } => {
+                    if let Some(live_window_id) = live_window_id {
+                        *window_id = live_window_id;
+                    }
+                    if let Some(device_id) = window_event_device_id(event) {
+                        *device_id = self.map_device(*device_id);
+                    }
+                },
+                Event::DeviceEvent { device_id, .. } => {
+                    *device_id = self.map_device(*device_id);
+                },
+                _ => (),
+            }
+
+            let yield_to_loop = matches!(event, Event::AboutToWait);
+            self.exiting |= matches!(event, Event::LoopExiting);
+            dispatch_event_for_app(&mut self.app, event_loop, event);
+
+            if yield_to_loop && self.timing == ReplayTiming::AsFastAsPossible {
+                event_loop.set_control_flow(ControlFlow::Poll);
+                return;
+            }
+        }
+
+        event_loop.exit();
+    }
+}
+
+impl<T: 'static, A, I> ApplicationHandler<T> for Replayer<A, I>
+where
+    A: ApplicationHandler<T>,
+    I: Iterator<Item = RecordedEvent<T>>,
+{
+    fn resumed(&mut self, _event_loop: &ActiveEventLoop) {}
+
+    fn window_event(
+        &mut self,
+        _event_loop: &ActiveEventLoop,
+        window_id: WindowId,
+        mut event: WindowEvent,
+    ) {
+        if !self.windows.contains(&window_id) {
+            self.windows.push(window_id);
+        }
+        if let Some(&mut device_id) = window_event_device_id(&mut event) {
+            self.register_device(device_id);
+        }
+    }
+
+    fn device_event(
+        &mut self,
+        _event_loop: &ActiveEventLoop,
+        device_id: DeviceId,
+        _event: DeviceEvent,
+    ) {
+        self.register_device(device_id);
+    }
+
+    fn about_to_wait(&mut self, event_loop: &ActiveEventLoop) {
+        self.replay(event_loop);
+    }
+
+    fn exiting(&mut self, event_loop: &ActiveEventLoop) {
+        // The application exited before the end of the recording.
+        if !self.exiting {
+            self.exiting = true;
+            self.app.exiting(event_loop);
+        }
+    }
+}
+
+/// The device the window event originates from.
+fn window_event_device_id(event: &mut WindowEvent) -> Option<&mut DeviceId> {
+    match event {
+        WindowEvent::KeyboardInput { device_id, .. }
+        | WindowEvent::CursorMoved { device_id, .. }
+        | WindowEvent::CursorEntered { device_id }
+        | WindowEvent::CursorLeft { device_id }
+        | WindowEvent::MouseWheel { device_id, .. }
+        | WindowEvent::MouseInput { device_id, .. }
+        | WindowEvent::PinchGesture { device_id, .. }
+        | WindowEvent::PanGesture { device_id, .. }
+        | WindowEvent::DoubleTapGesture { device_id }
+        | WindowEvent::RotationGesture { device_id, .. }
+        | WindowEvent::TouchpadPressure { device_id, .. }
+        | WindowEvent::AxisMotion { device_id, .. }
+        | WindowEvent::TabletTool { device_id, .. } => Some(device_id),
+        WindowEvent::Touch(touch) => Some(&mut touch.device_id),
+        _ => None,
+    }
+}
@@ -68,6 +68,7 @@ impl Drop for Window {
 /// Whenever you receive an event specific to a window, this event contains a `WindowId` which you
 /// can then compare to the ids of your windows.
 #[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(from = "u64", into = "u64"))]
 pub struct WindowId(pub(crate) platform_impl::WindowId);
 
 impl WindowId {
@@ -125,6 +126,8 @@ pub struct WindowAttributes {
     pub cursor: Cursor,
     #[cfg(feature = "rwh_06")]
     pub(crate) parent_window: Option<SendSyncRawWindowHandle>,
+    pub(crate) owner: Option<WindowId>,
+    pub(crate) popup: Option<PopupAttributes>,
     pub fullscreen: Option<Fullscreen>,
     // Platform-specific configuration.
     #[allow(dead_code)]
@@ -156,6 +159,8 @@ impl Default for WindowAttributes {
             cursor: Cursor::default(),
             #[cfg(feature = "rwh_06")]
             parent_window: None,
+            owner: None,
+            popup: None,
             active: true,
             platform_specific: Default::default(),
         }
@@ -172,6 +177,16 @@ impl Default for WindowAttributes {
 #[cfg(feature = "rwh_06")]
 pub(crate) struct SendSyncRawWindowHandle(pub(crate) rwh_06::RawWindowHandle);
 
+/// The placement of a popup window, see [`WindowAttributes::with_popup()`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct PopupAttributes {
+    pub(crate) parent: WindowId,
+    pub(crate) anchor_rect: (Position, Size),
+    pub(crate) anchor: PopupAnchor,
+    pub(crate) gravity: PopupGravity,
+    pub(crate) constraint_adjustment: PopupConstraintAdjustment,
+}
+
 #[cfg(feature = "rwh_06")]
 unsafe impl Send for SendSyncRawWindowHandle {}
 #[cfg(feature = "rwh_06")]
@@ -193,6 +208,11 @@ impl WindowAttributes {
         self.parent_window.as_ref().map(|handle| &handle.0)
     }
 
+    /// Get the owner window stored on the attributes.
+    pub fn owner(&self) -> Option<WindowId> {
+        self.owner
+    }
+
     /// Requests the window to be of specific dimensions.
     ///
     /// If this is not set, some platform-specific dimensions will be used.
@@ -482,6 +502,67 @@ impl WindowAttributes {
         self
     }
 
+    /// Build window owned by the `owner` window.
+    ///
+    /// An owned window, such as a tool palette or an inspector, is kept above its owner and is
+    /// minimized along with it. Unlike [`WindowAttributes::with_parent_window`], the window is
+    /// not embedded into its owner, and unlike [`Window::set_modal`], the owner keeps receiving
+    /// input.
+    ///
+    /// The default is `None`.
+    ///
+    /// See [`Window::set_owner`] for details.
+    #[inline]
+    pub fn with_owner(mut self, owner: &Window) -> Self {
+        self.owner = Some(owner.id());
+        self
+    }
+
+    /// Build a popup window, such as a menu, a tooltip or the list of a combo box, placed
+    /// relative to the `parent` window.
+    ///
+    /// The popup is attached to the `anchor` edge or corner of the `anchor_rect`, given in the
+    /// coordinates of the parent's client area, and extends from there in the direction of the
+    /// `gravity`. Its size is the one requested with [`WindowAttributes::with_inner_size`]. When
+    /// the popup doesn't fit on the screen, the `constraint_adjustment` tells how to move or
+    /// resize it.
+    ///
+    /// The popup grabs the pointer and the keyboard, and is dismissed with
+    /// [`WindowEvent::PopupDismissed`] on a click outside of the windows of the application.
+    /// Popups built with [`WindowAttributes::with_active`]`(false)`, such as tooltips, don't
+    /// take the grab and are never dismissed. A dismissed popup is hidden, and should be
+    /// dropped.
+    ///
+    /// The decorations, title, and other attributes of regular windows don't apply to popups.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Wayland:** Backed by `xdg_popup`, the compositor handles the placement and the
+    ///   dismissal. The parent of a grabbing popup must be either a regular window with no
+    ///   grabbing popups open, or the topmost grabbing popup, otherwise the creation fails.
+    /// - **X11:** Backed by an override-redirect window, which takes a pointer and keyboard grab.
+    /// - **Windows / macOS / iOS / Android / Web / Orbital:** Unsupported.
+    ///
+    /// [`WindowEvent::PopupDismissed`]: crate::event::WindowEvent::PopupDismissed
+    #[inline]
+    pub fn with_popup<P: Into<Position>, S: Into<Size>>(
+        mut self,
+        parent: &Window,
+        anchor_rect: (P, S),
+        anchor: PopupAnchor,
+        gravity: PopupGravity,
+        constraint_adjustment: PopupConstraintAdjustment,
+    ) -> Self {
+        self.popup = Some(PopupAttributes {
+            parent: parent.id(),
+            anchor_rect: (anchor_rect.0.into(), anchor_rect.1.into()),
+            anchor,
+            gravity,
+            constraint_adjustment,
+        });
+        self
+    }
+
     #[inline]
     #[cfg(x11_platform)]
     pub unsafe fn with_modal(
@@ -769,11 +850,47 @@ impl Window {
         self.window.maybe_queue_on_main(move |w| w.set_outer_position(position))
     }
 
+    /// Makes the window a modal dialog of the `owner` window.
+    ///
+    /// The window is kept above its owner. Whether the owner stops receiving input until the
+    /// dialog is closed depends on the platform, so the application should ignore that input
+    /// itself when it matters.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **X11:** Sets the `_NET_WM_STATE_MODAL` state and the `WM_TRANSIENT_FOR` hint, the window
+    ///   manager decides whether the owner still receives input.
+    /// - **Wayland:** The window becomes a child of the owner. Only when the compositor supports
+    ///   the `xdg-dialog-v1` protocol is it asked to treat the window as modal, otherwise the
+    ///   owner keeps receiving input.
+    /// - **Windows / macOS / iOS / Android / Web / Orbital:** Unsupported.
     #[allow(unused)]
     pub fn set_modal(&self, owner: &Self) {
-        //TODO support other platforms?
-        #[cfg(x11_platform)]
-        let _ = self.window.maybe_wait_on_main(move |w| w.set_modal(&owner.window));
+        #[cfg(any(x11_platform, wayland_platform))]
+        if let Err(err) = self.window.maybe_wait_on_main(move |w| w.set_modal(&owner.window)) {
+            tracing::warn!("Failed to make the window modal: {err}");
+        }
+    }
+
+    /// Sets or unsets the owner of the window.
+    ///
+    /// See [`WindowAttributes::with_owner`] for details.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **X11:** Sets the `WM_TRANSIENT_FOR` hint.
+    /// - **Wayland:** Sets the parent of the `xdg_toplevel`.
+    /// - **Windows:** Sets the owner window with `GWLP_HWNDPARENT`. The taskbar button of the
+    ///   window is decided at creation, by the owner given there.
+    /// - **macOS / iOS / Android / Web / Orbital:** Unsupported.
+    #[inline]
+    pub fn set_owner(&self, owner: Option<&Window>) {
+        let _span = tracing::debug_span!("winit::Window::set_owner",).entered();
+
+        #[cfg(any(x11_platform, wayland_platform, windows_platform))]
+        self.window.maybe_wait_on_main(|w| w.set_owner(owner.map(|owner| &owner.window)));
+        #[cfg(not(any(x11_platform, wayland_platform, windows_platform)))]
+        let _ = owner;
     }
 
     /// Returns the physical size of the window's client area.
@@ -1335,6 +1452,26 @@ impl Window {
         self.window.maybe_queue_on_main(move |w| w.set_ime_purpose(purpose))
     }
 
+    /// Sets the text around the cursor, for the IME to take it into account.
+    ///
+    /// The `cursor` and the `anchor` are byte-wise indices into the `text`, equal when there's
+    /// no selection. The text shouldn't include the preedit. It should be updated whenever it
+    /// changes, so that [`Ime::DeleteSurrounding`] events stay relative to the right text.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Wayland:** The text is trimmed around the cursor to the size limit of the protocol.
+    /// - **iOS / Android / Web / Windows / X11 / macOS / Orbital:** Unsupported.
+    ///
+    /// [`Ime::DeleteSurrounding`]: crate::event::Ime::DeleteSurrounding
+    #[inline]
+    pub fn set_ime_surrounding_text(&self, text: impl Into<String>, cursor: usize, anchor: usize) {
+        let text = text.into();
+        let _span = tracing::debug_span!("winit::Window::set_ime_surrounding_text", cursor, anchor)
+            .entered();
+        self.window.maybe_queue_on_main(move |w| w.set_ime_surrounding_text(text, cursor, anchor))
+    }
+
     #[inline]
     pub fn commit_ime(&self) {
         self.window.maybe_queue_on_main(|w| w.commit_ime())
@@ -1859,6 +1996,65 @@ pub enum WindowLevel {
     AlwaysOnTop,
 }
 
+/// The edge or corner of the anchor rectangle a popup is attached to.
+///
+/// See [`WindowAttributes::with_popup`] for details.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PopupAnchor {
+    /// The center of the anchor rectangle.
+    #[default]
+    None,
+    Top,
+    Bottom,
+    Left,
+    Right,
+    TopLeft,
+    BottomLeft,
+    TopRight,
+    BottomRight,
+}
+
+/// The direction in which a popup extends from its anchor point.
+///
+/// For example, with [`PopupGravity::BottomRight`] the top-left corner of the popup is at the
+/// anchor point. See [`WindowAttributes::with_popup`] for details.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PopupGravity {
+    /// The popup is centered on the anchor point.
+    #[default]
+    None,
+    Top,
+    Bottom,
+    Left,
+    Right,
+    TopLeft,
+    BottomLeft,
+    TopRight,
+    BottomRight,
+}
+
+bitflags::bitflags! {
+    /// How to adjust a popup that doesn't fit on the screen.
+    ///
+    /// The adjustments are tried in order: flipping, sliding, and then resizing. See
+    /// [`WindowAttributes::with_popup`] for details.
+    #[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+    pub struct PopupConstraintAdjustment: u32 {
+        /// Move the popup horizontally until it fits.
+        const SLIDE_X = 1 << 0;
+        /// Move the popup vertically until it fits.
+        const SLIDE_Y = 1 << 1;
+        /// Mirror the anchor and the gravity horizontally, if the popup fits then.
+        const FLIP_X = 1 << 2;
+        /// Mirror the anchor and the gravity vertically, if the popup fits then.
+        const FLIP_Y = 1 << 3;
+        /// Shrink the popup horizontally to the visible part.
+        const RESIZE_X = 1 << 4;
+        /// Shrink the popup vertically to the visible part.
+        const RESIZE_Y = 1 << 5;
+    }
+}
+
 /// Generic IME purposes for use in [`Window::set_ime_purpose`].
 ///
 /// The purpose may improve UX by optimizing the IME for the specific use case,
@@ -1890,6 +2086,7 @@ impl Default for ImePurpose {
 ///
 /// [`Window`]: crate::window::Window
 #[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(transparent))]
 pub struct ActivationToken {
     pub(crate) token: String,
 }
@@ -0,0 +1,61 @@
+#![cfg(all(feature = "futures", unix, not(target_vendor = "apple"), not(target_os = "android")))]
+
+use std::cell::Cell;
+use std::panic::{self, AssertUnwindSafe};
+use std::rc::Rc;
+
+use deft_winit::event::{Event, WindowEvent};
+use deft_winit::event_loop::EventLoop;
+use deft_winit::executor::AsyncEventLoop;
+use deft_winit::platform::headless::EventLoopBuilderExtHeadless;
+use deft_winit::window::Window;
+
+#[test]
+fn futures_are_driven_by_the_event_loop() {
+    let event_loop = EventLoop::with_user_event().with_headless().build().unwrap();
+    let event_loop = AsyncEventLoop::<u32>::new(event_loop);
+    let handle = event_loop.handle();
+    let proxy = event_loop.create_proxy();
+
+    let redraws = Rc::new(Cell::new(0));
+    let user_event = Rc::new(Cell::new(None));
+    let (redraws_seen, user_event_seen) = (redraws.clone(), user_event.clone());
+
+    event_loop
+        .run(async move {
+            let mut events = handle.events();
+            let second = panic::catch_unwind(AssertUnwindSafe(|| handle.events()));
+            assert!(second.is_err(), "a second stream of the events was created");
+
+            let window = handle.create_window(Window::default_attributes()).unwrap();
+            let window_id = window.id();
+
+            // The spawned task resolves from within the callback delivering the redraw.
+            let draw_handle = handle.clone();
+            handle.spawn_local(async move {
+                draw_handle.redraw_requested(window_id).await;
+                redraws.set(redraws.get() + 1);
+            });
+
+            proxy.send_event(7).unwrap();
+
+            let mut redraw_streamed = false;
+            while let Some(event) = events.next_event().await {
+                match event {
+                    Event::UserEvent(event) => user_event.set(Some(event)),
+                    Event::WindowEvent { event: WindowEvent::RedrawRequested, .. } => {
+                        redraw_streamed = true;
+                    },
+                    _ => (),
+                }
+
+                if redraw_streamed && user_event.get().is_some() {
+                    break;
+                }
+            }
+        })
+        .unwrap();
+
+    assert_eq!(redraws_seen.get(), 1);
+    assert_eq!(user_event_seen.get(), Some(7));
+}
@@ -0,0 +1,53 @@
+#![cfg(all(unix, not(target_vendor = "apple"), not(target_os = "android")))]
+
+use std::io::Write;
+use std::os::unix::io::AsFd;
+use std::os::unix::net::UnixStream;
+use std::time::Duration;
+
+use deft_winit::application::ApplicationHandler;
+use deft_winit::event::WindowEvent;
+use deft_winit::event_loop::{ActiveEventLoop, EventLoop, Interest, SourceToken};
+use deft_winit::platform::headless::EventLoopBuilderExtHeadless;
+use deft_winit::platform::pump_events::EventLoopExtPumpEvents;
+use deft_winit::platform::unix::ActiveEventLoopExtUnix;
+use deft_winit::window::WindowId;
+
+struct App {
+    stream: UnixStream,
+    token: Option<SourceToken>,
+    ready: Vec<(SourceToken, Interest)>,
+}
+
+impl ApplicationHandler for App {
+    fn resumed(&mut self, event_loop: &ActiveEventLoop) {
+        let fd = self.stream.as_fd().try_clone_to_owned().unwrap();
+        self.token = Some(event_loop.register_fd(fd, Interest::READABLE).unwrap());
+    }
+
+    fn window_event(&mut self, _: &ActiveEventLoop, _: WindowId, _: WindowEvent) {}
+
+    fn fd_ready(&mut self, event_loop: &ActiveEventLoop, token: SourceToken, readiness: Interest) {
+        self.ready.push((token, readiness));
+        event_loop.unregister_fd(token);
+    }
+}
+
+#[test]
+fn registered_fd_is_owned_by_the_event_loop() {
+    let mut event_loop = EventLoop::builder().with_headless().build().unwrap();
+    let (stream, mut peer) = UnixStream::pair().unwrap();
+
+    let mut app = App { stream, token: None, ready: Vec::new() };
+    event_loop.pump_app_events(Some(Duration::ZERO), &mut app);
+    assert!(app.ready.is_empty());
+
+    peer.write_all(b"ping").unwrap();
+    event_loop.pump_app_events(Some(Duration::from_secs(1)), &mut app);
+    assert_eq!(app.ready, [(app.token.unwrap(), Interest::READABLE)]);
+
+    // Only the registered duplicate is closed once it's unregistered.
+    event_loop.pump_app_events(Some(Duration::ZERO), &mut app);
+    assert_eq!(app.ready.len(), 1);
+    assert!(app.stream.peer_addr().is_ok());
+}
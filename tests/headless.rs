@@ -0,0 +1,66 @@
+#![cfg(all(unix, not(target_vendor = "apple"), not(target_os = "android")))]
+
+use std::time::Duration;
+
+use deft_winit::application::ApplicationHandler;
+use deft_winit::dpi::PhysicalSize;
+use deft_winit::event::WindowEvent;
+use deft_winit::event_loop::{ActiveEventLoop, EventLoop};
+use deft_winit::platform::headless::{EventLoopBuilderExtHeadless, EventLoopExtHeadless};
+use deft_winit::platform::pump_events::EventLoopExtPumpEvents;
+use deft_winit::window::{Window, WindowId};
+
+#[derive(Default)]
+struct App {
+    window: Option<Window>,
+    events: Vec<WindowEvent>,
+}
+
+impl ApplicationHandler for App {
+    fn resumed(&mut self, event_loop: &ActiveEventLoop) {
+        let attributes = Window::default_attributes().with_inner_size(PhysicalSize::new(640, 480));
+        self.window = Some(event_loop.create_window(attributes).unwrap());
+    }
+
+    fn window_event(&mut self, _: &ActiveEventLoop, _: WindowId, event: WindowEvent) {
+        self.events.push(event);
+    }
+}
+
+#[test]
+fn headless_window_events() {
+    let mut event_loop = EventLoop::builder().with_headless().build().unwrap();
+    assert!(event_loop.is_headless());
+
+    let mut app = App::default();
+    event_loop.pump_app_events(Some(Duration::ZERO), &mut app);
+    event_loop.pump_app_events(Some(Duration::ZERO), &mut app);
+    assert_eq!(app.events, [WindowEvent::Focused(true), WindowEvent::RedrawRequested]);
+
+    let window = app.window.take().unwrap();
+    let window_id = window.id();
+    assert_eq!(window.inner_size(), PhysicalSize::new(640, 480));
+    assert!(window.has_focus());
+
+    // The setters take effect right away, and are reported.
+    app.events.clear();
+    let size = window.request_inner_size(PhysicalSize::new(320, 240));
+    assert_eq!(size, Some(PhysicalSize::new(320, 240)));
+    assert_eq!(window.inner_size(), PhysicalSize::new(320, 240));
+    event_loop.pump_app_events(Some(Duration::ZERO), &mut app);
+    let resized = WindowEvent::Resized(PhysicalSize::new(320, 240));
+    assert_eq!(app.events, [resized, WindowEvent::RedrawRequested]);
+
+    // The injected events update the window.
+    app.events.clear();
+    event_loop.inject_window_event(window_id, WindowEvent::Focused(false)).unwrap();
+    event_loop.inject_window_event(window_id, WindowEvent::CloseRequested).unwrap();
+    event_loop.pump_app_events(Some(Duration::ZERO), &mut app);
+    assert_eq!(app.events, [WindowEvent::Focused(false), WindowEvent::CloseRequested]);
+    assert!(!window.has_focus());
+
+    app.events.clear();
+    drop(window);
+    event_loop.pump_app_events(Some(Duration::ZERO), &mut app);
+    assert_eq!(app.events, [WindowEvent::Destroyed]);
+}
@@ -0,0 +1,87 @@
+#![cfg(all(unix, not(target_vendor = "apple"), not(target_os = "android")))]
+
+use std::time::Duration;
+
+use deft_winit::application::ApplicationHandler;
+use deft_winit::event::{Event, WindowEvent};
+use deft_winit::event_loop::{ActiveEventLoop, ControlFlow, EventLoop};
+use deft_winit::platform::headless::{EventLoopBuilderExtHeadless, EventLoopExtHeadless};
+use deft_winit::platform::pump_events::{EventLoopExtPumpEvents, PumpStatus};
+use deft_winit::record::{RecordedEvent, Recorder, ReplayTiming, Replayer};
+use deft_winit::window::{Window, WindowId};
+
+#[derive(Default)]
+struct App {
+    window: Option<Window>,
+    events: Vec<(WindowId, WindowEvent)>,
+}
+
+impl ApplicationHandler for App {
+    fn resumed(&mut self, event_loop: &ActiveEventLoop) {
+        self.window = Some(event_loop.create_window(Window::default_attributes()).unwrap());
+    }
+
+    fn window_event(&mut self, _: &ActiveEventLoop, window_id: WindowId, event: WindowEvent) {
+        self.events.push((window_id, event));
+    }
+}
+
+struct Drain;
+
+impl ApplicationHandler for Drain {
+    fn resumed(&mut self, _: &ActiveEventLoop) {}
+
+    fn window_event(&mut self, _: &ActiveEventLoop, _: WindowId, _: WindowEvent) {}
+
+    fn about_to_wait(&mut self, event_loop: &ActiveEventLoop) {
+        event_loop.set_control_flow(ControlFlow::Poll);
+    }
+}
+
+#[test]
+fn record_and_replay() {
+    let mut event_loop = EventLoop::builder().with_headless().build().unwrap();
+
+    let mut trace = Vec::new();
+    let mut recorder = Recorder::new(App::default(), |record: &RecordedEvent<()>| {
+        trace.push(record.clone());
+    });
+    event_loop.pump_app_events(Some(Duration::ZERO), &mut recorder);
+    event_loop.pump_app_events(Some(Duration::ZERO), &mut recorder);
+    let window_id = recorder.app().window.as_ref().unwrap().id();
+    event_loop.inject_window_event(window_id, WindowEvent::CloseRequested).unwrap();
+    event_loop.pump_app_events(Some(Duration::ZERO), &mut recorder);
+    let recorded = recorder.into_inner();
+
+    assert!(matches!(trace.first().unwrap().event, Event::NewEvents(_)));
+    assert!(trace.windows(2).all(|records| records[0].time <= records[1].time));
+    let recorded_events: Vec<_> = trace
+        .iter()
+        .filter_map(|record| match &record.event {
+            Event::WindowEvent { window_id, event } => Some((*window_id, event.clone())),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(recorded.events, recorded_events);
+
+    // Let the recorded window go away, and keep the loop iterating for the replay.
+    drop(recorded);
+    event_loop.pump_app_events(Some(Duration::ZERO), &mut Drain);
+
+    let mut replayer = Replayer::new(App::default(), trace, ReplayTiming::AsFastAsPossible);
+    let mut status = PumpStatus::Continue;
+    for _ in 0..100 {
+        status = event_loop.pump_app_events(Some(Duration::ZERO), &mut replayer);
+        if let PumpStatus::Exit(_) = status {
+            break;
+        }
+    }
+    assert!(matches!(status, PumpStatus::Exit(0)));
+
+    // The recorded events were delivered to the window of the replayed application.
+    let replayed = replayer.into_inner();
+    let window_id = replayed.window.as_ref().unwrap().id();
+    let expected: Vec<_> =
+        recorded_events.into_iter().map(|(_, event)| (window_id, event)).collect();
+    assert_eq!(replayed.events, expected);
+}
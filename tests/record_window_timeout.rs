@@ -0,0 +1,61 @@
+#![cfg(all(unix, not(target_vendor = "apple"), not(target_os = "android")))]
+
+use std::time::Duration;
+
+use deft_winit::application::ApplicationHandler;
+use deft_winit::event::{Event, WindowEvent};
+use deft_winit::event_loop::{ActiveEventLoop, EventLoop};
+use deft_winit::platform::headless::EventLoopBuilderExtHeadless;
+use deft_winit::platform::pump_events::{EventLoopExtPumpEvents, PumpStatus};
+use deft_winit::record::{RecordedEvent, ReplayTiming, Replayer};
+use deft_winit::window::WindowId;
+
+/// The application which never creates a window.
+#[derive(Default)]
+struct App {
+    events: Vec<WindowEvent>,
+    about_to_wait: usize,
+}
+
+impl ApplicationHandler for App {
+    fn resumed(&mut self, _: &ActiveEventLoop) {}
+
+    fn window_event(&mut self, _: &ActiveEventLoop, _: WindowId, event: WindowEvent) {
+        self.events.push(event);
+    }
+
+    fn about_to_wait(&mut self, _: &ActiveEventLoop) {
+        self.about_to_wait += 1;
+    }
+}
+
+#[test]
+fn replay_drops_the_events_of_missing_windows() {
+    let mut event_loop = EventLoop::builder().with_headless().build().unwrap();
+
+    let trace = vec![
+        RecordedEvent {
+            time: Duration::ZERO,
+            event: Event::WindowEvent {
+                window_id: WindowId::from(42),
+                event: WindowEvent::Focused(true),
+            },
+        },
+        RecordedEvent { time: Duration::ZERO, event: Event::AboutToWait },
+    ];
+    let mut replayer = Replayer::new(App::default(), trace, ReplayTiming::AsFastAsPossible)
+        .with_window_timeout(Duration::from_millis(10));
+    let mut status = PumpStatus::Continue;
+    for _ in 0..100 {
+        status = event_loop.pump_app_events(Some(Duration::from_millis(10)), &mut replayer);
+        if let PumpStatus::Exit(_) = status {
+            break;
+        }
+    }
+    assert!(matches!(status, PumpStatus::Exit(0)));
+
+    // The replay went on past the event of the window the application never created.
+    let app = replayer.into_inner();
+    assert!(app.events.is_empty());
+    assert_eq!(app.about_to_wait, 1);
+}
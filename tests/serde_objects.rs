@@ -2,9 +2,17 @@
 
 use serde::{Deserialize, Serialize};
 use deft_winit::dpi::{LogicalPosition, LogicalSize, PhysicalPosition, PhysicalSize};
-use deft_winit::event::{ElementState, MouseButton, MouseScrollDelta, TouchPhase};
-use deft_winit::keyboard::{Key, KeyCode, KeyLocation, ModifiersState, NamedKey, PhysicalKey};
-use deft_winit::window::CursorIcon;
+use deft_winit::event::{
+    DeviceEvent, DeviceId, ElementState, Event, Force, Ime, KeyEvent, Modifiers, MouseButton,
+    MouseScrollDelta, PreeditSegment, PreeditStyle, StartCause, TabletTool, TabletToolAxes,
+    TabletToolButton, TabletToolEvent, TabletToolKind, Touch, TouchContact, TouchPhase,
+    WindowEvent,
+};
+use deft_winit::keyboard::{
+    Key, KeyCode, KeyLocation, KeyboardLayouts, ModifiersState, NamedKey, PhysicalKey,
+};
+use deft_winit::record::RecordedEvent;
+use deft_winit::window::{CursorIcon, WindowId};
 
 #[allow(dead_code)]
 fn needs_serde<S: Serialize + Deserialize<'static>>() {}
@@ -12,6 +20,7 @@ fn needs_serde<S: Serialize + Deserialize<'static>>() {}
 #[test]
 fn window_serde() {
     needs_serde::<CursorIcon>();
+    needs_serde::<WindowId>();
 }
 
 #[test]
@@ -26,6 +35,26 @@ fn events_serde() {
     needs_serde::<PhysicalKey>();
     needs_serde::<KeyLocation>();
     needs_serde::<ModifiersState>();
+    needs_serde::<Modifiers>();
+    needs_serde::<KeyboardLayouts>();
+    needs_serde::<KeyEvent>();
+    needs_serde::<Ime>();
+    needs_serde::<PreeditSegment>();
+    needs_serde::<PreeditStyle>();
+    needs_serde::<Touch>();
+    needs_serde::<TouchContact>();
+    needs_serde::<Force>();
+    needs_serde::<TabletTool>();
+    needs_serde::<TabletToolKind>();
+    needs_serde::<TabletToolEvent>();
+    needs_serde::<TabletToolButton>();
+    needs_serde::<TabletToolAxes>();
+    needs_serde::<DeviceId>();
+    needs_serde::<StartCause>();
+    needs_serde::<WindowEvent>();
+    needs_serde::<DeviceEvent>();
+    needs_serde::<Event<()>>();
+    needs_serde::<RecordedEvent<()>>();
 }
 
 #[test]
@@ -36,3 +65,51 @@ fn dpi_serde() {
     needs_serde::<LogicalSize<f64>>();
     needs_serde::<PhysicalSize<u32>>();
 }
+
+#[test]
+fn events_golden() {
+    // The representation is stable, the events must serialize back to the exact same text.
+    let golden = include_str!("golden/events.jsonl");
+    for line in golden.lines() {
+        let event: Event<()> = serde_json::from_str(line).unwrap();
+        assert_eq!(serde_json::to_string(&event).unwrap(), line);
+    }
+
+    // The records mean what they say, wherever they are in the file.
+    let resized = WindowEvent::Resized(PhysicalSize::new(800, 600));
+    let expected: [Event<()>; 3] = [
+        Event::NewEvents(StartCause::Init),
+        Event::Resumed,
+        Event::WindowEvent { window_id: WindowId::from(1), event: resized },
+    ];
+    for event in expected {
+        let record = serde_json::to_string(&event).unwrap();
+        assert!(golden.lines().any(|line| line == record), "missing golden record {record}");
+    }
+}
+
+#[cfg(any(
+    target_os = "windows",
+    target_os = "macos",
+    all(target_os = "linux", not(target_env = "ohos"))
+))]
+#[test]
+fn key_event_extra_serde() {
+    use deft_winit::platform::modifier_supplement::KeyEventExtModifierSupplement;
+
+    let json = r#"{"physical_key":{"Code":"KeyA"},"logical_key":{"Character":"A"},"text":"A","location":"Standard","state":"Pressed","repeat":false,"text_with_all_modifiers":"\u0001","key_without_modifiers":{"Character":"a"}}"#;
+    let event: KeyEvent = serde_json::from_str(json).unwrap();
+    assert_eq!(event.text_with_all_modifiers(), Some("\u{1}"));
+    assert_eq!(event.key_without_modifiers(), Key::Character("a".into()));
+    assert_eq!(serde_json::to_string(&event).unwrap(), json);
+}
+
+#[cfg(any(target_os = "windows", all(target_os = "linux", feature = "x11")))]
+#[test]
+fn device_id_out_of_range() {
+    // The identifiers don't wrap around, the ones out of the platform range are rejected.
+    assert!(serde_json::from_str::<DeviceId>(&i64::MAX.to_string()).is_err());
+    assert!(serde_json::from_str::<DeviceId>("-1").is_err());
+    let device_id: DeviceId = serde_json::from_str("2").unwrap();
+    assert_eq!(serde_json::to_string(&device_id).unwrap(), "2");
+}